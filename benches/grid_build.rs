@@ -0,0 +1,40 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ray_tracer::grid::HierarchicalGrid;
+use ray_tracer::scenes::{create_fractal_scene, create_stress_scene, create_walls_scene};
+
+/// Benchmark: grid build for the built-in scenes
+fn bench_scene_grids(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grid_build_scenes");
+
+    for (name, boxes) in [("walls", create_walls_scene()), ("fractal", create_fractal_scene())] {
+        group.bench_with_input(BenchmarkId::new("parallel", name), &boxes, |b, boxes| {
+            b.iter(|| black_box(HierarchicalGrid::build(boxes, &[])))
+        });
+        group.bench_with_input(BenchmarkId::new("serial", name), &boxes, |b, boxes| {
+            b.iter(|| black_box(HierarchicalGrid::build_serial(boxes, &[])))
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark: grid build scaling with box count
+fn bench_stress_grids(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grid_build_stress");
+    group.sample_size(10);
+
+    for count in [1_000, 10_000, 100_000] {
+        let boxes = create_stress_scene(count);
+        group.bench_with_input(BenchmarkId::new("parallel", count), &boxes, |b, boxes| {
+            b.iter(|| black_box(HierarchicalGrid::build(boxes, &[])))
+        });
+        group.bench_with_input(BenchmarkId::new("serial", count), &boxes, |b, boxes| {
+            b.iter(|| black_box(HierarchicalGrid::build_serial(boxes, &[])))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_scene_grids, bench_stress_grids);
+criterion_main!(benches);
@@ -81,7 +81,7 @@ impl ApplicationHandler for App {
 
         // Create layer stack
         let mut layers = LayerStack::new();
-        layers = layers.add_layer(rt_layer);
+        layers = layers.with_layer(rt_layer);
 
         println!("Ray Tracing Layer Example initialized");
         println!("Scene: {}", scene);
@@ -149,11 +149,12 @@ impl ApplicationHandler for App {
                     height: size.height,
                 };
 
-                let outputs = layers.render(&context);
+                let mask = vec![true; context.pixel_count()];
+                let mut outputs = layers.render(&mask, &context);
 
                 // Display on surface
-                if let Some(output) = outputs.first() {
-                    if let Err(e) = surface_renderer.render(output) {
+                if let Some(output) = outputs.next() {
+                    if let Err(e) = surface_renderer.render(&output) {
                         eprintln!("Render error: {}", e);
                     }
                 }
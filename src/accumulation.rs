@@ -0,0 +1,70 @@
+/// Progressive accumulation state: how many frames have been blended into the current image
+///
+/// In single-shot mode every frame is rendered from scratch, so the counter never advances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Accumulation {
+    pub enabled: bool,
+    pub frame: u32,
+}
+
+impl Accumulation {
+    pub const fn new(enabled: bool) -> Self {
+        Self { enabled, frame: 0 }
+    }
+
+    /// Discard accumulated frames (camera moved, scene edited or a setting changed)
+    pub const fn reset(self) -> Self {
+        Self { frame: 0, ..self }
+    }
+
+    /// A frame was rendered
+    pub const fn advance(self) -> Self {
+        if self.enabled {
+            Self { frame: self.frame.saturating_add(1), ..self }
+        } else {
+            self
+        }
+    }
+
+    /// Switch between accumulation and single-shot; always starts from a clean image
+    pub const fn with_enabled(self, enabled: bool) -> Self {
+        Self { enabled, frame: 0 }
+    }
+}
+
+impl Default for Accumulation {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_increments_frame() {
+        let acc = Accumulation::default().advance().advance().advance();
+        assert_eq!(acc.frame, 3);
+    }
+
+    #[test]
+    fn test_reset_returns_frame_to_zero() {
+        let acc = Accumulation::default().advance().advance().reset();
+        assert_eq!(acc.frame, 0);
+        assert!(acc.enabled);
+        assert_eq!(acc.advance().frame, 1);
+    }
+
+    #[test]
+    fn test_single_shot_never_accumulates() {
+        let acc = Accumulation::new(false).advance().advance();
+        assert_eq!(acc.frame, 0);
+    }
+
+    #[test]
+    fn test_mode_switch_clears() {
+        let acc = Accumulation::default().advance().advance().with_enabled(false);
+        assert_eq!(acc, Accumulation::new(false));
+    }
+}
@@ -1,10 +1,56 @@
 use glam::Vec3;
+use serde::{Deserialize, Serialize};
 use winit::event::KeyEvent;
 use winit::keyboard::{KeyCode, PhysicalKey};
+use crate::camera_path::CameraPath;
+use crate::core::{Button, Controller, WinitController};
+use crate::key_bindings::KeyBindings;
+use crate::resolution::Resolution;
+use crate::scenes::{gltf_file, DEFAULT_AMBIENT, DEFAULT_GLTF_FILE};
 use crate::types::CameraUniform;
 
 pub const CAMERA_SPEED: f32 = 0.1;
 pub const CAMERA_ROTATION_SPEED: f32 = 0.05;
+pub const DEFAULT_FOV: f32 = std::f32::consts::FRAC_PI_4;  // π/4 = 45 degrees
+pub const DEFAULT_FOCUS_DISTANCE: f32 = 10.0;
+/// Default primary-ray clip range (see `CameraUniform::near`/`far`)
+pub const DEFAULT_NEAR_CLIP: f32 = 0.0;
+pub const DEFAULT_FAR_CLIP: f32 = 10_000.0;
+/// Speed factor while the sprint key (Ctrl by default) is held
+pub const SPRINT_MULTIPLIER: f32 = 2.0;
+/// Factor applied per +/- key press or scroll notch
+pub const SPEED_STEP: f32 = 1.25;
+pub const MIN_SPEED_MULTIPLIER: f32 = 0.05;
+pub const MAX_SPEED_MULTIPLIER: f32 = 50.0;
+
+/// Viewpoint (position, yaw, pitch) for scenes without a hand-placed camera when their bounds are unknown
+pub const FALLBACK_VIEWPOINT: (Vec3, f32, f32) = (Vec3::new(0.0, 8.0, 15.0), std::f32::consts::PI, -0.6);
+
+/// Hand-placed viewpoint (position, yaw, pitch) of a built-in scene; `None` for scene files, a user's
+/// GLTF_FILE model and unknown names, which should be framed from their bounds instead
+pub fn scene_viewpoint(scene_name: &str) -> Option<(Vec3, f32, f32)> {
+    Some(match scene_name {
+        "composed" => (Vec3::new(0.0, 40.0, 40.0), std::f32::consts::PI, -0.7),
+        "walls" => (Vec3::new(0.0, 5.0, 0.0), 0.0, 0.0),
+        "tunnel" => (Vec3::new(0.0, 0.0, 20.0), std::f32::consts::PI, 0.0),
+        "gltf" if gltf_file() == DEFAULT_GLTF_FILE => (Vec3::new(200.0, 200.0, 300.0), 3.35, -0.28),
+        "pyramid" => (Vec3::new(0.0, 8.0, 20.0), std::f32::consts::PI, -0.5),
+        "stress" => (Vec3::new(0.0, 5.0, 40.0), std::f32::consts::PI, -0.1),
+        "fractal" | "default" | "reflected" => FALLBACK_VIEWPOINT,
+        _ => return None,
+    })
+}
+
+/// Scale a speed multiplier by `steps` increments of `SPEED_STEP`, clamped to a sane range
+pub fn adjust_speed_multiplier(multiplier: f32, steps: f32) -> f32 {
+    (multiplier * SPEED_STEP.powf(steps)).clamp(MIN_SPEED_MULTIPLIER, MAX_SPEED_MULTIPLIER)
+}
+
+/// Scale combined forward/right input to unit length so diagonals aren't faster than a single axis
+pub fn normalize_planar(forward: f32, right: f32) -> (f32, f32) {
+    let planar = glam::Vec2::new(forward, right).normalize_or_zero();
+    (planar.x, planar.y)
+}
 
 #[derive(Default, Clone, Copy)]
 pub struct MovementState {
@@ -16,6 +62,7 @@ pub struct MovementState {
     pub down: bool,
     pub rotate_left: bool,
     pub rotate_right: bool,
+    pub sprint: bool,
 }
 
 impl MovementState {
@@ -38,38 +85,173 @@ impl MovementState {
     const fn rotation_velocity(self) -> f32 {
         self.to_direction(self.rotate_right, self.rotate_left)
     }
+
+    /// Movement requested by a `Controller` (e.g. a gamepad) through the default keyboard-equivalent buttons
+    pub fn from_controller(controller: &dyn Controller) -> Self {
+        KeyBindings::default().movement(controller)
+    }
+
+    /// Either source holding a direction moves the camera
+    pub const fn merged(self, other: Self) -> Self {
+        Self {
+            forward: self.forward || other.forward,
+            backward: self.backward || other.backward,
+            left: self.left || other.left,
+            right: self.right || other.right,
+            up: self.up || other.up,
+            down: self.down || other.down,
+            rotate_left: self.rotate_left || other.rotate_left,
+            rotate_right: self.rotate_right || other.rotate_right,
+            sprint: self.sprint || other.sprint,
+        }
+    }
+}
+
+/// Saved camera pose that can be restored later (survives scene reloads)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraBookmark {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov: f32,
+}
+
+impl CameraBookmark {
+    /// One-line form accepted by `--camera`, e.g. `pos=1,2,3 yaw=0.5 pitch=-0.1 fov=0.785`
+    ///
+    /// Angles are radians; values print with full precision so parsing returns the same pose.
+    pub fn to_line(&self) -> String {
+        format!(
+            "pos={},{},{} yaw={} pitch={} fov={}",
+            self.position.x, self.position.y, self.position.z, self.yaw, self.pitch, self.fov
+        )
+    }
+
+    /// Parse the output of `to_line`
+    pub fn parse_line(line: &str) -> Result<Self, String> {
+        let value = |key: &str| {
+            line.split_whitespace()
+                .find_map(|field| field.strip_prefix(key)?.strip_prefix('='))
+                .ok_or_else(|| format!("Missing '{}=' in camera '{}'", key, line))
+        };
+        let number = |s: &str| s.parse::<f32>().map_err(|e| format!("Invalid number '{}' in camera: {}", s, e));
+
+        let position: Vec<f32> = value("pos")?.split(',').map(number).collect::<Result<_, _>>()?;
+        let [x, y, z] = position[..] else {
+            return Err(format!("Camera pos needs 3 components, got {}", position.len()));
+        };
+
+        Ok(Self {
+            position: Vec3::new(x, y, z),
+            yaw: number(value("yaw")?)?,
+            pitch: number(value("pitch")?)?,
+            fov: number(value("fov")?)?,
+        })
+    }
+}
+
+/// Axes WASD moves along
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StrafeMode {
+    /// Follow the view direction (W flies where the camera looks)
+    #[default]
+    Camera,
+    /// Fixed world axes: W/S along +Z, D to the right of +Z (-X), whatever the yaw (handy for top-down views)
+    World,
+}
+
+impl StrafeMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Camera => Self::World,
+            Self::World => Self::Camera,
+        }
+    }
+
+    /// Forward and right movement directions, given the camera's own
+    pub fn axes(self, forward: Vec3, right: Vec3) -> (Vec3, Vec3) {
+        match self {
+            Self::Camera => (forward, right),
+            // Right follows the camera's `forward × up` convention, so D matches screen-right when facing +Z
+            Self::World => (Vec3::Z, Vec3::Z.cross(Vec3::Y)),
+        }
+    }
 }
 
 pub struct Camera {
     pub position: Vec3,
     pub yaw: f32,
     pub pitch: f32,
+    pub fov: f32,
     pub movement: MovementState,
+    /// Runtime movement speed factor, carried across scene reloads by `with_controls_of`
+    pub speed_multiplier: f32,
+    pub strafe: StrafeMode,
+    pub bindings: KeyBindings,
 }
 
 impl Camera {
-    fn calculate_lod_factor(screen_height: f32, fov: f32) -> f32 {
-        debug_assert!(fov > 0.0 && fov < std::f32::consts::PI, "FOV must be in range (0, π)");
-        screen_height / (2.0 * (fov / 2.0).tan())
-    }
-
     pub fn new() -> Self {
         let scene_name = std::env::var("SCENE").unwrap_or_else(|_| "fractal".to_string());
+        Self::for_scene(&scene_name)
+    }
 
-        let (position, yaw, pitch) = match scene_name.as_str() {
-            "composed" => (Vec3::new(0.0, 40.0, 40.0), std::f32::consts::PI, -0.7),
-            "walls" => (Vec3::new(0.0, 5.0, 0.0), 0.0, 0.0),
-            "tunnel" => (Vec3::new(0.0, 0.0, 20.0), std::f32::consts::PI, 0.0),
-            "gltf" => (Vec3::new(200.0, 200.0, 300.0), 3.35, -0.28),
-            "pyramid" => (Vec3::new(0.0, 8.0, 20.0), std::f32::consts::PI, -0.5),
-            _ => (Vec3::new(0.0, 8.0, 15.0), std::f32::consts::PI, -0.6),
-        };
+    /// Default viewpoint for a named scene
+    pub fn for_scene(scene_name: &str) -> Self {
+        let (position, yaw, pitch) = scene_viewpoint(scene_name).unwrap_or(FALLBACK_VIEWPOINT);
 
         Self {
             position,
             yaw,
             pitch,
+            fov: DEFAULT_FOV,
             movement: MovementState::default(),
+            speed_multiplier: 1.0,
+            strafe: StrafeMode::Camera,
+            bindings: KeyBindings::default(),
+        }
+    }
+
+    /// Override the field of view (radians)
+    pub fn with_fov(self, fov: f32) -> Self {
+        Self { fov, ..self }
+    }
+
+    pub fn with_key_bindings(self, bindings: KeyBindings) -> Self {
+        Self { bindings, ..self }
+    }
+
+    /// Keep `other`'s controls (bindings, held keys, speed multiplier, strafe mode) at this camera's pose
+    pub fn with_controls_of(self, other: &Camera) -> Self {
+        Self {
+            movement: other.movement,
+            speed_multiplier: other.speed_multiplier,
+            strafe: other.strafe,
+            bindings: other.bindings,
+            ..self
+        }
+    }
+
+    pub fn snapshot(&self) -> CameraBookmark {
+        CameraBookmark {
+            position: self.position,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            fov: self.fov,
+        }
+    }
+
+    pub fn restore(&mut self, bookmark: &CameraBookmark) {
+        self.position = bookmark.position;
+        self.yaw = bookmark.yaw;
+        self.pitch = bookmark.pitch;
+        self.fov = bookmark.fov;
+    }
+
+    /// Set the pose sampled from `path` at `t` seconds (no-op for an empty path)
+    pub fn follow_path(&mut self, path: &CameraPath, t: f32) {
+        if let Some(pose) = path.sample(t) {
+            self.restore(&pose);
         }
     }
 
@@ -90,19 +272,48 @@ impl Camera {
         Vec3::Y
     }
 
+    /// Direction of the primary ray through pixel `(x, y)`, widened horizontally by the aspect ratio
+    /// (mirrors the compute shader's ray generation)
+    pub fn primary_ray(&self, (x, y): (u32, u32), resolution: Resolution) -> Vec3 {
+        let ndc_x = (x as f32 + 0.5) / resolution.width as f32 * 2.0 - 1.0;
+        let ndc_y = (y as f32 + 0.5) / resolution.height as f32 * 2.0 - 1.0;
+        let fov_scale = self.fov.tan();
+        (self.forward() + self.right() * ndc_x * resolution.aspect_ratio() * fov_scale - self.up() * ndc_y * fov_scale)
+            .normalize()
+    }
+
+    /// Faster (positive) or slower (negative) movement, e.g. from +/- or the scroll wheel
+    pub fn adjust_speed(&mut self, steps: f32) {
+        self.speed_multiplier = adjust_speed_multiplier(self.speed_multiplier, steps);
+    }
+
+    fn speed(&self, movement: MovementState) -> f32 {
+        let sprint = if movement.sprint { SPRINT_MULTIPLIER } else { 1.0 };
+        CAMERA_SPEED * self.speed_multiplier * sprint
+    }
+
     pub fn update(&mut self) {
-        let (fwd, right_dir, up_dir) = self.movement.velocity();
+        self.update_with(MovementState::default());
+    }
 
-        let displacement = self.forward() * fwd * CAMERA_SPEED
-            + self.right() * right_dir * CAMERA_SPEED
-            + Vec3::Y * up_dir * CAMERA_SPEED;
+    /// Advance one frame with keyboard movement combined with `extra` (e.g. a gamepad)
+    pub fn update_with(&mut self, extra: MovementState) {
+        let movement = self.movement.merged(extra);
+        let (fwd, right_dir, up_dir) = movement.velocity();
+        let (fwd, right_dir) = normalize_planar(fwd, right_dir);
+        let speed = self.speed(movement);
+
+        let (forward, right) = self.strafe.axes(self.forward(), self.right());
+        let displacement = forward * fwd * speed
+            + right * right_dir * speed
+            + Vec3::Y * up_dir * speed;
 
         self.position += displacement;
-        self.yaw += self.movement.rotation_velocity() * CAMERA_ROTATION_SPEED;
+        self.yaw += movement.rotation_velocity() * CAMERA_ROTATION_SPEED;
     }
 
-    pub fn to_uniform(&self, time: f32, screen_height: f32, fov: f32, show_grid: bool) -> CameraUniform {
-        let lod_factor = Self::calculate_lod_factor(screen_height, fov);
+    pub fn to_uniform(&self, time: f32, resolution: Resolution, fov: f32, show_grid: bool) -> CameraUniform {
+        let lod_factor = resolution.lod_factor(fov);
         let min_pixel_size = 2.0;
 
         CameraUniform {
@@ -117,24 +328,33 @@ impl Camera {
             lod_factor,
             min_pixel_size,
             show_grid: if show_grid { 1.0 } else { 0.0 },
+            aperture: 0.0,
+            focus_distance: DEFAULT_FOCUS_DISTANCE,
+            samples_per_pixel: 1,
+            fov,
+            ambient: DEFAULT_AMBIENT,
+            near: DEFAULT_NEAR_CLIP,
+            far: DEFAULT_FAR_CLIP,
+            sample_offset: 0,
             _pad4: 0.0,
         }
     }
 
     pub fn process_keyboard(&mut self, event: &KeyEvent) {
-        let is_pressed = event.state.is_pressed();
         if let PhysicalKey::Code(keycode) = event.physical_key {
-            match keycode {
-                KeyCode::KeyW => self.movement.forward = is_pressed,
-                KeyCode::KeyS => self.movement.backward = is_pressed,
-                KeyCode::KeyA => self.movement.left = is_pressed,
-                KeyCode::KeyD => self.movement.right = is_pressed,
-                KeyCode::Space => self.movement.up = is_pressed,
-                KeyCode::ShiftLeft => self.movement.down = is_pressed,
-                KeyCode::KeyQ => self.movement.rotate_left = is_pressed,
-                KeyCode::KeyE => self.movement.rotate_right = is_pressed,
-                _ => {}
-            }
+            self.process_key(keycode, event.state.is_pressed());
+        }
+    }
+
+    /// Press or release `keycode`; movement and sprint keys go through the camera's key bindings
+    pub fn process_key(&mut self, keycode: KeyCode, is_pressed: bool) {
+        let Some(button) = WinitController::keycode_to_button(keycode) else {
+            return;
+        };
+        match button {
+            Button::Plus if is_pressed => self.adjust_speed(1.0),
+            Button::Minus if is_pressed => self.adjust_speed(-1.0),
+            _ => self.bindings.apply(&mut self.movement, button, is_pressed),
         }
     }
 }
@@ -144,3 +364,131 @@ impl Default for Camera {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wide_resolution_spreads_rays_horizontally() {
+        let mut camera = Camera::new();
+        camera.pitch = 0.0;
+        let edge_angle = |resolution: Resolution| {
+            camera.primary_ray((0, resolution.height / 2), resolution).angle_between(camera.forward())
+        };
+
+        let square = edge_angle(Resolution::new(900, 900));
+        let wide = edge_angle(Resolution::new(1600, 900));
+        assert!(wide > square * 1.3, "16:9 edge angle {} vs 1:1 {}", wide, square);
+
+        let top = camera.primary_ray((800, 0), Resolution::new(1600, 900)).angle_between(camera.forward());
+        assert!((top - square).abs() < 0.01, "vertical spread follows the FOV alone");
+    }
+
+    #[test]
+    fn test_snapshot_restore_returns_exact_pose() {
+        let mut camera = Camera::new();
+        let bookmark = camera.snapshot();
+
+        camera.position += Vec3::new(12.5, -3.0, 7.25);
+        camera.yaw += 1.3;
+        camera.pitch -= 0.4;
+        camera.fov = 1.0;
+        camera.movement.forward = true;
+        camera.update();
+
+        camera.restore(&bookmark);
+
+        assert_eq!(camera.snapshot(), bookmark);
+        assert_eq!(camera.position, bookmark.position);
+        assert_eq!(camera.yaw, bookmark.yaw);
+        assert_eq!(camera.pitch, bookmark.pitch);
+        assert_eq!(camera.fov, bookmark.fov);
+    }
+
+    #[test]
+    fn test_camera_line_round_trips() {
+        let bookmark = CameraBookmark {
+            position: Vec3::new(12.345_678, -0.1, 1e-7),
+            yaw: std::f32::consts::PI / 3.0,
+            pitch: -0.123_456_79,
+            fov: DEFAULT_FOV,
+        };
+
+        let line = bookmark.to_line();
+        assert!(!line.contains('\n'));
+        assert_eq!(CameraBookmark::parse_line(&line), Ok(bookmark));
+
+        assert!(CameraBookmark::parse_line("pos=1,2 yaw=0 pitch=0 fov=1").is_err());
+        assert!(CameraBookmark::parse_line("pos=1,2,3 yaw=0 pitch=0").is_err());
+    }
+
+    fn forward_step(sprint: bool, speed_multiplier: f32) -> f32 {
+        let mut camera = Camera::for_scene("tunnel");
+        let start = camera.position;
+        camera.speed_multiplier = speed_multiplier;
+        camera.movement.forward = true;
+        camera.movement.sprint = sprint;
+        camera.update();
+        (camera.position - start).length()
+    }
+
+    #[test]
+    fn test_sprint_doubles_displacement() {
+        let walk = forward_step(false, 1.0);
+        assert!((walk - CAMERA_SPEED).abs() < 1e-5);
+        assert!((forward_step(true, 1.0) - walk * SPRINT_MULTIPLIER).abs() < 1e-5);
+        assert!((forward_step(false, 3.0) - walk * 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_controls_survive_scene_change() {
+        let mut previous = Camera::for_scene("walls");
+        previous.adjust_speed(2.0);
+        previous.strafe = StrafeMode::World;
+        previous.movement.sprint = true;
+
+        let camera = Camera::for_scene("tunnel").with_controls_of(&previous);
+        assert_eq!(camera.snapshot(), Camera::for_scene("tunnel").snapshot());
+        assert_eq!(camera.speed_multiplier, previous.speed_multiplier);
+        assert_eq!(camera.strafe, StrafeMode::World);
+        assert!(camera.movement.sprint);
+    }
+
+    #[test]
+    fn test_remapped_forward_moves_on_arrow_key() {
+        let bindings = KeyBindings { forward: crate::core::Button::KeyUp, ..KeyBindings::default() };
+        let mut camera = Camera::for_scene("walls").with_key_bindings(bindings);
+        let start = camera.position;
+
+        camera.process_key(KeyCode::KeyW, true);
+        camera.update();
+        assert_eq!(camera.position, start);
+
+        camera.process_key(KeyCode::KeyW, false);
+        camera.process_key(KeyCode::ArrowUp, true);
+        camera.update();
+        let moved = camera.position - start;
+        assert!((moved - camera.forward() * CAMERA_SPEED).length() < 1e-5, "{:?}", moved);
+    }
+
+    #[test]
+    fn test_speed_multiplier_is_clamped() {
+        assert_eq!(adjust_speed_multiplier(1.0, 1.0), SPEED_STEP);
+        assert_eq!(adjust_speed_multiplier(1.0, 1000.0), MAX_SPEED_MULTIPLIER);
+        assert_eq!(adjust_speed_multiplier(1.0, -1000.0), MIN_SPEED_MULTIPLIER);
+    }
+
+    #[test]
+    fn test_uniform_carries_ambient() {
+        let uniform = Camera::new().to_uniform(0.0, Resolution::new(600, 600), DEFAULT_FOV, false);
+        assert_eq!(uniform.ambient, DEFAULT_AMBIENT);
+        // Near/far follow ambient, padded so the WGSL struct is a multiple of 16 bytes
+        assert_eq!(std::mem::offset_of!(CameraUniform, ambient), 92);
+        assert_eq!(std::mem::offset_of!(CameraUniform, far), 100);
+        assert_eq!(std::mem::size_of::<CameraUniform>(), 112);
+
+        let dark = CameraUniform { ambient: 0.0, ..uniform };
+        assert_ne!(dark.ambient, uniform.ambient);
+    }
+}
@@ -0,0 +1,159 @@
+// camera_path.rs - Keyframed camera flythroughs
+use anyhow::{Context, Result};
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::camera::CameraBookmark;
+
+/// A camera pose at a point in time (seconds from the start of the path)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    pub time: f32,
+    pub pose: CameraBookmark,
+}
+
+/// Camera flythrough: Catmull-Rom through keyframe positions, linear yaw/pitch/fov
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CameraPath {
+    keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    /// Keyframes are sorted by time
+    pub fn new(mut keyframes: Vec<Keyframe>) -> Self {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self { keyframes }
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Time of the last keyframe
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Append a pose `spacing` seconds after the last keyframe
+    pub fn with_keyframe(mut self, pose: CameraBookmark, spacing: f32) -> Self {
+        let time = self.keyframes.last().map_or(0.0, |k| k.time + spacing);
+        self.keyframes.push(Keyframe { time, pose });
+        self
+    }
+
+    /// Pose at time `t`, clamped to the path's first and last keyframes
+    pub fn sample(&self, t: f32) -> Option<CameraBookmark> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        if t <= first.time {
+            return Some(first.pose);
+        }
+        if t >= last.time {
+            return Some(last.pose);
+        }
+
+        let i = self.keyframes.partition_point(|k| k.time <= t) - 1;
+        let (a, b) = (&self.keyframes[i], &self.keyframes[i + 1]);
+        let u = (t - a.time) / (b.time - a.time);
+
+        let before = self.keyframes[i.saturating_sub(1)].pose.position;
+        let after = self.keyframes[(i + 2).min(self.keyframes.len() - 1)].pose.position;
+        let lerp = |x: f32, y: f32| x + (y - x) * u;
+
+        Some(CameraBookmark {
+            position: catmull_rom(before, a.pose.position, b.pose.position, after, u),
+            yaw: lerp(a.pose.yaw, b.pose.yaw),
+            pitch: lerp(a.pose.pitch, b.pose.pitch),
+            fov: lerp(a.pose.fov, b.pose.fov),
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize camera path")?;
+        std::fs::write(path, json).context(format!("Failed to write camera path: {:?}", path))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).context(format!("Failed to read camera path: {:?}", path))?;
+        let path: CameraPath =
+            serde_json::from_str(&json).context(format!("Failed to parse camera path: {:?}", path))?;
+        Ok(Self::new(path.keyframes))
+    }
+}
+
+/// Uniform Catmull-Rom segment between `p1` (u = 0) and `p2` (u = 1)
+pub fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, u: f32) -> Vec3 {
+    let u2 = u * u;
+    let u3 = u2 * u;
+    p1 + ((p2 - p0) * u + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * u2 + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * u3) * 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pose(x: f32, yaw: f32) -> CameraBookmark {
+        CameraBookmark { position: Vec3::new(x, 2.0, -x), yaw, pitch: -0.1, fov: 0.8 }
+    }
+
+    fn sample_path() -> CameraPath {
+        CameraPath::new(vec![
+            Keyframe { time: 2.0, pose: pose(10.0, 1.0) },
+            Keyframe { time: 0.0, pose: pose(0.0, 0.0) },
+            Keyframe { time: 3.0, pose: pose(15.0, 1.5) },
+            Keyframe { time: 5.0, pose: pose(25.0, 2.5) },
+        ])
+    }
+
+    #[test]
+    fn test_sample_at_keyframe_returns_keyframe() {
+        let path = sample_path();
+        for keyframe in path.keyframes() {
+            assert_eq!(path.sample(keyframe.time), Some(keyframe.pose));
+        }
+    }
+
+    #[test]
+    fn test_midpoints_interpolate_monotonically() {
+        let path = sample_path();
+        let samples: Vec<CameraBookmark> = (0..=50).map(|i| path.sample(i as f32 * 0.1).unwrap()).collect();
+
+        assert!(samples.windows(2).all(|w| w[1].position.x >= w[0].position.x));
+        assert!(samples.windows(2).all(|w| w[1].yaw >= w[0].yaw));
+
+        let mid = path.sample(1.0).unwrap();
+        assert!(mid.position.x > 0.0 && mid.position.x < 10.0);
+        assert!((mid.yaw - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sample_clamps_outside_range() {
+        let path = sample_path();
+        assert_eq!(path.sample(-1.0), Some(pose(0.0, 0.0)));
+        assert_eq!(path.sample(99.0), Some(pose(25.0, 2.5)));
+        assert_eq!(CameraPath::default().sample(0.0), None);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let path = sample_path();
+        let file = std::env::temp_dir().join("ray_tracer_camera_path_test.json");
+        path.save(&file).unwrap();
+        assert_eq!(CameraPath::load(&file).unwrap(), path);
+        std::fs::remove_file(&file).ok();
+    }
+
+    #[test]
+    fn test_with_keyframe_spaces_times() {
+        let path = CameraPath::default().with_keyframe(pose(0.0, 0.0), 2.0).with_keyframe(pose(1.0, 0.0), 2.0);
+        assert_eq!(path.keyframes()[0].time, 0.0);
+        assert_eq!(path.duration(), 2.0);
+    }
+}
@@ -1,11 +1,634 @@
 // cli.rs - Command-line interface configuration
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::{Path, PathBuf};
 
+use crate::camera::CameraBookmark;
+use crate::frame::DEFAULT_IDLE_FPS;
+use crate::lighting::LightingPreset;
+use crate::scenes::DEFAULT_STRESS_COUNT;
+use crate::sky::SkyMode;
+
+pub const DEFAULT_SCENE: &str = "fractal";
+pub const DEFAULT_WIDTH: u32 = 600;
+pub const DEFAULT_HEIGHT: u32 = 600;
+pub const DEFAULT_FOV_DEGREES: f32 = 45.0;
+
+/// Command-line options
+///
+/// Every option can also come from an environment variable (listed in `--help`)
+/// or a `--config` TOML file. Precedence is: command-line flag, then
+/// environment variable, then config file, then default. Switches take an
+/// optional value so a file setting can be turned off, e.g. `--no-ui=false`.
 #[derive(Parser, Debug, Clone)]
 #[command(name = "ray-tracer")]
 #[command(about = "WebGPU Ray Tracer", long_about = None)]
 pub struct Cli {
     /// Disable UI elements and console output
-    #[arg(long = "no-ui", default_value = "false")]
+    #[arg(long = "no-ui", env = "RAY_TRACER_NO_UI", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pub no_ui: Option<bool>,
+
+    /// Refuse to load glTF models with more triangles than this
+    #[arg(long = "max-triangles", env = "RAY_TRACER_MAX_TRIANGLES")]
+    pub max_triangles: Option<usize>,
+
+    /// Number of boxes in the stress scene (defaults to 10000)
+    #[arg(long = "stress-count", env = "RAY_TRACER_STRESS_COUNT")]
+    pub stress_count: Option<usize>,
+
+    /// Scene to load (defaults to "fractal")
+    #[arg(long, env = "SCENE")]
+    pub scene: Option<String>,
+
+    /// Initial window width in pixels (defaults to 600)
+    #[arg(long, env = "RAY_TRACER_WIDTH", value_parser = clap::value_parser!(u32).range(1..))]
+    pub width: Option<u32>,
+
+    /// Initial window height in pixels (defaults to 600)
+    #[arg(long, env = "RAY_TRACER_HEIGHT", value_parser = clap::value_parser!(u32).range(1..))]
+    pub height: Option<u32>,
+
+    /// Camera field of view in degrees (defaults to 45); a camera saved in a `file:` scene replaces it
+    #[arg(long, env = "RAY_TRACER_FOV", value_parser = parse_fov)]
+    pub fov: Option<f32>,
+
+    /// Cap the frame rate (uncapped when absent)
+    #[arg(long, env = "RAY_TRACER_FPS", value_parser = parse_positive_f32)]
+    pub fps: Option<f32>,
+
+    /// Frame rate while the window is unfocused (defaults to 5)
+    #[arg(long = "idle-fps", env = "RAY_TRACER_IDLE_FPS", value_parser = parse_positive_f32)]
+    pub idle_fps: Option<f32>,
+
+    /// Back up edited scenes every N seconds (off when absent)
+    #[arg(long = "autosave-interval", env = "RAY_TRACER_AUTOSAVE_INTERVAL", value_parser = parse_positive_f32)]
+    pub autosave_interval: Option<f32>,
+
+    /// Ambient light level (0.0-1.0); defaults per scene
+    #[arg(long, env = "RAY_TRACER_AMBIENT", value_parser = parse_unit_f32)]
+    pub ambient: Option<f32>,
+
+    /// Lighting preset: default (single sun) or studio (key + fill)
+    #[arg(long, env = "RAY_TRACER_LIGHTING", value_parser = parse_lighting)]
+    pub lighting: Option<LightingPreset>,
+
+    /// Make only the ground box reflective (0.0-1.0), in any scene
+    #[arg(long = "reflective-floor", env = "RAY_TRACER_REFLECTIVE_FLOOR", value_parser = parse_unit_f32)]
+    pub reflective_floor: Option<f32>,
+
+    /// Background: gradient, procedural (sun-lit, follows the key light) or solid
+    #[arg(long, env = "RAY_TRACER_SKY", value_parser = parse_sky)]
+    pub sky: Option<SkyMode>,
+
+    /// Equirectangular PNG/HDR image used as the background and in reflections
+    #[arg(long, env = "RAY_TRACER_SKYBOX")]
+    pub skybox: Option<PathBuf>,
+
+    /// Exponential distance fog density towards the horizon color (off when absent)
+    #[arg(long, env = "RAY_TRACER_FOG", value_parser = parse_positive_f32)]
+    pub fog: Option<f32>,
+
+    /// Keep the --width/--height aspect when the window is resized, adding black bars
+    #[arg(long = "preserve-aspect", env = "RAY_TRACER_PRESERVE_ASPECT", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pub preserve_aspect: Option<bool>,
+
+    /// Adjust exposure each frame towards the average scene brightness
+    #[arg(long = "auto-exposure", env = "RAY_TRACER_AUTO_EXPOSURE", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pub auto_exposure: Option<bool>,
+
+    /// Generate the fractal scene with less detail far from the camera, regenerating as it moves
+    #[arg(long = "fractal-lod", env = "RAY_TRACER_FRACTAL_LOD", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pub fractal_lod: Option<bool>,
+
+    /// Camera flythrough JSON to play back with P (K records keyframes, Ctrl+K saves)
+    #[arg(long = "camera-path", env = "RAY_TRACER_CAMERA_PATH")]
+    pub camera_path: Option<PathBuf>,
+
+    /// TOML file remapping movement keys, e.g. `forward = "KeyZ"` for AZERTY; a bound key replaces
+    /// any single-key shortcut on it (Alt chords still work)
+    #[arg(long = "key-bindings", env = "RAY_TRACER_KEY_BINDINGS")]
+    pub key_bindings: Option<PathBuf>,
+
+    /// Open one window per scene for side-by-side comparison, e.g. "walls,tunnel"
+    #[arg(long, env = "RAY_TRACER_WINDOWS", value_delimiter = ',')]
+    pub windows: Option<Vec<String>>,
+
+    /// Drive every window from one shared camera
+    #[arg(long = "sync-camera", env = "RAY_TRACER_SYNC_CAMERA", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pub sync_camera: Option<bool>,
+
+    /// Starting camera as copied from the UI, e.g. "pos=0,2,-8 yaw=1.57 pitch=0 fov=0.785";
+    /// wins over a camera saved in a `file:` scene
+    #[arg(long, env = "RAY_TRACER_CAMERA", value_parser = parse_camera)]
+    pub camera: Option<CameraBookmark>,
+
+    /// Without a GPU, write a low-res CPU preview (cpu_preview.png) instead of exiting; ignored with a GPU
+    #[arg(long = "cpu-fallback", env = "RAY_TRACER_CPU_FALLBACK", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pub cpu_fallback: Option<bool>,
+
+    /// TOML file providing defaults for any of the options above
+    #[arg(long, env = "RAY_TRACER_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Print the resolved configuration as JSON and exit
+    #[arg(long = "print-config")]
+    pub print_config: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Render scenes headless and print average/p95 frame times
+    Profile {
+        /// Scene to profile, or "all"
+        #[arg(long, default_value = "all")]
+        scene: String,
+
+        /// Frames to measure per scene
+        #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+        frames: u64,
+    },
+    /// Render a scene headless and write the final frame as PNG
+    Render {
+        /// Scene to render (defaults to --scene / SCENE)
+        #[arg(long)]
+        scene: Option<String>,
+
+        /// Output PNG path
+        #[arg(long, default_value = "render.png")]
+        out: PathBuf,
+
+        /// Frames to render before writing, advancing time each frame
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+        frames: u64,
+    },
+}
+
+/// Option defaults loaded from a `--config` TOML file; every key is optional
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub scene: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fov: Option<f32>,
+    pub fps: Option<f32>,
+    pub idle_fps: Option<f32>,
+    pub autosave_interval: Option<f32>,
+    pub no_ui: Option<bool>,
+    pub max_triangles: Option<usize>,
+    pub stress_count: Option<usize>,
+    pub ambient: Option<f32>,
+    pub lighting: Option<LightingPreset>,
+    pub reflective_floor: Option<f32>,
+    pub sky: Option<SkyMode>,
+    pub skybox: Option<PathBuf>,
+    pub fog: Option<f32>,
+    pub preserve_aspect: Option<bool>,
+    pub auto_exposure: Option<bool>,
+    pub fractal_lod: Option<bool>,
+    pub camera_path: Option<PathBuf>,
+    pub key_bindings: Option<PathBuf>,
+    pub camera: Option<CameraBookmark>,
+    pub windows: Option<Vec<String>>,
+    pub sync_camera: Option<bool>,
+    pub cpu_fallback: Option<bool>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config {}: {}", path.display(), e))?;
+        Ok(toml::from_str(&text).map_err(|e| format!("Invalid config {}: {}", path.display(), e))?)
+    }
+}
+
+/// Fully resolved startup configuration (flags, env vars, config file and defaults applied)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RayTracerConfig {
+    pub scene: String,
+    pub width: u32,
+    pub height: u32,
+    /// Field of view in degrees
+    pub fov: f32,
+    pub fps: Option<f32>,
+    /// Frame rate cap while no window has focus
+    pub idle_fps: f32,
+    /// Seconds between backups of edited scenes; `None` disables auto-save
+    pub autosave_interval: Option<f32>,
     pub no_ui: bool,
+    pub max_triangles: Option<usize>,
+    /// Boxes in the `stress` scene
+    pub stress_count: usize,
+    /// Ambient light override; `None` uses the scene's default
+    pub ambient: Option<f32>,
+    pub lighting: LightingPreset,
+    pub reflective_floor: Option<f32>,
+    pub sky: SkyMode,
+    pub skybox: Option<PathBuf>,
+    /// Fog density; `None` disables fog
+    pub fog: Option<f32>,
+    /// Letterbox instead of stretching when the window aspect differs from `width` / `height`
+    pub preserve_aspect: bool,
+    pub auto_exposure: bool,
+    /// Distance-based detail for the fractal scene
+    pub fractal_lod: bool,
+    pub camera_path: Option<PathBuf>,
+    /// Movement key remapping; WASD/QE/Space/Shift when `None`
+    pub key_bindings: Option<PathBuf>,
+    /// Starting camera pose; overrides the scene default, a camera saved in the scene file, and `fov`
+    pub camera: Option<CameraBookmark>,
+    /// Scenes opened in separate windows; empty opens just `scene`
+    pub windows: Vec<String>,
+    /// All windows share one camera
+    pub sync_camera: bool,
+    /// Render a CPU preview instead of failing when no GPU is found
+    pub cpu_fallback: bool,
+}
+
+impl RayTracerConfig {
+    /// Reject values the flag parsers would refuse, e.g. `fps = 0` from a config file
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(format!("Resolution {}x{} must be non-zero", self.width, self.height));
+        }
+        check(parse_fov, "fov", self.fov)?;
+        check(parse_positive_f32, "fps", self.fps)?;
+        check(parse_positive_f32, "idle_fps", self.idle_fps)?;
+        check(parse_positive_f32, "autosave_interval", self.autosave_interval)?;
+        check(parse_positive_f32, "fog", self.fog)?;
+        check(parse_unit_f32, "ambient", self.ambient)?;
+        check(parse_unit_f32, "reflective_floor", self.reflective_floor)?;
+        Ok(())
+    }
+}
+
+/// Run a flag parser over an already-merged value so files get the same checks as flags
+fn check(parser: fn(&str) -> Result<f32, String>, name: &str, value: impl Into<Option<f32>>) -> Result<(), String> {
+    match value.into() {
+        Some(v) => parser(&v.to_string()).map(|_| ()).map_err(|e| format!("Invalid {}: {}", name, e)),
+        None => Ok(()),
+    }
+}
+
+impl Cli {
+    /// Scene from `--scene`, else the SCENE env var, else the default; a `--config` file's scene
+    /// is only applied by `config()`
+    pub fn scene_name(&self) -> String {
+        self.scene.clone().unwrap_or_else(|| DEFAULT_SCENE.to_string())
+    }
+
+    /// Resolve the configuration, loading `--config` when given
+    pub fn config(&self) -> Result<RayTracerConfig, Box<dyn Error>> {
+        let file = match &self.config {
+            Some(path) => ConfigFile::load(path)?,
+            None => ConfigFile::default(),
+        };
+        let config = self.merge(file);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Layer the parsed flags over config file values, then defaults
+    pub fn merge(&self, file: ConfigFile) -> RayTracerConfig {
+        RayTracerConfig {
+            scene: self.scene.clone().or(file.scene).unwrap_or_else(|| DEFAULT_SCENE.to_string()),
+            width: self.width.or(file.width).unwrap_or(DEFAULT_WIDTH),
+            height: self.height.or(file.height).unwrap_or(DEFAULT_HEIGHT),
+            fov: self.fov.or(file.fov).unwrap_or(DEFAULT_FOV_DEGREES),
+            fps: self.fps.or(file.fps),
+            idle_fps: self.idle_fps.or(file.idle_fps).unwrap_or(DEFAULT_IDLE_FPS),
+            autosave_interval: self.autosave_interval.or(file.autosave_interval),
+            no_ui: self.no_ui.or(file.no_ui).unwrap_or(false),
+            max_triangles: self.max_triangles.or(file.max_triangles),
+            stress_count: self.stress_count.or(file.stress_count).unwrap_or(DEFAULT_STRESS_COUNT),
+            ambient: self.ambient.or(file.ambient),
+            lighting: self.lighting.or(file.lighting).unwrap_or_default(),
+            reflective_floor: self.reflective_floor.or(file.reflective_floor),
+            sky: self.sky.or(file.sky).unwrap_or_default(),
+            skybox: self.skybox.clone().or(file.skybox),
+            fog: self.fog.or(file.fog),
+            preserve_aspect: self.preserve_aspect.or(file.preserve_aspect).unwrap_or(false),
+            auto_exposure: self.auto_exposure.or(file.auto_exposure).unwrap_or(false),
+            fractal_lod: self.fractal_lod.or(file.fractal_lod).unwrap_or(false),
+            camera_path: self.camera_path.clone().or(file.camera_path),
+            key_bindings: self.key_bindings.clone().or(file.key_bindings),
+            camera: self.camera.or(file.camera),
+            windows: self.windows.clone().or(file.windows).unwrap_or_default(),
+            sync_camera: self.sync_camera.or(file.sync_camera).unwrap_or(false),
+            cpu_fallback: self.cpu_fallback.or(file.cpu_fallback).unwrap_or(false),
+        }
+    }
+}
+
+fn parse_positive_f32(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(v) if v > 0.0 && v.is_finite() => Ok(v),
+        Ok(_) => Err(format!("{} must be a positive number", s)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn parse_fov(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(v) if v > 0.0 && v < 180.0 => Ok(v),
+        Ok(_) => Err(format!("{} must be between 0 and 180 degrees", s)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn parse_unit_f32(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(v) if (0.0..=1.0).contains(&v) => Ok(v),
+        Ok(_) => Err(format!("{} must be between 0 and 1", s)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn parse_lighting(s: &str) -> Result<LightingPreset, String> {
+    LightingPreset::from_name(s).ok_or_else(|| format!("Unknown lighting preset '{}' (expected default or studio)", s))
+}
+
+fn parse_camera(s: &str) -> Result<CameraBookmark, String> {
+    CameraBookmark::parse_line(s)
+}
+
+fn parse_sky(s: &str) -> Result<SkyMode, String> {
+    SkyMode::from_name(s).ok_or_else(|| format!("Unknown sky '{}' (expected gradient, procedural or solid)", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    /// Parses `args` with every `env = ...` lookup disabled, so a caller's `SCENE` or `RAY_TRACER_*`
+    /// cannot leak in; env precedence is covered by tests/cli_env.rs
+    fn parse<const N: usize>(args: [&str; N]) -> Result<Cli, clap::Error> {
+        let command = Cli::command().mut_args(|arg| arg.env(None::<&str>));
+        Cli::from_arg_matches(&command.try_get_matches_from(args)?)
+    }
+
+    #[test]
+    fn test_parse_all_flags() {
+        let cli = parse([
+            "ray-tracer", "--scene", "walls", "--width", "1280", "--height", "720", "--fps", "30",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.scene.as_deref(), Some("walls"));
+        assert_eq!(cli.scene_name(), "walls");
+        assert_eq!(cli.width, Some(1280));
+        assert_eq!(cli.height, Some(720));
+        assert_eq!(cli.fps, Some(30.0));
+        assert_eq!(cli.no_ui, None);
+    }
+
+    #[test]
+    fn test_defaults() {
+        let cli = parse(["ray-tracer"]).unwrap();
+
+        assert_eq!(cli.scene, None);
+        assert_eq!(cli.width, None);
+        assert_eq!(cli.height, None);
+        assert_eq!(cli.fps, None);
+        assert_eq!(cli.max_triangles, None);
+        assert_eq!(cli.reflective_floor, None);
+    }
+
+    #[test]
+    fn test_profile_subcommand() {
+        let cli = parse(["ray-tracer", "profile", "--scene", "walls", "--frames", "20"]).unwrap();
+        assert_eq!(cli.command, Some(Command::Profile { scene: "walls".to_string(), frames: 20 }));
+
+        let cli = parse(["ray-tracer", "profile"]).unwrap();
+        assert_eq!(cli.command, Some(Command::Profile { scene: "all".to_string(), frames: 100 }));
+    }
+
+    #[test]
+    fn test_render_subcommand() {
+        let cli = parse([
+            "ray-tracer", "--width", "64", "--height", "32", "render", "--scene", "pyramid", "--out", "out.png", "--frames", "3",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Command::Render { scene: Some("pyramid".to_string()), out: PathBuf::from("out.png"), frames: 3 })
+        );
+        assert_eq!((cli.width, cli.height), (Some(64), Some(32)));
+        assert!(parse(["ray-tracer", "render", "--frames", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_reflective_floor_range() {
+        let cli = parse(["ray-tracer", "--reflective-floor", "0.7"]).unwrap();
+        assert_eq!(cli.reflective_floor, Some(0.7));
+        assert!(parse(["ray-tracer", "--reflective-floor", "1.5"]).is_err());
+    }
+
+    #[test]
+    fn test_print_config_resolves_flags() {
+        let cli = parse([
+            "ray-tracer", "--print-config", "--scene", "tunnel", "--width", "800", "--fps", "60", "--no-ui",
+        ])
+        .unwrap();
+        assert!(cli.print_config);
+
+        let config = cli.config().unwrap();
+        assert_eq!(config.scene, "tunnel");
+        assert_eq!(config.width, 800);
+        assert_eq!(config.height, 600);
+        assert_eq!(config.fps, Some(60.0));
+        assert!(config.no_ui);
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains("\"scene\":\"tunnel\""));
+    }
+
+    #[test]
+    fn test_config_file_merged_under_flags() {
+        let path = std::env::temp_dir().join("ray_tracer_cli_config_test.toml");
+        std::fs::write(&path, "scene = \"tunnel\"\nwidth = 1280\nheight = 720\nfov = 60.0\n").unwrap();
+        let path_arg = path.to_str().unwrap();
+
+        let from_file = parse(["ray-tracer", "--config", path_arg]).unwrap().config().unwrap();
+        assert_eq!(from_file.scene, "tunnel");
+        assert_eq!((from_file.width, from_file.height), (1280, 720));
+        assert_eq!(from_file.fov, 60.0);
+
+        let overridden = parse(["ray-tracer", "--config", path_arg, "--width", "800", "--fov", "30"])
+            .unwrap()
+            .config()
+            .unwrap();
+        assert_eq!(overridden.scene, "tunnel");
+        assert_eq!((overridden.width, overridden.height), (800, 720));
+        assert_eq!(overridden.fov, 30.0);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_config_file_rejects_unknown_keys() {
+        assert!(toml::from_str::<ConfigFile>("widht = 10").is_err());
+    }
+
+    #[test]
+    fn test_defaults_without_config_file() {
+        let config = parse(["ray-tracer"]).unwrap().config().unwrap();
+        assert_eq!(config.scene, DEFAULT_SCENE);
+        assert_eq!((config.width, config.height), (DEFAULT_WIDTH, DEFAULT_HEIGHT));
+        assert_eq!(config.fov, DEFAULT_FOV_DEGREES);
+    }
+
+    #[test]
+    fn test_ambient_zero_is_distinct_from_default() {
+        assert_eq!(parse(["ray-tracer"]).unwrap().config().unwrap().ambient, None);
+
+        let dark = parse(["ray-tracer", "--ambient", "0"]).unwrap().config().unwrap();
+        assert_eq!(dark.ambient, Some(0.0));
+
+        assert!(parse(["ray-tracer", "--ambient", "1.5"]).is_err());
+    }
+
+    #[test]
+    fn test_fog_density() {
+        assert_eq!(parse(["ray-tracer"]).unwrap().config().unwrap().fog, None);
+        assert_eq!(parse(["ray-tracer", "--fog", "0.05"]).unwrap().config().unwrap().fog, Some(0.05));
+        assert!(parse(["ray-tracer", "--fog", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_key_bindings_path() {
+        assert_eq!(parse(["ray-tracer"]).unwrap().config().unwrap().key_bindings, None);
+        let config = parse(["ray-tracer", "--key-bindings", "keys.toml"]).unwrap().config().unwrap();
+        assert_eq!(config.key_bindings, Some(PathBuf::from("keys.toml")));
+    }
+
+    #[test]
+    fn test_fractal_lod_flag() {
+        assert!(!parse(["ray-tracer"]).unwrap().config().unwrap().fractal_lod);
+        assert!(parse(["ray-tracer", "--fractal-lod"]).unwrap().config().unwrap().fractal_lod);
+    }
+
+    #[test]
+    fn test_cpu_fallback_flag() {
+        assert!(!parse(["ray-tracer"]).unwrap().config().unwrap().cpu_fallback);
+        assert!(parse(["ray-tracer", "--cpu-fallback"]).unwrap().config().unwrap().cpu_fallback);
+        assert!(!parse(["ray-tracer", "--cpu-fallback=false"]).unwrap().config().unwrap().cpu_fallback);
+    }
+
+    #[test]
+    fn test_preserve_aspect_flag() {
+        assert!(!parse(["ray-tracer"]).unwrap().config().unwrap().preserve_aspect);
+        assert!(parse(["ray-tracer", "--preserve-aspect"]).unwrap().config().unwrap().preserve_aspect);
+        let file = ConfigFile { preserve_aspect: Some(true), ..ConfigFile::default() };
+        assert!(parse(["ray-tracer"]).unwrap().merge(file).preserve_aspect);
+    }
+
+    #[test]
+    fn test_windows_list() {
+        assert!(parse(["ray-tracer"]).unwrap().config().unwrap().windows.is_empty());
+        let config = parse(["ray-tracer", "--windows", "walls,tunnel"]).unwrap().config().unwrap();
+        assert_eq!(config.windows, ["walls", "tunnel"]);
+    }
+
+    #[test]
+    fn test_idle_fps() {
+        assert_eq!(parse(["ray-tracer"]).unwrap().config().unwrap().idle_fps, DEFAULT_IDLE_FPS);
+        assert_eq!(parse(["ray-tracer", "--idle-fps", "1"]).unwrap().config().unwrap().idle_fps, 1.0);
+        assert!(parse(["ray-tracer", "--idle-fps", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_autosave_interval() {
+        assert_eq!(parse(["ray-tracer"]).unwrap().config().unwrap().autosave_interval, None);
+        let config = parse(["ray-tracer", "--autosave-interval", "30"]).unwrap().config().unwrap();
+        assert_eq!(config.autosave_interval, Some(30.0));
+        assert!(parse(["ray-tracer", "--autosave-interval", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_sync_camera_flag() {
+        assert!(!parse(["ray-tracer"]).unwrap().config().unwrap().sync_camera);
+        let config = parse(["ray-tracer", "--windows", "walls,tunnel", "--sync-camera"]).unwrap().config().unwrap();
+        assert!(config.sync_camera);
+    }
+
+    #[test]
+    fn test_stress_count() {
+        assert_eq!(parse(["ray-tracer"]).unwrap().config().unwrap().stress_count, DEFAULT_STRESS_COUNT);
+        let config = parse(["ray-tracer", "--stress-count", "50000"]).unwrap().config().unwrap();
+        assert_eq!(config.stress_count, 50_000);
+    }
+
+    #[test]
+    fn test_lighting_preset() {
+        assert_eq!(parse(["ray-tracer"]).unwrap().config().unwrap().lighting, LightingPreset::Default);
+        assert_eq!(parse(["ray-tracer", "--lighting", "studio"]).unwrap().lighting, Some(LightingPreset::Studio));
+        assert!(parse(["ray-tracer", "--lighting", "disco"]).is_err());
+        assert_eq!(toml::from_str::<ConfigFile>("lighting = \"studio\"").unwrap().lighting, Some(LightingPreset::Studio));
+    }
+
+    #[test]
+    fn test_sky_mode() {
+        assert_eq!(parse(["ray-tracer"]).unwrap().config().unwrap().sky, SkyMode::Gradient);
+        assert_eq!(parse(["ray-tracer", "--sky", "procedural"]).unwrap().sky, Some(SkyMode::Procedural));
+        assert!(parse(["ray-tracer", "--sky", "starry"]).is_err());
+        assert_eq!(toml::from_str::<ConfigFile>("sky = \"procedural\"").unwrap().sky, Some(SkyMode::Procedural));
+    }
+
+    #[test]
+    fn test_camera_line() {
+        let cli = parse(["ray-tracer", "--camera", "pos=0,2,-8 yaw=1.5 pitch=0 fov=0.8"]).unwrap();
+        assert_eq!(cli.camera.map(|c| (c.position.y, c.yaw)), Some((2.0, 1.5)));
+        assert!(parse(["ray-tracer", "--camera", "pos=0,2 yaw=1.5"]).is_err());
+    }
+
+    #[test]
+    fn test_skybox_path() {
+        assert_eq!(parse(["ray-tracer"]).unwrap().config().unwrap().skybox, None);
+        let cli = parse(["ray-tracer", "--skybox", "sky.hdr"]).unwrap();
+        assert_eq!(cli.merge(ConfigFile::default()).skybox, Some(PathBuf::from("sky.hdr")));
+        let file = toml::from_str::<ConfigFile>("skybox = \"studio.png\"").unwrap();
+        assert_eq!(parse(["ray-tracer"]).unwrap().merge(file).skybox, Some(PathBuf::from("studio.png")));
+    }
+
+    #[test]
+    fn test_zero_resolution_rejected() {
+        assert!(parse(["ray-tracer", "--width", "0"]).is_err());
+        assert!(parse(["ray-tracer", "--height", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_non_positive_fps_rejected() {
+        assert!(parse(["ray-tracer", "--fps", "0"]).is_err());
+        assert!(parse(["ray-tracer", "--fps", "-5"]).is_err());
+    }
+
+    #[test]
+    fn test_config_file_values_validated() {
+        let file = |text: &str| toml::from_str::<ConfigFile>(text).unwrap();
+        let cli = parse(["ray-tracer"]).unwrap();
+        assert!(cli.merge(file("fps = 0.0")).validate().is_err());
+        assert!(cli.merge(file("idle_fps = 0.0")).validate().is_err());
+        assert!(cli.merge(file("width = 0")).validate().is_err());
+        assert!(cli.merge(file("fov = 180.0")).validate().is_err());
+        assert!(cli.merge(file("ambient = 2.0")).validate().is_err());
+        assert!(cli.merge(file("fps = 30.0\nidle_fps = 2.0")).validate().is_ok());
+    }
+
+    #[test]
+    fn test_flag_can_turn_off_file_switch() {
+        let file = ConfigFile { no_ui: Some(true), fractal_lod: Some(true), ..ConfigFile::default() };
+        let config = parse(["ray-tracer", "--no-ui=false", "--fractal-lod=false"]).unwrap().merge(file.clone());
+        assert!(!config.no_ui && !config.fractal_lod);
+        let config = parse(["ray-tracer"]).unwrap().merge(file);
+        assert!(config.no_ui && config.fractal_lod);
+        let cli = parse(["ray-tracer", "--no-ui", "profile"]).unwrap();
+        assert_eq!(cli.no_ui, Some(true));
+        assert!(cli.command.is_some());
+    }
 }
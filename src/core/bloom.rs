@@ -0,0 +1,117 @@
+/// CPU bloom post-process for layer pixel buffers
+///
+/// Extracts pixels brighter than a threshold, blurs them and adds the glow
+/// back on top of the original image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BloomSettings {
+    /// Luminance (0.0-1.0) above which pixels contribute to the glow
+    pub threshold: f32,
+    /// Strength of the glow added back to the image
+    pub intensity: f32,
+    /// Box blur radius in pixels
+    pub radius: usize,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 0.8,
+            intensity: 0.6,
+            radius: 4,
+        }
+    }
+}
+
+/// Rec. 709 luminance of a linear RGB color
+pub fn luminance(rgb: [f32; 3]) -> f32 {
+    0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2]
+}
+
+/// Bright-pass filter: keeps colors above `threshold` luminance, black otherwise
+pub fn bright_pass(rgb: [f32; 3], threshold: f32) -> [f32; 3] {
+    if luminance(rgb) > threshold {
+        rgb
+    } else {
+        [0.0; 3]
+    }
+}
+
+/// Apply bloom to an RGBA8 buffer, returning a new buffer (alpha is preserved)
+pub fn apply_bloom(pixels: &[u8], width: usize, height: usize, settings: &BloomSettings) -> Vec<u8> {
+    let bright: Vec<[f32; 3]> = pixels
+        .chunks_exact(4)
+        .map(|p| bright_pass([p[0] as f32 / 255.0, p[1] as f32 / 255.0, p[2] as f32 / 255.0], settings.threshold))
+        .collect();
+
+    let horizontal = box_blur(&bright, width, height, settings.radius, (1, 0));
+    let glow = box_blur(&horizontal, width, height, settings.radius, (0, 1));
+
+    pixels
+        .chunks_exact(4)
+        .zip(glow)
+        .flat_map(|(p, g)| {
+            let add = |c: u8, g: f32| (c as f32 + g * settings.intensity * 255.0).round().min(255.0) as u8;
+            [add(p[0], g[0]), add(p[1], g[1]), add(p[2], g[2]), p[3]]
+        })
+        .collect()
+}
+
+/// One-dimensional box blur along `step` (edge pixels are clamped)
+fn box_blur(src: &[[f32; 3]], width: usize, height: usize, radius: usize, step: (usize, usize)) -> Vec<[f32; 3]> {
+    let weight = 1.0 / (2 * radius + 1) as f32;
+    let r = radius as isize;
+
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            (-r..=r).fold([0.0; 3], |mut acc, offset| {
+                let sx = (x as isize + offset * step.0 as isize).clamp(0, width as isize - 1) as usize;
+                let sy = (y as isize + offset * step.1 as isize).clamp(0, height as isize - 1) as usize;
+                let s = src[sy * width + sx];
+                acc[0] += s[0] * weight;
+                acc[1] += s[1] * weight;
+                acc[2] += s[2] * weight;
+                acc
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bright_pass_keeps_bright_pixels() {
+        assert_eq!(bright_pass([1.0, 1.0, 1.0], 0.8), [1.0, 1.0, 1.0]);
+        assert_eq!(bright_pass([0.9, 0.95, 0.9], 0.8), [0.9, 0.95, 0.9]);
+    }
+
+    #[test]
+    fn test_bright_pass_drops_dim_pixels() {
+        assert_eq!(bright_pass([0.5, 0.5, 0.5], 0.8), [0.0; 3]);
+        // Saturated blue is dim in luminance terms
+        assert_eq!(bright_pass([0.0, 0.0, 1.0], 0.8), [0.0; 3]);
+    }
+
+    #[test]
+    fn test_bloom_spreads_glow_to_neighbors() {
+        let (width, height) = (5, 1);
+        let mut pixels = vec![0u8; width * height * 4];
+        pixels[8..12].copy_from_slice(&[255, 255, 255, 255]);
+
+        let settings = BloomSettings { threshold: 0.5, intensity: 1.0, radius: 1 };
+        let out = apply_bloom(&pixels, width, height, &settings);
+
+        assert!(out[4] > 0, "left neighbor should glow");
+        assert!(out[12] > 0, "right neighbor should glow");
+        assert_eq!(out[0], 0, "glow should not reach beyond the radius");
+        assert_eq!(out[3], 0, "alpha is preserved");
+    }
+
+    #[test]
+    fn test_dark_image_is_unchanged() {
+        let pixels = vec![40u8; 3 * 3 * 4];
+        assert_eq!(apply_bloom(&pixels, 3, 3, &BloomSettings::default()), pixels);
+    }
+}
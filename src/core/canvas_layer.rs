@@ -1,6 +1,10 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::sync::Arc;
+
 use super::controller::Controller;
 use super::display_context::DisplayContext;
-use super::layer::{Layer, LayerLogic, LayerOutput, TimedLayer};
+use super::layer::{blend_channel, BlendMode, Layer, LayerLogic, LayerOutput, TimedLayer};
 
 /// 2D drawing operations for canvas
 #[derive(Debug, Clone, PartialEq)]
@@ -23,15 +27,42 @@ pub enum DrawOp {
     /// Draw circle at (cx, cy) with radius and color
     Circle { cx: u32, cy: u32, radius: u32, r: u8, g: u8, b: u8, a: u8 },
 
+    /// Draw an anti-aliased ring of `thickness` centered on `radius`; sub-pixel positions and sizes are kept
+    CircleAA { cx: f32, cy: f32, radius: f32, thickness: f32, r: u8, g: u8, b: u8, a: u8 },
+
     /// Draw filled circle at (cx, cy) with radius and color
     FilledCircle { cx: u32, cy: u32, radius: u32, r: u8, g: u8, b: u8, a: u8 },
 
     /// Draw line from (x1, y1) to (x2, y2) with color
     Line { x1: u32, y1: u32, x2: u32, y2: u32, r: u8, g: u8, b: u8, a: u8 },
+
+    /// Fill canvas with a linear gradient from (x0, y0) to (x1, y1), colors as RGBA
+    LinearGradient { x0: u32, y0: u32, x1: u32, y1: u32, color_start: [u8; 4], color_end: [u8; 4] },
+
+    /// Fill canvas with a radial gradient from the center (cx, cy) out to radius, colors as RGBA
+    RadialGradient { cx: u32, cy: u32, radius: u32, inner: [u8; 4], outer: [u8; 4] },
+
+    /// Draw the circle outline between two angles (radians, counter-clockwise from +x with y up)
+    Arc { cx: u32, cy: u32, radius: u32, start_angle: f32, end_angle: f32, r: u8, g: u8, b: u8, a: u8 },
+
+    /// Fill the wedge between two angles, including both radii (angles as for `Arc`)
+    PieSlice { cx: u32, cy: u32, radius: u32, start_angle: f32, end_angle: f32, r: u8, g: u8, b: u8, a: u8 },
+
+    /// Alpha-blend another canvas (e.g. from `Canvas::from_image`) with its top-left at (x, y), clipped to this canvas
+    Image { x: u32, y: u32, canvas: Arc<Canvas> },
+}
+
+/// Resampling used by `Canvas::resize`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleFilter {
+    /// Copy the closest source pixel; keeps hard edges
+    Nearest,
+    /// Blend the four closest source pixels
+    Bilinear,
 }
 
 /// Canvas state - pixel buffer with draw operations
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Canvas {
     /// RGBA pixel buffer
     pixels: Vec<u8>,
@@ -59,6 +90,24 @@ impl Canvas {
         }
     }
 
+    /// Load a PNG (or any format the `image` crate reads) as a canvas with its own alpha
+    pub fn from_image(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .with_context(|| format!("Failed to load canvas image {}", path.display()))?
+            .to_rgba8();
+        let (width, height) = image.dimensions();
+        let pixels = image.into_raw();
+
+        Ok(Self {
+            alpha: pixels.chunks_exact(4).map(|p| p[3] as f32 / 255.0).collect(),
+            pixels,
+            operations: Vec::new(),
+            width,
+            height,
+        })
+    }
+
     /// Add draw operation - functional style
     pub fn draw(mut self, op: DrawOp) -> Self {
         self.operations.push(op);
@@ -95,12 +144,127 @@ impl Canvas {
             DrawOp::Circle { cx, cy, radius, r, g, b, a } => {
                 self.draw_circle(*cx, *cy, *radius, *r, *g, *b, *a)
             }
+            DrawOp::CircleAA { cx, cy, radius, thickness, r, g, b, a } => {
+                self.draw_circle_aa((*cx, *cy), *radius, *thickness, [*r, *g, *b, *a])
+            }
             DrawOp::FilledCircle { cx, cy, radius, r, g, b, a } => {
                 self.draw_filled_circle(*cx, *cy, *radius, *r, *g, *b, *a)
             }
             DrawOp::Line { x1, y1, x2, y2, r, g, b, a } => {
                 self.draw_line(*x1, *y1, *x2, *y2, *r, *g, *b, *a)
             }
+            DrawOp::LinearGradient { x0, y0, x1, y1, color_start, color_end } => {
+                let (ax, ay) = (*x1 as f32 - *x0 as f32, *y1 as f32 - *y0 as f32);
+                let len_sq = ax * ax + ay * ay;
+                self.fill_gradient(*color_start, *color_end, |x, y| {
+                    // Projection onto the gradient axis
+                    if len_sq == 0.0 {
+                        0.0
+                    } else {
+                        ((x - *x0 as f32) * ax + (y - *y0 as f32) * ay) / len_sq
+                    }
+                })
+            }
+            DrawOp::RadialGradient { cx, cy, radius, inner, outer } => {
+                let (cx, cy, radius) = (*cx as f32, *cy as f32, *radius as f32);
+                self.fill_gradient(*inner, *outer, |x, y| {
+                    let dist = ((x - cx).powi(2) + (y - cy).powi(2)).sqrt();
+                    if radius == 0.0 { 1.0 } else { dist / radius }
+                })
+            }
+            DrawOp::Arc { cx, cy, radius, start_angle, end_angle, r, g, b, a } => {
+                let radius_f = *radius as f32;
+                self.fill_sweep(*cx, *cy, *radius, (*start_angle, *end_angle), [*r, *g, *b, *a], |dist| {
+                    (dist - radius_f).abs() < 0.5
+                })
+            }
+            DrawOp::PieSlice { cx, cy, radius, start_angle, end_angle, r, g, b, a } => {
+                let radius_f = *radius as f32;
+                self.fill_sweep(*cx, *cy, *radius, (*start_angle, *end_angle), [*r, *g, *b, *a], |dist| {
+                    dist <= radius_f
+                })
+            }
+            DrawOp::Image { x, y, canvas } => self.blit(*x, *y, canvas),
+        }
+    }
+
+    /// Blend `source` over this canvas at (x, y) like a `SourceOver` layer; pixels past the edges are dropped
+    fn blit(&mut self, x: u32, y: u32, source: &Canvas) {
+        let width = source.width.min(self.width.saturating_sub(x));
+        let height = source.height.min(self.height.saturating_sub(y));
+
+        for sy in 0..height {
+            for sx in 0..width {
+                let src = ((sy * source.width + sx) * 4) as usize;
+                let color = [0, 1, 2, 3].map(|c| source.pixels[src + c]);
+                self.blend_pixel(x + sx, y + sy, color, source.alpha[src / 4]);
+            }
+        }
+    }
+
+    /// Blend `color` over the in-bounds pixel (x, y) with `alpha` coverage, as a `SourceOver` layer would
+    fn blend_pixel(&mut self, x: u32, y: u32, color: [u8; 4], alpha: f32) {
+        let dst = ((y * self.width + x) * 4) as usize;
+        let blend = |c: usize| blend_channel(BlendMode::SourceOver, self.pixels[dst + c], color[c], alpha);
+        let [r, g, b, a] = [0, 1, 2, 3].map(blend);
+        self.set_pixel(x, y, r, g, b, a);
+    }
+
+    /// Blend `color` into each pixel near the ring by how much of the pixel the ring covers
+    fn draw_circle_aa(&mut self, (cx, cy): (f32, f32), radius: f32, thickness: f32, color: [u8; 4]) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let half = thickness.max(0.0) * 0.5;
+        let reach = radius + half + 1.0;
+        let (x_min, x_max) = ((cx - reach).floor().max(0.0) as u32, (cx + reach).ceil().max(0.0) as u32);
+        let (y_min, y_max) = ((cy - reach).floor().max(0.0) as u32, (cy + reach).ceil().max(0.0) as u32);
+
+        for py in y_min..=y_max.min(self.height.saturating_sub(1)) {
+            for px in x_min..=x_max.min(self.width.saturating_sub(1)) {
+                let dist = (px as f32 - cx).hypot(py as f32 - cy);
+                // Box-filter approximation: full inside the band, ramping to zero over one pixel at each edge
+                let coverage = (half + 0.5 - (dist - radius).abs()).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    self.blend_pixel(px, py, color, color[3] as f32 / 255.0 * coverage);
+                }
+            }
+        }
+    }
+
+    /// Set pixels around (cx, cy) whose angle lies in the (start, end) sweep and whose distance passes `inside`
+    fn fill_sweep(
+        &mut self,
+        cx: u32,
+        cy: u32,
+        radius: u32,
+        (start_angle, end_angle): (f32, f32),
+        [r, g, b, a]: [u8; 4],
+        inside: impl Fn(f32) -> bool,
+    ) {
+        let (cx_i, cy_i, reach) = (cx as i32, cy as i32, radius as i32 + 1);
+
+        for py in (cy_i - reach).max(0)..=cy_i + reach {
+            for px in (cx_i - reach).max(0)..=cx_i + reach {
+                // Screen y grows downwards; flip it so angles run counter-clockwise
+                let (dx, dy) = ((px - cx_i) as f32, (cy_i - py) as f32);
+                let dist = (dx * dx + dy * dy).sqrt();
+                let on_center = dx == 0.0 && dy == 0.0;
+                if inside(dist) && (on_center || angle_in_sweep(dy.atan2(dx), start_angle, end_angle)) {
+                    self.set_pixel(px as u32, py as u32, r, g, b, a);
+                }
+            }
+        }
+    }
+
+    /// Fill every pixel by interpolating two colors with `t(x, y)`, clamped to [0, 1]
+    fn fill_gradient(&mut self, start: [u8; 4], end: [u8; 4], t: impl Fn(f32, f32) -> f32) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let t = t(x as f32, y as f32).clamp(0.0, 1.0);
+                let [r, g, b, a] = [0, 1, 2, 3].map(|i| lerp_u8(start[i], end[i], t));
+                self.set_pixel(x, y, r, g, b, a);
+            }
         }
     }
 
@@ -243,6 +407,36 @@ impl Canvas {
         }
     }
 
+    /// Rescale the pixels and alpha into a new `width` x `height` canvas; pending operations are kept as-is
+    pub fn resize(&self, width: u32, height: u32, filter: ScaleFilter) -> Self {
+        let mut canvas = Self { operations: self.operations.clone(), ..Self::new(width, height) };
+        if self.width == 0 || self.height == 0 {
+            return canvas;
+        }
+
+        for y in 0..height {
+            let (y0, y1, ty) = axis_sample(y, self.height, height, filter);
+            for x in 0..width {
+                let (x0, x1, tx) = axis_sample(x, self.width, width, filter);
+                let corners = [
+                    (x0, y0, (1.0 - tx) * (1.0 - ty)),
+                    (x1, y0, tx * (1.0 - ty)),
+                    (x0, y1, (1.0 - tx) * ty),
+                    (x1, y1, tx * ty),
+                ]
+                .map(|(sx, sy, weight)| ((sy * self.width + sx) as usize, weight));
+                let dst = (y * width + x) as usize;
+
+                for c in 0..4 {
+                    let value: f32 = corners.iter().map(|&(src, w)| self.pixels[src * 4 + c] as f32 * w).sum();
+                    canvas.pixels[dst * 4 + c] = value.round() as u8;
+                }
+                canvas.alpha[dst] = corners.iter().map(|&(src, w)| self.alpha[src] * w).sum();
+            }
+        }
+        canvas
+    }
+
     /// Get pixel buffer
     pub fn pixels(&self) -> &[u8] {
         &self.pixels
@@ -259,6 +453,39 @@ impl Canvas {
     }
 }
 
+/// True when `angle` lies counter-clockwise from `start` no further than `end`
+///
+/// Angles may be any value; the sweep wraps past 2π, and a sweep of 2π or more covers the full circle.
+fn angle_in_sweep(angle: f32, start: f32, end: f32) -> bool {
+    use std::f32::consts::TAU;
+    if end - start >= TAU {
+        return true;
+    }
+    let sweep = (end - start).rem_euclid(TAU);
+    (angle - start).rem_euclid(TAU) <= sweep
+}
+
+/// Source pixels and the weight of the second for destination pixel `i` along an axis scaled from `src` to `dst`
+fn axis_sample(i: u32, src: u32, dst: u32, filter: ScaleFilter) -> (u32, u32, f32) {
+    let center = (i as f32 + 0.5) * src as f32 / dst as f32;
+    match filter {
+        ScaleFilter::Nearest => {
+            let s = (center as u32).min(src - 1);
+            (s, s, 0.0)
+        }
+        ScaleFilter::Bilinear => {
+            let pos = (center - 0.5).clamp(0.0, (src - 1) as f32);
+            let s0 = pos as u32;
+            (s0, (s0 + 1).min(src - 1), pos - s0 as f32)
+        }
+    }
+}
+
+/// Linear interpolation between two channel values, rounded
+fn lerp_u8(a: u8, b: u8, t: f32) -> u8 {
+    (a as f32 + (b as f32 - a as f32) * t).round() as u8
+}
+
 /// Canvas layer logic - executes draw operations
 #[derive(Clone)]
 pub struct CanvasLogic {
@@ -995,6 +1222,25 @@ fn test_canvas_logic_creation() {
     assert_eq!(logic.canvas().dimensions(), (100, 100));
 }
 
+#[test]
+fn test_canvas_update_reacts_to_mouse_click() {
+    fn update_fn(canvas: &Canvas, _delta: f32, controller: &dyn Controller) -> Canvas {
+        let (width, height) = canvas.dimensions();
+        let color = if controller.is_down(Button::MouseLeft) { (0, 255, 0, 255) } else { (255, 0, 0, 255) };
+        Canvas::new(width, height).draw(DrawOp::Clear(color.0, color.1, color.2, color.3))
+    }
+
+    let logic = CanvasLogic::new(4, 4, update_fn);
+    let clicked = TestController { down_keys: vec![Button::MouseLeft, Button::KeyW] };
+    assert_eq!(clicked.mouse_buttons_down(), vec![Button::MouseLeft]);
+
+    let context = DisplayContext::new(4, 4);
+    let pressed = logic.update(0.016, &clicked).render(&[true; 16], &context);
+    let released = logic.update(0.016, &MockController).render(&[true; 16], &context);
+    assert_eq!(&pressed.pixels[0..4], &[0, 255, 0, 255]);
+    assert_eq!(&released.pixels[0..4], &[255, 0, 0, 255]);
+}
+
 #[test]
 fn test_canvas_logic_update() {
     fn update_fn(canvas: &Canvas, _delta: f32, _controller: &dyn Controller) -> Canvas {
@@ -1731,4 +1977,218 @@ fn test_layer_timing_different_fps() {
     assert!((layer_60fps.target_fps() - 60.0).abs() < 0.01);
     assert!((layer_30fps.target_fps() - 30.0).abs() < 0.01);
 }
+
+// ============================================================================
+// Gradient Tests
+// ============================================================================
+
+#[test]
+fn test_linear_gradient_midpoint_is_gray() {
+    let canvas = Canvas::new(101, 3)
+        .draw(DrawOp::LinearGradient { x0: 0, y0: 0, x1: 100, y1: 0, color_start: [0, 0, 0, 255], color_end: [255, 255, 255, 255] })
+        .execute_ops();
+
+    let pixels = canvas.pixels();
+    let mid = (101 + 50) * 4;
+    assert_eq!(&pixels[mid..mid + 4], &[128, 128, 128, 255]);
+    assert_eq!(&pixels[0..4], &[0, 0, 0, 255]);
+    assert_eq!(&pixels[100 * 4..100 * 4 + 4], &[255, 255, 255, 255]);
+}
+
+#[test]
+fn test_linear_gradient_clamps_outside_axis() {
+    let canvas = Canvas::new(100, 1)
+        .draw(DrawOp::LinearGradient { x0: 25, y0: 0, x1: 75, y1: 0, color_start: [10, 20, 30, 255], color_end: [200, 100, 0, 128] })
+        .execute_ops();
+
+    let pixels = canvas.pixels();
+    assert_eq!(&pixels[0..4], &[10, 20, 30, 255]);
+    assert_eq!(&pixels[99 * 4..99 * 4 + 4], &[200, 100, 0, 128]);
+    assert!((canvas.alpha()[99] - 128.0 / 255.0).abs() < 0.01);
+}
+
+#[test]
+fn test_radial_gradient_center_and_edge() {
+    let canvas = Canvas::new(41, 41)
+        .draw(DrawOp::RadialGradient { cx: 20, cy: 20, radius: 10, inner: [255, 0, 0, 255], outer: [0, 0, 255, 255] })
+        .execute_ops();
+
+    let pixels = canvas.pixels();
+    let at = |x: usize, y: usize| &pixels[(y * 41 + x) * 4..(y * 41 + x) * 4 + 4];
+    assert_eq!(at(20, 20), &[255, 0, 0, 255]);
+    assert_eq!(at(25, 20), &[128, 0, 128, 255]);
+    // Beyond the radius stays at the outer color
+    assert_eq!(at(0, 0), &[0, 0, 255, 255]);
+    assert_eq!(at(30, 20), &[0, 0, 255, 255]);
+}
+
+// ============================================================================
+// Image Tests
+// ============================================================================
+
+fn write_test_png(name: &str) -> std::path::PathBuf {
+    // 2x2: red, green / blue, half-transparent white
+    let pixels = [255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255, 255, 255, 128];
+    let path = std::env::temp_dir().join(format!("ray_tracer_{}_{}.png", name, std::process::id()));
+    image::save_buffer(&path, &pixels, 2, 2, image::ColorType::Rgba8).unwrap();
+    path
+}
+
+#[test]
+fn test_from_image_loads_pixels_and_alpha() {
+    let path = write_test_png("canvas_load");
+    let canvas = Canvas::from_image(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(canvas.dimensions(), (2, 2));
+    assert_eq!(&canvas.pixels()[4..8], &[0, 255, 0, 255]);
+    assert!((canvas.alpha()[3] - 128.0 / 255.0).abs() < 1e-6);
+    assert!(Canvas::from_image("does/not/exist.png").is_err());
+}
+
+#[test]
+fn test_image_blit_offsets_and_clips() {
+    let path = write_test_png("canvas_blit");
+    let image = Arc::new(Canvas::from_image(&path).unwrap());
+    std::fs::remove_file(&path).ok();
+
+    let canvas = Canvas::new(3, 3)
+        .draw(DrawOp::Clear(0, 0, 0, 255))
+        .draw(DrawOp::Image { x: 2, y: 1, canvas: image })
+        .execute_ops();
+
+    let pixels = canvas.pixels();
+    let at = |x: usize, y: usize| &pixels[(y * 3 + x) * 4..(y * 3 + x) * 4 + 4];
+    assert_eq!(at(2, 1), &[255, 0, 0, 255]);
+    assert_eq!(at(2, 2), &[0, 0, 255, 255]);
+    // The image's right column falls off the canvas and nothing wraps to the next row
+    assert_eq!(at(0, 2), &[0, 0, 0, 255]);
+    assert_eq!(at(1, 1), &[0, 0, 0, 255]);
+}
+
+#[test]
+fn test_circle_aa_ring_coverage() {
+    let canvas = Canvas::new(41, 41)
+        .draw(DrawOp::CircleAA { cx: 20.0, cy: 20.0, radius: 10.0, thickness: 2.5, r: 255, g: 255, b: 255, a: 255 })
+        .execute_ops();
+    let alpha = |x: usize, y: usize| canvas.alpha()[y * 41 + x];
+
+    assert_eq!(alpha(30, 20), 1.0);
+    assert_eq!(alpha(20, 10), 1.0);
+    // One pixel inside/outside the band's center sits half a pixel past its edge
+    for a in [alpha(29, 20), alpha(31, 20)] {
+        assert!(a > 0.0 && a < 1.0, "expected partial coverage, got {}", a);
+    }
+    assert_eq!((alpha(32, 20), alpha(20, 20)), (0.0, 0.0));
+}
+
+#[test]
+fn test_circle_aa_sub_pixel_radius_and_clipping() {
+    let shifted = Canvas::new(10, 10)
+        .draw(DrawOp::CircleAA { cx: 0.0, cy: 0.0, radius: 4.5, thickness: 1.0, r: 255, g: 0, b: 0, a: 255 })
+        .execute_ops();
+    // Half way between two pixel rings, both get partial coverage; off-canvas parts are dropped
+    let (inner, outer) = (shifted.alpha()[4], shifted.alpha()[5]);
+    assert!((inner - 0.5).abs() < 0.02 && (outer - 0.5).abs() < 0.02);
+}
+
+#[test]
+fn test_circle_aa_on_empty_canvas() {
+    let canvas = Canvas::new(0, 0)
+        .draw(DrawOp::CircleAA { cx: 0.0, cy: 0.0, radius: 2.0, thickness: 1.0, r: 255, g: 0, b: 0, a: 255 })
+        .execute_ops();
+    assert!(canvas.pixels().is_empty());
+}
+
+fn checkerboard_2x2() -> Canvas {
+    Canvas::new(2, 2)
+        .draw(DrawOp::Clear(0, 0, 0, 255))
+        .draw(DrawOp::Pixel { x: 0, y: 0, r: 255, g: 255, b: 255, a: 255 })
+        .draw(DrawOp::Pixel { x: 1, y: 1, r: 255, g: 255, b: 255, a: 255 })
+        .execute_ops()
+}
+
+#[test]
+fn test_nearest_resize_duplicates_pixels() {
+    let canvas = checkerboard_2x2().resize(4, 4, ScaleFilter::Nearest);
+    assert_eq!(canvas.dimensions(), (4, 4));
+
+    let red = |x: usize, y: usize| canvas.pixels()[(y * 4 + x) * 4];
+    let rows: Vec<Vec<u8>> = (0..4).map(|y| (0..4).map(|x| red(x, y)).collect()).collect();
+    assert_eq!(rows, vec![vec![255, 255, 0, 0], vec![255, 255, 0, 0], vec![0, 0, 255, 255], vec![0, 0, 255, 255]]);
+    assert!(canvas.alpha().iter().all(|&a| a == 1.0));
+}
+
+#[test]
+fn test_bilinear_resize_blends_neighbours() {
+    let canvas = checkerboard_2x2().resize(4, 4, ScaleFilter::Bilinear);
+    let red = |x: usize, y: usize| canvas.pixels()[(y * 4 + x) * 4];
+
+    // Corners keep their source color; pixels between black and white land in between
+    assert_eq!((red(0, 0), red(3, 0)), (255, 0));
+    assert!((1..255).contains(&red(1, 1)) && (1..255).contains(&red(2, 1)));
+    assert_eq!(red(1, 1), red(2, 2));
+}
+
+#[test]
+fn test_resize_scales_alpha() {
+    let canvas = Canvas::new(2, 1)
+        .draw(DrawOp::Pixel { x: 1, y: 0, r: 255, g: 255, b: 255, a: 255 })
+        .execute_ops()
+        .resize(4, 1, ScaleFilter::Bilinear);
+    let alpha = canvas.alpha();
+    assert_eq!((alpha[0], alpha[3]), (0.0, 1.0));
+    assert!(alpha[1] > 0.0 && alpha[1] < alpha[2] && alpha[2] < 1.0);
+}
+
+fn lit_pixels(canvas: &Canvas) -> Vec<(i32, i32)> {
+    let (width, _) = canvas.dimensions();
+    canvas
+        .alpha()
+        .iter()
+        .enumerate()
+        .filter(|(_, &a)| a > 0.0)
+        .map(|(i, _)| ((i as u32 % width) as i32, (i as u32 / width) as i32))
+        .collect()
+}
+
+#[test]
+fn test_arc_quarter_stays_in_first_quadrant() {
+    let canvas = Canvas::new(41, 41)
+        .draw(DrawOp::Arc { cx: 20, cy: 20, radius: 10, start_angle: 0.0, end_angle: std::f32::consts::FRAC_PI_2, r: 255, g: 255, b: 255, a: 255 })
+        .execute_ops();
+
+    let lit = lit_pixels(&canvas);
+    assert!(lit.contains(&(30, 20)), "arc starts on +x");
+    assert!(lit.contains(&(20, 10)), "arc ends straight up");
+    assert!(lit.iter().all(|&(x, y)| x >= 20 && y <= 20));
+    // Outline only: the center stays empty
+    assert!(!lit.contains(&(25, 15)));
+}
+
+#[test]
+fn test_arc_wraps_past_full_turn() {
+    use std::f32::consts::PI;
+    let canvas = Canvas::new(41, 41)
+        .draw(DrawOp::Arc { cx: 20, cy: 20, radius: 10, start_angle: 1.5 * PI, end_angle: 2.5 * PI, r: 255, g: 0, b: 0, a: 255 })
+        .execute_ops();
+
+    let lit = lit_pixels(&canvas);
+    assert!(lit.contains(&(30, 20)));
+    assert!(lit.iter().all(|&(x, _)| x >= 20));
+}
+
+#[test]
+fn test_pie_slice_fills_wedge() {
+    let canvas = Canvas::new(41, 41)
+        .draw(DrawOp::PieSlice { cx: 20, cy: 20, radius: 10, start_angle: 0.0, end_angle: std::f32::consts::FRAC_PI_2, r: 0, g: 255, b: 0, a: 255 })
+        .execute_ops();
+
+    let lit = lit_pixels(&canvas);
+    assert!(lit.contains(&(20, 20)), "wedge includes the center");
+    assert!(lit.contains(&(25, 15)), "wedge is filled");
+    assert!(lit.contains(&(25, 20)) && lit.contains(&(20, 15)), "both radii are drawn");
+    assert!(!lit.contains(&(15, 25)));
+    assert!(!lit.contains(&(28, 12)), "nothing beyond the radius");
+}
 }
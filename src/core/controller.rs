@@ -1,17 +1,81 @@
+use serde::{Deserialize, Serialize};
+
 /// Input button identifier
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Button {
-    KeyW,
+    /// Letter keys by physical position (US QWERTY names), so any layout can be bound
     KeyA,
-    KeyS,
+    KeyB,
+    KeyC,
     KeyD,
-    KeyQ,
     KeyE,
+    KeyF,
+    KeyG,
+    KeyH,
+    KeyI,
+    KeyJ,
+    KeyK,
+    KeyL,
+    KeyM,
+    KeyN,
+    KeyO,
+    KeyP,
+    KeyQ,
+    KeyR,
+    KeyS,
+    KeyT,
+    KeyU,
+    KeyV,
+    KeyW,
+    KeyX,
+    KeyY,
+    KeyZ,
+    Digit0,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    /// Punctuation keys by physical position
+    Comma,
+    Period,
+    Semicolon,
+    Quote,
+    Slash,
+    Backslash,
+    BracketLeft,
+    BracketRight,
+    Backquote,
+    IntlBackslash,
+    /// Arrow keys
+    KeyUp,
+    KeyDown,
+    KeyLeft,
+    KeyRight,
+    PageUp,
+    PageDown,
     Space,
     Shift,
+    Control,
+    Plus,
+    Minus,
     Escape,
+    /// Mouse buttons, held like keys
     MouseLeft,
     MouseRight,
+    MouseMiddle,
+}
+
+impl Button {
+    pub const MOUSE: [Button; 3] = [Button::MouseLeft, Button::MouseRight, Button::MouseMiddle];
+
+    pub fn is_mouse(self) -> bool {
+        Self::MOUSE.contains(&self)
+    }
 }
 
 /// Controller - handles button input states
@@ -21,6 +85,11 @@ pub trait Controller {
 
     /// Get all currently pressed buttons
     fn get_down_keys(&self) -> &[Button];
+
+    /// Mouse buttons currently held, in `Button::MOUSE` order
+    fn mouse_buttons_down(&self) -> Vec<Button> {
+        Button::MOUSE.into_iter().filter(|&button| self.is_down(button)).collect()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,152 @@
+use super::controller::{Button, Controller};
+
+/// Stick deflection (0.0-1.0) below which the stick counts as centered
+pub const STICK_DEADZONE: f32 = 0.25;
+/// Analog trigger travel (0.0-1.0) at which a trigger counts as pressed
+pub const TRIGGER_THRESHOLD: f32 = 0.5;
+
+/// Snapshot of the gamepad inputs that drive the camera
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GamepadState {
+    /// Left stick (x right, y forward), each in -1.0..=1.0
+    pub left_stick: (f32, f32),
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+    pub left_bumper: bool,
+    pub right_bumper: bool,
+    /// Left stick click
+    pub stick_click: bool,
+}
+
+/// Keyboard-equivalent buttons for a gamepad state
+///
+/// Left stick moves (WASD), right/left trigger rise/sink (Space/Shift),
+/// bumpers rotate (Q/E) and clicking the stick sprints (Ctrl).
+pub fn buttons_from_state(state: &GamepadState) -> Vec<Button> {
+    let (x, y) = state.left_stick;
+    [
+        (y > STICK_DEADZONE, Button::KeyW),
+        (y < -STICK_DEADZONE, Button::KeyS),
+        (x < -STICK_DEADZONE, Button::KeyA),
+        (x > STICK_DEADZONE, Button::KeyD),
+        (state.right_trigger >= TRIGGER_THRESHOLD, Button::Space),
+        (state.left_trigger >= TRIGGER_THRESHOLD, Button::Shift),
+        (state.left_bumper, Button::KeyQ),
+        (state.right_bumper, Button::KeyE),
+        (state.stick_click, Button::Control),
+    ]
+    .into_iter()
+    .filter_map(|(down, button)| down.then_some(button))
+    .collect()
+}
+
+/// Controller backed by the first active gamepad (needs the `gamepad` feature; inert without it)
+pub struct GamepadController {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    #[cfg(feature = "gamepad")]
+    active: Option<gilrs::GamepadId>,
+    pressed: Vec<Button>,
+}
+
+impl GamepadController {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "gamepad")]
+            gilrs: gilrs::Gilrs::new()
+                .map_err(|e| eprintln!("Gamepad input unavailable: {}", e))
+                .ok(),
+            #[cfg(feature = "gamepad")]
+            active: None,
+            pressed: Vec::new(),
+        }
+    }
+
+    /// Controller reporting a fixed state (tests, replays)
+    pub fn from_state(state: &GamepadState) -> Self {
+        Self {
+            #[cfg(feature = "gamepad")]
+            gilrs: None,
+            #[cfg(feature = "gamepad")]
+            active: None,
+            pressed: buttons_from_state(state),
+        }
+    }
+
+    /// Read the latest gamepad state; call once per frame
+    pub fn poll(&mut self) {
+        #[cfg(feature = "gamepad")]
+        if let Some(gilrs) = &mut self.gilrs {
+            self.pressed = buttons_from_state(&read_state(gilrs, &mut self.active));
+        }
+    }
+}
+
+impl Default for GamepadController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Controller for GamepadController {
+    fn is_down(&self, button: Button) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    fn get_down_keys(&self) -> &[Button] {
+        &self.pressed
+    }
+}
+
+/// Drain pending events (the most recently used pad becomes active) and sample it
+#[cfg(feature = "gamepad")]
+fn read_state(gilrs: &mut gilrs::Gilrs, active: &mut Option<gilrs::GamepadId>) -> GamepadState {
+    use gilrs::{Axis, Button as PadButton};
+
+    while let Some(gilrs::Event { id, .. }) = gilrs.next_event() {
+        *active = Some(id);
+    }
+    let id = active.or_else(|| gilrs.gamepads().next().map(|(id, _)| id));
+
+    id.map(|id| gilrs.gamepad(id))
+        .filter(|pad| pad.is_connected())
+        .map(|pad| {
+            let trigger = |button| pad.button_data(button).map_or(0.0, |data| data.value());
+            GamepadState {
+                left_stick: (pad.value(Axis::LeftStickX), pad.value(Axis::LeftStickY)),
+                left_trigger: trigger(PadButton::LeftTrigger2),
+                right_trigger: trigger(PadButton::RightTrigger2),
+                left_bumper: pad.is_pressed(PadButton::LeftTrigger),
+                right_bumper: pad.is_pressed(PadButton::RightTrigger),
+                stick_click: pad.is_pressed(PadButton::LeftThumb),
+            }
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stick_forward_past_deadzone_is_key_w() {
+        let forward = GamepadController::from_state(&GamepadState { left_stick: (0.0, 0.8), ..Default::default() });
+        assert!(forward.is_down(Button::KeyW));
+        assert!(!forward.is_down(Button::KeyS));
+
+        let drift = GamepadController::from_state(&GamepadState { left_stick: (0.1, STICK_DEADZONE * 0.9), ..Default::default() });
+        assert!(drift.get_down_keys().is_empty());
+    }
+
+    #[test]
+    fn test_triggers_and_bumpers_map_to_vertical_and_rotation() {
+        let state = GamepadState {
+            left_stick: (-0.9, -0.9),
+            right_trigger: 1.0,
+            left_trigger: TRIGGER_THRESHOLD * 0.5,
+            right_bumper: true,
+            ..Default::default()
+        };
+        assert_eq!(buttons_from_state(&state), vec![Button::KeyS, Button::KeyA, Button::Space, Button::KeyE]);
+    }
+}
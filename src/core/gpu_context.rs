@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use wgpu::{Device, Queue, Instance, Surface, Adapter, Features, Limits, DeviceDescriptor, Buffer};
+use wgpu::{Device, Queue, Instance, Surface, Adapter, AdapterInfo, Features, Limits, DeviceDescriptor, Buffer};
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
@@ -11,6 +11,7 @@ type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 pub struct GpuContext {
     device: Arc<Device>,
     queue: Arc<Queue>,
+    adapter_info: AdapterInfo,
 }
 
 impl GpuContext {
@@ -29,6 +30,7 @@ impl GpuContext {
         Ok(Self {
             device: Arc::new(device),
             queue: Arc::new(queue),
+            adapter_info: adapter.get_info(),
         })
     }
 
@@ -47,6 +49,7 @@ impl GpuContext {
         Ok(Self {
             device: Arc::new(device),
             queue: Arc::new(queue),
+            adapter_info: adapter.get_info(),
         })
     }
 
@@ -60,6 +63,11 @@ impl GpuContext {
         &self.queue
     }
 
+    /// Name, vendor and backend of the adapter this context runs on
+    pub fn adapter_info(&self) -> &AdapterInfo {
+        &self.adapter_info
+    }
+
     /// Synchronously read data from a buffer
     ///
     /// IMPORTANT: This is a blocking operation that polls the device.
@@ -95,16 +95,65 @@ impl WinitController {
     }
 
     /// Map Winit KeyCode to Button
-    fn keycode_to_button(keycode: KeyCode) -> Option<Button> {
+    pub fn keycode_to_button(keycode: KeyCode) -> Option<Button> {
         match keycode {
-            KeyCode::KeyW => Some(Button::KeyW),
             KeyCode::KeyA => Some(Button::KeyA),
-            KeyCode::KeyS => Some(Button::KeyS),
+            KeyCode::KeyB => Some(Button::KeyB),
+            KeyCode::KeyC => Some(Button::KeyC),
             KeyCode::KeyD => Some(Button::KeyD),
-            KeyCode::KeyQ => Some(Button::KeyQ),
             KeyCode::KeyE => Some(Button::KeyE),
+            KeyCode::KeyF => Some(Button::KeyF),
+            KeyCode::KeyG => Some(Button::KeyG),
+            KeyCode::KeyH => Some(Button::KeyH),
+            KeyCode::KeyI => Some(Button::KeyI),
+            KeyCode::KeyJ => Some(Button::KeyJ),
+            KeyCode::KeyK => Some(Button::KeyK),
+            KeyCode::KeyL => Some(Button::KeyL),
+            KeyCode::KeyM => Some(Button::KeyM),
+            KeyCode::KeyN => Some(Button::KeyN),
+            KeyCode::KeyO => Some(Button::KeyO),
+            KeyCode::KeyP => Some(Button::KeyP),
+            KeyCode::KeyQ => Some(Button::KeyQ),
+            KeyCode::KeyR => Some(Button::KeyR),
+            KeyCode::KeyS => Some(Button::KeyS),
+            KeyCode::KeyT => Some(Button::KeyT),
+            KeyCode::KeyU => Some(Button::KeyU),
+            KeyCode::KeyV => Some(Button::KeyV),
+            KeyCode::KeyW => Some(Button::KeyW),
+            KeyCode::KeyX => Some(Button::KeyX),
+            KeyCode::KeyY => Some(Button::KeyY),
+            KeyCode::KeyZ => Some(Button::KeyZ),
+            KeyCode::Digit0 => Some(Button::Digit0),
+            KeyCode::Digit1 => Some(Button::Digit1),
+            KeyCode::Digit2 => Some(Button::Digit2),
+            KeyCode::Digit3 => Some(Button::Digit3),
+            KeyCode::Digit4 => Some(Button::Digit4),
+            KeyCode::Digit5 => Some(Button::Digit5),
+            KeyCode::Digit6 => Some(Button::Digit6),
+            KeyCode::Digit7 => Some(Button::Digit7),
+            KeyCode::Digit8 => Some(Button::Digit8),
+            KeyCode::Digit9 => Some(Button::Digit9),
+            KeyCode::Comma => Some(Button::Comma),
+            KeyCode::Period => Some(Button::Period),
+            KeyCode::Semicolon => Some(Button::Semicolon),
+            KeyCode::Quote => Some(Button::Quote),
+            KeyCode::Slash => Some(Button::Slash),
+            KeyCode::Backslash => Some(Button::Backslash),
+            KeyCode::BracketLeft => Some(Button::BracketLeft),
+            KeyCode::BracketRight => Some(Button::BracketRight),
+            KeyCode::Backquote => Some(Button::Backquote),
+            KeyCode::IntlBackslash => Some(Button::IntlBackslash),
+            KeyCode::ArrowUp => Some(Button::KeyUp),
+            KeyCode::ArrowDown => Some(Button::KeyDown),
+            KeyCode::ArrowLeft => Some(Button::KeyLeft),
+            KeyCode::ArrowRight => Some(Button::KeyRight),
+            KeyCode::PageUp => Some(Button::PageUp),
+            KeyCode::PageDown => Some(Button::PageDown),
             KeyCode::Space => Some(Button::Space),
             KeyCode::ShiftLeft | KeyCode::ShiftRight => Some(Button::Shift),
+            KeyCode::ControlLeft | KeyCode::ControlRight => Some(Button::Control),
+            KeyCode::Equal | KeyCode::NumpadAdd => Some(Button::Plus),
+            KeyCode::Minus | KeyCode::NumpadSubtract => Some(Button::Minus),
             KeyCode::Escape => Some(Button::Escape),
             _ => None,
         }
@@ -115,6 +164,7 @@ impl WinitController {
         match button {
             MouseButton::Left => Some(Button::MouseLeft),
             MouseButton::Right => Some(Button::MouseRight),
+            MouseButton::Middle => Some(Button::MouseMiddle),
             _ => None,
         }
     }
@@ -172,6 +222,14 @@ mod tests {
         assert_eq!(controller.mouse_position(), Some((100.0, 200.0)));
     }
 
+    #[test]
+    fn test_mouse_button_mapping() {
+        assert_eq!(WinitController::mouse_button_to_button(MouseButton::Left), Some(Button::MouseLeft));
+        assert_eq!(WinitController::mouse_button_to_button(MouseButton::Middle), Some(Button::MouseMiddle));
+        assert_eq!(WinitController::mouse_button_to_button(MouseButton::Back), None);
+        assert!(Button::MouseMiddle.is_mouse() && !Button::KeyW.is_mouse());
+    }
+
     #[test]
     fn test_button_mapping() {
         // Test that Button enum variants exist and can be used
@@ -184,6 +242,9 @@ mod tests {
             Button::KeyE,
             Button::Space,
             Button::Shift,
+            Button::Control,
+            Button::Plus,
+            Button::Minus,
             Button::Escape,
             Button::MouseLeft,
             Button::MouseRight,
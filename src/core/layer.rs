@@ -1,6 +1,18 @@
 use super::controller::Controller;
 use super::display_context::DisplayContext;
 
+/// How a layer combines with the layers beneath it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Standard alpha blending over the layers below
+    #[default]
+    SourceOver,
+    /// Sum with the layers below and clamp (glow, particles)
+    Additive,
+    /// Per-channel maximum of this layer and the layers below
+    Max,
+}
+
 /// Output from a layer's render call - just pixels
 #[derive(Debug, Clone)]
 pub struct LayerOutput {
@@ -8,12 +20,18 @@ pub struct LayerOutput {
     pub pixels: Vec<u8>,
     /// Optional alpha mask (0.0 = transparent, 1.0 = opaque)
     pub alpha: Option<Vec<f32>>,
+    /// Compositing mode against lower-priority layers
+    pub blend: BlendMode,
 }
 
 impl LayerOutput {
     /// Create output with fully opaque pixels
     pub fn opaque(pixels: Vec<u8>) -> Self {
-        Self { pixels, alpha: None }
+        Self {
+            pixels,
+            alpha: None,
+            blend: BlendMode::SourceOver,
+        }
     }
 
     /// Create output with alpha mask
@@ -21,8 +39,46 @@ impl LayerOutput {
         Self {
             pixels,
             alpha: Some(alpha),
+            blend: BlendMode::SourceOver,
         }
     }
+
+    /// Set the compositing mode
+    pub fn with_blend(self, blend: BlendMode) -> Self {
+        Self { blend, ..self }
+    }
+
+    /// Blend these pixels over `dst` (RGBA8) using this layer's mode and alpha mask
+    pub fn blend_onto(&self, mut dst: Vec<u8>) -> Vec<u8> {
+        dst.chunks_exact_mut(4)
+            .zip(self.pixels.chunks_exact(4))
+            .enumerate()
+            .for_each(|(i, (d, s))| {
+                let alpha = self.alpha.as_ref().map_or(1.0, |a| a[i]);
+                d.iter_mut()
+                    .zip(s)
+                    .for_each(|(d, &s)| *d = blend_channel(self.blend, *d, s, alpha));
+            });
+        dst
+    }
+
+    /// Composite `layers` back-to-front over transparent black into one RGBA8 buffer
+    ///
+    /// Layers are expected in priority order (lowest first), as `LayerStack::render` yields them.
+    pub fn composite(layers: &[LayerOutput], width: u32, height: u32) -> Vec<u8> {
+        let blank = vec![0; (width * height * 4) as usize];
+        layers.iter().fold(blank, |dst, layer| layer.blend_onto(dst))
+    }
+}
+
+pub(crate) fn blend_channel(mode: BlendMode, dst: u8, src: u8, alpha: f32) -> u8 {
+    let (dst, src) = (dst as f32, src as f32);
+    let blended = match mode {
+        BlendMode::SourceOver => dst + (src - dst) * alpha,
+        BlendMode::Additive => dst + src * alpha,
+        BlendMode::Max => dst.max(src * alpha),
+    };
+    blended.round().clamp(0.0, 255.0) as u8
 }
 
 /// Layer with independent update rate control
@@ -43,6 +99,12 @@ pub trait Layer {
     fn target_fps(&self) -> f32 {
         60.0
     }
+
+    /// Whether the layer is drawn; disabled layers still update
+    fn enabled(&self) -> bool;
+
+    /// Mute (false) or unmute (true) the layer
+    fn set_enabled(&mut self, enabled: bool);
 }
 
 /// Core layer logic - implemented by specific layers
@@ -59,6 +121,7 @@ pub struct TimedLayer<T: LayerLogic> {
     logic: T,
     timer: super::timer::FixedHz,
     priority: i32,
+    enabled: bool,
 }
 
 impl<T: LayerLogic> TimedLayer<T> {
@@ -68,6 +131,7 @@ impl<T: LayerLogic> TimedLayer<T> {
             logic,
             timer: super::timer::FixedHz::new(hz),
             priority,
+            enabled: true,
         }
     }
 
@@ -92,6 +156,7 @@ impl<T: LayerLogic + 'static> Layer for TimedLayer<T> {
             logic: new_logic,
             timer: new_timer,
             priority: self.priority,
+            enabled: self.enabled,
         })
     }
 
@@ -106,27 +171,95 @@ impl<T: LayerLogic + 'static> Layer for TimedLayer<T> {
     fn target_fps(&self) -> f32 {
         self.hz()
     }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
 }
 
 /// Composable layer stack
 pub struct LayerStack {
     layers: Vec<Box<dyn Layer>>,
+    /// Render-order index of the only layer drawn, ignoring mutes; kept on the same layer as the stack changes
+    soloed: Option<usize>,
 }
 
 impl LayerStack {
     /// Create empty layer stack
     pub fn new() -> Self {
-        Self { layers: Vec::new() }
+        Self { layers: Vec::new(), soloed: None }
     }
 
     /// Add layer and return new stack
     pub fn with_layer(mut self, layer: Box<dyn Layer>) -> Self {
         self.layers.push(layer);
-        self.layers.sort_by_key(|l| l.priority());
+        self.sorted()
+    }
+
+    /// Remove the layer at `index` (in render order) and return new stack
+    ///
+    /// Removing the soloed layer ends the solo. Panics if `index` is out of bounds.
+    pub fn remove_layer(mut self, index: usize) -> Self {
+        self.layers.remove(index);
+        self.soloed = match self.soloed {
+            Some(solo) if solo == index => None,
+            Some(solo) if solo > index => Some(solo - 1),
+            solo => solo,
+        };
+        self
+    }
+
+    /// Replace the layer at `index` (in render order) and return new stack
+    ///
+    /// The replacement may have a different priority, so the stack is re-sorted; replacing the
+    /// soloed layer solos its replacement. Panics if `index` is out of bounds.
+    pub fn replace_layer(mut self, index: usize, layer: Box<dyn Layer>) -> Self {
+        self.layers[index] = layer;
+        self.sorted()
+    }
+
+    /// Stable sort by priority, moving `soloed` along with its layer
+    fn sorted(self) -> Self {
+        let mut tagged: Vec<_> =
+            self.layers.into_iter().enumerate().map(|(i, layer)| (self.soloed == Some(i), layer)).collect();
+        tagged.sort_by_key(|(_, layer)| layer.priority());
+        Self {
+            soloed: tagged.iter().position(|(soloed, _)| *soloed),
+            layers: tagged.into_iter().map(|(_, layer)| layer).collect(),
+        }
+    }
+
+    /// Mute or unmute the layer at `index` (in render order) and return new stack
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_layer_enabled(mut self, index: usize, enabled: bool) -> Self {
+        self.layers[index].set_enabled(enabled);
         self
     }
 
-    /// Update all layers - functional transformation
+    /// Render only the layer at `index` (in render order) until `unsolo`
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn solo(self, index: usize) -> Self {
+        assert!(index < self.layers.len(), "solo index {} out of bounds for {} layers", index, self.layers.len());
+        Self { soloed: Some(index), ..self }
+    }
+
+    /// Render every enabled layer again
+    pub fn unsolo(self) -> Self {
+        Self { soloed: None, ..self }
+    }
+
+    /// Number of layers in the stack
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Update all layers, muted or not - functional transformation
     pub fn update(&self, delta: f32, controller: &dyn Controller) -> LayerStack {
         LayerStack {
             layers: self
@@ -134,16 +267,21 @@ impl LayerStack {
                 .iter()
                 .map(|layer| layer.update(delta, controller))
                 .collect(),
+            soloed: self.soloed,
         }
     }
 
-    /// Aggregate all layer outputs
+    /// Aggregate the outputs of the soloed layer, or else of every enabled layer
     pub fn render<'a>(
         &'a self,
         mask: &'a [bool],
         context: &'a DisplayContext,
     ) -> impl Iterator<Item = LayerOutput> + 'a {
-        self.layers.iter().map(move |layer| layer.render(mask, context))
+        self.layers
+            .iter()
+            .enumerate()
+            .filter(move |(i, layer)| self.soloed.map_or(layer.enabled(), |solo| solo == *i))
+            .map(move |(_, layer)| layer.render(mask, context))
     }
 }
 
@@ -223,4 +361,173 @@ mod tests {
         assert_eq!(outputs[0].pixels[0], 11);
         assert_eq!(outputs[1].pixels[0], 21);
     }
+
+    fn layer(value: u32, priority: i32) -> Box<dyn Layer> {
+        Box::new(TimedLayer::new(TestLogic { value }, 60.0, priority))
+    }
+
+    fn render_values(stack: &LayerStack) -> Vec<u8> {
+        let ctx = DisplayContext::new(1, 1);
+        stack.render(&[true], &ctx).map(|o| o.pixels[0]).collect()
+    }
+
+    #[test]
+    fn layer_stack_renders_in_priority_order() {
+        let stack = LayerStack::new()
+            .with_layer(layer(30, 10))
+            .with_layer(layer(10, 0))
+            .with_layer(layer(20, 5));
+
+        assert_eq!(stack.layer_count(), 3);
+        assert_eq!(render_values(&stack), vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn layer_stack_remove_middle_keeps_order() {
+        let stack = LayerStack::new()
+            .with_layer(layer(30, 10))
+            .with_layer(layer(10, 0))
+            .with_layer(layer(20, 5))
+            .remove_layer(1);
+
+        assert_eq!(stack.layer_count(), 2);
+        assert_eq!(render_values(&stack), vec![10, 30]);
+    }
+
+    #[test]
+    fn layer_stack_replace_resorts_by_priority() {
+        let stack = LayerStack::new()
+            .with_layer(layer(10, 0))
+            .with_layer(layer(20, 5))
+            .with_layer(layer(30, 10))
+            .replace_layer(0, layer(40, 20));
+
+        assert_eq!(stack.layer_count(), 3);
+        assert_eq!(render_values(&stack), vec![20, 30, 40]);
+    }
+
+    #[test]
+    fn layer_stack_replace_same_priority_swaps_in_place() {
+        let stack = LayerStack::new()
+            .with_layer(layer(10, 0))
+            .with_layer(layer(20, 5))
+            .replace_layer(1, layer(99, 5));
+
+        assert_eq!(render_values(&stack), vec![10, 99]);
+    }
+
+    #[test]
+    fn layer_stack_solo_renders_only_that_layer() {
+        let stack = LayerStack::new()
+            .with_layer(layer(10, 0))
+            .with_layer(layer(20, 5))
+            .with_layer(layer(30, 10))
+            .set_layer_enabled(1, false)
+            .solo(1);
+
+        assert_eq!(render_values(&stack), vec![20]);
+        assert_eq!(render_values(&stack.unsolo()), vec![10, 30]);
+    }
+
+    #[test]
+    fn layer_stack_solo_follows_layer_when_stack_changes() {
+        let stack = LayerStack::new()
+            .with_layer(layer(10, 0))
+            .with_layer(layer(20, 5))
+            .with_layer(layer(30, 10))
+            .solo(2)
+            .remove_layer(0);
+        assert_eq!(render_values(&stack), vec![30]);
+
+        // A lower-priority layer sorts in ahead of the soloed one
+        let stack = stack.with_layer(layer(40, -5));
+        assert_eq!(render_values(&stack), vec![30]);
+
+        let stack = stack.remove_layer(2);
+        assert_eq!(render_values(&stack), vec![40, 20]);
+    }
+
+    #[test]
+    fn layer_stack_replacing_soloed_layer_solos_replacement() {
+        let stack = LayerStack::new()
+            .with_layer(layer(10, 0))
+            .with_layer(layer(20, 5))
+            .solo(0)
+            .replace_layer(0, layer(50, 20));
+        assert_eq!(render_values(&stack), vec![50]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn layer_stack_solo_out_of_bounds_panics() {
+        let _ = LayerStack::new().with_layer(layer(10, 0)).solo(1);
+    }
+
+    #[test]
+    fn layer_stack_muted_layer_still_updates() {
+        let stack = LayerStack::new()
+            .with_layer(layer(10, 0))
+            .with_layer(layer(20, 5))
+            .set_layer_enabled(0, false);
+        assert_eq!(render_values(&stack), vec![20]);
+
+        let updated = stack.update(0.02, &MockController).set_layer_enabled(0, true);
+        assert_eq!(render_values(&updated), vec![11, 21]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn layer_stack_remove_out_of_bounds_panics() {
+        let _ = LayerStack::new().with_layer(layer(10, 0)).remove_layer(1);
+    }
+
+    #[test]
+    fn test_composite_empty_is_transparent() {
+        assert_eq!(LayerOutput::composite(&[], 2, 1), vec![0; 8]);
+    }
+
+    #[test]
+    fn test_additive_half_bright_saturates() {
+        let base = LayerOutput::opaque(vec![128, 128, 128, 255]);
+        let glow = LayerOutput::opaque(vec![128, 128, 128, 255]).with_blend(BlendMode::Additive);
+
+        let pixels = LayerOutput::composite(&[base, glow], 1, 1);
+        assert_eq!(pixels, vec![255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_additive_below_saturation_sums() {
+        let base = LayerOutput::opaque(vec![100, 50, 0, 255]);
+        let glow = LayerOutput::opaque(vec![100, 50, 0, 0]).with_blend(BlendMode::Additive);
+
+        let pixels = LayerOutput::composite(&[base, glow], 1, 1);
+        assert_eq!(pixels, vec![200, 100, 0, 255]);
+    }
+
+    #[test]
+    fn test_max_takes_brighter_channel() {
+        let base = LayerOutput::opaque(vec![200, 10, 50, 255]);
+        let top = LayerOutput::opaque(vec![100, 90, 50, 255]).with_blend(BlendMode::Max);
+
+        let pixels = LayerOutput::composite(&[base, top], 1, 1);
+        assert_eq!(pixels, vec![200, 90, 50, 255]);
+    }
+
+    #[test]
+    fn test_source_over_uses_alpha_mask() {
+        let base = LayerOutput::opaque(vec![0, 0, 0, 255, 0, 0, 0, 255]);
+        let top = LayerOutput::with_alpha(vec![200, 200, 200, 255, 200, 200, 200, 255], vec![0.5, 0.0]);
+
+        let pixels = LayerOutput::composite(&[base, top], 2, 1);
+        assert_eq!(pixels, vec![100, 100, 100, 255, 0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_half_alpha_blue_over_opaque_red() {
+        let red = LayerOutput::opaque(vec![255, 0, 0, 255]);
+        let blue = LayerOutput::with_alpha(vec![0, 0, 255, 255], vec![0.5]);
+
+        let pixels = LayerOutput::composite(&[red, blue], 1, 1);
+        assert_eq!(pixels, vec![128, 0, 128, 255]);
+    }
 }
@@ -1,11 +1,14 @@
+pub mod bloom;
 pub mod canvas_layer;
 pub mod clock;
 pub mod controller;
 pub mod display_context;
 pub mod game;
+pub mod gamepad;
 pub mod gpu_context;
 pub mod input_adapter;
 pub mod layer;
+pub mod particle_layer;
 pub mod ray_tracing_layer;
 pub mod renderer;
 pub mod surface_renderer;
@@ -19,14 +22,17 @@ pub mod triangle_intersection;
 pub mod perf_test;
 pub mod benchmark;
 
+pub use bloom::*;
 pub use canvas_layer::*;
 pub use clock::*;
 pub use controller::*;
 pub use display_context::*;
 pub use game::*;
+pub use gamepad::*;
 pub use gpu_context::*;
 pub use input_adapter::*;
 pub use layer::*;
+pub use particle_layer::*;
 pub use ray_tracing_layer::*;
 pub use renderer::*;
 pub use surface_renderer::*;
@@ -0,0 +1,275 @@
+use glam::Vec3;
+
+use super::controller::Controller;
+use super::display_context::DisplayContext;
+use super::layer::{Layer, LayerLogic, LayerOutput, TimedLayer};
+use crate::types::BoxData;
+
+/// A single particle with simple ballistic motion
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Seconds since spawn
+    pub age: f32,
+}
+
+impl Particle {
+    /// Advance by `delta` seconds (semi-implicit Euler)
+    pub fn integrate(self, gravity: Vec3, delta: f32) -> Self {
+        let velocity = self.velocity + gravity * delta;
+        Self {
+            position: self.position + velocity * delta,
+            velocity,
+            age: self.age + delta,
+        }
+    }
+}
+
+/// Particle system logic - particles are emitted as boxes for the ray tracer grid
+///
+/// Rendering produces a fully transparent layer; use `boxes()` to feed the
+/// particles into the scene each frame.
+#[derive(Debug, Clone)]
+pub struct ParticleSystemLogic {
+    particles: Vec<Particle>,
+    max_particles: usize,
+    emitter: Vec3,
+    initial_velocity: Vec3,
+    spread: f32,
+    gravity: Vec3,
+    lifetime: f32,
+    spawn_rate: f32,
+    spawn_accumulator: f32,
+    spawned: u32,
+    size: f32,
+    color: [f32; 3],
+}
+
+impl ParticleSystemLogic {
+    /// Live particles
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Particles as boxes, ready for `HierarchicalGrid::build`
+    pub fn boxes(&self) -> Vec<BoxData> {
+        let half = Vec3::splat(self.size * 0.5);
+        self.particles
+            .iter()
+            .map(|p| BoxData::new((p.position - half).to_array(), (p.position + half).to_array(), self.color))
+            .collect()
+    }
+
+    /// Fresh particle at the emitter; velocity is jittered deterministically by spawn index
+    fn spawn(&self, index: u32) -> Particle {
+        let u = (index as f32 * 0.618_034) % 1.0;
+        let v = (index as f32 * 0.381_966 + 0.5) % 1.0;
+        let angle = u * std::f32::consts::TAU;
+        let jitter = Vec3::new(angle.cos(), 0.0, angle.sin()) * (v * self.spread);
+
+        Particle {
+            position: self.emitter,
+            velocity: self.initial_velocity + jitter,
+            age: 0.0,
+        }
+    }
+}
+
+impl LayerLogic for ParticleSystemLogic {
+    fn update(&self, delta: f32, _controller: &dyn Controller) -> Self {
+        let mut spawned = self.spawned;
+        let particles: Vec<Particle> = self
+            .particles
+            .iter()
+            .map(|p| p.integrate(self.gravity, delta))
+            .map(|p| {
+                if p.age >= self.lifetime {
+                    spawned += 1;
+                    self.spawn(spawned)
+                } else {
+                    p
+                }
+            })
+            .collect();
+
+        let spawn_accumulator = self.spawn_accumulator + self.spawn_rate * delta;
+        let free = self.max_particles - particles.len();
+        let new_count = (spawn_accumulator as usize).min(free);
+        let new_particles: Vec<Particle> = (1..=new_count as u32).map(|i| self.spawn(spawned + i)).collect();
+
+        Self {
+            particles: particles.into_iter().chain(new_particles).collect(),
+            spawn_accumulator: if new_count == free { 0.0 } else { spawn_accumulator.fract() },
+            spawned: spawned + new_count as u32,
+            ..self.clone()
+        }
+    }
+
+    fn render(&self, _mask: &[bool], context: &DisplayContext) -> LayerOutput {
+        let pixel_count = context.pixel_count();
+        LayerOutput::with_alpha(vec![0; pixel_count * 4], vec![0.0; pixel_count])
+    }
+}
+
+/// Builder for particle system layers
+pub struct ParticleSystemBuilder {
+    logic: ParticleSystemLogic,
+    fps: f32,
+    priority: i32,
+}
+
+impl ParticleSystemBuilder {
+    pub fn new(max_particles: usize) -> Self {
+        Self {
+            logic: ParticleSystemLogic {
+                particles: Vec::with_capacity(max_particles),
+                max_particles,
+                emitter: Vec3::ZERO,
+                initial_velocity: Vec3::new(0.0, 5.0, 0.0),
+                spread: 1.0,
+                gravity: Vec3::new(0.0, -9.81, 0.0),
+                lifetime: 2.0,
+                spawn_rate: 20.0,
+                spawn_accumulator: 0.0,
+                spawned: 0,
+                size: 0.2,
+                color: [1.0, 0.6, 0.2],
+            },
+            fps: 60.0,
+            priority: 0,
+        }
+    }
+
+    /// Position new particles spawn at
+    pub fn emitter(mut self, position: Vec3) -> Self {
+        self.logic.emitter = position;
+        self
+    }
+
+    /// Particles spawned per second until `max_particles` are alive
+    pub fn spawn_rate(mut self, per_second: f32) -> Self {
+        self.logic.spawn_rate = per_second.max(0.0);
+        self
+    }
+
+    pub fn gravity(mut self, gravity: Vec3) -> Self {
+        self.logic.gravity = gravity;
+        self
+    }
+
+    /// Seconds before a particle respawns at the emitter
+    pub fn lifetime(mut self, seconds: f32) -> Self {
+        self.logic.lifetime = seconds;
+        self
+    }
+
+    /// Launch velocity, jittered horizontally by up to `spread`
+    pub fn velocity(mut self, velocity: Vec3, spread: f32) -> Self {
+        self.logic.initial_velocity = velocity;
+        self.logic.spread = spread;
+        self
+    }
+
+    /// Edge length and color of each particle box
+    pub fn appearance(mut self, size: f32, color: [f32; 3]) -> Self {
+        self.logic.size = size;
+        self.logic.color = color;
+        self
+    }
+
+    pub fn fps(mut self, fps: f32) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Build the logic alone (for driving particles into a scene directly)
+    pub fn build_logic(self) -> ParticleSystemLogic {
+        self.logic
+    }
+
+    pub fn build(self) -> Box<dyn Layer> {
+        Box::new(TimedLayer::new(self.logic, self.fps, self.priority))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::controller::Button;
+
+    struct MockController;
+
+    impl Controller for MockController {
+        fn is_down(&self, _button: Button) -> bool {
+            false
+        }
+
+        fn get_down_keys(&self) -> &[Button] {
+            &[]
+        }
+    }
+
+    #[test]
+    fn test_velocity_integrates_position() {
+        let particle = Particle { position: Vec3::new(1.0, 2.0, 3.0), velocity: Vec3::new(2.0, 0.0, -4.0), age: 0.0 };
+
+        let moved = particle.integrate(Vec3::ZERO, 0.5);
+        assert_eq!(moved.position, Vec3::new(2.0, 2.0, 1.0));
+        assert_eq!(moved.velocity, particle.velocity);
+        assert_eq!(moved.age, 0.5);
+
+        let falling = particle.integrate(Vec3::new(0.0, -10.0, 0.0), 0.5);
+        assert_eq!(falling.velocity, Vec3::new(2.0, -5.0, -4.0));
+        assert_eq!(falling.position, Vec3::new(2.0, -0.5, 1.0));
+    }
+
+    #[test]
+    fn test_expired_particle_respawns_at_emitter() {
+        let emitter = Vec3::new(5.0, 1.0, -2.0);
+        let logic = ParticleSystemBuilder::new(1)
+            .emitter(emitter)
+            .lifetime(1.0)
+            .spawn_rate(100.0)
+            .build_logic()
+            .update(0.1, &MockController);
+        assert_eq!(logic.particles().len(), 1);
+
+        let moved = logic.update(0.5, &MockController);
+        assert_ne!(moved.particles()[0].position, emitter);
+
+        let respawned = moved.update(0.6, &MockController);
+        assert_eq!(respawned.particles().len(), 1);
+        assert_eq!(respawned.particles()[0].position, emitter);
+        assert_eq!(respawned.particles()[0].age, 0.0);
+    }
+
+    #[test]
+    fn test_spawn_rate_caps_at_max_particles() {
+        let logic = ParticleSystemBuilder::new(10).spawn_rate(40.0).build_logic();
+
+        let after_quarter_second = logic.update(0.25, &MockController);
+        assert_eq!(after_quarter_second.particles().len(), 10);
+
+        let later = after_quarter_second.update(0.25, &MockController);
+        assert_eq!(later.particles().len(), 10);
+    }
+
+    #[test]
+    fn test_particles_emit_boxes() {
+        let logic = ParticleSystemBuilder::new(4)
+            .appearance(1.0, [0.0, 1.0, 0.0])
+            .spawn_rate(100.0)
+            .build_logic()
+            .update(0.05, &MockController);
+
+        let boxes = logic.boxes();
+        assert_eq!(boxes.len(), logic.particles().len());
+        assert!(boxes.iter().all(|b| (b.max[0] - b.min[0] - 1.0).abs() < 1e-5));
+    }
+}
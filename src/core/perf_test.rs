@@ -9,6 +9,7 @@ pub struct PerfResult {
     pub avg_duration: Duration,
     pub min_duration: Duration,
     pub max_duration: Duration,
+    pub p95_duration: Duration,
     pub std_dev: f64,
 }
 
@@ -25,6 +26,7 @@ impl PerfResult {
         println!("Average:    {:?}", self.avg_duration);
         println!("Min:        {:?}", self.min_duration);
         println!("Max:        {:?}", self.max_duration);
+        println!("P95:        {:?}", self.p95_duration);
         println!("Std Dev:    {:.2} µs", self.std_dev * 1_000_000.0);
     }
 
@@ -94,6 +96,10 @@ impl PerfTest {
         let min = *durations.iter().min().unwrap();
         let max = *durations.iter().max().unwrap();
 
+        let mut sorted = durations.to_vec();
+        sorted.sort();
+        let p95 = sorted[(sorted.len() * 95).div_ceil(100).saturating_sub(1)];
+
         // Calculate standard deviation
         let avg_secs = avg.as_secs_f64();
         let variance: f64 = durations
@@ -113,6 +119,7 @@ impl PerfTest {
             avg_duration: avg,
             min_duration: min,
             max_duration: max,
+            p95_duration: p95,
             std_dev,
         }
     }
@@ -309,6 +316,7 @@ mod tests {
             avg_duration: Duration::from_millis(10),
             min_duration: Duration::from_millis(9),
             max_duration: Duration::from_millis(11),
+            p95_duration: Duration::from_millis(11),
             std_dev: 0.001,
         };
 
@@ -352,6 +360,16 @@ mod tests {
 
         assert_eq!(result.min_duration, Duration::from_millis(9));
         assert_eq!(result.max_duration, Duration::from_millis(13));
+        assert_eq!(result.p95_duration, Duration::from_millis(13));
         assert!(result.std_dev > 0.0);
     }
+
+    #[test]
+    fn test_p95_calculation() {
+        let durations: Vec<Duration> = (1..=100).rev().map(Duration::from_millis).collect();
+
+        let result = PerfTest::new("p95_test").calculate_stats(&durations);
+
+        assert_eq!(result.p95_duration, Duration::from_millis(95));
+    }
 }
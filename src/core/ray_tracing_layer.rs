@@ -1,21 +1,70 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use glam::Vec3;
-use wgpu::util::DeviceExt;
+use half::f16;
 
 use super::controller::{Button, Controller};
 use super::display_context::DisplayContext;
 use super::gpu_context::GpuContext;
 use super::layer::{Layer, LayerLogic, LayerOutput, TimedLayer};
 
-use crate::camera::{CAMERA_SPEED, CAMERA_ROTATION_SPEED};
+use crate::accumulation::Accumulation;
+use crate::camera::{
+    adjust_speed_multiplier, normalize_planar, Camera, MovementState, StrafeMode, CAMERA_SPEED, CAMERA_ROTATION_SPEED, DEFAULT_FAR_CLIP,
+    DEFAULT_FOCUS_DISTANCE, DEFAULT_NEAR_CLIP, FALLBACK_VIEWPOINT, SPRINT_MULTIPLIER, scene_viewpoint,
+};
+use crate::editor::pick;
+use crate::fog::DEFAULT_FOG_COLOR;
 use crate::grid::HierarchicalGrid;
+use crate::key_bindings::KeyBindings;
+use crate::math::{intersect_aabb_range, AABB};
+use crate::render_settings::{scaled_size, MAX_RENDER_SCALE};
+use crate::renderer::{
+    padded_row_bytes, resolve_lights, workgroup_counts, ComputeResources, RayTracer, RenderTargets, SceneUpload,
+    OUTPUT_TEXEL_BYTES,
+};
+use crate::resolution::Resolution;
 use crate::scenes::*;
-use crate::types::{CameraUniform, MaterialData};
+use crate::sky::{SkyMode, GRADIENT_BOTTOM, GRADIENT_TOP};
+use crate::lighting::LightingConfig;
+use crate::types::{BoxData, CameraUniform, LightData, MaterialData, SceneConfig};
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-const WORKGROUP_SIZE: u32 = 8;
 const DEFAULT_FOV: f32 = std::f32::consts::FRAC_PI_4; // π/4 = 45 degrees
+/// Speed steps per second while +/- is held
+const SPEED_STEPS_PER_SECOND: f32 = 4.0;
+/// Keyboard pitch stops just short of straight up/down, where yaw would become degenerate
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+/// Pose change below which progressive rendering keeps accumulating
+const CAMERA_MOVE_EPSILON: f32 = 1e-5;
+/// Smallest bounding sphere `frame_scene` fits, so empty or flat scenes keep a usable distance
+const MIN_FRAME_RADIUS: f32 = 0.1;
+
+/// Distance at which a sphere of `radius` just fits a view whose edge rays are `half_fov` off-axis
+fn frame_distance(radius: f32, half_fov: f32) -> f32 {
+    radius / half_fov.sin()
+}
+
+/// Region the camera is kept inside, optionally excluding solid boxes
+#[derive(Debug)]
+struct CameraBounds {
+    limits: AABB,
+    solids: Vec<AABB>,
+}
+
+impl CameraBounds {
+    /// `to` clamped into the limits; a move whose path enters a solid box keeps `from`
+    ///
+    /// The whole segment is swept, so a fast step cannot tunnel through a thin wall.
+    fn constrain(&self, from: Vec3, to: Vec3) -> Vec3 {
+        let clamped = self.limits.clamp(to);
+        let enters_solid = self.solids.iter().any(|s| {
+            !s.contains_point(from)
+                && intersect_aabb_range(from, clamped - from, s.min, s.max).is_some_and(|(t_near, _)| t_near <= 1.0)
+        });
+        if enters_solid { from } else { clamped }
+    }
+}
 
 /// Functional camera state for ray tracing
 #[derive(Clone, Debug)]
@@ -23,27 +72,58 @@ struct CameraState {
     position: Vec3,
     yaw: f32,
     pitch: f32,
+    aperture: f32,
+    focus_distance: f32,
+    samples_per_pixel: u32,
+    /// Runtime movement speed factor; a fresh state per scene resets it
+    speed_multiplier: f32,
+    strafe: StrafeMode,
+    /// Movement limits; `None` lets the camera fly anywhere
+    bounds: Option<Arc<CameraBounds>>,
+    bindings: KeyBindings,
 }
 
 impl CameraState {
     /// Create camera for a specific scene
     fn new_for_scene(scene_name: &str) -> Self {
-        let (position, yaw, pitch) = match scene_name {
-            "composed" => (Vec3::new(0.0, 40.0, 40.0), std::f32::consts::PI, -0.7),
-            "walls" => (Vec3::new(0.0, 5.0, 0.0), 0.0, 0.0),
-            "tunnel" => (Vec3::new(0.0, 0.0, 20.0), std::f32::consts::PI, 0.0),
-            "gltf" => (Vec3::new(200.0, 200.0, 300.0), 3.35, -0.28),
-            "pyramid" => (Vec3::new(0.0, 8.0, 20.0), std::f32::consts::PI, -0.5),
-            _ => (Vec3::new(0.0, 8.0, 15.0), std::f32::consts::PI, -0.6),
-        };
+        let (position, yaw, pitch) = scene_viewpoint(scene_name).unwrap_or(FALLBACK_VIEWPOINT);
 
         Self {
             position,
             yaw,
             pitch,
+            aperture: 0.0,
+            focus_distance: DEFAULT_FOCUS_DISTANCE,
+            samples_per_pixel: 1,
+            speed_multiplier: 1.0,
+            strafe: StrafeMode::Camera,
+            bounds: None,
+            bindings: KeyBindings::default(),
         }
     }
 
+    /// Set thin-lens depth of field parameters
+    fn with_lens(self, aperture: f32, focus_distance: f32, samples_per_pixel: u32) -> Self {
+        Self {
+            aperture,
+            focus_distance,
+            samples_per_pixel,
+            ..self
+        }
+    }
+
+    fn with_strafe(self, strafe: StrafeMode) -> Self {
+        Self { strafe, ..self }
+    }
+
+    fn with_bounds(self, bounds: Option<CameraBounds>) -> Self {
+        Self { bounds: bounds.map(Arc::new), ..self }
+    }
+
+    fn with_key_bindings(self, bindings: KeyBindings) -> Self {
+        Self { bindings, ..self }
+    }
+
     /// Functional update from controller input
     fn update(&self, delta: f32, controller: &dyn Controller) -> Self {
         // Calculate movement velocity
@@ -51,49 +131,90 @@ impl CameraState {
         let mut right_dir = 0.0f32;
         let mut up_dir = 0.0f32;
 
-        if controller.is_down(Button::KeyW) {
+        if controller.is_down(self.bindings.forward) {
             fwd += 1.0;
         }
-        if controller.is_down(Button::KeyS) {
+        if controller.is_down(self.bindings.back) {
             fwd -= 1.0;
         }
-        if controller.is_down(Button::KeyD) {
+        if controller.is_down(self.bindings.strafe_right) {
             right_dir += 1.0;
         }
-        if controller.is_down(Button::KeyA) {
+        if controller.is_down(self.bindings.strafe_left) {
             right_dir -= 1.0;
         }
-        if controller.is_down(Button::Space) {
+        if controller.is_down(self.bindings.up) {
             up_dir += 1.0;
         }
-        if controller.is_down(Button::Shift) {
+        if controller.is_down(self.bindings.down) {
             up_dir -= 1.0;
         }
 
         // Calculate rotation velocity
         let mut yaw_delta = 0.0f32;
-        if controller.is_down(Button::KeyE) {
+        if controller.is_down(self.bindings.yaw_right) {
             yaw_delta += 1.0;
         }
-        if controller.is_down(Button::KeyQ) {
+        if controller.is_down(self.bindings.yaw_left) {
             yaw_delta -= 1.0;
         }
+        let mut pitch_delta = 0.0f32;
+        if controller.is_down(self.bindings.pitch_up) {
+            pitch_delta += 1.0;
+        }
+        if controller.is_down(self.bindings.pitch_down) {
+            pitch_delta -= 1.0;
+        }
+
+        // Holding +/- scales speed continuously
+        let mut speed_steps = 0.0f32;
+        if controller.is_down(Button::Plus) {
+            speed_steps += 1.0;
+        }
+        if controller.is_down(Button::Minus) {
+            speed_steps -= 1.0;
+        }
+        let speed_multiplier =
+            adjust_speed_multiplier(self.speed_multiplier, speed_steps * SPEED_STEPS_PER_SECOND * delta);
+        let sprint = if controller.is_down(self.bindings.sprint) { SPRINT_MULTIPLIER } else { 1.0 };
+        let speed = CAMERA_SPEED * speed_multiplier * sprint;
 
-        // Calculate displacement
-        let forward = self.forward();
-        let right = self.right();
+        // Calculate displacement; diagonals move at the same speed as a single axis
+        let (forward, right) = self.strafe.axes(self.forward(), self.right());
+        let (fwd, right_dir) = normalize_planar(fwd, right_dir);
 
-        let displacement = forward * fwd * CAMERA_SPEED * delta
-            + right * right_dir * CAMERA_SPEED * delta
-            + Vec3::Y * up_dir * CAMERA_SPEED * delta;
+        let displacement = forward * fwd * speed * delta
+            + right * right_dir * speed * delta
+            + Vec3::Y * up_dir * speed * delta;
+
+        let position = self.position + displacement;
 
         Self {
-            position: self.position + displacement,
+            position: self.bounds.as_ref().map_or(position, |b| b.constrain(self.position, position)),
             yaw: self.yaw + yaw_delta * CAMERA_ROTATION_SPEED * delta,
-            pitch: self.pitch,
+            pitch: (self.pitch + pitch_delta * CAMERA_ROTATION_SPEED * delta).clamp(-MAX_PITCH, MAX_PITCH),
+            speed_multiplier,
+            ..self.clone()
+        }
+    }
+
+    /// Back away along the view direction until the bounding sphere of `bounds` fits the field of view,
+    /// leaving the camera aimed at its center
+    fn frame_scene(&self, bounds: &AABB) -> Self {
+        let radius = ((bounds.max - bounds.min).length() * 0.5).max(MIN_FRAME_RADIUS);
+        Self {
+            position: bounds.center() - self.forward() * frame_distance(radius, DEFAULT_FOV),
+            ..self.clone()
         }
     }
 
+    /// Whether the view differs from `other` enough to invalidate accumulated frames
+    fn moved_from(&self, other: &CameraState) -> bool {
+        !self.position.abs_diff_eq(other.position, CAMERA_MOVE_EPSILON)
+            || (self.yaw - other.yaw).abs() > CAMERA_MOVE_EPSILON
+            || (self.pitch - other.pitch).abs() > CAMERA_MOVE_EPSILON
+    }
+
     /// Get forward vector
     fn forward(&self) -> Vec3 {
         Vec3::new(
@@ -114,9 +235,23 @@ impl CameraState {
         Vec3::Y
     }
 
+    /// The windowed renderer's camera at the same pose, for its ray generation
+    fn to_camera(&self, fov: f32) -> Camera {
+        Camera {
+            position: self.position,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            fov,
+            movement: MovementState::default(),
+            speed_multiplier: self.speed_multiplier,
+            strafe: self.strafe,
+            bindings: self.bindings,
+        }
+    }
+
     /// Convert to GPU uniform
-    fn to_uniform(&self, time: f32, screen_height: f32, fov: f32, show_grid: bool) -> CameraUniform {
-        let lod_factor = Self::calculate_lod_factor(screen_height, fov);
+    fn to_uniform(&self, time: f32, resolution: Resolution, fov: f32, show_grid: bool) -> CameraUniform {
+        let lod_factor = resolution.lod_factor(fov);
         let min_pixel_size = 2.0;
 
         CameraUniform {
@@ -131,13 +266,17 @@ impl CameraState {
             lod_factor,
             min_pixel_size,
             show_grid: if show_grid { 1.0 } else { 0.0 },
+            aperture: self.aperture,
+            focus_distance: self.focus_distance,
+            samples_per_pixel: self.samples_per_pixel,
+            fov,
+            ambient: DEFAULT_AMBIENT,
+            near: DEFAULT_NEAR_CLIP,
+            far: DEFAULT_FAR_CLIP,
+            sample_offset: 0,
             _pad4: 0.0,
         }
     }
-
-    fn calculate_lod_factor(screen_height: f32, fov: f32) -> f32 {
-        screen_height / (2.0 * (fov / 2.0).tan())
-    }
 }
 
 /// GPU compute state for ray tracing
@@ -145,35 +284,53 @@ struct ComputeState {
     pipeline: wgpu::ComputePipeline,
     bind_group: wgpu::BindGroup,
     camera_buffer: wgpu::Buffer,
-    output_texture: wgpu::Texture,
+    targets: RenderTargets,
     staging_buffer: wgpu::Buffer,
     width: u32,
     height: u32,
+    /// Output texture size: `width` x `height` scaled by the layer's render scale
+    render_width: u32,
+    render_height: u32,
+    /// Whole-scene bounds from the grid metadata
+    scene_bounds: AABB,
+    /// Scene boxes, for camera collision
+    solids: Vec<AABB>,
+    /// Scene boxes as uploaded, for picking
+    boxes: Vec<BoxData>,
+    /// Progressive frame count and the camera it was accumulated for; the running average itself lives in
+    /// `targets.accumulation_buffer`, blended by the shader
+    accumulation: Mutex<(Accumulation, Option<CameraState>)>,
 }
 
 impl ComputeState {
-    async fn new(
-        gpu: &GpuContext,
-        scene_name: &str,
-        width: u32,
-        height: u32,
-    ) -> Result<Self> {
+    async fn new(builder: &RayTracingLayerBuilder) -> Result<Self> {
+        let RayTracingLayerBuilder { gpu, width, height, .. } = builder;
+        let (width, height) = (*width, *height);
+        let scene_name = builder.scene_name.as_str();
         let device = gpu.device();
+        let (render_width, render_height) = scaled_size(width, height, builder.render_scale);
 
         // Load scene data
+        let SceneFile { boxes: file_boxes, triangles: file_triangles, materials: file_materials, .. } =
+            match scene_name.strip_prefix(SCENE_FILE_PREFIX) {
+                Some(path) => load_scene(path)?,
+                None => SceneFile::default(),
+            };
         let boxes = match scene_name {
             "composed" => create_composed_scene(),
             "walls" => create_walls_scene(),
             "tunnel" => create_tunnel_scene(),
             "default" => create_default_scene(),
             "reflected" => create_reflected_scene(),
+            "stress" => create_stress_scene(builder.stress_count),
             "gltf" => vec![],
             "pyramid" => vec![],
+            name if name.starts_with(SCENE_FILE_PREFIX) => file_boxes,
             _ => create_fractal_scene(),
         };
 
         // Load triangles and materials
-        let (triangles, materials, _textures) = if scene_name == "pyramid" {
+        let (triangles, materials, textures) = if scene_name == "pyramid" {
             let tris = create_pyramid_triangles();
             let mats = vec![
                 MaterialData::new_color([1.0, 0.2, 0.2, 1.0]), // Red
@@ -184,266 +341,88 @@ impl ComputeState {
             ];
             (tris, mats, vec![])
         } else if scene_name == "gltf" {
-            create_gltf_triangles()
+            create_gltf_triangles(None)?
         } else {
-            (vec![], vec![], vec![])
+            (file_triangles, file_materials, vec![])
         };
 
         // Build hierarchical grid
         let grid = HierarchicalGrid::build(&boxes, &triangles);
-        let (metadata, coarse_counts, fine_cells) = grid.to_gpu_buffers();
-
-        // Create GPU buffers
-        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Camera Buffer"),
-            size: std::mem::size_of::<CameraUniform>() as u64,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        let boxes_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Boxes Buffer"),
-            contents: bytemuck::cast_slice(&boxes),
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-
-        let triangles_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Triangles Buffer"),
-            contents: bytemuck::cast_slice(&triangles),
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-
-        let materials_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Materials Buffer"),
-            contents: bytemuck::cast_slice(&materials),
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-
-        let grid_metadata_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Grid Metadata Buffer"),
-            contents: bytemuck::cast_slice(&[metadata]),
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-
-        let coarse_counts_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Coarse Counts Buffer"),
-            contents: bytemuck::cast_slice(&coarse_counts),
-            usage: wgpu::BufferUsages::STORAGE,
+        let scene_bounds = grid.bounds;
+        let solids = boxes.iter().map(|b| AABB::new(b.min.into(), b.max.into())).collect();
+
+        // Same resources, layout and shader as the windowed renderer
+        let lights = resolve_lights(&builder.lights, scene_name, &LightingConfig::default());
+        let resources = ComputeResources::new(device, gpu.queue(), &SceneUpload {
+            grid: &grid,
+            boxes: &boxes,
+            triangles: &triangles,
+            materials: &materials,
+            textures: &textures,
+            lights: &lights,
+            scene_config: SceneConfig::new(boxes.len(), triangles.len())
+                .with_lights(&lights)
+                .with_fog(builder.fog_color, builder.fog_density)
+                .with_sky(builder.sky)
+                .with_sky_colors(builder.sky_top, builder.sky_bottom),
+            skybox: None,
         });
+        let targets = RenderTargets::new(device, (render_width, render_height));
+        let (pipeline, bind_group_layout) = RayTracer::create_compute_pipeline(device);
+        let bind_group = RayTracer::create_compute_bind_group(device, &bind_group_layout, &resources, &targets);
 
-        let fine_cells_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Fine Cells Buffer"),
-            contents: bytemuck::cast_slice(&fine_cells),
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-
-        // Create output texture
-        let output_texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("Ray Tracing Output Texture"),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
-            view_formats: &[],
-        });
-
-        let output_view = output_texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-        // Create staging buffer for readback
+        // Create staging buffer for readback; texture copies pad each row to the copy alignment
         let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Staging Buffer"),
-            size: (width * height * 4) as u64,
+            size: (padded_row_bytes(render_width, OUTPUT_TEXEL_BYTES) * render_height) as u64,
             usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
             mapped_at_creation: false,
         });
 
-        // Load compute shader
-        let shader_source = include_str!("../raytracer_unified.wgsl");
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Ray Tracing Compute Shader"),
-            source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-        });
-
-        // Create bind group layout
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Ray Tracing Bind Group Layout"),
-            entries: &[
-                // Camera (binding 0)
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                // Output texture (binding 1)
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::StorageTexture {
-                        access: wgpu::StorageTextureAccess::WriteOnly,
-                        format: wgpu::TextureFormat::Rgba8Unorm,
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                    },
-                    count: None,
-                },
-                // Boxes (binding 2)
-                wgpu::BindGroupLayoutEntry {
-                    binding: 2,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                // Triangles (binding 3)
-                wgpu::BindGroupLayoutEntry {
-                    binding: 3,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                // Materials (binding 4)
-                wgpu::BindGroupLayoutEntry {
-                    binding: 4,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                // Grid metadata (binding 5)
-                wgpu::BindGroupLayoutEntry {
-                    binding: 5,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                // Coarse counts (binding 6)
-                wgpu::BindGroupLayoutEntry {
-                    binding: 6,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                // Fine cells (binding 7)
-                wgpu::BindGroupLayoutEntry {
-                    binding: 7,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-            ],
-        });
-
-        // Create bind group
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Ray Tracing Bind Group"),
-            layout: &bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: camera_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(&output_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: boxes_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 3,
-                    resource: triangles_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 4,
-                    resource: materials_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 5,
-                    resource: grid_metadata_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 6,
-                    resource: coarse_counts_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 7,
-                    resource: fine_cells_buffer.as_entire_binding(),
-                },
-            ],
-        });
-
-        // Create compute pipeline
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Ray Tracing Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
-            push_constant_ranges: &[],
-        });
-
-        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            label: Some("Ray Tracing Compute Pipeline"),
-            layout: Some(&pipeline_layout),
-            module: &shader,
-            entry_point: Some("main"),
-            compilation_options: Default::default(),
-            cache: None,
-        });
-
         Ok(Self {
             pipeline,
             bind_group,
-            camera_buffer,
-            output_texture,
+            camera_buffer: resources.camera_buffer,
+            targets,
             staging_buffer,
             width,
             height,
+            render_width,
+            render_height,
+            scene_bounds,
+            solids,
+            boxes,
+            accumulation: Mutex::new((Accumulation::new(builder.progressive), None)),
         })
     }
 
-    /// Render a frame and return pixels
-    fn render(
-        &self,
-        gpu: &GpuContext,
-        camera: &CameraState,
-        time: f32,
-    ) -> Result<Vec<u8>> {
+    /// Scene bounds grown by `margin`, with the boxes as obstacles when `block_boxes` is set
+    fn camera_bounds(&self, margin: f32, block_boxes: bool) -> CameraBounds {
+        CameraBounds {
+            limits: self.scene_bounds.expanded(margin),
+            solids: if block_boxes { self.solids.clone() } else { vec![] },
+        }
+    }
+
+    /// Render a frame and return pixels, averaged on the GPU with earlier frames of the same view while
+    /// progressive rendering is enabled
+    fn render(&self, gpu: &GpuContext, camera: &CameraState, time: f32) -> Result<Vec<u8>> {
         let device = gpu.device();
         let queue = gpu.queue();
 
+        let accumulation = {
+            let mut state = self.accumulation.lock().unwrap();
+            let (accumulation, last_camera) = &mut *state;
+            *accumulation = next_accumulation(*accumulation, last_camera.as_ref(), camera);
+            *last_camera = Some(camera.clone());
+            *accumulation
+        };
+
         // Update camera uniform
-        let camera_uniform = camera.to_uniform(time, self.height as f32, DEFAULT_FOV, false);
+        let camera_uniform = CameraUniform {
+            sample_offset: accumulation.frame,
+            ..camera.to_uniform(time, Resolution::new(self.render_width, self.render_height), DEFAULT_FOV, false)
+        };
         queue.write_buffer(&self.camera_buffer, 0, bytemuck::bytes_of(&camera_uniform));
 
         // Create command encoder
@@ -461,25 +440,24 @@ impl ComputeState {
             compute_pass.set_pipeline(&self.pipeline);
             compute_pass.set_bind_group(0, &self.bind_group, &[]);
 
-            let workgroup_count_x = (self.width + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
-            let workgroup_count_y = (self.height + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
-            compute_pass.dispatch_workgroups(workgroup_count_x, workgroup_count_y, 1);
+            let (workgroups_x, workgroups_y) = workgroup_counts((self.render_width, self.render_height));
+            compute_pass.dispatch_workgroups(workgroups_x, workgroups_y, 1);
         }
 
         // Copy texture to staging buffer
         encoder.copy_texture_to_buffer(
-            self.output_texture.as_image_copy(),
+            self.targets.output_texture.as_image_copy(),
             wgpu::TexelCopyBufferInfo {
                 buffer: &self.staging_buffer,
                 layout: wgpu::TexelCopyBufferLayout {
                     offset: 0,
-                    bytes_per_row: Some(4 * self.width),
-                    rows_per_image: Some(self.height),
+                    bytes_per_row: Some(padded_row_bytes(self.render_width, OUTPUT_TEXEL_BYTES)),
+                    rows_per_image: Some(self.render_height),
                 },
             },
             wgpu::Extent3d {
-                width: self.width,
-                height: self.height,
+                width: self.render_width,
+                height: self.render_height,
                 depth_or_array_layers: 1,
             },
         );
@@ -487,10 +465,51 @@ impl ComputeState {
         queue.submit(Some(encoder.finish()));
 
         // Read pixels (BLOCKING)
-        let pixels = gpu.read_buffer_sync(&self.staging_buffer)?;
+        let padded = gpu.read_buffer_sync(&self.staging_buffer)?;
+        let row_bytes = self.render_width * OUTPUT_TEXEL_BYTES;
+        let texels = strip_row_padding(&padded, row_bytes as usize, padded_row_bytes(self.render_width, OUTPUT_TEXEL_BYTES) as usize);
+        let pixels = hdr_to_rgba8(&texels);
+
+        Ok(upscale_nearest(&pixels, (self.render_width, self.render_height), (self.width, self.height)))
+    }
+}
+
+/// Progressive state for rendering `camera`: restarts when it moved from `previous` or nothing was rendered yet
+fn next_accumulation(accumulation: Accumulation, previous: Option<&CameraState>, camera: &CameraState) -> Accumulation {
+    match previous {
+        Some(previous) if !camera.moved_from(previous) => accumulation.advance(),
+        _ => accumulation.reset(),
+    }
+}
+
+/// Tightly packed rows of a texture readback whose rows are `padded_row` bytes apart
+fn strip_row_padding(padded: &[u8], row: usize, padded_row: usize) -> Vec<u8> {
+    padded.chunks(padded_row).flat_map(|r| &r[..row]).copied().collect()
+}
+
+/// RGBA8 from little-endian RGBA16F texels, clamped to the displayable range (layers are composited untone-mapped)
+fn hdr_to_rgba8(texels: &[u8]) -> Vec<u8> {
+    texels
+        .chunks_exact(2)
+        .map(|c| (f16::from_le_bytes([c[0], c[1]]).to_f32().clamp(0.0, 1.0) * 255.0).round() as u8)
+        .collect()
+}
 
-        Ok(pixels)
+/// Resize an RGBA8 image by nearest-neighbour sampling (a no-op copy when sizes match)
+fn upscale_nearest(pixels: &[u8], (src_width, src_height): (u32, u32), (width, height): (u32, u32)) -> Vec<u8> {
+    if (src_width, src_height) == (width, height) {
+        return pixels.to_vec();
     }
+
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            let sx = (x as u64 * src_width as u64 / width as u64) as usize;
+            let sy = (y as u64 * src_height as u64 / height as u64) as usize;
+            let i = (sy * src_width as usize + sx) * 4;
+            [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+        })
+        .collect()
 }
 
 /// Ray tracing layer logic
@@ -504,29 +523,57 @@ pub struct RayTracingLogic {
 }
 
 impl RayTracingLogic {
-    async fn new(
-        gpu: Arc<GpuContext>,
-        scene_name: String,
-        width: u32,
-        height: u32,
-    ) -> Result<Self> {
-        let camera = CameraState::new_for_scene(&scene_name);
-        let compute = ComputeState::new(&gpu, &scene_name, width, height).await?;
+    async fn new(builder: &RayTracingLayerBuilder) -> Result<Self> {
+        let compute = ComputeState::new(builder).await?;
+        let camera = CameraState::new_for_scene(&builder.scene_name)
+            .with_lens(builder.aperture, builder.focus_distance, builder.samples_per_pixel)
+            .with_strafe(builder.strafe)
+            .with_bounds(builder.confine.map(|(margin, block_boxes)| compute.camera_bounds(margin, block_boxes)))
+            .with_key_bindings(builder.key_bindings);
+        let camera = match scene_viewpoint(&builder.scene_name) {
+            Some(_) => camera,
+            None => camera.frame_scene(&compute.scene_bounds),
+        };
 
         Ok(Self {
-            gpu,
+            gpu: builder.gpu.clone(),
             compute: Arc::new(compute),
             camera,
-            scene_name,
+            scene_name: builder.scene_name.clone(),
             elapsed_time: 0.0,
         })
     }
 }
 
+impl RayTracingLogic {
+    /// Index and data of the scene box seen at layer pixel (x, y), `None` for background
+    ///
+    /// A CPU ray cast, not a GPU readback: the pixel's primary ray comes from `Camera::primary_ray` and is
+    /// tested against the uploaded boxes, so it ignores triangles and finds the same box as the shader's pinhole ray.
+    pub fn pick(&self, x: u32, y: u32) -> Option<(usize, BoxData)> {
+        pick_box(&self.camera, (x, y), Resolution::new(self.compute.width, self.compute.height), &self.compute.boxes)
+    }
+}
+
+/// Nearest box along the primary ray through `pixel`
+fn pick_box(
+    camera: &CameraState,
+    pixel: (u32, u32),
+    resolution: Resolution,
+    boxes: &[BoxData],
+) -> Option<(usize, BoxData)> {
+    let dir = camera.to_camera(DEFAULT_FOV).primary_ray(pixel, resolution);
+    pick(camera.position, dir, boxes).map(|index| (index, boxes[index]))
+}
+
 impl LayerLogic for RayTracingLogic {
     fn update(&self, delta: f32, controller: &dyn Controller) -> Self {
         let new_camera = self.camera.update(delta, controller);
-
+        let new_camera = if controller.is_down(new_camera.bindings.frame_scene) {
+            new_camera.frame_scene(&self.compute.scene_bounds)
+        } else {
+            new_camera
+        };
         Self {
             gpu: self.gpu.clone(),
             compute: self.compute.clone(),
@@ -557,6 +604,26 @@ pub struct RayTracingLayerBuilder {
     height: u32,
     fps: f32,
     priority: i32,
+    aperture: f32,
+    focus_distance: f32,
+    samples_per_pixel: u32,
+    render_scale: f32,
+    strafe: StrafeMode,
+    /// Margin around the scene bounds and whether boxes block the camera
+    confine: Option<(f32, bool)>,
+    progressive: bool,
+    key_bindings: KeyBindings,
+    /// Exponential fog density; 0 disables fog
+    fog_density: f32,
+    fog_color: [f32; 3],
+    /// Background for rays that miss the scene
+    sky: SkyMode,
+    sky_top: [f32; 3],
+    sky_bottom: [f32; 3],
+    /// Replaces the scene's lights when non-empty
+    lights: Vec<LightData>,
+    /// Box count for the `stress` scene
+    stress_count: usize,
 }
 
 impl RayTracingLayerBuilder {
@@ -568,6 +635,21 @@ impl RayTracingLayerBuilder {
             height,
             fps: 60.0,
             priority: 0,
+            aperture: 0.0,
+            focus_distance: DEFAULT_FOCUS_DISTANCE,
+            samples_per_pixel: 1,
+            render_scale: MAX_RENDER_SCALE,
+            strafe: StrafeMode::Camera,
+            confine: None,
+            progressive: false,
+            key_bindings: KeyBindings::default(),
+            fog_density: 0.0,
+            fog_color: DEFAULT_FOG_COLOR,
+            sky: SkyMode::default(),
+            sky_top: GRADIENT_TOP,
+            sky_bottom: GRADIENT_BOTTOM,
+            lights: Vec::new(),
+            stress_count: DEFAULT_STRESS_COUNT,
         }
     }
 
@@ -581,14 +663,92 @@ impl RayTracingLayerBuilder {
         self
     }
 
+    /// Lens diameter for depth of field (0 = pinhole)
+    pub fn aperture(mut self, aperture: f32) -> Self {
+        self.aperture = aperture;
+        self
+    }
+
+    /// Distance along each primary ray that stays in sharp focus
+    pub fn focus_distance(mut self, focus_distance: f32) -> Self {
+        self.focus_distance = focus_distance;
+        self
+    }
+
+    /// Lens samples per pixel; depth of field needs more than 1
+    pub fn samples_per_pixel(mut self, samples_per_pixel: u32) -> Self {
+        self.samples_per_pixel = samples_per_pixel.max(1);
+        self
+    }
+
+    /// Trace at this fraction of the layer resolution (0.25-1.0) and upscale the result
+    pub fn render_scale(mut self, render_scale: f32) -> Self {
+        self.render_scale = render_scale;
+        self
+    }
+
+    /// Move along world axes instead of the view direction (e.g. for top-down views)
+    pub fn strafe(mut self, strafe: StrafeMode) -> Self {
+        self.strafe = strafe;
+        self
+    }
+
+    /// Keep the camera within the scene bounds grown by `margin`, optionally unable to enter boxes
+    pub fn confine_to_scene(mut self, margin: f32, block_boxes: bool) -> Self {
+        self.confine = Some((margin.max(0.0), block_boxes));
+        self
+    }
+
+    /// Average frames while the camera is still, so depth of field and glossy reflections converge
+    pub fn progressive(mut self, enabled: bool) -> Self {
+        self.progressive = enabled;
+        self
+    }
+
+    /// Remap the camera's movement keys
+    pub fn key_bindings(mut self, key_bindings: KeyBindings) -> Self {
+        self.key_bindings = key_bindings;
+        self
+    }
+
+    /// Blend hits toward the fog color by `1 - exp(-density * distance)`; 0 disables fog
+    pub fn fog_density(mut self, fog_density: f32) -> Self {
+        self.fog_density = fog_density.max(0.0);
+        self
+    }
+
+    pub fn fog_color(mut self, fog_color: [f32; 3]) -> Self {
+        self.fog_color = fog_color;
+        self
+    }
+
+    /// Background for rays that miss the scene
+    pub fn sky(mut self, sky: SkyMode) -> Self {
+        self.sky = sky;
+        self
+    }
+
+    /// Gradient colors straight up and down; `SkyMode::Solid` fills with `top`
+    pub fn sky_colors(mut self, top: [f32; 3], bottom: [f32; 3]) -> Self {
+        self.sky_top = top;
+        self.sky_bottom = bottom;
+        self
+    }
+
+    /// Light the scene with `lights` (e.g. `DemoScene::lights`) instead of its default lighting
+    pub fn lights(mut self, lights: Vec<LightData>) -> Self {
+        self.lights = lights;
+        self
+    }
+
+    /// Box count for the `stress` scene (`--stress-count`)
+    pub fn stress_count(mut self, stress_count: usize) -> Self {
+        self.stress_count = stress_count;
+        self
+    }
+
     pub async fn build(self) -> Result<Box<dyn Layer>> {
-        let logic = RayTracingLogic::new(
-            self.gpu,
-            self.scene_name,
-            self.width,
-            self.height,
-        )
-        .await?;
+        let logic = RayTracingLogic::new(&self).await?;
 
         let layer = TimedLayer::new(logic, self.fps, self.priority);
 
@@ -612,6 +772,7 @@ mod tests {
             position: Vec3::ZERO,
             yaw: 0.0,
             pitch: 0.0,
+            ..CameraState::new_for_scene("pyramid")
         };
 
         let forward = camera.forward();
@@ -638,4 +799,205 @@ mod tests {
         // Position should not change with no input
         assert_eq!(new_camera.position, camera.position);
     }
+
+    struct HeldController(Vec<Button>);
+
+    impl Controller for HeldController {
+        fn is_down(&self, button: Button) -> bool {
+            self.0.contains(&button)
+        }
+        fn get_down_keys(&self) -> &[Button] {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn test_sprint_doubles_displacement() {
+        let camera = CameraState::new_for_scene("tunnel");
+        let walk = camera.update(0.5, &HeldController(vec![Button::KeyW]));
+        let sprint = camera.update(0.5, &HeldController(vec![Button::KeyW, Button::Control]));
+
+        let walked = (walk.position - camera.position).length();
+        let sprinted = (sprint.position - camera.position).length();
+        assert!(walked > 0.0);
+        assert!((sprinted - walked * 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_remapped_forward_follows_arrow_key() {
+        let bindings = KeyBindings { forward: Button::KeyUp, ..KeyBindings::default() };
+        let camera = CameraState::new_for_scene("tunnel").with_key_bindings(bindings);
+
+        let arrow = camera.update(0.5, &HeldController(vec![Button::KeyUp])).position - camera.position;
+        assert!(arrow.abs_diff_eq(camera.forward() * CAMERA_SPEED * 0.5, 1e-6));
+        assert_eq!(camera.update(0.5, &HeldController(vec![Button::KeyW])).position, camera.position);
+    }
+
+    #[test]
+    fn test_pitch_keys_tilt_within_clamp() {
+        let camera = CameraState::new_for_scene("walls");
+        let up = HeldController(vec![Button::PageUp]);
+        assert!(camera.update(0.1, &up).pitch > camera.pitch);
+        assert!(camera.update(0.1, &HeldController(vec![Button::PageDown])).pitch < camera.pitch);
+
+        let tilted = (0..100).fold(camera, |camera, _| camera.update(1.0, &up));
+        assert!(tilted.pitch <= MAX_PITCH);
+        assert_eq!(tilted.pitch, MAX_PITCH);
+    }
+
+    #[test]
+    fn test_frame_scene_fits_bounding_sphere() {
+        let unit = AABB::new(Vec3::splat(-0.5), Vec3::splat(0.5));
+        let radius = 3.0_f32.sqrt() * 0.5;
+        let full_fov = DEFAULT_FOV * 2.0;
+        let framed = CameraState::new_for_scene("walls").frame_scene(&unit).frame_scene(&unit);
+
+        assert!((framed.position.length() - radius / (full_fov / 2.0).sin()).abs() < 1e-4);
+        assert!(framed.forward().abs_diff_eq(-framed.position.normalize(), 1e-5));
+        assert!(scene_viewpoint("walls").is_some() && scene_viewpoint("file:custom.json").is_none());
+        assert!(scene_viewpoint("stress").is_some() && scene_viewpoint("no-such-scene").is_none());
+    }
+
+    #[test]
+    fn test_pick_returns_box_under_pixel() {
+        let camera = CameraState { position: Vec3::ZERO, yaw: 0.0, pitch: 0.0, ..CameraState::new_for_scene("walls") };
+        let boxes = vec![
+            BoxData::new([-1.0, -1.0, 10.0], [1.0, 1.0, 12.0], [1.0, 0.0, 0.0]),
+            BoxData::new([-1.0, -1.0, 5.0], [1.0, 1.0, 6.0], [0.0, 1.0, 0.0]),
+            BoxData::new([20.0, -1.0, 5.0], [22.0, 1.0, 6.0], [0.0, 0.0, 1.0]),
+        ];
+        let resolution = Resolution::new(64, 64);
+
+        let (index, data) = pick_box(&camera, (32, 32), resolution, &boxes).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(data.color, boxes[1].color);
+        assert!(pick_box(&camera, (0, 0), resolution, &boxes).is_none());
+    }
+
+    #[test]
+    fn test_world_strafe_is_fixed_screen_right_for_any_yaw() {
+        let strafe_right = |strafe: StrafeMode, yaw: f32| {
+            let camera = CameraState { yaw, ..CameraState::new_for_scene("walls").with_strafe(strafe) };
+            camera.update(1.0, &HeldController(vec![Button::KeyD])).position - camera.position
+        };
+
+        // Screen-right of a camera facing +Z is -X
+        for yaw in [0.0, 1.0, std::f32::consts::PI] {
+            assert!(strafe_right(StrafeMode::World, yaw).abs_diff_eq(Vec3::NEG_X * CAMERA_SPEED, 1e-6));
+        }
+        let facing_z = strafe_right(StrafeMode::Camera, 0.0);
+        assert!(facing_z.abs_diff_eq(strafe_right(StrafeMode::World, 0.0), 1e-6));
+        let facing_x = strafe_right(StrafeMode::Camera, std::f32::consts::FRAC_PI_2);
+        assert!(!facing_z.abs_diff_eq(facing_x, 1e-3));
+    }
+
+    #[test]
+    fn test_diagonal_moves_at_single_axis_speed() {
+        let camera = CameraState::new_for_scene("pyramid");
+        let step = |keys: Vec<Button>| (camera.update(0.5, &HeldController(keys)).position - camera.position).length();
+
+        let straight = step(vec![Button::KeyW]);
+        assert!((step(vec![Button::KeyW, Button::KeyD]) - straight).abs() < 1e-5);
+        // Vertical movement is kept separate from the planar input
+        assert!(step(vec![Button::KeyW, Button::Space]) > straight);
+    }
+
+    #[test]
+    fn test_bounds_clamp_move_to_boundary() {
+        let limits = AABB::new(Vec3::splat(-10.0), Vec3::splat(10.0));
+        let camera = CameraState {
+            position: Vec3::new(0.0, 0.0, 9.95),
+            yaw: 0.0,
+            pitch: 0.0,
+            ..CameraState::new_for_scene("walls")
+        };
+        let forward = HeldController(vec![Button::KeyW]);
+
+        let free = camera.clone().update(1.0, &forward);
+        assert!(free.position.z > 10.0);
+
+        let bounded = camera.with_bounds(Some(CameraBounds { limits, solids: vec![] })).update(1.0, &forward);
+        assert_eq!(bounded.position, Vec3::new(0.0, 0.0, 10.0));
+    }
+
+    #[test]
+    fn test_bounds_block_entering_solid_boxes() {
+        let bounds = CameraBounds {
+            limits: AABB::new(Vec3::splat(-100.0), Vec3::splat(100.0)),
+            solids: vec![AABB::new(Vec3::new(-1.0, -1.0, 4.0), Vec3::new(1.0, 1.0, 6.0))],
+        };
+        let (outside, inside) = (Vec3::ZERO, Vec3::new(0.0, 0.0, 5.0));
+
+        assert_eq!(bounds.constrain(outside, inside), outside);
+        assert_eq!(bounds.constrain(outside, Vec3::X), Vec3::X);
+        // A camera already inside a box can still move out
+        assert_eq!(bounds.constrain(inside, Vec3::new(0.0, 0.0, 8.0)), Vec3::new(0.0, 0.0, 8.0));
+        // A step long enough to land past the box still hits it on the way
+        assert_eq!(bounds.constrain(outside, Vec3::new(0.0, 0.0, 10.0)), outside);
+        assert_eq!(bounds.constrain(outside, Vec3::new(3.0, 0.0, 10.0)), Vec3::new(3.0, 0.0, 10.0));
+    }
+
+    #[test]
+    fn test_camera_moved_detection() {
+        let camera = CameraState::new_for_scene("pyramid");
+        assert!(!camera.moved_from(&camera.clone()));
+
+        let nudged = CameraState { position: camera.position + Vec3::splat(1e-7), ..camera.clone() };
+        assert!(!nudged.moved_from(&camera));
+
+        let turned = CameraState { yaw: camera.yaw + 0.01, ..camera.clone() };
+        assert!(turned.moved_from(&camera));
+        let stepped = camera.update(0.1, &HeldController(vec![Button::KeyW]));
+        assert!(stepped.moved_from(&camera));
+    }
+
+    #[test]
+    fn test_accumulation_advances_until_camera_moves() {
+        let camera = CameraState::new_for_scene("pyramid");
+        let first = next_accumulation(Accumulation::new(true), None, &camera);
+        assert_eq!(first.frame, 0);
+
+        let second = next_accumulation(first, Some(&camera), &camera);
+        assert_eq!(second.frame, 1);
+
+        let turned = CameraState { yaw: camera.yaw + 0.01, ..camera.clone() };
+        assert_eq!(next_accumulation(second, Some(&camera), &turned).frame, 0);
+        assert_eq!(next_accumulation(Accumulation::new(false), Some(&camera), &camera).frame, 0);
+    }
+
+    #[test]
+    fn test_speed_multiplier_persists_across_updates() {
+        let faster = CameraState::new_for_scene("tunnel")
+            .update(0.5, &HeldController(vec![Button::Plus]))
+            .update(0.5, &HeldController(vec![]));
+        assert!(faster.speed_multiplier > 1.0);
+        assert_eq!(CameraState::new_for_scene("tunnel").speed_multiplier, 1.0);
+    }
+
+    #[test]
+    fn test_strip_row_padding_keeps_pixel_bytes() {
+        // 3 px wide rows padded to 16 bytes
+        let padded: Vec<u8> = (0..2).flat_map(|y| (0..16).map(move |i| if i < 12 { y * 12 + i } else { 0xff })).collect();
+        assert_eq!(strip_row_padding(&padded, 12, 16), (0..24).collect::<Vec<u8>>());
+        assert_eq!(padded_row_bytes(3, 4), wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        assert_eq!(padded_row_bytes(64, 4), 256);
+        assert_eq!(padded_row_bytes(40, OUTPUT_TEXEL_BYTES), 512);
+    }
+
+    #[test]
+    fn test_hdr_to_rgba8_clamps_highlights() {
+        let texels: Vec<u8> = [0.0f32, 0.5, 4.0, 1.0].iter().flat_map(|&v| f16::from_f32(v).to_le_bytes()).collect();
+        assert_eq!(hdr_to_rgba8(&texels), [0, 128, 255, 255]);
+    }
+
+    #[test]
+    fn test_upscale_nearest_repeats_pixels() {
+        let pixels = [10, 0, 0, 255, 20, 0, 0, 255];
+        let upscaled = upscale_nearest(&pixels, (2, 1), (4, 2));
+
+        assert_eq!(upscaled.len(), 4 * 2 * 4);
+        let reds: Vec<u8> = upscaled.chunks_exact(4).map(|p| p[0]).collect();
+        assert_eq!(reds, [10, 10, 20, 20, 10, 10, 20, 20]);
+        assert_eq!(upscale_nearest(&pixels, (2, 1), (2, 1)), pixels);
+    }
 }
@@ -1,18 +1,68 @@
 use std::sync::Arc;
-use wgpu::{Device, Surface, SurfaceConfiguration, Texture, TextureView, RenderPipeline, BindGroup};
+use wgpu::util::DeviceExt;
+use wgpu::{Buffer, Device, Surface, SurfaceConfiguration, Texture, TextureView, RenderPipeline, BindGroup};
 use winit::window::Window;
 
+use super::bloom::{apply_bloom, BloomSettings};
 use super::gpu_context::GpuContext;
 use super::layer::LayerOutput;
+use crate::render_settings::ToneMap;
+use crate::types::DisplayParams;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+/// Dimensions pixel buffers must match to be displayed; follows window resizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SurfaceSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl SurfaceSize {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    /// New size after a resize; zero-sized (minimized) windows keep the current size
+    pub fn resized(self, width: u32, height: u32) -> Self {
+        if width == 0 || height == 0 {
+            self
+        } else {
+            Self::new(width, height)
+        }
+    }
+
+    /// Check that a `width` x `height` RGBA8 buffer can be uploaded to this surface
+    pub fn validate(self, pixels: &[u8], width: u32, height: u32) -> Result<()> {
+        if width != self.width || height != self.height {
+            return Err(format!(
+                "Pixel dimensions {}x{} don't match surface {}x{}",
+                width, height, self.width, self.height
+            )
+            .into());
+        }
+
+        let expected_size = (width * height * 4) as usize;
+        if pixels.len() != expected_size {
+            return Err(format!(
+                "Invalid pixel buffer size: expected {} bytes, got {}",
+                expected_size,
+                pixels.len()
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
 /// Renders layer pixel buffers to a window surface
 ///
 /// This takes LayerOutput (CPU pixel buffers) and displays them on a WebGPU surface.
 /// Supports:
 /// - Single layer rendering
-/// - Multi-layer compositing with alpha blending
+/// - Multi-layer compositing (alpha, additive and max blending)
+/// - Optional bloom post-process (see `with_bloom`)
+/// - Tone mapping in the display pass (see `set_tone_map`)
 /// - Automatic texture upload and presentation
 pub struct SurfaceRenderer {
     gpu: Arc<GpuContext>,
@@ -22,8 +72,9 @@ pub struct SurfaceRenderer {
     texture: Texture,
     texture_view: TextureView,
     bind_group: BindGroup,
-    width: u32,
-    height: u32,
+    display_params_buffer: Buffer,
+    size: SurfaceSize,
+    bloom: Option<BloomSettings>,
 }
 
 impl SurfaceRenderer {
@@ -66,10 +117,17 @@ impl SurfaceRenderer {
         let texture = Self::create_output_texture(gpu.device(), width, height);
         let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
+        let display_params_buffer = gpu.device().create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Surface Display Params Buffer"),
+            contents: bytemuck::bytes_of(&DisplayParams::new(ToneMap::default().as_u32())),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
         // Create render pipeline
         let (render_pipeline, bind_group) = Self::create_render_pipeline(
             gpu.device(),
             &texture_view,
+            &display_params_buffer,
             surface_format,
         );
 
@@ -81,35 +139,46 @@ impl SurfaceRenderer {
             texture,
             texture_view,
             bind_group,
-            width,
-            height,
+            display_params_buffer,
+            size: SurfaceSize::new(width, height),
+            bloom: None,
         })
     }
 
+    /// Enable the bloom post-process for everything rendered to this surface
+    pub fn with_bloom(mut self, settings: BloomSettings) -> Self {
+        self.bloom = Some(settings);
+        self
+    }
+
+    /// Tone curve applied when displaying
+    pub fn set_tone_map(&self, tone_map: ToneMap) {
+        self.gpu.queue().write_buffer(
+            &self.display_params_buffer,
+            0,
+            bytemuck::bytes_of(&DisplayParams::new(tone_map.as_u32())),
+        );
+    }
+
+    /// Builder form of `set_tone_map`
+    pub fn with_tone_map(self, tone_map: ToneMap) -> Self {
+        self.set_tone_map(tone_map);
+        self
+    }
+
     /// Render a single layer to the surface
     pub fn render(&self, output: &LayerOutput) -> Result<()> {
-        self.render_pixels(&output.pixels, self.width, self.height)
+        self.render_pixels(&output.pixels, self.size.width, self.size.height)
     }
 
     /// Render raw pixel data to the surface
     pub fn render_pixels(&self, pixels: &[u8], width: u32, height: u32) -> Result<()> {
-        if width != self.width || height != self.height {
-            return Err(format!(
-                "Pixel dimensions {}x{} don't match surface {}x{}",
-                width, height, self.width, self.height
-            )
-            .into());
-        }
+        self.size.validate(pixels, width, height)?;
 
-        let expected_size = (width * height * 4) as usize;
-        if pixels.len() != expected_size {
-            return Err(format!(
-                "Invalid pixel buffer size: expected {} bytes, got {}",
-                expected_size,
-                pixels.len()
-            )
-            .into());
-        }
+        let bloomed = self
+            .bloom
+            .map(|settings| apply_bloom(pixels, width as usize, height as usize, &settings));
+        let pixels = bloomed.as_deref().unwrap_or(pixels);
 
         // Upload pixels to texture
         self.gpu.queue().write_texture(
@@ -170,27 +239,24 @@ impl SurfaceRenderer {
 
     /// Composite multiple layers and render to surface
     ///
-    /// Layers are composited back-to-front with alpha blending.
+    /// Layers are composited back-to-front, each using its own `BlendMode`.
     /// Assumes layers are already sorted by priority (lowest first).
     pub fn composite_layers(&self, outputs: &[LayerOutput]) -> Result<()> {
         if outputs.is_empty() {
             return Ok(());
         }
-
-        // Simple compositing: just render the last opaque layer
-        // TODO: Implement proper alpha compositing for multiple layers
-        let output = outputs.last().unwrap();
-        self.render(output)
+        let pixels = LayerOutput::composite(outputs, self.size.width, self.size.height);
+        self.render_pixels(&pixels, self.size.width, self.size.height)
     }
 
-    /// Resize the surface
+    /// Reconfigure the surface and recreate the output texture and bind group for a new window size
     pub fn resize(&mut self, width: u32, height: u32) {
-        if width == 0 || height == 0 {
+        let size = self.size.resized(width, height);
+        if size == self.size {
             return;
         }
 
-        self.width = width;
-        self.height = height;
+        self.size = size;
         self.surface_config.width = width;
         self.surface_config.height = height;
 
@@ -209,12 +275,13 @@ impl SurfaceRenderer {
             self.gpu.device(),
             &bind_group_layout,
             &self.texture_view,
+            &self.display_params_buffer,
         );
     }
 
     /// Get current surface dimensions
     pub fn dimensions(&self) -> (u32, u32) {
-        (self.width, self.height)
+        (self.size.width, self.size.height)
     }
 
     /// Create output texture
@@ -239,6 +306,7 @@ impl SurfaceRenderer {
     fn create_render_pipeline(
         device: &Device,
         texture_view: &TextureView,
+        display_params_buffer: &Buffer,
         surface_format: wgpu::TextureFormat,
     ) -> (RenderPipeline, BindGroup) {
         // Use the existing display shader
@@ -267,10 +335,20 @@ impl SurfaceRenderer {
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
         });
 
-        let bind_group = Self::create_bind_group(device, &bind_group_layout, texture_view);
+        let bind_group = Self::create_bind_group(device, &bind_group_layout, texture_view, display_params_buffer);
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Surface Render Pipeline Layout"),
@@ -315,6 +393,7 @@ impl SurfaceRenderer {
         device: &Device,
         layout: &wgpu::BindGroupLayout,
         texture_view: &TextureView,
+        display_params_buffer: &Buffer,
     ) -> BindGroup {
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("Surface Texture Sampler"),
@@ -339,6 +418,10 @@ impl SurfaceRenderer {
                     binding: 1,
                     resource: wgpu::BindingResource::Sampler(&sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: display_params_buffer.as_entire_binding(),
+                },
             ],
         })
     }
@@ -388,6 +471,17 @@ mod tests {
         assert_eq!(height, 600);
     }
 
+    #[test]
+    fn test_resize_accepts_buffers_of_the_new_size() {
+        let size = SurfaceSize::new(100, 100).resized(800, 600);
+        assert_eq!((size.width, size.height), (800, 600));
+        assert!(size.validate(&vec![0u8; 800 * 600 * 4], 800, 600).is_ok());
+        assert!(size.validate(&vec![0u8; 100 * 100 * 4], 100, 100).is_err());
+        assert!(size.validate(&vec![0u8; 800 * 600 * 4 - 1], 800, 600).is_err());
+
+        assert_eq!(size.resized(0, 0), size);
+    }
+
     #[test]
     fn test_layer_output_to_pixels() {
         let width = 2;
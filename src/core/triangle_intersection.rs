@@ -1,3 +1,4 @@
+use crate::math::intersect_triangle;
 use crate::types::TriangleData;
 use glam::Vec3;
 
@@ -24,6 +25,12 @@ impl TriangleIntersection {
             w * uv0[1] + u * uv1[1] + v * uv2[1],
         ]
     }
+
+    /// Smooth shading normal: vertex normals weighted like `interpolate_uv`, renormalized
+    pub fn interpolate_normal(&self, n0: Vec3, n1: Vec3, n2: Vec3) -> Vec3 {
+        let (u, v, w) = self.barycentric();
+        (n0 * w + n1 * u + n2 * v).normalize_or(self.normal)
+    }
 }
 
 /// Möller-Trumbore ray-triangle intersection algorithm
@@ -35,50 +42,12 @@ pub fn moller_trumbore_intersect(
     v1: Vec3,
     v2: Vec3,
 ) -> Option<TriangleIntersection> {
-    const EPSILON: f32 = 1e-6;
-
-    // Find vectors for two edges sharing v0
-    let edge1 = v1 - v0;
-    let edge2 = v2 - v0;
-
-    // Calculate determinant
-    let h = ray_dir.cross(edge2);
-    let a = edge1.dot(h);
-
-    // Ray is parallel to triangle
-    if a.abs() < EPSILON {
-        return None;
-    }
-
-    let f = 1.0 / a;
-    let s = ray_origin - v0;
-    let u = f * s.dot(h);
-
-    // Intersection outside triangle
-    if u < 0.0 || u > 1.0 {
-        return None;
-    }
-
-    let q = s.cross(edge1);
-    let v = f * ray_dir.dot(q);
-
-    // Intersection outside triangle
-    if v < 0.0 || u + v > 1.0 {
-        return None;
-    }
-
-    // Calculate t to find intersection point
-    let t = f * edge2.dot(q);
-
-    // Ray intersection behind origin
-    if t < EPSILON {
-        return None;
-    }
+    let (t, barycentric) = intersect_triangle(ray_origin, ray_dir, [v0, v1, v2], false)?;
 
     // Calculate normal (counter-clockwise winding)
-    let normal = edge1.cross(edge2).normalize();
+    let normal = (v1 - v0).cross(v2 - v0).normalize();
 
-    Some(TriangleIntersection { t, u, v, normal })
+    Some(TriangleIntersection { t, u: barycentric.y, v: barycentric.z, normal })
 }
 
 /// Optimized triangle intersection for TriangleData
@@ -87,13 +56,21 @@ pub fn intersect_triangle_data(
     ray_dir: Vec3,
     triangle: &TriangleData,
 ) -> Option<TriangleIntersection> {
-    moller_trumbore_intersect(
+    let hit = moller_trumbore_intersect(
         ray_origin,
         ray_dir,
         Vec3::from_array(triangle.v0),
         Vec3::from_array(triangle.v1),
         Vec3::from_array(triangle.v2),
-    )
+    )?;
+    // Vertex normals replace the face normal when the mesh provides them
+    let normal = if triangle.has_normals > 0.5 {
+        let [n0, n1, n2] = [triangle.n0, triangle.n1, triangle.n2].map(Vec3::from_array);
+        hit.interpolate_normal(n0, n1, n2)
+    } else {
+        hit.normal
+    };
+    Some(TriangleIntersection { normal, ..hit })
 }
 
 /// Watertight ray-triangle intersection (Woop et al. 2013)
@@ -264,6 +241,29 @@ mod tests {
         assert!((u + v + w - 1.0).abs() < 1e-5); // Should sum to 1
     }
 
+    #[test]
+    fn test_normal_interpolation_at_centroid_is_normalized_average() {
+        let centroid = TriangleIntersection { t: 1.0, u: 1.0 / 3.0, v: 1.0 / 3.0, normal: Vec3::Z };
+        let (n0, n1, n2) = (Vec3::X, Vec3::Y, Vec3::Z);
+
+        let normal = centroid.interpolate_normal(n0, n1, n2);
+        assert!(normal.abs_diff_eq((n0 + n1 + n2).normalize(), 1e-6));
+        assert!((normal.length() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_triangle_without_vertex_normals_keeps_face_normal() {
+        let (v0, v1, v2) = ([-1.0, -1.0, -5.0], [1.0, -1.0, -5.0], [0.0, 1.0, -5.0]);
+        let tri = TriangleData::new(v0, v1, v2, [0.0; 2], [0.0; 2], [0.0; 2], 0);
+        let flat = intersect_triangle_data(Vec3::ZERO, Vec3::NEG_Z, &tri).unwrap();
+        assert!(flat.normal.abs_diff_eq(Vec3::Z, 1e-6));
+
+        let tilted = Vec3::new(0.0, 1.0, 1.0).normalize().to_array();
+        let smooth_tri = tri.with_normals(tilted, tilted, tilted);
+        let smooth = intersect_triangle_data(Vec3::ZERO, Vec3::NEG_Z, &smooth_tri).unwrap();
+        assert!(smooth.normal.abs_diff_eq(Vec3::from_array(tilted), 1e-6));
+    }
+
     #[test]
     fn test_uv_interpolation() {
         let (v0, v1, v2) = create_test_triangle();
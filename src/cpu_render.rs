@@ -0,0 +1,167 @@
+// cpu_render.rs - Brute-force CPU reference renderer for machines without a GPU
+use glam::Vec3;
+use std::path::Path;
+
+use crate::camera::Camera;
+use crate::core::triangle_intersection::intersect_triangle_data;
+use crate::headless::write_png;
+use crate::lighting::LightingConfig;
+use crate::resolution::Resolution;
+use crate::math::intersect_aabb_range;
+use crate::scenes;
+use crate::types::{BoxData, TriangleData};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Longest side of the CPU preview; brute-force tracing is slow
+pub const CPU_PREVIEW_MAX_SIZE: u32 = 320;
+const TRIANGLE_COLOR: Vec3 = Vec3::splat(0.7);
+
+/// Where frames get rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderBackend {
+    Gpu,
+    Cpu,
+}
+
+/// GPU when available, the CPU renderer only when `--cpu-fallback` allows it
+pub fn select_backend(gpu_available: bool, cpu_fallback: bool) -> Option<RenderBackend> {
+    match (gpu_available, cpu_fallback) {
+        (true, _) => Some(RenderBackend::Gpu),
+        (false, true) => Some(RenderBackend::Cpu),
+        (false, false) => None,
+    }
+}
+
+/// Nearest hit: (distance, normal, color)
+fn closest_hit(origin: Vec3, dir: Vec3, boxes: &[BoxData], triangles: &[TriangleData]) -> Option<(f32, Vec3, Vec3)> {
+    let box_hits = boxes.iter().filter_map(|b| {
+        // Slab test in the box's local frame (as in the shader)
+        let center = (Vec3::from_array(b.min) + Vec3::from_array(b.max)) * 0.5;
+        let half = Vec3::from_array(b.half_size);
+        let to_local = b.rotation().inverse();
+        let (local_origin, local_dir) = (to_local * (origin - center), to_local * dir);
+        let (t_near, t_far) = intersect_aabb_range(local_origin, local_dir, -half, half)?;
+        let t = if t_near > 0.0 { t_near } else { t_far };
+        (t > 0.0).then(|| {
+            // Dominant axis of the local hit point, rotated back to world space
+            let p = local_origin + local_dir * t;
+            let d = p.abs() - half;
+            let local_normal = if d.x > d.y && d.x > d.z {
+                Vec3::new(p.x.signum(), 0.0, 0.0)
+            } else if d.y > d.z {
+                Vec3::new(0.0, p.y.signum(), 0.0)
+            } else {
+                Vec3::new(0.0, 0.0, p.z.signum())
+            };
+            let normal = b.rotation() * local_normal;
+            let color = Vec3::from_array(b.color_at(origin + dir * t)) * (scenes::DEFAULT_AMBIENT + diffuse(normal)) + Vec3::from_array(b.emissive);
+            (t, normal, color)
+        })
+    });
+
+    let triangle_hits = triangles.iter().filter_map(|tri| {
+        let hit = intersect_triangle_data(origin, dir, tri)?;
+        let normal = if hit.normal.dot(dir) > 0.0 { -hit.normal } else { hit.normal };
+        Some((hit.t, normal, TRIANGLE_COLOR * (scenes::DEFAULT_AMBIENT + diffuse(normal))))
+    });
+
+    box_hits.chain(triangle_hits).min_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+fn diffuse(normal: Vec3) -> f32 {
+    LightingConfig::default().diffuse(normal)
+}
+
+fn sky(dir: Vec3) -> Vec3 {
+    let t = (dir.y + 1.0) * 0.5;
+    Vec3::new(0.3, 0.5, 0.7).lerp(Vec3::new(0.5, 0.7, 1.0), t)
+}
+
+/// Render the scene as RGBA8 with direct lighting only (no reflections or grid)
+pub fn cpu_render(boxes: &[BoxData], triangles: &[TriangleData], camera: &Camera, resolution: Resolution) -> Vec<u8> {
+    let Resolution { width, height } = resolution;
+
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .flat_map(|pixel| {
+            let dir = camera.primary_ray(pixel, resolution);
+
+            let color = closest_hit(camera.position, dir, boxes, triangles).map_or_else(|| sky(dir), |(_, _, c)| c);
+            let [r, g, b] = color.clamp(Vec3::ZERO, Vec3::ONE).to_array().map(|c| (c * 255.0).round() as u8);
+            [r, g, b, 255]
+        })
+        .collect()
+}
+
+/// Fit `width` x `height` inside the preview size, keeping the aspect ratio
+pub fn preview_size(width: u32, height: u32) -> (u32, u32) {
+    let scale = (CPU_PREVIEW_MAX_SIZE as f32 / width.max(height) as f32).min(1.0);
+    (((width as f32 * scale) as u32).max(1), ((height as f32 * scale) as u32).max(1))
+}
+
+/// Render a low-res CPU preview of a generated scene and save it as PNG
+pub fn run_preview(scene: &str, width: u32, height: u32, out: &Path) -> Result<()> {
+    let entry = scenes::available()
+        .iter()
+        .find(|s| s.name == scene)
+        .ok_or_else(|| format!("CPU preview supports generated scenes only, not '{}'", scene))?;
+
+    let (width, height) = preview_size(width, height);
+    println!("No GPU adapter found; rendering {} on the CPU at {}x{}...", scene, width, height);
+
+    let pixels = cpu_render(&(entry.boxes)(), &(entry.triangles)(), &Camera::for_scene(scene), Resolution::new(width, height));
+    write_png(out, &pixels, width, height)?;
+
+    println!("Wrote CPU preview to {}", out.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_gpu_with_flag_selects_cpu() {
+        assert_eq!(select_backend(false, true), Some(RenderBackend::Cpu));
+        assert_eq!(select_backend(false, false), None);
+        assert_eq!(select_backend(true, true), Some(RenderBackend::Gpu));
+    }
+
+    #[test]
+    fn test_cpu_render_produces_image() {
+        let boxes = vec![BoxData::new([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0], [1.0, 0.0, 0.0])];
+        let mut camera = Camera::for_scene("default");
+        camera.position = Vec3::new(0.0, 0.0, 5.0);
+        camera.yaw = std::f32::consts::PI;
+        camera.pitch = 0.0;
+
+        let pixels = cpu_render(&boxes, &[], &camera, Resolution::new(16, 16));
+        assert_eq!(pixels.len(), 16 * 16 * 4);
+
+        // Center pixel sees the red box, the corner sees sky
+        let center = &pixels[(8 * 16 + 8) * 4..][..4];
+        assert!(center[0] > 0 && center[1] == 0 && center[2] == 0);
+        assert!(pixels[2] > pixels[0]);
+    }
+
+    #[test]
+    fn test_run_preview_writes_rendered_pixels() {
+        let out = std::env::temp_dir().join("ray_tracer_cpu_preview_test.png");
+        run_preview("default", 48, 32, &out).unwrap();
+        let image = image::open(&out).unwrap().to_rgba8();
+        std::fs::remove_file(&out).ok();
+
+        assert_eq!(image.dimensions(), (48, 32));
+        let expected = cpu_render(&scenes::create_default_scene(), &[], &Camera::for_scene("default"), Resolution::new(48, 32));
+        assert_eq!(image.as_raw(), &expected);
+        assert!(expected.chunks(4).any(|px| px != &expected[..4]), "preview is a single flat color");
+        assert!(run_preview("file:missing.json", 48, 32, &out).is_err());
+    }
+
+    #[test]
+    fn test_preview_size_keeps_aspect() {
+        assert_eq!(preview_size(1280, 720), (320, 180));
+        assert_eq!(preview_size(100, 50), (100, 50));
+    }
+}
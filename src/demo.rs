@@ -1,5 +1,5 @@
-use glam::Vec3;
-use crate::types::BoxData;
+use glam::{Quat, Vec3};
+use crate::types::{BoxData, LightData, MaterialData, TriangleData};
 use crate::math::hsv_to_rgb;
 
 /// Demo module provides reusable primitives and builders for creating ray tracer scenes
@@ -13,6 +13,7 @@ use crate::math::hsv_to_rgb;
 ///     .add_ground([0.2, 0.2, 0.2])
 ///     .add_ring(20.0, 32, 10.0, rainbow_gradient(32))
 ///     .build();
+/// assert_eq!(scene.boxes.len(), 33);
 /// ```
 
 // ============================================================================
@@ -48,6 +49,21 @@ pub fn reflective_box_at(position: Vec3, size: Vec3, color: [f32; 3], reflectivi
     )
 }
 
+/// Creates an emissive box at position with size
+pub fn emissive_box_at(position: Vec3, size: Vec3, color: [f32; 3], emissive: [f32; 3]) -> BoxData {
+    BoxData::new_emissive(
+        (position - size * 0.5).to_array(),
+        (position + size * 0.5).to_array(),
+        color,
+        emissive,
+    )
+}
+
+/// Creates a box at position with size, turned by `rotation` about its center
+pub fn rotated_box_at(position: Vec3, size: Vec3, rotation: Quat, color: [f32; 3]) -> BoxData {
+    BoxData::new_rotated(position, size, rotation, color)
+}
+
 /// Creates a grid of boxes in the XZ plane
 pub fn grid<F>(
     center: Vec3,
@@ -200,6 +216,33 @@ where
         .collect()
 }
 
+/// Two triangles covering the quad p0-p1-p2-p3, split along the p0-p2 diagonal
+///
+/// Both triangles keep the quad's winding order.
+pub fn quad(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, material_id: u32) -> [TriangleData; 2] {
+    [
+        TriangleData::new(p0.to_array(), p1.to_array(), p2.to_array(), [0.0, 0.0], [1.0, 0.0], [1.0, 1.0], material_id),
+        TriangleData::new(p0.to_array(), p2.to_array(), p3.to_array(), [0.0, 0.0], [1.0, 1.0], [0.0, 1.0], material_id),
+    ]
+}
+
+/// Triangles from an indexed mesh (three indices per triangle; a trailing partial triangle is ignored)
+///
+/// Fails if any index points past the end of `vertices`.
+pub fn triangle_mesh(vertices: &[Vec3], indices: &[u32], material_id: u32) -> anyhow::Result<Vec<TriangleData>> {
+    if let Some(&bad) = indices.iter().find(|&&i| i as usize >= vertices.len()) {
+        anyhow::bail!("mesh index {bad} is out of range for {} vertices", vertices.len());
+    }
+    let uv = [0.0, 0.0];
+    Ok(indices
+        .chunks_exact(3)
+        .map(|tri| {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| vertices[i as usize].to_array());
+            TriangleData::new(a, b, c, uv, uv, uv, material_id)
+        })
+        .collect())
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum WallDirection {
     NorthSouth,
@@ -271,13 +314,10 @@ pub fn gradient(color1: [f32; 3], color2: [f32; 3], steps: usize) -> impl Fn(usi
 // Transformation Functions - Modify existing boxes
 // ============================================================================
 
-/// Makes all boxes in a collection reflective
+/// Makes all boxes in a collection reflective (reflectivity is clamped to [0, 1])
 pub fn make_reflective(boxes: Vec<BoxData>, reflectivity: f32) -> Vec<BoxData> {
     boxes.into_iter()
-        .map(|mut b| {
-            b.reflectivity = reflectivity;
-            b
-        })
+        .map(|b| b.with_reflectivity(reflectivity))
         .collect()
 }
 
@@ -316,15 +356,39 @@ pub fn scale(boxes: Vec<BoxData>, center: Vec3, factor: f32) -> Vec<BoxData> {
 // DemoBuilder - Fluent API for scene construction
 // ============================================================================
 
+/// Geometry produced by `DemoBuilder`; triangles index into `materials`
+#[derive(Debug, Clone, Default)]
+pub struct DemoScene {
+    pub boxes: Vec<BoxData>,
+    pub triangles: Vec<TriangleData>,
+    pub materials: Vec<MaterialData>,
+    /// Lights for `SceneOptions::lights`; empty keeps the lighting preset
+    pub lights: Vec<LightData>,
+}
+
 /// Builder for creating demo scenes with a fluent API
 pub struct DemoBuilder {
     boxes: Vec<BoxData>,
+    triangles: Vec<TriangleData>,
+    materials: Vec<MaterialData>,
+    lights: Vec<LightData>,
 }
 
 impl DemoBuilder {
     /// Creates a new empty demo builder
     pub fn new() -> Self {
-        Self { boxes: Vec::new() }
+        Self {
+            boxes: Vec::new(),
+            triangles: Vec::new(),
+            materials: Vec::new(),
+            lights: Vec::new(),
+        }
+    }
+
+    /// Registers a material and returns its index
+    fn push_material(&mut self, material: MaterialData) -> u32 {
+        self.materials.push(material);
+        (self.materials.len() - 1) as u32
     }
 
     /// Adds a ground plane
@@ -360,6 +424,39 @@ impl DemoBuilder {
         self
     }
 
+    /// Adds a reflective box with its own material, whose reflections blur with `roughness` (0 = mirror)
+    pub fn add_glossy_box(mut self, position: Vec3, size: Vec3, color: [f32; 3], reflectivity: f32, roughness: f32) -> Self {
+        let [r, g, b] = color;
+        self.materials.push(MaterialData { roughness, ..MaterialData::new_color([r, g, b, 1.0]) });
+        let material = (self.materials.len() - 1) as u32;
+        self.boxes.push(reflective_box_at(position, size, color, reflectivity).with_material(material));
+        self
+    }
+
+    /// Adds a box that glows with `emissive` regardless of lighting
+    pub fn add_emissive_box(mut self, position: Vec3, size: Vec3, color: [f32; 3], emissive: [f32; 3]) -> Self {
+        self.boxes.push(emissive_box_at(position, size, color, emissive));
+        self
+    }
+
+    /// Adds the standard ground plane as a checkerboard of `tile`-sized squares
+    pub fn add_checkered_ground(mut self, color_a: [f32; 3], color_b: [f32; 3], tile: f32) -> Self {
+        self.boxes.push(ground([-200.0, -1.0, -200.0], [200.0, -0.99, 200.0], color_a).with_checker(color_b, tile));
+        self
+    }
+
+    /// Adds a point light whose `intensity` falls off with the inverse square of distance
+    pub fn add_point_light(mut self, position: Vec3, color: [f32; 3], intensity: f32) -> Self {
+        self.lights.push(LightData::point(position, color, intensity));
+        self
+    }
+
+    /// Adds a box turned by `rotation`; the grid stores its world-space AABB
+    pub fn add_rotated_box(mut self, position: Vec3, size: Vec3, rotation: Quat, color: [f32; 3]) -> Self {
+        self.boxes.push(rotated_box_at(position, size, rotation, color));
+        self
+    }
+
     /// Adds a moving box
     pub fn add_moving_box(mut self, size: Vec3, start: Vec3, end: Vec3, color: [f32; 3]) -> Self {
         self.boxes.push(BoxData::create_moving_box(size, start, end, color));
@@ -471,6 +568,21 @@ impl DemoBuilder {
         self
     }
 
+    /// Adds an indexed triangle mesh with a single material; fails on out-of-range indices
+    pub fn add_triangle_mesh(mut self, vertices: &[Vec3], indices: &[u32], material: MaterialData) -> anyhow::Result<Self> {
+        let mesh = triangle_mesh(vertices, indices, self.materials.len() as u32)?;
+        self.push_material(material);
+        self.triangles.extend(mesh);
+        Ok(self)
+    }
+
+    /// Adds a flat-colored quad (corners in winding order) as two triangles
+    pub fn add_quad(mut self, p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, color: [f32; 3]) -> Self {
+        let material_id = self.push_material(MaterialData::new_color([color[0], color[1], color[2], 1.0]));
+        self.triangles.extend(quad(p0, p1, p2, p3, material_id));
+        self
+    }
+
     /// Adds custom boxes from any iterator
     pub fn add_custom(mut self, boxes: impl IntoIterator<Item = BoxData>) -> Self {
         self.boxes.extend(boxes);
@@ -504,9 +616,18 @@ impl DemoBuilder {
     }
 
     /// Builds the final scene
-    pub fn build(self) -> Vec<BoxData> {
-        println!("Demo scene created: {} total boxes", self.boxes.len());
-        self.boxes
+    pub fn build(self) -> DemoScene {
+        println!(
+            "Demo scene created: {} total boxes, {} triangles",
+            self.boxes.len(),
+            self.triangles.len()
+        );
+        DemoScene {
+            boxes: self.boxes,
+            triangles: self.triangles,
+            materials: self.materials,
+            lights: self.lights,
+        }
     }
 }
 
@@ -515,3 +636,112 @@ impl Default for DemoBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normal(tri: &TriangleData) -> Vec3 {
+        let [a, b, c] = [tri.v0, tri.v1, tri.v2].map(Vec3::from_array);
+        (b - a).cross(c - a).normalize()
+    }
+
+    #[test]
+    fn test_quad_shares_diagonal_and_winding() {
+        let (p0, p1, p2, p3) = (
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+        let scene = DemoBuilder::new().add_quad(p0, p1, p2, p3, [1.0, 0.0, 0.0]).build();
+
+        assert_eq!(scene.triangles.len(), 2);
+        let [first, second] = [&scene.triangles[0], &scene.triangles[1]];
+
+        // Shared diagonal p0-p2
+        assert_eq!((first.v0, first.v2), (p0.to_array(), p2.to_array()));
+        assert_eq!((second.v0, second.v1), (p0.to_array(), p2.to_array()));
+
+        // Same facing as the quad's corner order (counter-clockwise seen from -Y)
+        let expected = (p1 - p0).cross(p3 - p0).normalize();
+        assert!(normal(first).abs_diff_eq(expected, 1e-6));
+        assert!(normal(second).abs_diff_eq(expected, 1e-6));
+
+        assert_eq!(scene.materials.len(), 1);
+        assert_eq!(scene.materials[0].base_color, [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_mesh_and_boxes_mix() {
+        let vertices = [Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::Z];
+        let scene = DemoBuilder::new()
+            .add_ground([0.2, 0.2, 0.2])
+            .add_triangle_mesh(&vertices, &[0, 1, 2, 0, 2, 3], MaterialData::new_color([1.0; 4]))
+            .unwrap()
+            .add_quad(Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y, [0.0, 1.0, 0.0])
+            .build();
+
+        assert_eq!(scene.boxes.len(), 1);
+        assert_eq!(scene.triangles.len(), 4);
+        assert_eq!(scene.materials.len(), 2);
+        assert_eq!(scene.triangles[1].v2, Vec3::Z.to_array());
+        assert_eq!(scene.triangles[3].material_id, 1.0);
+    }
+
+    #[test]
+    fn test_mesh_rejects_out_of_range_index() {
+        let vertices = [Vec3::ZERO, Vec3::X, Vec3::Y];
+        assert!(triangle_mesh(&vertices, &[0, 1, 3], 0).is_err());
+
+        let result = DemoBuilder::new().add_triangle_mesh(&vertices, &[0, 1, 7], MaterialData::new_color([1.0; 4]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_point_lights_are_collected() {
+        let scene = DemoBuilder::new().add_point_light(Vec3::Y, [1.0, 0.5, 0.2], 20.0).build();
+        assert_eq!(scene.lights, vec![LightData::point(Vec3::Y, [1.0, 0.5, 0.2], 20.0)]);
+        assert!(DemoBuilder::new().build().lights.is_empty());
+    }
+
+    #[test]
+    fn test_checkered_ground_alternates_by_world_position() {
+        let (a, b) = ([1.0; 3], [0.1; 3]);
+        let scene = DemoBuilder::new().add_checkered_ground(a, b, 2.0).build();
+        let ground = &scene.boxes[0];
+
+        assert_eq!(ground.color_at(Vec3::new(1.0, -0.99, 1.0)), a);
+        assert_eq!(ground.color_at(Vec3::new(3.0, -0.99, 1.0)), b);
+        assert_eq!(ground.color_at(Vec3::new(-1.0, -0.99, 1.0)), b);
+    }
+
+    #[test]
+    fn test_rotated_box_bounds_cover_turned_extents() {
+        let center = Vec3::new(1.0, 2.0, 3.0);
+        let scene = DemoBuilder::new()
+            .add_rotated_box(center, Vec3::new(4.0, 2.0, 1.0), Quat::from_rotation_y(std::f32::consts::FRAC_PI_2), [1.0; 3])
+            .build();
+
+        // A quarter turn about Y swaps the X and Z extents
+        let b = &scene.boxes[0];
+        let extent = Vec3::from_array(b.max) - Vec3::from_array(b.min);
+        assert!((extent - Vec3::new(1.0, 2.0, 4.0)).abs().max_element() < 1e-5);
+        assert!(((Vec3::from_array(b.max) + Vec3::from_array(b.min)) * 0.5 - center).length() < 1e-5);
+        assert_eq!(b.half_size, [2.0, 1.0, 0.5]);
+        assert!(b.is_rotated());
+    }
+
+    #[test]
+    fn test_glossy_box_roughness_lives_in_its_material() {
+        let scene = DemoBuilder::new()
+            .add_box(Vec3::ZERO, Vec3::ONE, [1.0; 3])
+            .add_glossy_box(Vec3::X * 3.0, Vec3::ONE, [0.9; 3], 0.8, 0.3)
+            .build();
+
+        assert_eq!(scene.boxes[0].material_index, -1);
+        let material = scene.materials[scene.boxes[1].material_index as usize];
+        assert_eq!(material.roughness, 0.3);
+        assert_eq!(scene.boxes[1].reflectivity, 0.8);
+    }
+}
@@ -0,0 +1,67 @@
+/// Tracks which GPU-side state is stale and needs re-uploading
+///
+/// Camera and scene are tracked separately so that camera-only frames never
+/// re-upload box, triangle or grid buffers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirtyFlags {
+    pub camera: bool,
+    pub scene: bool,
+}
+
+impl DirtyFlags {
+    /// Camera pose changed
+    pub const fn camera_moved(self) -> Self {
+        Self { camera: true, ..self }
+    }
+
+    /// Scene geometry or materials changed
+    pub const fn scene_edited(self) -> Self {
+        Self { scene: true, ..self }
+    }
+
+    /// Camera uniform was uploaded
+    pub const fn camera_uploaded(self) -> Self {
+        Self { camera: false, ..self }
+    }
+
+    /// Scene buffers were uploaded
+    pub const fn scene_uploaded(self) -> Self {
+        Self { scene: false, ..self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_clean() {
+        assert_eq!(DirtyFlags::default(), DirtyFlags { camera: false, scene: false });
+    }
+
+    #[test]
+    fn test_camera_move_does_not_dirty_scene() {
+        let flags = DirtyFlags::default().camera_moved();
+        assert!(flags.camera);
+        assert!(!flags.scene);
+    }
+
+    #[test]
+    fn test_box_edit_dirties_scene() {
+        let flags = DirtyFlags::default().scene_edited();
+        assert!(flags.scene);
+        assert!(!flags.camera);
+    }
+
+    #[test]
+    fn test_uploads_clear_independently() {
+        let flags = DirtyFlags::default().camera_moved().scene_edited();
+
+        let after_camera = flags.camera_uploaded();
+        assert!(!after_camera.camera);
+        assert!(after_camera.scene);
+
+        let after_both = after_camera.scene_uploaded();
+        assert_eq!(after_both, DirtyFlags::default());
+    }
+}
@@ -6,6 +6,27 @@ var ray_traced_texture: texture_2d<f32>;
 @group(0) @binding(1)
 var texture_sampler: sampler;
 
+// Mirrors types::DisplayParams
+struct DisplayParams {
+    tone_map: u32,
+    wireframe: u32,
+    exposure: f32,
+    _pad: u32,
+    viewport: vec4<f32>,
+};
+
+@group(0) @binding(2)
+var<uniform> display_params: DisplayParams;
+
+const TONE_MAP_NONE: u32 = 0u;
+const TONE_MAP_REINHARD: u32 = 1u;
+const TONE_MAP_ACES: u32 = 2u;
+
+// Edge strength at which wireframe darkening starts and saturates
+const EDGE_THRESHOLD_LOW: f32 = 0.1;
+const EDGE_THRESHOLD_HIGH: f32 = 0.3;
+const EDGE_DARKEN: f32 = 0.85;
+
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) uv: vec2<f32>,
@@ -25,7 +46,75 @@ fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
     return out;
 }
 
+// Mirrors math::reinhard
+fn reinhard(x: vec3<f32>) -> vec3<f32> {
+    return x / (1.0 + x);
+}
+
+// Mirrors math::aces (Narkowicz fit)
+fn aces(x: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((x * (a * x + b)) / (x * (c * x + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// Rec. 709 luminance, clamped to the displayable range so HDR highlights do not swamp the edge thresholds
+fn luminance(rgb: vec3<f32>) -> f32 {
+    return dot(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+// Sobel magnitudes of (encoded depth, luminance) around `center`, clamping at the borders (mirrors math::sobel_magnitude)
+fn sobel_edges(center: vec2<i32>) -> vec2<f32> {
+    let max_coord = vec2<i32>(textureDimensions(ray_traced_texture)) - vec2<i32>(1);
+    var gx = vec2<f32>(0.0);
+    var gy = vec2<f32>(0.0);
+    for (var dy = -1; dy <= 1; dy++) {
+        for (var dx = -1; dx <= 1; dx++) {
+            let texel = textureLoad(ray_traced_texture, clamp(center + vec2<i32>(dx, dy), vec2<i32>(0), max_coord), 0);
+            let value = vec2<f32>(texel.a, luminance(texel.rgb));
+            // 1-2-1 weights across the row/column being differenced
+            gx += value * f32(dx) * f32(2 - abs(dy));
+            gy += value * f32(dy) * f32(2 - abs(dx));
+        }
+    }
+    return sqrt(gx * gx + gy * gy);
+}
+
+// Darken depth silhouettes and shading creases; the alpha carried depth, so output is opaque
+fn apply_wireframe(color: vec3<f32>, uv: vec2<f32>) -> vec4<f32> {
+    let size = vec2<f32>(textureDimensions(ray_traced_texture));
+    let edges = sobel_edges(vec2<i32>(uv * size));
+    let edge = smoothstep(EDGE_THRESHOLD_LOW, EDGE_THRESHOLD_HIGH, max(edges.x, edges.y));
+    return vec4<f32>(color * (1.0 - edge * EDGE_DARKEN), 1.0);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(ray_traced_texture, texture_sampler, in.uv);
+    // Letterbox/pillarbox: the image fills only the viewport rect (mirrors resolution::fit_rect)
+    let uv = (in.uv - display_params.viewport.xy) / display_params.viewport.zw;
+    let outside = any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0));
+
+    var color = textureSample(ray_traced_texture, texture_sampler, clamp(uv, vec2<f32>(0.0), vec2<f32>(1.0)));
+    if outside {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    if display_params.wireframe != 0u {
+        color = apply_wireframe(color.rgb, uv);
+    }
+    color = vec4<f32>(color.rgb * display_params.exposure, color.a);
+
+    switch display_params.tone_map {
+        case TONE_MAP_REINHARD: {
+            return vec4<f32>(reinhard(color.rgb), color.a);
+        }
+        case TONE_MAP_ACES: {
+            return vec4<f32>(aces(color.rgb), color.a);
+        }
+        default: {
+            return color;
+        }
+    }
 }
@@ -0,0 +1,243 @@
+// editor.rs - Minimal box editor: click to select a box, nudge it with Alt+arrow keys, undo with Ctrl+Z
+use std::collections::VecDeque;
+
+use glam::{Quat, Vec3};
+use winit::keyboard::KeyCode;
+
+use crate::math::intersect_aabb;
+use crate::types::BoxData;
+
+/// World units a box moves per nudge key press
+pub const NUDGE_STEP: f32 = 0.25;
+
+/// Edits kept for undo; the oldest are dropped beyond this
+pub const MAX_UNDO_DEPTH: usize = 100;
+
+/// One box replaced by another at `index`
+#[derive(Debug, Clone, Copy)]
+pub struct BoxEdit {
+    pub index: usize,
+    pub before: BoxData,
+    pub after: BoxData,
+}
+
+/// Undo and redo stacks of box edits; a new edit clears the redo stack
+///
+/// Only valid while every change to the boxes goes through it: `RayTracer::set_box` records each
+/// replacement, and anything that swaps the whole box list (scene reload, LOD refresh) starts a new history.
+#[derive(Debug, Clone)]
+pub struct EditHistory {
+    undo: VecDeque<BoxEdit>,
+    redo: Vec<BoxEdit>,
+    limit: usize,
+}
+
+impl EditHistory {
+    pub fn new(limit: usize) -> Self {
+        Self { undo: VecDeque::new(), redo: Vec::new(), limit }
+    }
+
+    pub fn push(&mut self, edit: BoxEdit) {
+        self.redo.clear();
+        self.undo.push_back(edit);
+        if self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Revert the latest edit in `boxes`, returning the index it touched
+    ///
+    /// An edit whose box no longer exists stays on the stack and `None` is returned.
+    pub fn undo(&mut self, boxes: &mut [BoxData]) -> Option<usize> {
+        let edit = *self.undo.back()?;
+        *boxes.get_mut(edit.index)? = edit.before;
+        self.undo.pop_back();
+        self.redo.push(edit);
+        Some(edit.index)
+    }
+
+    /// Re-apply the latest undone edit in `boxes`, returning the index it touched
+    ///
+    /// An edit whose box no longer exists stays on the stack and `None` is returned.
+    pub fn redo(&mut self, boxes: &mut [BoxData]) -> Option<usize> {
+        let edit = *self.redo.last()?;
+        *boxes.get_mut(edit.index)? = edit.after;
+        self.redo.pop();
+        self.undo.push_back(edit);
+        Some(edit.index)
+    }
+
+    pub fn undo_depth(&self) -> usize {
+        self.undo.len()
+    }
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new(MAX_UNDO_DEPTH)
+    }
+}
+
+/// Periodic backup timer on the frame clock (`FrameInfo::time`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoSave {
+    /// Seconds between backups
+    pub interval: f32,
+    last_save: f32,
+}
+
+impl AutoSave {
+    pub fn new(interval: f32) -> Self {
+        Self { interval, last_save: 0.0 }
+    }
+
+    /// Whether a backup is due at frame time `now`
+    pub fn should_save(&self, now: f32) -> bool {
+        now - self.last_save >= self.interval
+    }
+
+    /// Restart the interval at `now`
+    pub fn saved(self, now: f32) -> Self {
+        Self { last_save: now, ..self }
+    }
+}
+
+/// Index of the nearest box hit by the ray, tested against each box's oriented bounds
+pub fn pick(origin: Vec3, dir: Vec3, boxes: &[BoxData]) -> Option<usize> {
+    boxes
+        .iter()
+        .enumerate()
+        .map(|(i, b)| (i, intersect_box(origin, dir, b)))
+        .filter(|&(_, t)| t >= 0.0)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+}
+
+/// Hit distance of the ray on `b` (negative on a miss), slab-tested in the box's local frame like the
+/// shader's `intersect_box`, so the empty corners of a rotated box's AABB are not hits
+fn intersect_box(origin: Vec3, dir: Vec3, b: &BoxData) -> f32 {
+    let to_local = Quat::from_array(b.rotation).inverse();
+    let half_size = Vec3::from_array(b.half_size);
+    intersect_aabb(to_local * (origin - Vec3::from_array(b.center0)), to_local * dir, -half_size, half_size)
+}
+
+/// Movement for an editor key: arrows along world X/Z, PageUp/PageDown along Y
+pub fn nudge_for_key(keycode: KeyCode) -> Option<Vec3> {
+    let direction = match keycode {
+        KeyCode::ArrowLeft => Vec3::NEG_X,
+        KeyCode::ArrowRight => Vec3::X,
+        KeyCode::ArrowUp => Vec3::NEG_Z,
+        KeyCode::ArrowDown => Vec3::Z,
+        KeyCode::PageUp => Vec3::Y,
+        KeyCode::PageDown => Vec3::NEG_Y,
+        _ => return None,
+    };
+    Some(direction * NUDGE_STEP)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_returns_nearest_box_on_ray() {
+        let boxes = [
+            BoxData::new([-1.0, -1.0, 9.0], [1.0, 1.0, 11.0], [1.0; 3]),
+            BoxData::new([-1.0, -1.0, 4.0], [1.0, 1.0, 6.0], [1.0; 3]),
+            BoxData::new([5.0, -1.0, 0.0], [7.0, 1.0, 2.0], [1.0; 3]),
+        ];
+        assert_eq!(pick(Vec3::ZERO, Vec3::Z, &boxes), Some(1));
+        assert_eq!(pick(Vec3::ZERO, Vec3::NEG_Z, &boxes), None);
+        assert_eq!(pick(Vec3::ZERO, Vec3::X, &boxes), Some(2));
+    }
+
+    #[test]
+    fn test_pick_tests_rotated_boxes_in_local_space() {
+        let turned = Quat::from_rotation_y(std::f32::consts::FRAC_PI_4);
+        let boxes = [BoxData::new_rotated(Vec3::new(0.0, 0.0, 10.0), Vec3::splat(2.0), turned, [1.0; 3])];
+
+        // Straight up through a corner of the world AABB that the turned box leaves empty
+        let corner = Vec3::new(1.2, -10.0, 11.2);
+        assert_eq!(pick(corner, Vec3::Y, &boxes), None);
+        assert_eq!(pick(Vec3::new(0.0, -10.0, 10.0), Vec3::Y, &boxes), Some(0));
+        assert_eq!(pick(Vec3::ZERO, Vec3::Z, &boxes), Some(0));
+    }
+
+    fn positions(boxes: &[BoxData]) -> Vec<[f32; 3]> {
+        boxes.iter().map(|b| b.min).collect()
+    }
+
+    /// Nudge box `index` in `boxes`, recording the edit
+    fn nudge(history: &mut EditHistory, boxes: &mut [BoxData], index: usize, delta: Vec3) {
+        let edit = BoxEdit { index, before: boxes[index], after: boxes[index].translated(delta) };
+        boxes[index] = edit.after;
+        history.push(edit);
+    }
+
+    #[test]
+    fn test_undo_restores_and_redo_reapplies() {
+        let mut boxes = vec![BoxData::new([0.0; 3], [1.0; 3], [1.0; 3]), BoxData::new([2.0; 3], [3.0; 3], [1.0; 3])];
+        let original = positions(&boxes);
+        let mut history = EditHistory::default();
+
+        nudge(&mut history, &mut boxes, 1, Vec3::X);
+        let edited = positions(&boxes);
+        assert_ne!(edited, original);
+
+        assert_eq!(history.undo(&mut boxes), Some(1));
+        assert_eq!(positions(&boxes), original);
+        assert_eq!(history.undo(&mut boxes), None);
+
+        assert_eq!(history.redo(&mut boxes), Some(1));
+        assert_eq!(positions(&boxes), edited);
+        assert_eq!(history.redo(&mut boxes), None);
+    }
+
+    #[test]
+    fn test_undo_keeps_edit_for_missing_box() {
+        let mut boxes = vec![BoxData::new([0.0; 3], [1.0; 3], [1.0; 3]), BoxData::new([2.0; 3], [3.0; 3], [1.0; 3])];
+        let mut history = EditHistory::default();
+        nudge(&mut history, &mut boxes, 1, Vec3::X);
+
+        assert_eq!(history.undo(&mut boxes[..1]), None);
+        assert_eq!(history.undo_depth(), 1);
+        assert_eq!(history.undo(&mut boxes), Some(1));
+
+        assert_eq!(history.redo(&mut boxes[..1]), None);
+        assert_eq!(history.redo(&mut boxes), Some(1));
+    }
+
+    #[test]
+    fn test_history_depth_is_limited_and_new_edits_clear_redo() {
+        let mut boxes = vec![BoxData::new([0.0; 3], [1.0; 3], [1.0; 3])];
+        let mut history = EditHistory::new(3);
+        (0..5).for_each(|_| nudge(&mut history, &mut boxes, 0, Vec3::Y));
+        assert_eq!(history.undo_depth(), 3);
+
+        while history.undo(&mut boxes).is_some() {}
+        assert_eq!(boxes[0].min, [0.0, 2.0, 0.0]);
+
+        history.redo(&mut boxes);
+        nudge(&mut history, &mut boxes, 0, Vec3::X);
+        assert_eq!(history.redo(&mut boxes), None);
+    }
+
+    #[test]
+    fn test_autosave_due_once_interval_elapses() {
+        let autosave = AutoSave::new(30.0);
+        assert!(!autosave.should_save(0.0));
+        assert!(!autosave.should_save(29.9));
+        assert!(autosave.should_save(30.0));
+
+        let autosave = autosave.saved(31.0);
+        assert!(!autosave.should_save(60.0));
+        assert!(autosave.should_save(61.0));
+    }
+
+    #[test]
+    fn test_nudge_keys() {
+        assert_eq!(nudge_for_key(KeyCode::ArrowRight), Some(Vec3::X * NUDGE_STEP));
+        assert_eq!(nudge_for_key(KeyCode::PageDown), Some(Vec3::NEG_Y * NUDGE_STEP));
+        assert_eq!(nudge_for_key(KeyCode::KeyW), None);
+    }
+}
@@ -0,0 +1,297 @@
+// exposure.rs - Auto-exposure from the average luminance of the HDR frame, reduced on the GPU
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::core::bloom::luminance;
+
+/// Average luminance the auto-exposure steers towards (middle gray)
+pub const TARGET_LUMINANCE: f32 = 0.18;
+/// Metering sees the HDR image, so highlights far above 1.0 need exposures well below 1
+pub const MIN_EXPOSURE: f32 = 1.0 / 64.0;
+pub const MAX_EXPOSURE: f32 = 16.0;
+/// Fraction of the gap to the target closed per second is `1 - exp(-ADAPTATION_RATE)`
+pub const ADAPTATION_RATE: f32 = 2.0;
+/// Only every `READBACK_STRIDE`-th pixel on every `READBACK_STRIDE`-th row is averaged
+pub const READBACK_STRIDE: usize = 8;
+/// Keeps black pixels from sending the log average to -inf
+const LUMINANCE_EPSILON: f32 = 1e-4;
+const SUM_BYTES: u64 = std::mem::size_of::<LuminanceSum>() as u64;
+
+/// Log-luminance total over the metered samples (mirrors `LuminanceSum` in luminance.wgsl)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Pod, Zeroable)]
+pub struct LuminanceSum {
+    pub log_sum: f32,
+    pub samples: u32,
+}
+
+impl LuminanceSum {
+    /// Log-average (geometric mean) luminance; `TARGET_LUMINANCE` when nothing was sampled
+    ///
+    /// The geometric mean keeps a few bright pixels (sun, emitters) from dominating.
+    pub fn average(self) -> f32 {
+        if self.samples == 0 {
+            return TARGET_LUMINANCE;
+        }
+        (self.log_sum / self.samples as f32).exp()
+    }
+}
+
+/// CPU reference for the GPU metering pass: log-average luminance of every `READBACK_STRIDE`-th texel
+/// of a linear HDR image, unclamped
+pub fn average_luminance(texels: &[[f32; 3]], width: usize, height: usize) -> f32 {
+    (0..height)
+        .step_by(READBACK_STRIDE)
+        .flat_map(|y| (0..width).step_by(READBACK_STRIDE).map(move |x| y * width + x))
+        .map(|i| luminance(texels[i]))
+        .fold(LuminanceSum::default(), |sum, l| LuminanceSum {
+            log_sum: sum.log_sum + (l + LUMINANCE_EPSILON).ln(),
+            samples: sum.samples + 1,
+        })
+        .average()
+}
+
+/// Exposure that maps `average` luminance to `TARGET_LUMINANCE`, within the allowed range
+pub fn target_exposure(average: f32) -> f32 {
+    (TARGET_LUMINANCE / average.max(LUMINANCE_EPSILON)).clamp(MIN_EXPOSURE, MAX_EXPOSURE)
+}
+
+/// Move `current` towards `target` exponentially over `delta` seconds, independent of frame rate
+pub fn adapt_exposure(current: f32, target: f32, delta: f32) -> f32 {
+    current + (target - current) * (1.0 - (-ADAPTATION_RATE * delta.max(0.0)).exp())
+}
+
+/// One auto-exposure frame: ease `current` towards the exposure for the measured `average` luminance
+pub fn exposure_step(current: f32, average: f32, delta: f32) -> f32 {
+    adapt_exposure(current, target_exposure(average), delta)
+}
+
+/// Meters the output texture with a one-workgroup reduction and reads the sum back without stalling the frame
+///
+/// Like `GpuTimer`, a measurement is skipped while the previous one is still being mapped, so the
+/// exposure follows the image with a frame or two of lag.
+pub struct LuminanceMeter {
+    pipeline: wgpu::ComputePipeline,
+    layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    sum_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// The sum was copied this frame and awaits `request_readback`
+    copied: bool,
+    /// In-flight map of `readback_buffer`
+    pending: Option<Receiver<Result<(), wgpu::BufferAsyncError>>>,
+    latest: Option<f32>,
+}
+
+impl LuminanceMeter {
+    pub fn new(device: &wgpu::Device, image: &wgpu::TextureView) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Luminance Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("luminance.wgsl").into()),
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("luminance_bind_group_layout"),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Luminance Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Luminance Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let sum_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Luminance Sum Buffer"),
+            size: SUM_BYTES,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Luminance Readback Buffer"),
+            size: SUM_BYTES,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let bind_group = Self::create_bind_group(device, &layout, image, &sum_buffer);
+
+        Self {
+            pipeline,
+            layout,
+            bind_group,
+            sum_buffer,
+            readback_buffer,
+            copied: false,
+            pending: None,
+            latest: None,
+        }
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        image: &wgpu::TextureView,
+        sum_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(image),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: sum_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("luminance_bind_group"),
+        })
+    }
+
+    /// Meter a new image after the output texture is reallocated
+    pub fn rebind(&mut self, device: &wgpu::Device, image: &wgpu::TextureView) {
+        self.bind_group = Self::create_bind_group(device, &self.layout, image, &self.sum_buffer);
+    }
+
+    /// Reduce the image rendered earlier in `encoder`; skipped while the previous sum is still mapped
+    pub fn measure(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.pending.is_some() {
+            return;
+        }
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Luminance Pass"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.dispatch_workgroups(1, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&self.sum_buffer, 0, &self.readback_buffer, 0, SUM_BYTES);
+        self.copied = true;
+    }
+
+    /// Start mapping the copied sum; call after submitting the encoder passed to `measure`
+    pub fn request_readback(&mut self) {
+        if !std::mem::take(&mut self.copied) {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        self.readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            tx.send(result).ok();
+        });
+        self.pending = Some(rx);
+    }
+
+    /// Collect a finished readback without blocking; the most recent average luminance measured so far
+    pub fn poll(&mut self, device: &wgpu::Device) -> Option<f32> {
+        device.poll(wgpu::PollType::Poll).ok();
+        let status = self.pending.as_ref().map(|rx| rx.try_recv());
+        match status {
+            Some(Ok(Ok(()))) => {
+                {
+                    let data = self.readback_buffer.slice(..).get_mapped_range();
+                    self.latest = Some(bytemuck::pod_read_unaligned::<LuminanceSum>(&data).average());
+                }
+                self.readback_buffer.unmap();
+                self.pending = None;
+            }
+            Some(Ok(Err(_)) | Err(TryRecvError::Disconnected)) => self.pending = None,
+            Some(Err(TryRecvError::Empty)) | None => {}
+        }
+        self.latest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_luminance_to_exposure() {
+        assert!((target_exposure(TARGET_LUMINANCE) - 1.0).abs() < 1e-6);
+        assert!((target_exposure(0.09) - 2.0).abs() < 1e-5);
+        assert_eq!(target_exposure(0.0), MAX_EXPOSURE);
+        assert_eq!(target_exposure(100.0), MIN_EXPOSURE);
+    }
+
+    #[test]
+    fn test_exposure_adapts_smoothly() {
+        let step = adapt_exposure(1.0, 2.0, 1.0 / 60.0);
+        assert!(step > 1.0 && step < 1.1, "one frame should only nudge exposure: {}", step);
+
+        // Two half steps land where one full step does, so adaptation ignores frame rate
+        let halves = adapt_exposure(adapt_exposure(1.0, 2.0, 0.25), 2.0, 0.25);
+        assert!((halves - adapt_exposure(1.0, 2.0, 0.5)).abs() < 1e-6);
+
+        let settled = (0..600).fold(1.0, |e, _| adapt_exposure(e, 2.0, 1.0 / 60.0));
+        assert!((settled - 2.0).abs() < 1e-3);
+        assert_eq!(adapt_exposure(1.5, 2.0, 0.0), 1.5);
+    }
+
+    #[test]
+    fn test_exposure_step_converges_to_key_value() {
+        let scene_luminance = 0.06;
+        let exposure = (0..600).fold(1.0, |e, _| exposure_step(e, scene_luminance, 1.0 / 60.0));
+        assert!((scene_luminance * exposure - TARGET_LUMINANCE).abs() < 1e-3, "exposure {}", exposure);
+    }
+
+    #[test]
+    fn test_luminance_sum_average_is_geometric_mean() {
+        let sum = LuminanceSum { log_sum: 0.5f32.ln() + 2.0f32.ln(), samples: 2 };
+        assert!((sum.average() - 1.0).abs() < 1e-6);
+        assert_eq!(LuminanceSum::default().average(), TARGET_LUMINANCE);
+        assert_eq!(std::mem::size_of::<LuminanceSum>(), 8);
+    }
+
+    #[test]
+    fn test_average_luminance_of_uniform_gray() {
+        let (width, height) = (16, 16);
+        let texels = vec![[0.5; 3]; width * height];
+        assert!((average_luminance(&texels, width, height) - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_hdr_highlights_are_metered_unclamped() {
+        let (width, height) = (16, 16);
+        let texels = vec![[4.0; 3]; width * height];
+        let average = average_luminance(&texels, width, height);
+        assert!((average - 4.0).abs() < 1e-3, "average {}", average);
+
+        // A frame brighter than display white is exposed down, not left at the LDR limit
+        assert!((target_exposure(average) - TARGET_LUMINANCE / 4.0).abs() < 1e-4);
+        assert!(target_exposure(average) < target_exposure(1.0));
+    }
+}
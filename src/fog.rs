@@ -0,0 +1,40 @@
+// fog.rs - Exponential distance fog applied to primary and reflected hits in the GPU shader
+use crate::sky::HORIZON_COLOR;
+
+/// Fog color used when only a density is given
+pub const DEFAULT_FOG_COLOR: [f32; 3] = HORIZON_COLOR;
+
+/// Share of the fog color at `distance`: `1 - exp(-density * distance)` (mirrors the shader's `fog_factor`)
+pub fn fog_factor(distance: f32, density: f32) -> f32 {
+    1.0 - (-density * distance).exp()
+}
+
+/// Blend `color` towards `fog_color` by the fog factor at `distance`; density 0 leaves it unchanged
+pub fn apply_fog(color: [f32; 3], fog_color: [f32; 3], distance: f32, density: f32) -> [f32; 3] {
+    let f = fog_factor(distance, density);
+    std::array::from_fn(|i| color[i] + (fog_color[i] - color[i]) * f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fog_factor_at_distances_and_densities() {
+        assert_eq!(fog_factor(0.0, 0.5), 0.0);
+        assert_eq!(fog_factor(100.0, 0.0), 0.0);
+        assert!((fog_factor(1.0, 1.0) - (1.0 - (-1.0f32).exp())).abs() < 1e-6);
+        assert!((fog_factor(10.0, 0.1) - 0.632_120_6).abs() < 1e-6);
+        assert!((fog_factor(20.0, 0.05) - fog_factor(10.0, 0.1)).abs() < 1e-6);
+        assert!(fog_factor(1000.0, 0.1) > 0.999);
+    }
+
+    #[test]
+    fn test_apply_fog_blends_towards_fog_color() {
+        let red = [1.0, 0.0, 0.0];
+        assert_eq!(apply_fog(red, DEFAULT_FOG_COLOR, 50.0, 0.0), red);
+
+        let half = apply_fog(red, [0.0, 0.0, 1.0], std::f32::consts::LN_2, 1.0);
+        assert!((half[0] - 0.5).abs() < 1e-6 && (half[2] - 0.5).abs() < 1e-6);
+    }
+}
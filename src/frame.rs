@@ -1,14 +1,41 @@
+/// Weight of the newest frame in the exponentially smoothed FPS
+pub const FPS_SMOOTHING: f32 = 0.1;
+
+/// Frame rate while no window has focus, to save power
+pub const DEFAULT_IDLE_FPS: f32 = 5.0;
+
 /// Frame metadata - carries frame number and timing info
 #[derive(Debug, Clone, Copy)]
 pub struct FrameInfo {
-    pub number: u64,
+    /// Monotonic frame counter, starting at 0
+    pub frame_number: u64,
     pub time: f32,
     pub delta: f32,
+    /// Exponentially smoothed frames per second (0 until a frame with a non-zero delta)
+    pub fps: f32,
 }
 
 impl FrameInfo {
-    pub fn new(number: u64, time: f32, delta: f32) -> Self {
-        Self { number, time, delta }
+    pub fn new(frame_number: u64, time: f32, delta: f32, fps: f32) -> Self {
+        Self { frame_number, time, delta, fps }
+    }
+}
+
+/// Blend the instantaneous rate of a `delta`-second frame into `fps`
+pub fn smooth_fps(fps: f32, delta: f32) -> f32 {
+    match (fps, delta) {
+        (_, d) if d <= 0.0 => fps,
+        (f, d) if f <= 0.0 => 1.0 / d,
+        (f, d) => f + (1.0 / d - f) * FPS_SMOOTHING,
+    }
+}
+
+/// Frame rate cap to render at: `fps` (None = uncapped) while focused, at most `idle_fps` otherwise
+pub fn target_fps(focused: bool, fps: Option<f32>, idle_fps: f32) -> Option<f32> {
+    match (focused, fps) {
+        (true, _) => fps,
+        (false, Some(cap)) => Some(cap.min(idle_fps)),
+        (false, None) => Some(idle_fps),
     }
 }
 
@@ -18,6 +45,9 @@ pub struct FrameIterator {
     frame_number: u64,
     start_time: std::time::Instant,
     last_frame_time: std::time::Instant,
+    /// Time of the last frame from `step`, which ignores the wall clock
+    stepped_time: f32,
+    fps: f32,
 }
 
 impl FrameIterator {
@@ -27,6 +57,8 @@ impl FrameIterator {
             frame_number: 0,
             start_time: now,
             last_frame_time: now,
+            stepped_time: 0.0,
+            fps: 0.0,
         }
     }
 
@@ -37,6 +69,24 @@ impl FrameIterator {
     pub fn time(&self) -> f32 {
         self.start_time.elapsed().as_secs_f32()
     }
+
+    /// Smoothed FPS as of the last frame
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    /// Advance by a fixed `delta` seconds instead of the wall clock (offline rendering, tests)
+    pub fn step(&mut self, delta: f32) -> FrameInfo {
+        self.stepped_time += delta;
+        self.emit(self.stepped_time, delta)
+    }
+
+    fn emit(&mut self, time: f32, delta: f32) -> FrameInfo {
+        self.fps = smooth_fps(self.fps, delta);
+        let info = FrameInfo::new(self.frame_number, time, delta, self.fps);
+        self.frame_number += 1;
+        info
+    }
 }
 
 impl Default for FrameIterator {
@@ -53,11 +103,48 @@ impl Iterator for FrameIterator {
         let delta = now.duration_since(self.last_frame_time).as_secs_f32();
         let time = now.duration_since(self.start_time).as_secs_f32();
 
-        let info = FrameInfo::new(self.frame_number, time, delta);
-
-        self.frame_number += 1;
         self.last_frame_time = now;
+        Some(self.emit(time, delta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_number_increments_per_next() {
+        let mut frames = FrameIterator::new();
+        let numbers: Vec<u64> = frames.by_ref().take(5).map(|f| f.frame_number).collect();
+        assert_eq!(numbers, vec![0, 1, 2, 3, 4]);
+        assert_eq!(frames.frame_number(), 5);
+        assert_eq!(frames.step(0.01).frame_number, 5);
+    }
+
+    #[test]
+    fn test_constant_delta_converges_to_60_fps() {
+        let mut frames = FrameIterator::new();
+        frames.step(0.05);
+        assert!((frames.fps() - 20.0).abs() < 1e-3);
+
+        let last = (0..100).map(|_| frames.step(0.0166)).last().unwrap();
+        assert!((last.fps - 1.0 / 0.0166).abs() < 0.01, "{}", last.fps);
+        assert!((last.time - (0.05 + 100.0 * 0.0166)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_zero_delta_keeps_fps() {
+        assert_eq!(smooth_fps(0.0, 0.0), 0.0);
+        assert_eq!(smooth_fps(30.0, 0.0), 30.0);
+        assert_eq!(smooth_fps(0.0, 0.5), 2.0);
+    }
 
-        Some(info)
+    #[test]
+    fn test_target_fps_throttles_only_when_unfocused() {
+        assert_eq!(target_fps(true, None, DEFAULT_IDLE_FPS), None);
+        assert_eq!(target_fps(true, Some(30.0), DEFAULT_IDLE_FPS), Some(30.0));
+        assert_eq!(target_fps(false, None, DEFAULT_IDLE_FPS), Some(5.0));
+        assert_eq!(target_fps(false, Some(30.0), 5.0), Some(5.0));
+        assert_eq!(target_fps(false, Some(2.0), 5.0), Some(2.0));
     }
 }
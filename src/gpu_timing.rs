@@ -0,0 +1,154 @@
+// gpu_timing.rs - Compute and display pass durations from GPU timestamp queries
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+/// Timestamps per frame: compute begin/end, then display begin/end
+const QUERY_COUNT: u32 = 4;
+const QUERY_BYTES: u64 = QUERY_COUNT as u64 * std::mem::size_of::<u64>() as u64;
+
+/// Milliseconds between two raw timestamps, at `period` nanoseconds per tick (`Queue::get_timestamp_period`)
+pub fn ticks_to_ms(start: u64, end: u64, period: f32) -> f64 {
+    end.saturating_sub(start) as f64 * period as f64 / 1_000_000.0
+}
+
+/// GPU time spent in each pass of one frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PassTimings {
+    pub compute_ms: f64,
+    pub display_ms: f64,
+}
+
+impl PassTimings {
+    /// From the four resolved timestamps in query order
+    pub fn from_ticks(ticks: &[u64], period: f32) -> Self {
+        Self {
+            compute_ms: ticks_to_ms(ticks[0], ticks[1], period),
+            display_ms: ticks_to_ms(ticks[2], ticks[3], period),
+        }
+    }
+}
+
+/// Timestamp queries around the compute and display passes, read back without stalling the frame
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    period: f32,
+    /// Timestamps were copied this frame and await `request_readback`
+    copied: bool,
+    /// In-flight map of `readback_buffer`
+    pending: Option<Receiver<Result<(), wgpu::BufferAsyncError>>>,
+    latest: Option<PassTimings>,
+}
+
+impl GpuTimer {
+    /// `None` when the device was created without `TIMESTAMP_QUERY`
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Pass Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: QUERY_COUNT,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size: QUERY_BYTES,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Readback Buffer"),
+            size: QUERY_BYTES,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period: queue.get_timestamp_period(),
+            copied: false,
+            pending: None,
+            latest: None,
+        })
+    }
+
+    pub fn compute_writes(&self) -> wgpu::ComputePassTimestampWrites<'_> {
+        wgpu::ComputePassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(0),
+            end_of_pass_write_index: Some(1),
+        }
+    }
+
+    pub fn display_writes(&self) -> wgpu::RenderPassTimestampWrites<'_> {
+        wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(2),
+            end_of_pass_write_index: Some(3),
+        }
+    }
+
+    /// Copy this frame's timestamps for readback; skipped while the previous copy is still mapped
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.pending.is_some() {
+            return;
+        }
+        encoder.resolve_query_set(&self.query_set, 0..QUERY_COUNT, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, QUERY_BYTES);
+        self.copied = true;
+    }
+
+    /// Start mapping the copied timestamps; call after submitting the encoder passed to `resolve`
+    pub fn request_readback(&mut self) {
+        if !std::mem::take(&mut self.copied) {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        self.readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            tx.send(result).ok();
+        });
+        self.pending = Some(rx);
+    }
+
+    /// Collect a finished readback without blocking; the most recent timings measured so far
+    pub fn poll(&mut self, device: &wgpu::Device) -> Option<PassTimings> {
+        device.poll(wgpu::PollType::Poll).ok();
+        let status = self.pending.as_ref().map(|rx| rx.try_recv());
+        match status {
+            Some(Ok(Ok(()))) => {
+                {
+                    let data = self.readback_buffer.slice(..).get_mapped_range();
+                    self.latest = Some(PassTimings::from_ticks(bytemuck::cast_slice(&data), self.period));
+                }
+                self.readback_buffer.unmap();
+                self.pending = None;
+            }
+            Some(Ok(Err(_)) | Err(TryRecvError::Disconnected)) => self.pending = None,
+            Some(Err(TryRecvError::Empty)) | None => {}
+        }
+        self.latest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticks_to_ms() {
+        assert_eq!(ticks_to_ms(1_000, 2_001_000, 1.0), 2.0);
+        assert_eq!(ticks_to_ms(0, 1_000_000, 83.333), 83.333_f32 as f64);
+        // Reset or reordered counters read as zero rather than wrapping
+        assert_eq!(ticks_to_ms(5_000, 1_000, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_pass_timings_from_ticks() {
+        let timings = PassTimings::from_ticks(&[0, 4_000_000, 5_000_000, 5_500_000], 1.0);
+        assert_eq!(timings, PassTimings { compute_ms: 4.0, display_ms: 0.5 });
+    }
+}
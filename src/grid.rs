@@ -1,18 +1,43 @@
+use crate::camera::Camera;
 use crate::types::{BoxData, TriangleData};
-use crate::math::AABB;
+use crate::math::{Frustum, AABB};
 use glam::Vec3;
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::ops::Range;
 
 pub const GRID_LEVELS: usize = 4;
 pub const FINEST_CELL_SIZE: f32 = 16.0;
 pub const MAX_OBJECTS_PER_CELL: usize = 8192;
+/// Scenes with at most this many primitives get a single cell that the shader brute-forces
+pub const SINGLE_CELL_MAX_PRIMITIVES: usize = 8;
+/// Smallest grid extent per axis, so single or coincident objects still get a full cell
+pub const MIN_GRID_EXTENT: f32 = FINEST_CELL_SIZE;
 
-fn calculate_grid_dimensions(bounds: &AABB, cell_size: f32) -> [usize; 3] {
-    let extent = bounds.max - bounds.min;
-    [
-        (extent.x / cell_size).ceil() as usize,
-        (extent.y / cell_size).ceil() as usize,
-        (extent.z / cell_size).ceil() as usize,
-    ]
+/// Fine cell coordinates (x, y, z)
+pub type CellIndex = (i32, i32, i32);
+
+/// Fraction of a cell ignored when counting cells, so rounding in an exact fit doesn't add a row
+const CELL_FIT_TOLERANCE: f32 = 1e-3;
+
+fn calculate_grid_dimensions(bounds: &AABB, cell_size: Vec3) -> [usize; 3] {
+    let cells = ((bounds.max - bounds.min) / cell_size - CELL_FIT_TOLERANCE).ceil().max(Vec3::ONE);
+    [cells.x as usize, cells.y as usize, cells.z as usize]
+}
+
+/// Per-axis cell size no larger than `target` that tiles `extent` exactly
+///
+/// Each axis keeps the cell count a cubic `target` grid would use, but its cells
+/// shrink to the axis' extent, so thin axes get thin cells and no cell overhangs the bounds.
+fn fit_cell_size(extent: Vec3, target: f32) -> Vec3 {
+    extent / (extent / target).ceil().max(Vec3::ONE)
+}
+
+/// Grow any axis narrower than `min_extent` symmetrically around its center
+fn pad_to_min_extent(bounds: AABB, min_extent: f32) -> AABB {
+    let center = bounds.center();
+    let half = ((bounds.max - bounds.min) * 0.5).max(Vec3::splat(min_extent * 0.5));
+    AABB::new(center - half, center + half)
 }
 
 const fn compute_cell_index(x: usize, y: usize, z: usize, grid_size: [usize; 3]) -> usize {
@@ -25,7 +50,10 @@ pub struct GridMetadata {
     pub bounds_min: [f32; 3],
     pub num_levels: u32,
     pub bounds_max: [f32; 3],
-    pub finest_cell_size: f32,
+    pub _pad: f32,
+    /// Fine cell size per axis; coarse levels double it per level
+    pub finest_cell_size: [f32; 3],
+    pub _pad2: f32,
     pub grid_sizes: [[u32; 4]; GRID_LEVELS],
 }
 
@@ -38,13 +66,14 @@ pub struct FineCellData {
 }
 
 pub struct CoarseGridLevel {
-    pub cell_size: f32,
+    pub cell_size: Vec3,
     pub grid_size: [usize; 3],
-    pub counts: Vec<u8>,
+    /// Primitives overlapping each cell; the GPU copy saturates at 255
+    pub counts: Vec<u32>,
 }
 
 impl CoarseGridLevel {
-    pub fn new(bounds: &AABB, cell_size: f32) -> Self {
+    pub fn new(bounds: &AABB, cell_size: Vec3) -> Self {
         let grid_size = calculate_grid_dimensions(bounds, cell_size);
         let total_cells = grid_size[0] * grid_size[1] * grid_size[2];
 
@@ -60,21 +89,23 @@ impl CoarseGridLevel {
     }
 
     pub fn increment_cell(&mut self, x: usize, y: usize, z: usize) {
-        let idx = self.cell_index(x, y, z);
-        if self.counts[idx] < 255 {
-            self.counts[idx] += 1;
-        }
+        self.increment_index(self.cell_index(x, y, z));
+    }
+
+    fn increment_index(&mut self, idx: usize) {
+        self.counts[idx] += 1;
     }
 }
 
 pub struct FineGridLevel {
-    pub cell_size: f32,
+    pub cell_size: Vec3,
     pub grid_size: [usize; 3],
+    /// Every primitive overlapping each cell, in ID order; the GPU copy keeps the first `MAX_OBJECTS_PER_CELL`
     pub cells: Vec<Vec<u32>>,
 }
 
 impl FineGridLevel {
-    pub fn new(bounds: &AABB, cell_size: f32) -> Self {
+    pub fn new(bounds: &AABB, cell_size: Vec3) -> Self {
         let grid_size = calculate_grid_dimensions(bounds, cell_size);
         let total_cells = grid_size[0] * grid_size[1] * grid_size[2];
 
@@ -90,24 +121,98 @@ impl FineGridLevel {
     }
 
     pub fn add_object(&mut self, x: usize, y: usize, z: usize, object_id: u32) {
-        let idx = self.cell_index(x, y, z);
-        if self.cells[idx].len() < MAX_OBJECTS_PER_CELL {
-            self.cells[idx].push(object_id);
-        } else {
-            eprintln!("WARNING: Cell ({}, {}, {}) exceeded MAX_OBJECTS_PER_CELL ({}), dropping object {}",
-                     x, y, z, MAX_OBJECTS_PER_CELL, object_id);
+        self.add_object_at(self.cell_index(x, y, z), object_id);
+    }
+
+    /// Insert `object_id` into the cell at flat index `idx` in ID order
+    ///
+    /// Overflowing IDs are kept so that removing one later lets the next come back into the GPU copy,
+    /// exactly as a fresh build would.
+    fn add_object_at(&mut self, idx: usize, object_id: u32) {
+        let cell = &mut self.cells[idx];
+        if cell.len() >= MAX_OBJECTS_PER_CELL {
+            eprintln!("WARNING: Cell {} exceeded MAX_OBJECTS_PER_CELL ({}), the GPU skips IDs past the first {}",
+                     idx, MAX_OBJECTS_PER_CELL, MAX_OBJECTS_PER_CELL);
         }
+        cell.insert(cell.partition_point(|&id| id < object_id), object_id);
     }
+
+    /// GPU layout of the cell at flat index `idx`, truncated to `MAX_OBJECTS_PER_CELL`
+    fn cell_data(&self, idx: usize) -> FineCellData {
+        let cell = &self.cells[idx];
+        let count = cell.len().min(MAX_OBJECTS_PER_CELL);
+        let mut object_indices = [0u32; MAX_OBJECTS_PER_CELL];
+        object_indices[..count].copy_from_slice(&cell[..count]);
+        FineCellData { object_indices, count: count as u32, _pad: [0; 3] }
+    }
+}
+
+/// Grid build statistics, useful for explaining slow traversal
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GridStats {
+    pub coarse_cells: usize,
+    pub fine_cells: usize,
+    pub occupied_cells: usize,
+    pub max_primitives_per_cell: usize,
+    /// Fraction of fine cells with no primitives (0.0-1.0)
+    pub empty_ratio: f32,
+}
+
+/// Byte ranges of the `to_gpu_buffers` buffers changed by an in-place grid edit, for partial re-upload
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GridUpdate {
+    /// Ranges of the coarse counts buffer (one byte per cell, levels back to back)
+    pub coarse: Vec<Range<u64>>,
+    /// Ranges of the fine cells buffer (one `FineCellData` per cell)
+    pub fine: Vec<Range<u64>>,
+}
+
+/// Sorted byte ranges of the elements at `cells`, `stride` bytes each, with neighbours merged
+fn cell_ranges(mut cells: Vec<usize>, stride: u64) -> Vec<Range<u64>> {
+    cells.sort_unstable();
+    cells.dedup();
+    cells.into_iter().fold(Vec::new(), |mut ranges: Vec<Range<u64>>, idx| {
+        let (start, end) = (idx as u64 * stride, (idx as u64 + 1) * stride);
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+        ranges
+    })
+}
+
+/// Cells of `a` not in `b`
+fn cells_only_in(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let b: HashSet<_> = b.iter().collect();
+    a.iter().copied().filter(|idx| !b.contains(idx)).collect()
 }
 
 pub struct HierarchicalGrid {
     pub bounds: AABB,
     pub coarse_levels: Vec<CoarseGridLevel>,
     pub fine_level: FineGridLevel,
+    /// Object IDs below this are boxes, the rest are triangles
+    pub num_boxes: u32,
+    /// Bounds each primitive was inserted with, by object ID
+    spans: Vec<(Vec3, Vec3)>,
 }
 
 impl HierarchicalGrid {
+    /// Build the grid, finding each primitive's cells in parallel when more than one thread is available
+    ///
+    /// With a single thread the parallel path's extra buffer only costs time, so the serial one is used.
     pub fn build(objects: &[BoxData], triangles: &[TriangleData]) -> Self {
+        let insert_all = if rayon::current_num_threads() > 1 { Self::insert_parallel } else { Self::insert_serial };
+        Self::build_with(objects, triangles, insert_all)
+    }
+
+    /// Single-threaded baseline for `build`: assigns each primitive to its cells directly, one at a time.
+    /// Produces identical GPU buffers.
+    pub fn build_serial(objects: &[BoxData], triangles: &[TriangleData]) -> Self {
+        Self::build_with(objects, triangles, Self::insert_serial)
+    }
+
+    fn build_with(objects: &[BoxData], triangles: &[TriangleData], insert_all: fn(&mut Self, &[(Vec3, Vec3)])) -> Self {
         // Compute bounds from both boxes and triangles
         let mut bounds = if !objects.is_empty() {
             objects[0].bounds()
@@ -132,12 +237,28 @@ impl HierarchicalGrid {
         let padding = Vec3::splat(1.0);
         bounds.min -= padding;
         bounds.max += padding;
+        let bounds = pad_to_min_extent(bounds, MIN_GRID_EXTENT);
+        assert!(
+            bounds.min.is_finite() && bounds.max.is_finite(),
+            "Grid bounds must be finite, got {:?} to {:?}",
+            bounds.min,
+            bounds.max
+        );
 
         println!("Grid bounds: {:?} to {:?}", bounds.min, bounds.max);
 
+        // One cell spanning the whole scene: subdivision isn't worth it for a handful of primitives
+        let extent = bounds.max - bounds.min;
+        let finest_cell_size = if objects.len() + triangles.len() <= SINGLE_CELL_MAX_PRIMITIVES {
+            extent
+        } else {
+            fit_cell_size(extent, FINEST_CELL_SIZE)
+        };
+
         let mut coarse_levels = Vec::new();
         for level in 0..(GRID_LEVELS - 1) {
-            let cell_size = FINEST_CELL_SIZE * (1 << (GRID_LEVELS - 1 - level)) as f32;
+            let cell_size = finest_cell_size * (1 << (GRID_LEVELS - 1 - level)) as f32;
+            assert!(cell_size.is_finite() && cell_size.min_element() > 0.0, "Invalid cell size {} at level {}", cell_size, level);
             coarse_levels.push(CoarseGridLevel::new(&bounds, cell_size));
             println!(
                 "Coarse level {}: {}x{}x{} cells (size: {})",
@@ -149,52 +270,34 @@ impl HierarchicalGrid {
             );
         }
 
-        let fine_level = FineGridLevel::new(&bounds, FINEST_CELL_SIZE);
+        let fine_level = FineGridLevel::new(&bounds, finest_cell_size);
         println!(
             "Fine level: {}x{}x{} cells (size: {})",
             fine_level.grid_size[0],
             fine_level.grid_size[1],
             fine_level.grid_size[2],
-            FINEST_CELL_SIZE
+            finest_cell_size
         );
 
         let mut grid = Self {
             bounds,
             coarse_levels,
             fine_level,
+            num_boxes: objects.len() as u32,
+            spans: Vec::new(),
         };
 
-        // Assign boxes (object IDs 0..num_boxes-1)
-        for (obj_id, obj) in objects.iter().enumerate() {
-            grid.assign_object(obj, obj_id as u32);
-        }
-
-        // Assign triangles (object IDs num_boxes..num_boxes+num_triangles-1)
-        let num_boxes = objects.len() as u32;
-        for (tri_id, tri) in triangles.iter().enumerate() {
-            grid.assign_triangle(tri, num_boxes + tri_id as u32);
-        }
-
-        let total_coarse_cells: usize = grid
-            .coarse_levels
+        // Object IDs 0..num_boxes-1 are boxes, num_boxes.. are triangles
+        let spans: Vec<(Vec3, Vec3)> = objects
             .iter()
-            .map(|level| level.counts.len())
-            .sum();
-        let occupied_fine_cells = grid
-            .fine_level
-            .cells
-            .iter()
-            .filter(|cell| !cell.is_empty())
-            .count();
+            .map(|obj| (Vec3::from_array(obj.min), Vec3::from_array(obj.max)))
+            .chain(triangles.iter().map(|tri| tri.bounds()).map(|b| (b.min, b.max)))
+            .collect();
 
-        let max_objects_in_cell = grid
-            .fine_level
-            .cells
-            .iter()
-            .map(|cell| cell.len())
-            .max()
-            .unwrap_or(0);
+        insert_all(&mut grid, &spans);
+        grid.spans = spans;
 
+        let stats = grid.stats();
         let cells_at_capacity = grid
             .fine_level
             .cells
@@ -203,13 +306,13 @@ impl HierarchicalGrid {
             .count();
 
         println!("Grid stats:");
-        println!("  Coarse cells total: {}", total_coarse_cells);
+        println!("  Coarse cells total: {}", stats.coarse_cells);
         println!(
             "  Fine cells occupied: {}/{}",
-            occupied_fine_cells,
-            grid.fine_level.cells.len()
+            stats.occupied_cells,
+            stats.fine_cells
         );
-        println!("  Max objects in a cell: {}", max_objects_in_cell);
+        println!("  Max objects in a cell: {}", stats.max_primitives_per_cell);
         println!("  Cells at capacity: {}", cells_at_capacity);
 
         grid
@@ -219,7 +322,7 @@ impl HierarchicalGrid {
         obj_min: Vec3,
         obj_max: Vec3,
         bounds_min: Vec3,
-        cell_size: f32,
+        cell_size: Vec3,
         grid_size: [usize; 3],
     ) -> impl Iterator<Item = (usize, usize, usize)> {
         let min_cell = Self::world_to_cell_static(&obj_min, bounds_min, cell_size);
@@ -236,66 +339,178 @@ impl HierarchicalGrid {
         })
     }
 
-    fn assign_object(&mut self, obj: &BoxData, obj_id: u32) {
-        let obj_min = Vec3::from_array(obj.min);
-        let obj_max = Vec3::from_array(obj.max);
+    /// Cell counts and occupancy of the built grid
+    pub fn stats(&self) -> GridStats {
+        let fine_cells = self.fine_level.cells.len();
+        let occupied_cells = self.fine_level.cells.iter().filter(|cell| !cell.is_empty()).count();
+
+        GridStats {
+            coarse_cells: self.coarse_levels.iter().map(|level| level.counts.len()).sum(),
+            fine_cells,
+            occupied_cells,
+            max_primitives_per_cell: self.fine_level.cells.iter().map(Vec::len).max().unwrap_or(0),
+            empty_ratio: if fine_cells == 0 {
+                0.0
+            } else {
+                (fine_cells - occupied_cells) as f32 / fine_cells as f32
+            },
+        }
+    }
+
+    /// Fine cell coordinates paired with the index of each box stored there
+    ///
+    /// A box spanning several cells appears once per cell; triangles are skipped.
+    pub fn box_cells(&self) -> Vec<(CellIndex, u32)> {
+        let [nx, ny, _] = self.fine_level.grid_size;
+        self.fine_level
+            .cells
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, cell)| {
+                let coords = ((idx % nx) as i32, ((idx / nx) % ny) as i32, (idx / (nx * ny)) as i32);
+                cell.iter()
+                    .take(MAX_OBJECTS_PER_CELL)
+                    .filter(|&&id| id < self.num_boxes)
+                    .map(move |&id| (coords, id))
+            })
+            .collect()
+    }
+
+    /// Fine cells overlapping the camera's view frustum at `aspect` (width / height)
+    ///
+    /// Uses the same projection as the shader's primary rays; the test is
+    /// conservative, so a few cells just outside a frustum corner may be kept.
+    pub fn visible_cells(&self, camera: &Camera, aspect: f32) -> Vec<CellIndex> {
+        let frustum = Frustum::new(camera.position, camera.forward(), camera.right(), camera.up(), camera.fov.tan(), aspect);
+        let [nx, ny, nz] = self.fine_level.grid_size;
+        let cell_size = self.fine_level.cell_size;
+
+        (0..nz)
+            .flat_map(|z| (0..ny).flat_map(move |y| (0..nx).map(move |x| (x, y, z))))
+            .filter(|&(x, y, z)| {
+                let min = self.bounds.min + Vec3::new(x as f32, y as f32, z as f32) * cell_size;
+                frustum.intersects_aabb(&AABB::new(min, min + cell_size))
+            })
+            .map(|(x, y, z)| (x as i32, y as i32, z as i32))
+            .collect()
+    }
+
+    /// True when the whole scene fits in one fine cell (see `SINGLE_CELL_MAX_PRIMITIVES`)
+    pub fn is_single_cell(&self) -> bool {
+        self.fine_level.grid_size == [1, 1, 1]
+    }
+
+    /// Flat indices of the cells a primitive spanning `obj_min..obj_max` covers: one list per coarse
+    /// level, then the fine level
+    fn footprint(&self, (obj_min, obj_max): (Vec3, Vec3)) -> Vec<Vec<usize>> {
         let bounds_min = self.bounds.min;
+        self.coarse_levels
+            .iter()
+            .map(|level| (level.cell_size, level.grid_size))
+            .chain(std::iter::once((self.fine_level.cell_size, self.fine_level.grid_size)))
+            .map(|(cell_size, grid_size)| {
+                Self::cells_in_bounds(obj_min, obj_max, bounds_min, cell_size, grid_size)
+                    .map(|(x, y, z)| compute_cell_index(x, y, z, grid_size))
+                    .collect()
+            })
+            .collect()
+    }
 
-        for level in self.coarse_levels.iter_mut() {
-            Self::cells_in_bounds(
-                obj_min,
-                obj_max,
-                bounds_min,
-                level.cell_size,
-                level.grid_size,
-            )
-            .for_each(|(x, y, z)| level.increment_cell(x, y, z));
+    /// Count each primitive in its coarse cells and store its ID in its fine cells, in ID order
+    fn insert_serial(&mut self, spans: &[(Vec3, Vec3)]) {
+        let bounds_min = self.bounds.min;
+        for (obj_id, &(obj_min, obj_max)) in spans.iter().enumerate() {
+            for level in self.coarse_levels.iter_mut() {
+                Self::cells_in_bounds(obj_min, obj_max, bounds_min, level.cell_size, level.grid_size)
+                    .for_each(|(x, y, z)| level.increment_cell(x, y, z));
+            }
+            Self::cells_in_bounds(obj_min, obj_max, bounds_min, self.fine_level.cell_size, self.fine_level.grid_size)
+                .for_each(|(x, y, z)| self.fine_level.add_object(x, y, z, obj_id as u32));
         }
-
-        Self::cells_in_bounds(
-            obj_min,
-            obj_max,
-            bounds_min,
-            self.fine_level.cell_size,
-            self.fine_level.grid_size,
-        )
-        .for_each(|(x, y, z)| self.fine_level.add_object(x, y, z, obj_id));
     }
 
-    fn assign_triangle(&mut self, tri: &TriangleData, obj_id: u32) {
-        let tri_bounds = tri.bounds();
-        let obj_min = tri_bounds.min;
-        let obj_max = tri_bounds.max;
+    /// `insert_serial` with the cell search done in parallel
+    ///
+    /// Finding the covered cells is the expensive part and independent per primitive. The results are
+    /// collected into one flat `(object ID, cell)` buffer in ID order, with cells numbered across the coarse
+    /// levels and then the fine level, and applied sequentially so cell contents stay deterministic.
+    fn insert_parallel(&mut self, spans: &[(Vec3, Vec3)]) {
         let bounds_min = self.bounds.min;
+        let levels: Vec<(Vec3, [usize; 3], usize)> = self
+            .coarse_levels
+            .iter()
+            .map(|level| (level.cell_size, level.grid_size, level.counts.len()))
+            .chain(std::iter::once((self.fine_level.cell_size, self.fine_level.grid_size, 0)))
+            .scan(0, |offset, (cell_size, grid_size, cells)| {
+                let level = (cell_size, grid_size, *offset);
+                *offset += cells;
+                Some(level)
+            })
+            .collect();
+        let fine_offset = levels.last().map_or(0, |&(_, _, offset)| offset);
+
+        let assignments: Vec<(u32, usize)> = spans
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(|(obj_id, &(obj_min, obj_max))| {
+                levels.iter().flat_map(move |&(cell_size, grid_size, offset)| {
+                    Self::cells_in_bounds(obj_min, obj_max, bounds_min, cell_size, grid_size)
+                        .map(move |(x, y, z)| (obj_id as u32, offset + compute_cell_index(x, y, z, grid_size)))
+                })
+            })
+            .collect();
+
+        for (obj_id, cell) in assignments {
+            if cell >= fine_offset {
+                self.fine_level.add_object_at(cell - fine_offset, obj_id);
+                continue;
+            }
+            let level = levels.iter().rposition(|&(_, _, offset)| offset <= cell).unwrap_or(0);
+            self.coarse_levels[level].increment_index(cell - levels[level].2);
+        }
+    }
+
+    /// Move box `index` to the bounds of `new_box` in place, touching only the cells it leaves or enters
+    ///
+    /// Returns the changed buffer ranges, or `None` (leaving the grid as it was) when `index` is not a box
+    /// or the new bounds reach outside the grid, which needs a full `build`.
+    pub fn update_primitive(&mut self, index: u32, new_box: &BoxData) -> Option<GridUpdate> {
+        let span = (Vec3::from_array(new_box.min), Vec3::from_array(new_box.max));
+        if index >= self.num_boxes || !self.bounds.contains_point(span.0) || !self.bounds.contains_point(span.1) {
+            return None;
+        }
 
-        for level in self.coarse_levels.iter_mut() {
-            Self::cells_in_bounds(
-                obj_min,
-                obj_max,
-                bounds_min,
-                level.cell_size,
-                level.grid_size,
-            )
-            .for_each(|(x, y, z)| level.increment_cell(x, y, z));
+        let mut old = self.footprint(self.spans[index as usize]);
+        let mut new = self.footprint(span);
+        self.spans[index as usize] = span;
+        let (old_fine, new_fine) = (old.pop().unwrap_or_default(), new.pop().unwrap_or_default());
+
+        let mut coarse_changed = Vec::new();
+        let mut level_offset = 0;
+        for ((level, old_cells), new_cells) in self.coarse_levels.iter_mut().zip(old).zip(new) {
+            for idx in cells_only_in(&old_cells, &new_cells) {
+                level.counts[idx] -= 1;
+                coarse_changed.push(level_offset + idx);
+            }
+            for idx in cells_only_in(&new_cells, &old_cells) {
+                level.increment_index(idx);
+                coarse_changed.push(level_offset + idx);
+            }
+            level_offset += level.counts.len();
         }
 
-        Self::cells_in_bounds(
-            obj_min,
-            obj_max,
-            bounds_min,
-            self.fine_level.cell_size,
-            self.fine_level.grid_size,
-        )
-        .for_each(|(x, y, z)| self.fine_level.add_object(x, y, z, obj_id));
+        let (left, entered) = (cells_only_in(&old_fine, &new_fine), cells_only_in(&new_fine, &old_fine));
+        left.iter().for_each(|&idx| self.fine_level.cells[idx].retain(|&id| id != index));
+        entered.iter().for_each(|&idx| self.fine_level.add_object_at(idx, index));
+
+        Some(GridUpdate {
+            coarse: cell_ranges(coarse_changed, 1),
+            fine: cell_ranges([left, entered].concat(), std::mem::size_of::<FineCellData>() as u64),
+        })
     }
 
-    fn world_to_cell_static(pos: &Vec3, bounds_min: Vec3, cell_size: f32) -> glam::UVec3 {
-        let rel_pos = *pos - bounds_min;
-        glam::UVec3::new(
-            (rel_pos.x / cell_size).floor().max(0.0) as u32,
-            (rel_pos.y / cell_size).floor().max(0.0) as u32,
-            (rel_pos.z / cell_size).floor().max(0.0) as u32,
-        )
+    fn world_to_cell_static(pos: &Vec3, bounds_min: Vec3, cell_size: Vec3) -> glam::UVec3 {
+        ((*pos - bounds_min) / cell_size).floor().max(Vec3::ZERO).as_uvec3()
     }
 
     pub fn to_gpu_buffers(&self) -> (GridMetadata, Vec<u8>, Vec<FineCellData>) {
@@ -325,35 +540,303 @@ impl HierarchicalGrid {
             bounds_min: self.bounds.min.to_array(),
             num_levels: GRID_LEVELS as u32,
             bounds_max: self.bounds.max.to_array(),
-            finest_cell_size: FINEST_CELL_SIZE,
+            _pad: 0.0,
+            finest_cell_size: self.fine_level.cell_size.to_array(),
+            _pad2: 0.0,
             grid_sizes,
         };
 
-        let all_counts: Vec<u8> = self
-            .coarse_levels
+        let all_counts: Vec<u8> = self.coarse_counts().collect();
+
+        let fine_cells: Vec<FineCellData> =
+            (0..self.fine_level.cells.len()).map(|idx| self.fine_level.cell_data(idx)).collect();
+
+        (metadata, all_counts, fine_cells)
+    }
+
+    /// Coarse counts as uploaded, levels back to back
+    fn coarse_counts(&self) -> impl Iterator<Item = u8> + '_ {
+        self.coarse_levels
             .iter()
-            .flat_map(|level| level.counts.iter().copied())
+            .flat_map(|level| level.counts.iter().map(|&count| count.min(u8::MAX as u32) as u8))
+    }
+
+    /// Bytes of the coarse counts buffer in `range`, e.g. from `GridUpdate::coarse`
+    pub fn coarse_bytes(&self, range: Range<u64>) -> Vec<u8> {
+        self.coarse_counts().skip(range.start as usize).take((range.end - range.start) as usize).collect()
+    }
+
+    /// Bytes of the fine cells buffer in `range`, which must cover whole cells as `GridUpdate::fine` does
+    pub fn fine_bytes(&self, range: Range<u64>) -> Vec<u8> {
+        let stride = std::mem::size_of::<FineCellData>() as u64;
+        let cells: Vec<FineCellData> = (range.start / stride..range.end / stride)
+            .map(|idx| self.fine_level.cell_data(idx as usize))
             .collect();
+        bytemuck::cast_slice(&cells).to_vec()
+    }
+}
 
-        let fine_cells: Vec<FineCellData> = self
-            .fine_level
-            .cells
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_valid(grid: &HierarchicalGrid) {
+        let extent = grid.bounds.max - grid.bounds.min;
+        assert!(extent.is_finite());
+        assert!(extent.cmpge(Vec3::splat(MIN_GRID_EXTENT)).all());
+        assert!(grid.fine_level.cell_size.is_finite() && grid.fine_level.cell_size.min_element() > 0.0);
+        assert!(grid.fine_level.grid_size.iter().all(|&n| n >= 1));
+        assert!(grid.coarse_levels.iter().all(|l| l.grid_size.iter().all(|&n| n >= 1)));
+    }
+
+    #[test]
+    fn test_single_box_grid() {
+        let boxes = [BoxData::new([0.0, 0.0, 0.0], [1.0, 1.0, 1.0], [1.0, 1.0, 1.0])];
+        let grid = HierarchicalGrid::build(&boxes, &[]);
+        assert_valid(&grid);
+        assert!(grid.fine_level.cells.iter().any(|c| c.contains(&0)));
+    }
+
+    #[test]
+    fn test_coincident_boxes_grid() {
+        let b = BoxData::new([2.0, 2.0, 2.0], [2.0, 2.0, 2.0], [1.0, 0.0, 0.0]);
+        let grid = HierarchicalGrid::build(&[b, b], &[]);
+        assert_valid(&grid);
+    }
+
+    #[test]
+    fn test_visible_cells_cull_behind_camera() {
+        let boxes: Vec<BoxData> = (0..10)
+            .map(|i| {
+                let z = i as f32 * 16.0;
+                BoxData::new([0.0, 0.0, z], [1.0, 1.0, z + 1.0], [1.0, 1.0, 1.0])
+            })
+            .collect();
+        let grid = HierarchicalGrid::build(&boxes, &[]);
+
+        let mut camera = Camera::for_scene("walls");
+        camera.position = Vec3::new(0.5, 0.5, 72.0);
+        let cell_of = |p: Vec3| {
+            let c = ((p - grid.bounds.min) / grid.fine_level.cell_size).floor();
+            (c.x as i32, c.y as i32, c.z as i32)
+        };
+
+        let visible = grid.visible_cells(&camera, 1.0);
+        assert!(visible.contains(&cell_of(Vec3::new(0.5, 0.5, 140.0))));
+        assert!(!visible.contains(&cell_of(Vec3::new(0.5, 0.5, 8.0))));
+    }
+
+    #[test]
+    fn test_tiny_scene_uses_single_cell() {
+        let boxes: Vec<BoxData> = (0..3)
+            .map(|i| {
+                let x = i as f32 * 40.0;
+                BoxData::new([x, 0.0, 0.0], [x + 1.0, 1.0, 1.0], [1.0, 1.0, 1.0])
+            })
+            .collect();
+
+        let grid = HierarchicalGrid::build(&boxes, &[]);
+        assert!(grid.is_single_cell());
+        assert_eq!(grid.fine_level.cells[0], vec![0, 1, 2]);
+
+        let (metadata, _, _) = grid.to_gpu_buffers();
+        assert_eq!(metadata.finest_cell_size, grid.fine_level.cell_size.to_array());
+    }
+
+    #[test]
+    fn test_large_scene_is_subdivided() {
+        let boxes: Vec<BoxData> = (0..10_000)
+            .map(|i| {
+                let (x, z) = ((i % 100) as f32 * 2.0, (i / 100) as f32 * 2.0);
+                BoxData::new([x, 0.0, z], [x + 1.0, 1.0, z + 1.0], [1.0, 1.0, 1.0])
+            })
+            .collect();
+
+        let grid = HierarchicalGrid::build(&boxes, &[]);
+        assert!(!grid.is_single_cell());
+        assert!(grid.fine_level.cell_size.cmple(Vec3::splat(FINEST_CELL_SIZE)).all());
+        assert!(grid.fine_level.cell_size.cmpgt(Vec3::splat(FINEST_CELL_SIZE * 0.5)).all());
+    }
+
+    #[test]
+    fn test_parallel_build_matches_serial() {
+        let scenes = [crate::scenes::create_stress_scene(5_000), crate::scenes::create_fractal_scene()];
+        for boxes in scenes {
+            let parallel = HierarchicalGrid::build_with(&boxes, &[], HierarchicalGrid::insert_parallel);
+            let (meta, coarse, fine) = parallel.to_gpu_buffers();
+            let serial = HierarchicalGrid::build_serial(&boxes, &[]);
+            let (serial_meta, serial_coarse, serial_fine) = serial.to_gpu_buffers();
+            assert_eq!(bytemuck::bytes_of(&meta), bytemuck::bytes_of(&serial_meta));
+            assert_eq!(coarse, serial_coarse);
+            assert_eq!(bytemuck::cast_slice::<_, u8>(&fine), bytemuck::cast_slice::<_, u8>(&serial_fine));
+        }
+    }
+
+    #[test]
+    fn test_cells_tile_bounds_exactly() {
+        let boxes: Vec<BoxData> = (0..20)
+            .map(|i| {
+                let x = i as f32 * 7.3;
+                BoxData::new([x, 0.0, -3.0], [x + 1.0, 50.0, 3.0], [1.0, 1.0, 1.0])
+            })
+            .collect();
+
+        let grid = HierarchicalGrid::build(&boxes, &[]);
+        let [nx, ny, nz] = grid.fine_level.grid_size;
+        let covered = grid.fine_level.cell_size * Vec3::new(nx as f32, ny as f32, nz as f32);
+        assert!((covered - (grid.bounds.max - grid.bounds.min)).abs().max_element() < 1e-3);
+    }
+
+    #[test]
+    fn test_thin_y_scene_gets_thin_cells() {
+        // Flat ground spanning 400 units with a row of short pillars: ~22 units tall with padding
+        let ground = BoxData::new([-200.0, -1.0, -200.0], [200.0, 0.0, 200.0], [0.5, 0.5, 0.5]);
+        let boxes: Vec<BoxData> = std::iter::once(ground)
+            .chain((0..10).map(|i| {
+                let x = i as f32 * 30.0 - 150.0;
+                BoxData::new([x, 0.0, 0.0], [x + 1.0, 19.0, 1.0], [1.0, 0.0, 0.0])
+            }))
+            .collect();
+
+        let grid = HierarchicalGrid::build(&boxes, &[]);
+        let cell = grid.fine_level.cell_size;
+        let extent = grid.bounds.max - grid.bounds.min;
+
+        assert_eq!(grid.fine_level.grid_size[1], (extent.y / FINEST_CELL_SIZE).ceil() as usize);
+        assert!(cell.y <= extent.y);
+        assert!(cell.y < cell.x, "thin axis should get thinner cells: {}", cell);
+        assert!(grid.fine_level.cells.iter().any(|c| c.contains(&0)));
+    }
+
+    #[test]
+    fn test_stats_for_separated_boxes() {
+        // Two columns of five boxes: ten primitives exceed SINGLE_CELL_MAX_PRIMITIVES, so the grid subdivides
+        let boxes: Vec<BoxData> = [-500.0, 500.0]
             .iter()
-            .map(|cell| {
-                let mut object_indices = [0u32; MAX_OBJECTS_PER_CELL];
-                cell.iter()
-                    .take(MAX_OBJECTS_PER_CELL)
-                    .enumerate()
-                    .for_each(|(i, &obj_id)| object_indices[i] = obj_id);
-
-                FineCellData {
-                    object_indices,
-                    count: cell.len() as u32,
-                    _pad: [0; 3],
-                }
+            .flat_map(|&x| {
+                (0..5).map(move |i| {
+                    let y = i as f32 * 2.0;
+                    BoxData::new([x, y, 0.0], [x + 1.0, y + 1.0, 1.0], [1.0, 1.0, 1.0])
+                })
             })
             .collect();
 
-        (metadata, all_counts, fine_cells)
+        let stats = HierarchicalGrid::build(&boxes, &[]).stats();
+
+        assert!(stats.fine_cells > 50);
+        assert!(stats.occupied_cells <= 4, "occupied {} of {}", stats.occupied_cells, stats.fine_cells);
+        assert_eq!(stats.max_primitives_per_cell, 5);
+        assert!(stats.empty_ratio > 0.9);
+        assert!(stats.coarse_cells > 0);
+    }
+
+    #[test]
+    fn test_box_cells_match_world_to_cell() {
+        let boxes: Vec<BoxData> = (0..10)
+            .map(|i| {
+                let x = i as f32 * 10.0 + 0.2;
+                BoxData::new([x, 0.2, 0.2], [x + 0.5, 0.7, 0.7], [1.0, 1.0, 1.0])
+            })
+            .collect();
+        let grid = HierarchicalGrid::build(&boxes, &[]);
+        let cells = grid.box_cells();
+
+        let target = 3u32;
+        let center = (Vec3::from_array(boxes[3].min) + Vec3::from_array(boxes[3].max)) * 0.5;
+        let expected = crate::math::world_to_cell_aniso(center, grid.bounds.min, grid.fine_level.cell_size);
+
+        let found: Vec<_> = cells.iter().filter(|(_, id)| *id == target).map(|(cell, _)| *cell).collect();
+        assert!(found.contains(&expected), "expected {:?} in {:?}", expected, found);
+        assert_eq!(cells.iter().filter(|(_, id)| *id < boxes.len() as u32).map(|(_, id)| *id).max(), Some(9));
+    }
+
+    #[test]
+    fn test_box_cells_skip_triangles() {
+        let boxes = vec![BoxData::new([0.0; 3], [1.0; 3], [1.0; 3])];
+        let triangles = vec![TriangleData::new([5.0, 0.0, 0.0], [6.0, 0.0, 0.0], [5.0, 1.0, 0.0], [0.0; 2], [1.0, 0.0], [0.0, 1.0], 0)];
+        let grid = HierarchicalGrid::build(&boxes, &triangles);
+
+        assert!(!grid.box_cells().is_empty());
+        assert!(grid.box_cells().iter().all(|(_, id)| *id == 0));
+    }
+
+    #[test]
+    fn test_update_primitive_moves_box_between_cells() {
+        // Corner boxes fix the bounds; box 2 starts in one cell and moves to a distant one
+        let unit = |x: f32, z: f32| BoxData::new([x, 0.0, z], [x + 1.0, 1.0, z + 1.0], [1.0; 3]);
+        let mut boxes: Vec<BoxData> = vec![unit(0.0, 0.0), unit(100.0, 100.0), unit(2.0, 2.0)];
+        boxes.extend((0..10).map(|i| unit(50.0, i as f32 * 10.0)));
+        let mut grid = HierarchicalGrid::build(&boxes, &[]);
+        let before = grid.fine_level.cells.clone();
+
+        boxes[2] = unit(90.0, 90.0);
+        let update = grid.update_primitive(2, &boxes[2]).unwrap();
+        let fine_cell = |b: &BoxData| {
+            let (min, size) = (grid.bounds.min, grid.fine_level.cell_size);
+            let cell = HierarchicalGrid::world_to_cell_static(&Vec3::from_array(b.min), min, size);
+            grid.fine_level.cell_index(cell.x as usize, cell.y as usize, cell.z as usize)
+        };
+        let (from, to) = (fine_cell(&unit(2.0, 2.0)), fine_cell(&boxes[2]));
+
+        assert!(!grid.fine_level.cells[from].contains(&2) && grid.fine_level.cells[from].contains(&0));
+        assert!(grid.fine_level.cells[to].contains(&2));
+        let changed: Vec<usize> = (0..before.len()).filter(|&i| before[i] != grid.fine_level.cells[i]).collect();
+        assert_eq!(changed, vec![from.min(to), from.max(to)]);
+
+        let stride = std::mem::size_of::<FineCellData>() as u64;
+        assert!(update.fine.iter().all(|r| r.end - r.start == stride));
+        assert!(update.fine.contains(&(from as u64 * stride..(from as u64 + 1) * stride)));
+        assert!(!update.coarse.is_empty());
+
+        // Same cells and counts as rebuilding from scratch
+        let rebuilt = HierarchicalGrid::build(&boxes, &[]);
+        assert_eq!(grid.fine_level.cells, rebuilt.fine_level.cells);
+        assert_eq!(grid.to_gpu_buffers().1, rebuilt.to_gpu_buffers().1);
+    }
+
+    #[test]
+    fn test_update_primitive_rejects_leaving_bounds() {
+        let boxes = vec![BoxData::new([0.0; 3], [1.0; 3], [1.0; 3])];
+        let mut grid = HierarchicalGrid::build(&boxes, &[]);
+        let cells = grid.fine_level.cells.clone();
+        assert!(grid.update_primitive(0, &BoxData::new([500.0; 3], [501.0; 3], [1.0; 3])).is_none());
+        assert!(grid.update_primitive(1, &boxes[0]).is_none());
+        assert_eq!(grid.fine_level.cells, cells);
+    }
+
+    #[test]
+    fn test_update_primitive_matches_build_with_full_cells() {
+        // More boxes than MAX_OBJECTS_PER_CELL stacked in one cell, so its GPU copy is full and its coarse
+        // counts saturate; moving stacked boxes out and back must leave the same buffers as a rebuild
+        let unit = |x: f32, z: f32| BoxData::new([x, 0.0, z], [x + 1.0, 1.0, z + 1.0], [1.0; 3]);
+        let mut boxes: Vec<BoxData> = vec![unit(0.0, 0.0), unit(100.0, 100.0)];
+        boxes.extend((0..MAX_OBJECTS_PER_CELL + 2).map(|_| unit(2.0, 2.0)));
+        let mut grid = HierarchicalGrid::build(&boxes, &[]);
+        let (_, mut coarse, fine) = grid.to_gpu_buffers();
+        let mut fine: Vec<u8> = bytemuck::cast_slice(&fine).to_vec();
+
+        for (index, target) in [(2, unit(90.0, 90.0)), (3, unit(90.0, 90.0)), (2, unit(2.0, 2.0))] {
+            boxes[index as usize] = target;
+            let update = grid.update_primitive(index, &target).unwrap();
+            for r in update.coarse {
+                coarse[r.start as usize..r.end as usize].copy_from_slice(&grid.coarse_bytes(r));
+            }
+            for r in update.fine {
+                fine[r.start as usize..r.end as usize].copy_from_slice(&grid.fine_bytes(r));
+            }
+
+            let (_, rebuilt_coarse, rebuilt_fine) = HierarchicalGrid::build(&boxes, &[]).to_gpu_buffers();
+            assert_eq!(coarse, rebuilt_coarse);
+            assert!(fine == bytemuck::cast_slice::<_, u8>(&rebuilt_fine), "fine cells differ after moving box {}", index);
+        }
+    }
+
+    #[test]
+    fn test_pad_to_min_extent_keeps_center() {
+        let bounds = AABB::new(Vec3::new(4.0, 0.0, -1.0), Vec3::new(4.0, 100.0, 1.0));
+        let padded = pad_to_min_extent(bounds, 10.0);
+        assert_eq!(padded.center(), bounds.center());
+        assert_eq!(padded.min, Vec3::new(-1.0, 0.0, -5.0));
+        assert_eq!(padded.max, Vec3::new(9.0, 100.0, 5.0));
     }
 }
@@ -0,0 +1,51 @@
+// headless.rs - Offscreen render-to-file (`ray-tracer render`)
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::core::{DisplayContext, GpuContext, Layer, RayTracingLayerBuilder, WinitController};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Simulated time step between rendered frames
+const FRAME_DELTA: f32 = 1.0 / 60.0;
+
+/// Render `frames` frames of a scene without a window and return the final RGBA8 frame; `stress_count` sizes
+/// the `stress` scene
+pub fn render_frames(
+    gpu: Arc<GpuContext>,
+    scene: &str,
+    width: u32,
+    height: u32,
+    frames: usize,
+    stress_count: usize,
+) -> Result<Vec<u8>> {
+    let context = DisplayContext::new(width, height);
+    let mask = vec![true; context.pixel_count()];
+    let idle = WinitController::new();
+
+    let layer = pollster::block_on(RayTracingLayerBuilder::new(gpu, scene, width, height).stress_count(stress_count).build())?;
+    let last = (1..frames).fold(layer, |layer: Box<dyn Layer>, _| {
+        std::hint::black_box(layer.render(&mask, &context));
+        layer.update(FRAME_DELTA, &idle)
+    });
+
+    Ok(last.render(&mask, &context).pixels)
+}
+
+/// Write an RGBA8 buffer as PNG
+pub fn write_png(path: &Path, pixels: &[u8], width: u32, height: u32) -> Result<()> {
+    image::save_buffer(path, pixels, width, height, image::ColorType::Rgba8)?;
+    Ok(())
+}
+
+/// Render a scene headless and save the final frame to `out`
+pub fn run_render(scene: &str, width: u32, height: u32, frames: usize, stress_count: usize, out: &Path) -> Result<()> {
+    let gpu = Arc::new(pollster::block_on(GpuContext::new())?);
+    println!("Rendering {} at {}x{} ({} frames)...", scene, width, height, frames);
+
+    let pixels = render_frames(gpu, scene, width, height, frames, stress_count)?;
+    write_png(out, &pixels, width, height)?;
+
+    println!("Wrote {}", out.display());
+    Ok(())
+}
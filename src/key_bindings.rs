@@ -0,0 +1,214 @@
+// key_bindings.rs - Remappable movement keys for the camera (e.g. for non-QWERTY layouts)
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::camera::MovementState;
+use crate::core::{Button, Controller};
+
+/// Button bound to each camera movement; speed (+/-) stays fixed
+///
+/// Loaded from TOML, where missing actions keep their WASD/QE/Space/Shift/PageUp/PageDown/F/Ctrl
+/// default, leaving the arrow keys free to remap: `forward = "KeyZ"`. Loading rejects a button bound
+/// to two actions, defaults included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    pub forward: Button,
+    pub back: Button,
+    pub strafe_left: Button,
+    pub strafe_right: Button,
+    pub up: Button,
+    pub down: Button,
+    pub yaw_left: Button,
+    pub yaw_right: Button,
+    /// Look up/down; only the layer camera (`RayTracingLayer`) pitches from the keyboard
+    pub pitch_up: Button,
+    pub pitch_down: Button,
+    /// Fit the whole scene in view (layer camera only)
+    pub frame_scene: Button,
+    /// Held to move faster
+    pub sprint: Button,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            forward: Button::KeyW,
+            back: Button::KeyS,
+            strafe_left: Button::KeyA,
+            strafe_right: Button::KeyD,
+            up: Button::Space,
+            down: Button::Shift,
+            yaw_left: Button::KeyQ,
+            yaw_right: Button::KeyE,
+            pitch_up: Button::PageUp,
+            pitch_down: Button::PageDown,
+            frame_scene: Button::KeyF,
+            sprint: Button::Control,
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).context(format!("Failed to read key bindings: {:?}", path))?;
+        let bindings: Self = toml::from_str(&text).context(format!("Failed to parse key bindings: {:?}", path))?;
+        if let Some((button, first, second)) = bindings.duplicate() {
+            anyhow::bail!("Key bindings {:?} bind {:?} to both {} and {}; rebind one of them", path, button, first, second);
+        }
+        Ok(bindings)
+    }
+
+    /// Every action with its button, in declaration order
+    fn actions(&self) -> [(&'static str, Button); 12] {
+        [
+            ("forward", self.forward),
+            ("back", self.back),
+            ("strafe_left", self.strafe_left),
+            ("strafe_right", self.strafe_right),
+            ("up", self.up),
+            ("down", self.down),
+            ("yaw_left", self.yaw_left),
+            ("yaw_right", self.yaw_right),
+            ("pitch_up", self.pitch_up),
+            ("pitch_down", self.pitch_down),
+            ("frame_scene", self.frame_scene),
+            ("sprint", self.sprint),
+        ]
+    }
+
+    /// First button bound to two actions, with both action names
+    pub fn duplicate(&self) -> Option<(Button, &'static str, &'static str)> {
+        let actions = self.actions();
+        actions.iter().enumerate().find_map(|(i, &(first, button))| {
+            actions[i + 1..].iter().find(|(_, other)| *other == button).map(|&(second, _)| (button, first, second))
+        })
+    }
+
+    /// Movement held on `controller` under these bindings
+    pub fn movement(&self, controller: &dyn Controller) -> MovementState {
+        MovementState {
+            forward: controller.is_down(self.forward),
+            backward: controller.is_down(self.back),
+            left: controller.is_down(self.strafe_left),
+            right: controller.is_down(self.strafe_right),
+            up: controller.is_down(self.up),
+            down: controller.is_down(self.down),
+            rotate_left: controller.is_down(self.yaw_left),
+            rotate_right: controller.is_down(self.yaw_right),
+            sprint: controller.is_down(self.sprint),
+        }
+    }
+
+    /// Whether `button` drives any movement or sprint, i.e. whether `apply` would act on it
+    pub fn moves(&self, button: Button) -> bool {
+        [
+            self.forward,
+            self.back,
+            self.strafe_left,
+            self.strafe_right,
+            self.up,
+            self.down,
+            self.yaw_left,
+            self.yaw_right,
+            self.sprint,
+        ]
+        .contains(&button)
+    }
+
+    /// Press or release every movement bound to `button`; unbound buttons are ignored
+    pub fn apply(&self, movement: &mut MovementState, button: Button, pressed: bool) {
+        let MovementState { forward, backward, left, right, up, down, rotate_left, rotate_right, sprint } = movement;
+        [
+            (self.forward, forward),
+            (self.back, backward),
+            (self.strafe_left, left),
+            (self.strafe_right, right),
+            (self.up, up),
+            (self.down, down),
+            (self.yaw_left, rotate_left),
+            (self.yaw_right, rotate_right),
+            (self.sprint, sprint),
+        ]
+        .into_iter()
+        .filter(|(bound, _)| *bound == button)
+        .for_each(|(_, held)| *held = pressed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_file_keeps_defaults() {
+        let bindings: KeyBindings = toml::from_str("forward = \"KeyUp\"\nback = \"KeyDown\"").unwrap();
+        assert_eq!(bindings.forward, Button::KeyUp);
+        assert_eq!(bindings.back, Button::KeyDown);
+        assert_eq!(bindings.strafe_left, KeyBindings::default().strafe_left);
+        assert!(toml::from_str::<KeyBindings>("forwrd = \"KeyUp\"").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_bindings_rejected() {
+        assert_eq!(KeyBindings::default().duplicate(), None);
+        let clash: KeyBindings = toml::from_str("forward = \"KeyF\"").unwrap();
+        assert_eq!(clash.duplicate(), Some((Button::KeyF, "forward", "frame_scene")));
+
+        let path = std::env::temp_dir().join(format!("ray_tracer_duplicate_bindings_{}.toml", std::process::id()));
+        std::fs::write(&path, "forward = \"KeyF\"").unwrap();
+        let err = KeyBindings::load(&path).unwrap_err().to_string();
+        std::fs::remove_file(&path).ok();
+        assert!(err.contains("frame_scene"), "{}", err);
+    }
+
+    #[test]
+    fn test_load_accepts_arrow_key_movement() {
+        let path = std::env::temp_dir().join(format!("ray_tracer_arrow_bindings_{}.toml", std::process::id()));
+        std::fs::write(&path, "forward = \"KeyUp\"\nback = \"KeyDown\"\nstrafe_left = \"KeyLeft\"\nstrafe_right = \"KeyRight\"")
+            .unwrap();
+        let bindings = KeyBindings::load(&path);
+        std::fs::remove_file(&path).ok();
+
+        let bindings = bindings.unwrap();
+        assert_eq!((bindings.forward, bindings.strafe_right), (Button::KeyUp, Button::KeyRight));
+    }
+
+    #[test]
+    fn test_azerty_and_remapped_sprint() {
+        let bindings: KeyBindings =
+            toml::from_str("forward = \"KeyZ\"\nstrafe_left = \"KeyQ\"\nyaw_left = \"KeyA\"\ndown = \"Control\"\nsprint = \"Shift\"")
+                .unwrap();
+        assert_eq!(bindings.duplicate(), None);
+        let mut movement = MovementState::default();
+
+        bindings.apply(&mut movement, Button::KeyZ, true);
+        bindings.apply(&mut movement, Button::Control, true);
+        assert!(movement.forward && movement.down && !movement.sprint);
+        bindings.apply(&mut movement, Button::Shift, true);
+        assert!(movement.sprint);
+    }
+
+    #[test]
+    fn test_moves_matches_movement_bindings() {
+        let bindings = KeyBindings { forward: Button::KeyZ, ..KeyBindings::default() };
+        assert!(bindings.moves(Button::KeyZ) && bindings.moves(Button::Control));
+        assert!(!bindings.moves(Button::KeyW));
+        assert!(!bindings.moves(bindings.frame_scene));
+    }
+
+    #[test]
+    fn test_apply_moves_only_bound_actions() {
+        let bindings = KeyBindings { forward: Button::KeyUp, ..KeyBindings::default() };
+        let mut movement = MovementState::default();
+
+        bindings.apply(&mut movement, Button::KeyW, true);
+        assert!(!movement.forward);
+        bindings.apply(&mut movement, Button::KeyUp, true);
+        assert!(movement.forward);
+        bindings.apply(&mut movement, Button::KeyUp, false);
+        assert!(!movement.forward);
+    }
+}
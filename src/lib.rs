@@ -1,15 +1,31 @@
+pub mod accumulation;
 pub mod camera;
+pub mod camera_path;
 pub mod cli;
 pub mod core;
+pub mod cpu_render;
 pub mod demo;
+pub mod dirty;
+pub mod editor;
+pub mod exposure;
+pub mod fog;
 pub mod frame;
 pub mod grid;
 pub mod grid_triangles;
+pub mod gpu_timing;
+pub mod headless;
+pub mod key_bindings;
+pub mod lighting;
 pub mod loaders;
 pub mod math;
+pub mod profile;
+pub mod render_settings;
+pub mod resolution;
 pub mod renderer;
 pub mod scenes;
+pub mod sky;
 pub mod types;
+pub mod views;
 pub mod window;
 
 // Re-export scene functions for backward compatibility
@@ -0,0 +1,126 @@
+// lighting.rs - Directional light presets shared by the GPU and CPU renderers
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use crate::types::LightData;
+
+/// A light at infinity shining along `direction`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectionalLight {
+    /// Direction the light travels (towards the scene), normalized
+    pub direction: Vec3,
+    pub intensity: f32,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, intensity: f32) -> Self {
+        Self { direction: direction.normalize(), intensity }
+    }
+
+    /// Lambert term for a surface with `normal`
+    pub fn diffuse(&self, normal: Vec3) -> f32 {
+        normal.dot(-self.direction).max(0.0) * self.intensity
+    }
+
+    /// White light-list entry for the shader
+    pub fn light_data(&self) -> LightData {
+        LightData::directional(self.direction, [1.0; 3], self.intensity)
+    }
+}
+
+/// Key and fill lights; a zero-intensity fill leaves a single sun
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightingConfig {
+    pub key: DirectionalLight,
+    pub fill: DirectionalLight,
+}
+
+impl LightingConfig {
+    /// Summed diffuse contribution of both lights
+    pub fn diffuse(&self, normal: Vec3) -> f32 {
+        self.key.diffuse(normal) + self.fill.diffuse(normal)
+    }
+
+    /// Shader light list: the key, plus the fill when it is lit
+    pub fn lights(&self) -> Vec<LightData> {
+        let fill = (self.fill.intensity > 0.0).then_some(self.fill);
+        std::iter::once(self.key).chain(fill).map(|light| light.light_data()).collect()
+    }
+}
+
+impl Default for LightingConfig {
+    /// Single overhead sun, the renderer's original lighting
+    fn default() -> Self {
+        Self {
+            key: DirectionalLight::new(Vec3::new(0.5, -1.0, 0.3), 0.7),
+            fill: DirectionalLight::new(Vec3::NEG_Y, 0.0),
+        }
+    }
+}
+
+/// Product-style lighting: a bright key from above front-left and a dim fill from the opposite side
+pub fn studio() -> LightingConfig {
+    LightingConfig {
+        key: DirectionalLight::new(Vec3::new(0.6, -0.8, -0.6), 0.8),
+        fill: DirectionalLight::new(Vec3::new(-0.7, -0.3, 0.4), 0.3),
+    }
+}
+
+/// Lighting selectable with `--lighting`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LightingPreset {
+    #[default]
+    Default,
+    Studio,
+}
+
+impl LightingPreset {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::Default),
+            "studio" => Some(Self::Studio),
+            _ => None,
+        }
+    }
+
+    pub fn config(self) -> LightingConfig {
+        match self {
+            Self::Default => LightingConfig::default(),
+            Self::Studio => studio(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_studio_has_distinct_key_and_fill() {
+        let lighting = studio();
+        assert!(lighting.key.direction.cross(lighting.fill.direction).length() > 0.1);
+        assert!(lighting.key.intensity > lighting.fill.intensity);
+        assert!(lighting.fill.intensity > 0.0);
+    }
+
+    #[test]
+    fn test_default_matches_original_sun() {
+        let up = LightingConfig::default().diffuse(Vec3::Y);
+        assert!((up - 0.7 / Vec3::new(0.5, -1.0, 0.3).length()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_light_lists() {
+        let lights = LightingConfig::default().lights();
+        assert_eq!(lights.len(), 1);
+        assert_eq!(lights[0].light_type, crate::types::LightType::Directional as u32);
+        assert_eq!(studio().lights().len(), 2);
+    }
+
+    #[test]
+    fn test_preset_names() {
+        assert_eq!(LightingPreset::from_name("studio"), Some(LightingPreset::Studio));
+        assert_eq!(LightingPreset::from_name("default").map(LightingPreset::config), Some(LightingConfig::default()));
+        assert_eq!(LightingPreset::from_name("disco"), None);
+    }
+}
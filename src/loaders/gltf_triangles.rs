@@ -9,6 +9,8 @@ pub struct GltfScene {
     pub triangles: Vec<TriangleData>,
     pub materials: Vec<MaterialData>,
     pub textures: Vec<TextureData>,
+    /// Features present in the file that the loader ignores
+    pub warnings: Vec<String>,
 }
 
 /// Texture data loaded from glTF
@@ -20,12 +22,27 @@ pub struct TextureData {
 
 /// Loads a glTF file and extracts triangles with UVs and materials
 pub fn load_gltf_triangles(path: impl AsRef<Path>) -> Result<GltfScene> {
+    load_gltf_triangles_with_budget(path, None)
+}
+
+/// Like `load_gltf_triangles`, but fails before extraction if the model exceeds `max_triangles`
+pub fn load_gltf_triangles_with_budget(path: impl AsRef<Path>, max_triangles: Option<usize>) -> Result<GltfScene> {
     let path = path.as_ref();
     println!("Loading glTF file for triangle rendering: {:?}", path);
 
     let (gltf, buffers, images) = gltf::import(path)
         .context(format!("Failed to load glTF file: {:?}", path))?;
 
+    if let Some(max) = max_triangles {
+        let count = count_gltf_triangles(&gltf);
+        if count > max {
+            anyhow::bail!(
+                "glTF file {:?} has {} triangles, exceeding the budget of {} (raise --max-triangles or simplify the model)",
+                path, count, max
+            );
+        }
+    }
+
     println!("glTF loaded:");
     println!("  Scenes: {}", gltf.scenes().count());
     println!("  Nodes: {}", gltf.nodes().count());
@@ -33,6 +50,11 @@ pub fn load_gltf_triangles(path: impl AsRef<Path>) -> Result<GltfScene> {
     println!("  Materials: {}", gltf.materials().count());
     println!("  Images: {}", images.len());
 
+    let warnings = unsupported_features(&gltf);
+    for warning in &warnings {
+        eprintln!("Warning: unsupported glTF feature ignored: {}", warning);
+    }
+
     let mut all_triangles = Vec::new();
     let mut materials = Vec::new();
     let mut textures = Vec::new();
@@ -172,9 +194,66 @@ pub fn load_gltf_triangles(path: impl AsRef<Path>) -> Result<GltfScene> {
         triangles: all_triangles,
         materials,
         textures,
+        warnings,
     })
 }
 
+/// Counts triangles the scene will produce (including instanced meshes) without reading buffers
+pub fn count_gltf_triangles(document: &gltf::Document) -> usize {
+    document
+        .scenes()
+        .flat_map(|scene| scene.nodes())
+        .map(|node| count_node_triangles(&node))
+        .sum()
+}
+
+fn count_node_triangles(node: &gltf::Node) -> usize {
+    let own = node.mesh().map_or(0, |mesh| {
+        mesh.primitives()
+            .map(|primitive| {
+                primitive
+                    .indices()
+                    .or_else(|| primitive.get(&gltf::Semantic::Positions))
+                    .map_or(0, |accessor| accessor.count() / 3)
+            })
+            .sum()
+    });
+
+    own + node.children().map(|child| count_node_triangles(&child)).sum::<usize>()
+}
+
+/// Collects features the triangle loader does not handle, so incorrect output isn't silent
+pub fn unsupported_features(document: &gltf::Document) -> Vec<String> {
+    let extensions = document
+        .extensions_used()
+        .map(|name| format!("extension {}", name));
+
+    let skins = (document.skins().count() > 0)
+        .then(|| format!("{} skin(s)", document.skins().count()));
+
+    let morph_targets = document
+        .meshes()
+        .flat_map(|mesh| mesh.primitives())
+        .filter(|primitive| primitive.morph_targets().count() > 0)
+        .count();
+    let morphs = (morph_targets > 0)
+        .then(|| format!("morph targets on {} primitive(s)", morph_targets));
+
+    let animations = (document.animations().count() > 0)
+        .then(|| format!("{} animation(s)", document.animations().count()));
+
+    // Camera control is programmatic only; glTF cameras are never used
+    let cameras = (document.cameras().count() > 0)
+        .then(|| format!("{} camera(s)", document.cameras().count()));
+
+    extensions
+        .chain(skins)
+        .chain(morphs)
+        .chain(animations)
+        .chain(cameras)
+        .collect()
+}
+
 /// Recursively processes glTF nodes to extract triangles
 fn process_node_triangles(
     node: &gltf::Node,
@@ -222,6 +301,12 @@ fn process_mesh_triangles(
             })
             .collect();
 
+        // Vertex normals for smooth shading, carried through the node transform; flat shading when absent
+        let normal_matrix = glam::Mat3::from_mat4(*transform).inverse().transpose();
+        let normals: Option<Vec<Vec3>> = reader
+            .read_normals()
+            .map(|normals| normals.map(|n| (normal_matrix * Vec3::from_array(n)).normalize_or_zero()).collect());
+
         // Extract UVs (texture coordinates)
         let uvs: Vec<[f32; 2]> = if let Some(uv_reader) = reader.read_tex_coords(0) {
             uv_reader.into_f32().collect()
@@ -236,47 +321,32 @@ fn process_mesh_triangles(
             .filter(|&i| i < material_count)  // Validate against loaded materials
             .unwrap_or(0) as u32;
 
-        // Extract indices and create triangles
-        if let Some(indices) = reader.read_indices() {
-            let indices: Vec<u32> = indices.into_u32().collect();
-
-            for tri_indices in indices.chunks(3) {
-                if tri_indices.len() == 3 {
-                    let i0 = tri_indices[0] as usize;
-                    let i1 = tri_indices[1] as usize;
-                    let i2 = tri_indices[2] as usize;
-
-                    let triangle = TriangleData::new(
-                        vertices[i0].to_array(),
-                        vertices[i1].to_array(),
-                        vertices[i2].to_array(),
-                        uvs[i0],
-                        uvs[i1],
-                        uvs[i2],
-                        material_id,
-                    );
-
-                    triangles.push(triangle);
-                }
-            }
-        } else {
-            // No indices - treat as triangle list
-            for i in (0..vertices.len()).step_by(3) {
-                if i + 2 < vertices.len() {
-                    let triangle = TriangleData::new(
-                        vertices[i].to_array(),
-                        vertices[i + 1].to_array(),
-                        vertices[i + 2].to_array(),
-                        uvs[i],
-                        uvs[i + 1],
-                        uvs[i + 2],
-                        material_id,
-                    );
-
-                    triangles.push(triangle);
-                }
-            }
+        // Non-indexed primitives are an implicit 0..n index list
+        let indices: Vec<u32> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..vertices.len() as u32).collect(),
+        };
+
+        if let Some(&bad) = indices.iter().find(|&&i| i as usize >= vertices.len()) {
+            anyhow::bail!("Index {} out of range for {} vertices", bad, vertices.len());
         }
+
+        triangles.extend(indices.chunks_exact(3).map(|tri| {
+            let [i0, i1, i2] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+            let triangle = TriangleData::new(
+                vertices[i0].to_array(),
+                vertices[i1].to_array(),
+                vertices[i2].to_array(),
+                uvs[i0],
+                uvs[i1],
+                uvs[i2],
+                material_id,
+            );
+            match &normals {
+                Some(n) => triangle.with_normals(n[i0].to_array(), n[i1].to_array(), n[i2].to_array()),
+                None => triangle,
+            }
+        }));
     }
 
     Ok(())
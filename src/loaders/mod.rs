@@ -1,5 +1,7 @@
 pub mod gltf;
 pub mod gltf_triangles;
+pub mod skybox;
 
 pub use gltf::{load_gltf_file, load_gltf_with_animation, AnimationData};
-pub use gltf_triangles::{load_gltf_triangles, GltfScene, TextureData};
+pub use gltf_triangles::{load_gltf_triangles, load_gltf_triangles_with_budget, GltfScene, TextureData};
+pub use skybox::load_skybox;
@@ -0,0 +1,18 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use super::TextureData;
+
+/// Loads an equirectangular PNG or Radiance HDR image as RGBA8 (HDR values are clamped to 1.0)
+pub fn load_skybox(path: impl AsRef<Path>) -> Result<TextureData> {
+    let path = path.as_ref();
+    let image = image::open(path)
+        .with_context(|| format!("Failed to load skybox {}", path.display()))?
+        .to_rgba8();
+
+    Ok(TextureData {
+        width: image.width(),
+        height: image.height(),
+        data: image.into_raw(),
+    })
+}
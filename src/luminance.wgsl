@@ -0,0 +1,58 @@
+// Auto-exposure metering: log-luminance sum of a strided sample grid of the ray traced image,
+// reduced in one workgroup so only a few bytes are read back (mirrors exposure::average_luminance)
+
+@group(0) @binding(0)
+var image: texture_2d<f32>;
+
+// Mirrors exposure::LuminanceSum
+struct LuminanceSum {
+    log_sum: f32,
+    samples: u32,
+};
+
+@group(0) @binding(1)
+var<storage, read_write> result: LuminanceSum;
+
+const WORKGROUP_SIZE: u32 = 256u;
+// Mirrors exposure::READBACK_STRIDE
+const STRIDE: u32 = 8u;
+// Mirrors exposure::LUMINANCE_EPSILON
+const LUMINANCE_EPSILON: f32 = 1e-4;
+
+var<workgroup> partial_sums: array<f32, WORKGROUP_SIZE>;
+var<workgroup> partial_counts: array<u32, WORKGROUP_SIZE>;
+
+// Rec. 709 luminance (mirrors bloom::luminance)
+fn luminance(rgb: vec3<f32>) -> f32 {
+    return dot(rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+@compute @workgroup_size(256, 1, 1)
+fn main(@builtin(local_invocation_index) index: u32) {
+    let samples = (textureDimensions(image) + vec2<u32>(STRIDE - 1u)) / STRIDE;
+
+    var sum = 0.0;
+    var count = 0u;
+    for (var i = index; i < samples.x * samples.y; i += WORKGROUP_SIZE) {
+        let texel = textureLoad(image, vec2<u32>(i % samples.x, i / samples.x) * STRIDE, 0);
+        sum += log(luminance(texel.rgb) + LUMINANCE_EPSILON);
+        count += 1u;
+    }
+    partial_sums[index] = sum;
+    partial_counts[index] = count;
+    workgroupBarrier();
+
+    // Tree reduction over the workgroup
+    for (var offset = WORKGROUP_SIZE / 2u; offset > 0u; offset /= 2u) {
+        if index < offset {
+            partial_sums[index] += partial_sums[index + offset];
+            partial_counts[index] += partial_counts[index + offset];
+        }
+        workgroupBarrier();
+    }
+
+    if index == 0u {
+        result.log_sum = partial_sums[0];
+        result.samples = partial_counts[0];
+    }
+}
@@ -1,72 +1,386 @@
-use ray_tracer::{camera, renderer, cli, frame, window};
+use ray_tracer::{camera, camera_path, renderer, cli, cpu_render, editor, frame, headless, profile, window};
 
 use clap::Parser;
+use glam::Vec3;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use winit::{
     application::ApplicationHandler,
     event::*,
-    event_loop::{ActiveEventLoop, EventLoop},
-    keyboard::{KeyCode, PhysicalKey},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
     window::{Window as WinitWindow, WindowId},
 };
-use camera::Camera;
-use renderer::RayTracer;
-use frame::{FrameIterator, FrameInfo};
+use camera::{Camera, CameraBookmark, MovementState};
+use camera_path::CameraPath;
+use renderer::{RayTracer, SceneOptions};
+use frame::{FrameIterator, FrameInfo, target_fps};
+use ray_tracer::core::{GamepadController, WinitController};
+use ray_tracer::editor::AutoSave;
+use ray_tracer::fog::DEFAULT_FOG_COLOR;
+use ray_tracer::key_bindings::KeyBindings;
+use ray_tracer::resolution::Resolution;
+use ray_tracer::scenes::{DEFAULT_LOD_BUDGET, LOD_REGENERATE_DISTANCE};
+use ray_tracer::views::{window_cameras, SharedCamera, Views};
 use window::Window;
 
 const FPS_UPDATE_INTERVAL: f32 = 1.0;
-const INITIAL_WINDOW_WIDTH: u32 = 600;
-const INITIAL_WINDOW_HEIGHT: u32 = 600;
+const BOOKMARK_SLOTS: usize = 9;
+const SCROLL_PIXELS_PER_STEP: f32 = 50.0;
+/// Seconds between keyframes recorded with K
+const KEYFRAME_SPACING: f32 = 2.0;
+const DEFAULT_CAMERA_PATH_FILE: &str = "camera_path.json";
+const CPU_PREVIEW_FILE: &str = "cpu_preview.png";
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+/// One OS window rendering its own scene from its own (or the shared) camera
+struct View {
+    window: Window,
+    raytracer: RayTracer,
+    camera: SharedCamera,
+    cursor_position: Option<(f64, f64)>,
+    /// Camera position the scene's level of detail was generated around
+    lod_origin: Vec3,
+    /// Zero-size window; drawing is skipped until the next non-zero resize
+    minimized: bool,
+}
+
+impl View {
+    fn draw(&mut self, frame: &FrameInfo) {
+        if self.minimized {
+            return;
+        }
+        let camera = self.camera.lock().unwrap();
+        if let Err(e) = self.window.draw(&mut self.raytracer, &camera, frame) {
+            eprintln!("Render error: {}", e);
+        }
+    }
+
+    /// Write the window's boxes, including live edits, back to JSON
+    fn save_scene(&self) {
+        match self.raytracer.save_scene() {
+            Ok(path) => println!("Saved scene to {}", path.display()),
+            Err(e) => eprintln!("{:#}", e),
+        }
+    }
+
+    /// Rebuild the renderer when the UI picked another scene; a synced camera keeps its pose,
+    /// otherwise a camera saved in the new scene file replaces the default view and `fov`
+    fn reload_if_requested(&mut self, fov: f32, no_ui: bool, sync_camera: bool, options: &SceneOptions) {
+        if !self.raytracer.take_reload_request() {
+            return;
+        }
+        let new_scene = self.raytracer.get_current_scene();
+        if !no_ui {
+            println!("Reloading scene: {}", new_scene);
+        }
+
+        let camera = (!sync_camera).then(|| {
+            Camera::for_scene(&new_scene).with_fov(fov).with_controls_of(&self.camera.lock().unwrap())
+        });
+        let origin = camera.as_ref().map_or_else(|| self.camera.lock().unwrap().position, |camera| camera.position);
+        if self.rebuild(&new_scene, no_ui, options, origin) {
+            if let Some(mut camera) = camera {
+                if let Some(pose) = self.raytracer.scene_camera() {
+                    camera.restore(&pose);
+                }
+                *self.camera.lock().unwrap() = camera;
+            }
+        }
+    }
+
+    /// Regenerate an LOD fractal around the camera once it has moved far from where the scene was built
+    fn refresh_lod(&mut self, no_ui: bool, options: &SceneOptions) {
+        let position = self.camera.lock().unwrap().position;
+        let scene = self.raytracer.get_current_scene();
+        let far_moved = position.distance(self.lod_origin) >= LOD_REGENERATE_DISTANCE;
+        if options.lod_budget.is_none() || scene != "fractal" || !far_moved {
+            return;
+        }
+        if !no_ui {
+            println!("Regenerating fractal detail around {:?}", position);
+        }
+        self.raytracer.refine_lod(position, options);
+        self.lod_origin = position;
+    }
+
+    /// Replace the renderer with a fresh load of `scene`, refining any LOD around `origin`
+    fn rebuild(&mut self, scene: &str, no_ui: bool, options: &SceneOptions, origin: Vec3) -> bool {
+        let options = SceneOptions { lod_origin: origin, ..options.clone() };
+        match pollster::block_on(RayTracer::new(self.window.inner().clone(), scene, no_ui, &options)) {
+            Ok(new_raytracer) => {
+                self.raytracer = new_raytracer;
+                self.lod_origin = origin;
+                true
+            }
+            Err(e) => {
+                eprintln!("Failed to reload scene: {}", e);
+                false
+            }
+        }
+    }
+}
+
 struct App {
-    window: Option<Window>,
-    raytracer: Option<RayTracer>,
-    camera: Camera,
+    views: Views<View>,
+    /// Scenes to open, one window each
+    scenes: Vec<String>,
+    /// Starting pose from `--camera`, applied to every window
+    start_pose: Option<CameraBookmark>,
+    key_bindings: KeyBindings,
+    /// Window driven by the gamepad
+    focused: Option<WindowId>,
+    /// All windows share one camera, moved only from the focused window
+    sync_camera: bool,
+    gamepad: GamepadController,
     frames: FrameIterator,
-    frame_count: u32,
-    fps: f32,
+    /// Frame drawn by every window until the next redraw request
+    frame: Option<FrameInfo>,
     fps_update_timer: f32,
-    cursor_position: Option<(f64, f64)>,
+    window_size: (u32, u32),
+    /// Frame rate cap while focused; None renders as fast as possible
+    fps: Option<f32>,
+    idle_fps: f32,
+    /// Whether any window has focus; unfocused rendering drops to `idle_fps`
+    has_focus: bool,
+    last_redraw: Instant,
+    scene_options: SceneOptions,
+    /// Field of view in radians, reapplied on scene reload
+    fov: f32,
+    modifiers: ModifiersState,
+    bookmarks: [Option<CameraBookmark>; BOOKMARK_SLOTS],
+    camera_path: CameraPath,
+    camera_path_file: PathBuf,
+    /// Window playing the flythrough and the frame time playback started
+    playback: Option<(WindowId, f32)>,
+    /// Periodic backup of edited scenes (`--autosave-interval`)
+    autosave: Option<AutoSave>,
     no_ui: bool,
     should_exit: bool,
 }
 
+/// Maps Digit1..Digit9 to bookmark slots 0..8
+fn bookmark_slot(keycode: KeyCode) -> Option<usize> {
+    match keycode {
+        KeyCode::Digit1 => Some(0),
+        KeyCode::Digit2 => Some(1),
+        KeyCode::Digit3 => Some(2),
+        KeyCode::Digit4 => Some(3),
+        KeyCode::Digit5 => Some(4),
+        KeyCode::Digit6 => Some(5),
+        KeyCode::Digit7 => Some(6),
+        KeyCode::Digit8 => Some(7),
+        KeyCode::Digit9 => Some(8),
+        _ => None,
+    }
+}
+
 impl App {
-    fn new(no_ui: bool) -> Self {
+    fn new(config: cli::RayTracerConfig) -> Self {
+        let fov = config.fov.to_radians();
+        let camera_path_file = config.camera_path.unwrap_or_else(|| PathBuf::from(DEFAULT_CAMERA_PATH_FILE));
+        let camera_path = if camera_path_file.exists() {
+            CameraPath::load(&camera_path_file).unwrap_or_else(|e| {
+                eprintln!("{:#}", e);
+                CameraPath::default()
+            })
+        } else {
+            CameraPath::default()
+        };
+        let key_bindings = config.key_bindings.map_or_else(KeyBindings::default, |path| {
+            KeyBindings::load(&path).unwrap_or_else(|e| {
+                eprintln!("{:#}", e);
+                KeyBindings::default()
+            })
+        });
+        let scenes = if config.windows.is_empty() { vec![config.scene] } else { config.windows };
+
         Self {
-            window: None,
-            raytracer: None,
-            camera: Camera::new(),
+            views: Views::new(),
+            scenes,
+            start_pose: config.camera,
+            key_bindings,
+            focused: None,
+            sync_camera: config.sync_camera,
+            gamepad: GamepadController::new(),
             frames: FrameIterator::new(),
-            frame_count: 0,
-            fps: 0.0,
+            frame: None,
             fps_update_timer: 0.0,
-            cursor_position: None,
-            no_ui,
+            window_size: (config.width, config.height),
+            fps: config.fps,
+            idle_fps: config.idle_fps,
+            has_focus: true,
+            last_redraw: Instant::now(),
+            scene_options: SceneOptions {
+                max_triangles: config.max_triangles,
+                stress_count: config.stress_count,
+                reflective_floor: config.reflective_floor,
+                ambient: config.ambient,
+                lighting: config.lighting.config(),
+                // No override: each scene gets the lighting preset plus its own `DemoScene::lights`
+                lights: Vec::new(),
+                sky: config.sky,
+                skybox: config.skybox,
+                auto_exposure: config.auto_exposure,
+                fog_density: config.fog.unwrap_or(0.0),
+                fog_color: DEFAULT_FOG_COLOR,
+                aspect: config.preserve_aspect.then(|| Resolution::new(config.width, config.height).aspect_ratio()),
+                lod_budget: config.fractal_lod.then_some(DEFAULT_LOD_BUDGET),
+                lod_origin: Vec3::ZERO,
+            },
+            fov,
+            modifiers: ModifiersState::empty(),
+            bookmarks: [None; BOOKMARK_SLOTS],
+            camera_path,
+            camera_path_file,
+            playback: None,
+            autosave: config.autosave_interval.map(AutoSave::new),
+            no_ui: config.no_ui,
             should_exit: false,
         }
     }
 
-    fn update_fps(&mut self, frame: &FrameInfo) {
-        self.frame_count += 1;
+    /// Scene camera with the configured FOV, moved to `--camera` when given
+    fn start_camera(&self, scene_name: &str) -> Camera {
+        let mut camera = Camera::for_scene(scene_name).with_fov(self.fov).with_key_bindings(self.key_bindings);
+        if let Some(pose) = &self.start_pose {
+            camera.restore(pose);
+        }
+        camera
+    }
+
+    /// Create a window and renderer for `scene_name`, viewed through `camera`
+    fn open_view(&self, event_loop: &ActiveEventLoop, scene_name: &str, camera: SharedCamera) -> Result<View> {
+        let title = match self.scenes.len() {
+            1 => "Ray Tracer".to_string(),
+            _ => format!("Ray Tracer - {}", scene_name),
+        };
+        let winit_window = Arc::new(event_loop.create_window(
+            WinitWindow::default_attributes()
+                .with_title(title)
+                .with_inner_size(winit::dpi::LogicalSize::new(self.window_size.0, self.window_size.1)),
+        )?);
+        let lod_origin = camera.lock().unwrap().position;
+        let options = SceneOptions { lod_origin, ..self.scene_options.clone() };
+        let raytracer = pollster::block_on(RayTracer::new(winit_window.clone(), scene_name, self.no_ui, &options))?;
+        // `--camera` wins over a camera saved in the scene file, and a saved camera replaces `--fov`
+        if let Some(pose) = raytracer.scene_camera().filter(|_| self.start_pose.is_none()) {
+            camera.lock().unwrap().restore(&pose);
+        }
+
+        Ok(View {
+            window: Window::new(winit_window),
+            raytracer,
+            camera,
+            cursor_position: None,
+            lod_origin,
+            minimized: false,
+        })
+    }
+
+    /// Print the smoothed FPS once per `FPS_UPDATE_INTERVAL`
+    fn log_fps(&mut self, frame: &FrameInfo) {
         self.fps_update_timer += frame.delta;
 
         if self.fps_update_timer >= FPS_UPDATE_INTERVAL {
-            self.fps = self.frame_count as f32 / self.fps_update_timer;
             if !self.no_ui {
-                println!("FPS: {:.1} | Time: {:.2}s", self.fps, frame.time);
+                println!("FPS: {:.1} | Time: {:.2}s", frame.fps, frame.time);
             }
-            self.frame_count = 0;
             self.fps_update_timer = 0.0;
         }
     }
 
-    fn draw_frame(&mut self, frame: &FrameInfo) {
-        if let (Some(window), Some(raytracer)) = (&self.window, &mut self.raytracer) {
-            if let Err(e) = window.draw(raytracer, &self.camera, self.fps, frame) {
-                eprintln!("Render error: {}", e);
+    /// Back up every window's edited boxes once the auto-save interval has elapsed
+    fn autosave_if_due(&mut self, time: f32) {
+        let Some(autosave) = self.autosave.filter(|autosave| autosave.should_save(time)) else {
+            return;
+        };
+        for view in self.views.iter_mut() {
+            match view.raytracer.save_backup() {
+                Ok(Some(path)) if !self.no_ui => println!("Auto-saved scene to {}", path.display()),
+                Ok(_) => {}
+                Err(e) => eprintln!("Auto-save failed: {:#}", e),
+            }
+        }
+        self.autosave = Some(autosave.saved(time));
+    }
+
+    /// Step the shared clock once for all windows
+    fn advance_frame(&mut self) {
+        let frame = self.frames.next().unwrap();
+        self.log_fps(&frame);
+        self.autosave_if_due(frame.time);
+        self.gamepad.poll();
+        self.frame = Some(frame);
+    }
+
+    /// Whether `event` is a key bound to camera movement or sprint
+    fn moves_camera(&self, event: &KeyEvent) -> bool {
+        match event.physical_key {
+            PhysicalKey::Code(keycode) => {
+                WinitController::keycode_to_button(keycode).is_some_and(|button| self.key_bindings.moves(button))
+            }
+            PhysicalKey::Unidentified(_) => false,
+        }
+    }
+
+    /// Alt+N stores the window's camera pose in slot N, N alone recalls it
+    fn handle_bookmark_key(&mut self, id: WindowId, slot: usize) {
+        let Some(view) = self.views.get_mut(id) else {
+            return;
+        };
+
+        let mut camera = view.camera.lock().unwrap();
+        // Alt rather than Ctrl, so digits pressed while sprinting still recall
+        if self.modifiers.alt_key() {
+            self.bookmarks[slot] = Some(camera.snapshot());
+            if !self.no_ui {
+                println!("Saved camera bookmark {}: {}", slot + 1, camera.snapshot().to_line());
+            }
+        } else if let Some(bookmark) = &self.bookmarks[slot] {
+            camera.restore(bookmark);
+            if !self.no_ui {
+                println!("Restored camera bookmark {}", slot + 1);
+            }
+        }
+    }
+
+    /// P toggles playback in the window, K records its current pose, Ctrl+K saves the path
+    fn handle_camera_path_key(&mut self, id: WindowId, keycode: KeyCode, time: f32) {
+        match keycode {
+            KeyCode::KeyP if self.camera_path.is_empty() => eprintln!("No camera path keyframes to play"),
+            KeyCode::KeyP => {
+                self.playback = match self.playback {
+                    Some(_) => None,
+                    None => Some((id, time)),
+                };
+            }
+            KeyCode::KeyK if self.modifiers.control_key() => match self.camera_path.save(&self.camera_path_file) {
+                Ok(()) => println!("Saved camera path to {}", self.camera_path_file.display()),
+                Err(e) => eprintln!("{:#}", e),
+            },
+            KeyCode::KeyK => {
+                let Some(pose) = self.views.route(id, |view| view.camera.lock().unwrap().snapshot()) else {
+                    return;
+                };
+                self.camera_path = std::mem::take(&mut self.camera_path).with_keyframe(pose, KEYFRAME_SPACING);
+                if !self.no_ui {
+                    println!("Recorded keyframe {}", self.camera_path.keyframes().len());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Drive the window's camera from the path while its playback is active
+    fn update_playback(&mut self, id: WindowId, frame: &FrameInfo) {
+        if let Some((playing, start)) = self.playback.filter(|&(playing, _)| playing == id) {
+            let t = frame.time - start;
+            self.views.route(playing, |view| view.camera.lock().unwrap().follow_path(&self.camera_path, t));
+            if t >= self.camera_path.duration() {
+                self.playback = None;
             }
         }
     }
@@ -74,52 +388,47 @@ impl App {
 
 impl ApplicationHandler for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        if self.window.is_none() {
-            let winit_window = match event_loop.create_window(
-                WinitWindow::default_attributes()
-                    .with_title("Ray Tracer")
-                    .with_inner_size(winit::dpi::LogicalSize::new(
-                        INITIAL_WINDOW_WIDTH,
-                        INITIAL_WINDOW_HEIGHT,
-                    )),
-            ) {
-                Ok(w) => Arc::new(w),
-                Err(e) => {
-                    eprintln!("Failed to create window: {}", e);
-                    event_loop.exit();
-                    return;
-                }
-            };
+        if !self.views.is_empty() {
+            return;
+        }
 
-            let raytracer = match pollster::block_on(RayTracer::new(winit_window.clone(), self.no_ui)) {
-                Ok(rt) => rt,
+        let start_cameras = self.scenes.iter().map(|scene| self.start_camera(scene)).collect();
+        let cameras = window_cameras(start_cameras, self.sync_camera);
+        for (scene_name, camera) in self.scenes.clone().into_iter().zip(cameras) {
+            match self.open_view(event_loop, &scene_name, camera) {
+                Ok(view) => self.views.insert(view.window.inner().id(), view),
                 Err(e) => {
-                    eprintln!("Failed to initialize ray tracer: {}", e);
+                    eprintln!("Failed to open window for {}: {}", scene_name, e);
                     event_loop.exit();
                     return;
                 }
-            };
-
-            self.window = Some(Window::new(winit_window));
-            self.raytracer = Some(raytracer);
+            }
         }
+        self.focused = self.views.first_id();
     }
 
     fn window_event(
         &mut self,
         event_loop: &ActiveEventLoop,
-        _window_id: WindowId,
+        window_id: WindowId,
         event: WindowEvent,
     ) {
-        if let (Some(raytracer), Some(window)) = (&mut self.raytracer, &self.window) {
-            if raytracer.handle_event(window.inner(), &event) {
-                return;
-            }
+        let consumed = self.views.route(window_id, |view| view.raytracer.handle_event(view.window.inner(), &event));
+        if consumed != Some(false) {
+            return;
         }
 
         match event {
-            WindowEvent::CloseRequested
-            | WindowEvent::KeyboardInput {
+            // Bound movement keys win over the single-key shortcuts below (e.g. AZERTY `forward = "KeyZ"`
+            // with Ctrl sprint is not undo); Alt chords stay shortcuts since Alt is never a binding
+            WindowEvent::KeyboardInput { event, .. } if !self.modifiers.alt_key() && self.moves_camera(&event) => {
+                self.views.route(window_id, |view| view.camera.lock().unwrap().process_keyboard(&event));
+            }
+            WindowEvent::CloseRequested => {
+                self.views.remove(window_id);
+                self.should_exit = self.views.is_empty();
+            }
+            WindowEvent::KeyboardInput {
                 event:
                     KeyEvent {
                         state: ElementState::Pressed,
@@ -127,49 +436,195 @@ impl ApplicationHandler for App {
                         ..
                     },
                 ..
-            } => self.should_exit = true,
+            } => {
+                // Escape first drops the editor selection, then quits
+                let deselected = self.views.route(window_id, |view| view.raytracer.clear_selection()) == Some(true);
+                self.should_exit = !deselected;
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
+            WindowEvent::Focused(true) => {
+                self.focused = Some(window_id);
+                self.has_focus = true;
+            }
+            // Switching windows can deliver the new window's focus first; ignore the stale loss
+            WindowEvent::Focused(false) if self.focused == Some(window_id) => self.has_focus = false,
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: PhysicalKey::Code(keycode),
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } if bookmark_slot(keycode).is_some() => {
+                if let Some(slot) = bookmark_slot(keycode) {
+                    self.handle_bookmark_key(window_id, slot);
+                }
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: PhysicalKey::Code(keycode @ (KeyCode::KeyP | KeyCode::KeyK)),
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                let time = self.frames.time();
+                self.handle_camera_path_key(window_id, keycode, time);
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: PhysicalKey::Code(KeyCode::KeyR),
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                self.views.route(window_id, |view| view.raytracer.toggle_reflections());
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: PhysicalKey::Code(KeyCode::KeyT),
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                let no_ui = self.no_ui;
+                self.views.route(window_id, |view| {
+                    let mut camera = view.camera.lock().unwrap();
+                    camera.strafe = camera.strafe.toggled();
+                    if !no_ui {
+                        println!("Strafe relative to {:?}", camera.strafe);
+                    }
+                });
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: PhysicalKey::Code(KeyCode::KeyH | KeyCode::F1),
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                self.views.route(window_id, |view| view.raytracer.toggle_ui());
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let steps = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y,
+                    MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / SCROLL_PIXELS_PER_STEP,
+                };
+                self.views.route(window_id, |view| view.camera.lock().unwrap().adjust_speed(steps));
+            }
+            WindowEvent::Resized(size) => {
+                self.views.route(window_id, |view| {
+                    view.minimized = window::is_minimized(size);
+                    view.raytracer.resize(size);
+                });
+            }
             WindowEvent::CursorMoved { position, .. } => {
-                self.cursor_position = Some((position.x, position.y));
+                self.views.route(window_id, |view| view.cursor_position = Some((position.x, position.y)));
             }
             WindowEvent::MouseInput {
                 state: ElementState::Pressed,
                 button: winit::event::MouseButton::Left,
                 ..
             } => {
-                if let (Some(raytracer), Some(cursor_pos)) = (&mut self.raytracer, self.cursor_position) {
-                    raytracer.set_debug_pixel(cursor_pos.0 as u32, cursor_pos.1 as u32);
+                let no_ui = self.no_ui;
+                self.views.route(window_id, |view| {
+                    if let Some((x, y)) = view.cursor_position {
+                        view.raytracer.set_debug_pixel(x as u32, y as u32);
+                        let selected = view.raytracer.select_at(&view.camera.lock().unwrap(), x as u32, y as u32);
+                        if let (Some(index), false) = (selected, no_ui) {
+                            println!("Selected box {} (Alt+arrows / PageUp / PageDown to move, Escape to deselect)", index);
+                        }
+                    }
+                });
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: PhysicalKey::Code(keycode),
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } if self.modifiers.control_key() && matches!(keycode, KeyCode::KeyZ | KeyCode::KeyY) => {
+                self.views.route(window_id, |view| match keycode {
+                    KeyCode::KeyZ => view.raytracer.undo_edit(),
+                    _ => view.raytracer.redo_edit(),
+                });
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: PhysicalKey::Code(KeyCode::KeyS),
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } if self.modifiers.alt_key() => {
+                // Alt rather than Ctrl: Ctrl is sprint, and Ctrl+S is a sprinting step back
+                self.views.route(window_id, |view| view.save_scene());
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: PhysicalKey::Code(keycode),
+                        ..
+                    },
+                ..
+            } if self.modifiers.alt_key()
+                && editor::nudge_for_key(keycode).is_some()
+                && self.views.route(window_id, |view| view.raytracer.selected_box().is_some()) == Some(true) =>
+            {
+                if let Some(delta) = editor::nudge_for_key(keycode) {
+                    self.views.route(window_id, |view| view.raytracer.nudge_selected(delta));
                 }
             }
-            WindowEvent::KeyboardInput { event, .. } => self.camera.process_keyboard(&event),
+            WindowEvent::KeyboardInput { event, .. } => {
+                self.views.route(window_id, |view| view.camera.lock().unwrap().process_keyboard(&event));
+            }
             WindowEvent::RedrawRequested => {
-                // Get next frame from iterator
-                let frame = self.frames.next().unwrap();
-
-                self.update_fps(&frame);
-                self.camera.update();
+                let Some(frame) = self.frame else {
+                    return;
+                };
 
-                if let (Some(raytracer), Some(window)) = (&mut self.raytracer, &self.window) {
-                    if raytracer.needs_reload() {
-                        let new_scene = raytracer.get_current_scene();
-                        if !self.no_ui {
-                            println!("Reloading scene: {}", new_scene);
-                        }
-                        std::env::set_var("SCENE", &new_scene);
-
-                        match pollster::block_on(RayTracer::new(window.inner().clone(), self.no_ui)) {
-                            Ok(new_raytracer) => {
-                                *raytracer = new_raytracer;
-                                self.camera = Camera::new();
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to reload scene: {}", e);
-                            }
-                        }
-                    }
+                let focused = self.focused == Some(window_id);
+                let gamepad = if focused {
+                    MovementState::from_controller(&self.gamepad)
+                } else {
+                    MovementState::default()
+                };
+                // A shared camera steps once per frame, from the focused window
+                if focused || !self.sync_camera {
+                    self.views.route(window_id, |view| view.camera.lock().unwrap().update_with(gamepad));
                 }
+                self.update_playback(window_id, &frame);
 
-                // Draw the frame using iterator pattern
-                self.draw_frame(&frame);
+                let (fov, no_ui, sync_camera) = (self.fov, self.no_ui, self.sync_camera);
+                self.views.route(window_id, |view| {
+                    if view.raytracer.take_save_request() {
+                        view.save_scene();
+                    }
+                    view.reload_if_requested(fov, no_ui, sync_camera, &self.scene_options);
+                    view.refresh_lod(no_ui, &self.scene_options);
+                    view.draw(&frame);
+                });
             }
             _ => {}
         }
@@ -179,9 +634,31 @@ impl ApplicationHandler for App {
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        if let Some(window) = &self.window {
-            window.request_redraw();
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.views.is_empty() {
+            return;
+        }
+
+        let frame_interval = target_fps(self.has_focus, self.fps, self.idle_fps)
+            .map(|fps| Duration::from_secs_f32(1.0 / fps));
+        let due = match frame_interval {
+            Some(interval) => {
+                let next_frame = self.last_redraw + interval;
+                if Instant::now() >= next_frame {
+                    self.last_redraw = Instant::now();
+                    event_loop.set_control_flow(ControlFlow::Poll);
+                    true
+                } else {
+                    event_loop.set_control_flow(ControlFlow::WaitUntil(next_frame));
+                    false
+                }
+            }
+            None => true,
+        };
+
+        if due {
+            self.advance_frame();
+            self.views.iter().for_each(|view| view.window.request_redraw());
         }
     }
 }
@@ -190,13 +667,41 @@ fn main() -> Result<()> {
     env_logger::init();
 
     let args = cli::Cli::parse();
-    let no_ui = args.no_ui;
+    let config = args.config()?;
+
+    match &args.command {
+        Some(cli::Command::Profile { scene, frames }) => {
+            return profile::run_profile(scene, *frames as usize, config.width, config.height, config.stress_count);
+        }
+        Some(cli::Command::Render { scene, out, frames }) => {
+            let scene = scene.clone().unwrap_or_else(|| config.scene.clone());
+            return headless::run_render(&scene, config.width, config.height, *frames as usize, config.stress_count, out);
+        }
+        None => {}
+    }
+
+    if args.print_config {
+        println!("{}", serde_json::to_string_pretty(&config)?);
+        return Ok(());
+    }
+
+    match cpu_render::select_backend(RayTracer::is_gpu_available(), config.cpu_fallback) {
+        Some(cpu_render::RenderBackend::Gpu) if config.cpu_fallback => {
+            eprintln!("GPU adapter found; ignoring --cpu-fallback");
+        }
+        Some(cpu_render::RenderBackend::Gpu) => {}
+        Some(cpu_render::RenderBackend::Cpu) => {
+            return cpu_render::run_preview(&config.scene, config.width, config.height, Path::new(CPU_PREVIEW_FILE));
+        }
+        None => return Err("No GPU adapter found (pass --cpu-fallback for a CPU preview)".into()),
+    }
 
+    let no_ui = config.no_ui;
     let event_loop = EventLoop::new()?;
-    let mut app = App::new(no_ui);
+    let mut app = App::new(config);
 
     if !no_ui {
-        println!("Ray Tracer - Controls: WASD (move), Q/E (rotate), Space/Shift (up/down), Escape to quit");
+        println!("Ray Tracer - Controls: WASD (move), Q/E (rotate), Space/Shift (up/down), Ctrl (sprint), +/-/scroll (speed), Alt+1-9/1-9 (save/recall camera), K/Ctrl+K/P (record/save/play path), click + Alt+arrows/PgUp/PgDn (select/move box), Ctrl+Z/Ctrl+Y (undo/redo edit), Alt+S (save scene), R (reflections), T (camera/world strafe), H/F1 (hide UI), Escape (deselect box, then quit)");
     }
     event_loop.run_app(&mut app)?;
 
@@ -26,6 +26,31 @@ impl AABB {
         let d = self.max - self.min;
         2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
     }
+
+    /// Grown by `margin` on every side
+    pub fn expanded(&self, margin: f32) -> AABB {
+        AABB::new(self.min - Vec3::splat(margin), self.max + Vec3::splat(margin))
+    }
+
+    /// Whether `point` lies inside or on the boundary
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    /// Whether the boxes share any point; touching faces count as overlapping
+    pub fn overlaps(&self, other: &AABB) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    /// Region common to both boxes (flat when they only touch), `None` when disjoint
+    pub fn intersection(&self, other: &AABB) -> Option<AABB> {
+        self.overlaps(other).then(|| AABB::new(self.min.max(other.min), self.max.min(other.max)))
+    }
+
+    /// Nearest point inside the box
+    pub fn clamp(&self, point: Vec3) -> Vec3 {
+        point.clamp(self.min, self.max)
+    }
 }
 
 #[cfg(test)]
@@ -70,6 +95,50 @@ mod tests {
         assert!((area - 52.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_aabb_contains_and_clamp() {
+        let aabb = AABB::new(Vec3::ZERO, Vec3::ONE).expanded(1.0);
+        assert!(aabb.contains_point(Vec3::splat(-1.0)));
+        assert!(!aabb.contains_point(Vec3::new(0.0, 2.5, 0.0)));
+        assert_eq!(aabb.clamp(Vec3::new(0.5, 2.5, -3.0)), Vec3::new(0.5, 2.0, -1.0));
+    }
+
+    #[test]
+    fn test_aabb_contains_boundary_points() {
+        let aabb = AABB::new(Vec3::ZERO, Vec3::ONE);
+        assert!(aabb.contains_point(Vec3::ZERO));
+        assert!(aabb.contains_point(Vec3::ONE));
+        assert!(aabb.contains_point(Vec3::new(1.0, 0.5, 0.0)));
+        assert!(!aabb.contains_point(Vec3::new(1.0 + 1e-4, 0.5, 0.5)));
+    }
+
+    #[test]
+    fn test_aabb_overlapping_pair() {
+        let aabb1 = AABB::new(Vec3::ZERO, Vec3::splat(2.0));
+        let aabb2 = AABB::new(Vec3::ONE, Vec3::splat(3.0));
+        assert!(aabb1.overlaps(&aabb2) && aabb2.overlaps(&aabb1));
+        let common = aabb1.intersection(&aabb2).unwrap();
+        assert_eq!((common.min, common.max), (Vec3::ONE, Vec3::splat(2.0)));
+    }
+
+    #[test]
+    fn test_aabb_touching_pair_shares_a_face() {
+        let aabb1 = AABB::new(Vec3::ZERO, Vec3::ONE);
+        let aabb2 = AABB::new(Vec3::new(1.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+        assert!(aabb1.overlaps(&aabb2));
+        let face = aabb1.intersection(&aabb2).unwrap();
+        assert_eq!((face.min, face.max), (Vec3::new(1.0, 0.0, 0.0), Vec3::ONE));
+        assert_eq!(face.surface_area(), 2.0);
+    }
+
+    #[test]
+    fn test_aabb_disjoint_pair() {
+        let aabb1 = AABB::new(Vec3::ZERO, Vec3::ONE);
+        let aabb2 = AABB::new(Vec3::new(0.0, 1.5, 0.0), Vec3::new(1.0, 2.5, 1.0));
+        assert!(!aabb1.overlaps(&aabb2) && !aabb2.overlaps(&aabb1));
+        assert!(aabb1.intersection(&aabb2).is_none());
+    }
+
     #[test]
     fn test_aabb_union_non_overlapping() {
         let aabb1 = AABB::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
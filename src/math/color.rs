@@ -1,3 +1,28 @@
+/// Step count at which the traversal heatmap saturates to red (mirrored in the shader)
+pub const HEATMAP_MAX_STEPS: f32 = 64.0;
+
+/// Maps a traversal step count to a heatmap color: few steps blue, many steps red
+pub fn step_heatmap_color(num_steps: f32) -> [f32; 3] {
+    let t = (num_steps / HEATMAP_MAX_STEPS).clamp(0.0, 1.0);
+    hsv_to_rgb((1.0 - t) * 2.0 / 3.0, 1.0, 1.0)
+}
+
+/// Maps a unit surface normal from [-1, 1] to an RGB color in [0, 1] (mirrored in the shader)
+pub fn normal_to_color(normal: [f32; 3]) -> [f32; 3] {
+    normal.map(|n| n * 0.5 + 0.5)
+}
+
+/// Reinhard tone curve `x / (1 + x)` (mirrored in display.wgsl)
+pub fn reinhard(x: f32) -> f32 {
+    x / (1.0 + x)
+}
+
+/// ACES filmic tone curve, Narkowicz fit (mirrored in display.wgsl)
+pub fn aces(x: f32) -> f32 {
+    let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+    ((x * (a * x + b)) / (x * (c * x + d) + e)).clamp(0.0, 1.0)
+}
+
 pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [f32; 3] {
     let c = v * s;
     let h_prime = (h * 6.0) % 6.0;
@@ -16,10 +41,88 @@ pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [f32; 3] {
     [r + m, g + m, b + m]
 }
 
+/// Inverse of `hsv_to_rgb`: hue in [0, 1), saturation and value in [0, 1]
+pub fn rgb_to_hsv([r, g, b]: [f32; 3]) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+
+    let sector = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    let s = if max == 0.0 { 0.0 } else { delta / max };
+
+    (sector / 6.0, s, max)
+}
+
+/// HSL to RGB via the equivalent HSV color; hue in [0, 1]
+pub fn hsl_to_rgb(h: f32, s: f32, l: f32) -> [f32; 3] {
+    let v = l + s * l.min(1.0 - l);
+    let s_v = if v == 0.0 { 0.0 } else { 2.0 * (1.0 - l / v) };
+    hsv_to_rgb(h, s_v, v)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn assert_rgb_eq(a: [f32; 3], b: [f32; 3]) {
+        assert!(a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-5), "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_pure_red_to_hsv() {
+        assert_eq!(rgb_to_hsv([1.0, 0.0, 0.0]), (0.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_hsv_round_trips() {
+        let colors = [[0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.2, 0.4, 0.6], [0.5, 0.5, 0.5], [0.0; 3]];
+        for rgb in colors {
+            let (h, s, v) = rgb_to_hsv(rgb);
+            assert_rgb_eq(hsv_to_rgb(h, s, v), rgb);
+            let (h2, s2, v2) = rgb_to_hsv(hsv_to_rgb(h, s, v));
+            assert!((h - h2).abs() < 1e-5 && (s - s2).abs() < 1e-5 && (v - v2).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_hsl_to_rgb() {
+        assert_rgb_eq(hsl_to_rgb(0.0, 1.0, 0.5), [1.0, 0.0, 0.0]);
+        assert_rgb_eq(hsl_to_rgb(1.0 / 3.0, 1.0, 0.25), [0.0, 0.5, 0.0]);
+        assert_rgb_eq(hsl_to_rgb(0.6, 0.0, 0.75), [0.75; 3]);
+        assert_rgb_eq(hsl_to_rgb(0.6, 1.0, 1.0), [1.0; 3]);
+    }
+
+    #[test]
+    fn test_normal_to_color_positive_z() {
+        assert_eq!(normal_to_color([0.0, 0.0, 1.0]), [0.5, 0.5, 1.0]);
+        assert_eq!(normal_to_color([-1.0, 0.0, 0.0]), [0.0, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_reinhard_curve() {
+        assert_eq!(reinhard(0.0), 0.0);
+        assert_eq!(reinhard(1.0), 0.5);
+        assert!((reinhard(4.0) - 0.8).abs() < 1e-6);
+        assert!(reinhard(1000.0) < 1.0);
+    }
+
+    #[test]
+    fn test_aces_curve() {
+        assert_eq!(aces(0.0), 0.0);
+        assert!((aces(0.18) - 0.2669).abs() < 1e-3);
+        assert!((aces(1.0) - 0.8038).abs() < 1e-3);
+        assert_eq!(aces(100.0), 1.0);
+        // Monotonic across the usable range
+        assert!(aces(0.5) < aces(1.0) && aces(1.0) < aces(2.0));
+    }
+
     #[test]
     fn test_hsv_to_rgb_red() {
         let rgb = hsv_to_rgb(0.0, 1.0, 1.0);
@@ -36,6 +139,26 @@ mod tests {
         assert!((rgb[2] - 1.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_step_heatmap_color() {
+        let cold = step_heatmap_color(0.0);
+        assert!(cold[0].abs() < 0.01 && cold[1].abs() < 0.01);
+        assert!((cold[2] - 1.0).abs() < 0.01);
+
+        let hot = step_heatmap_color(HEATMAP_MAX_STEPS);
+        assert!((hot[0] - 1.0).abs() < 0.01);
+        assert!(hot[1].abs() < 0.01 && hot[2].abs() < 0.01);
+
+        // Saturates beyond the max
+        assert_eq!(step_heatmap_color(HEATMAP_MAX_STEPS * 4.0), hot);
+
+        // More steps means more red, less blue
+        let mid = step_heatmap_color(HEATMAP_MAX_STEPS * 0.5);
+        let warm = step_heatmap_color(HEATMAP_MAX_STEPS * 0.8);
+        assert!(warm[0] >= mid[0]);
+        assert!(warm[2] <= mid[2]);
+    }
+
     #[test]
     fn test_hsv_to_rgb_black() {
         let rgb = hsv_to_rgb(0.0, 1.0, 0.0);
@@ -0,0 +1,56 @@
+/// Maps a hit distance in [0, inf) to [0, 1) for the output texture's alpha (mirrored in the shader)
+///
+/// Misses are stored as 1.0. Nearby surfaces get most of the range, so depth
+/// steps close to the camera stand out more than distant ones.
+pub fn encode_depth(distance: f32) -> f32 {
+    distance / (1.0 + distance)
+}
+
+/// Sobel gradient magnitude of a scalar image at (x, y), clamping at the borders (mirrored in display.wgsl)
+pub fn sobel_magnitude(values: &[f32], width: usize, height: usize, x: usize, y: usize) -> f32 {
+    let sample = |dx: isize, dy: isize| {
+        let sx = (x as isize + dx).clamp(0, width as isize - 1) as usize;
+        let sy = (y as isize + dy).clamp(0, height as isize - 1) as usize;
+        values[sy * width + sx]
+    };
+
+    let gx = (sample(1, -1) + 2.0 * sample(1, 0) + sample(1, 1)) - (sample(-1, -1) + 2.0 * sample(-1, 0) + sample(-1, 1));
+    let gy = (sample(-1, 1) + 2.0 * sample(0, 1) + sample(1, 1)) - (sample(-1, -1) + 2.0 * sample(0, -1) + sample(1, -1));
+    (gx * gx + gy * gy).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 6x4 depth buffer: a near object in the left half, background on the right
+    fn depth_step() -> Vec<f32> {
+        (0..4).flat_map(|_| (0..6).map(|x| encode_depth(if x < 3 { 2.0 } else { 20.0 }))).collect()
+    }
+
+    #[test]
+    fn test_flat_depth_has_no_edges() {
+        let depth = vec![encode_depth(5.0); 16];
+        assert!((0..4).all(|i| sobel_magnitude(&depth, 4, 4, i, i) == 0.0));
+    }
+
+    #[test]
+    fn test_depth_step_is_an_edge() {
+        let depth = depth_step();
+        let step = encode_depth(20.0) - encode_depth(2.0);
+
+        // Pixels either side of the boundary see the full 1-2-1 weighted step
+        assert!((sobel_magnitude(&depth, 6, 4, 2, 1) - 4.0 * step).abs() < 1e-5);
+        assert!((sobel_magnitude(&depth, 6, 4, 3, 2) - 4.0 * step).abs() < 1e-5);
+        // Away from it (including at the clamped border) nothing fires
+        assert_eq!(sobel_magnitude(&depth, 6, 4, 0, 0), 0.0);
+        assert_eq!(sobel_magnitude(&depth, 6, 4, 5, 3), 0.0);
+    }
+
+    #[test]
+    fn test_encode_depth_is_monotonic() {
+        assert_eq!(encode_depth(0.0), 0.0);
+        assert!(encode_depth(1.0) < encode_depth(10.0));
+        assert!(encode_depth(1.0e6) < 1.0);
+    }
+}
@@ -0,0 +1,65 @@
+use glam::Vec3;
+
+use super::AABB;
+
+/// View frustum of a pinhole camera, as inward-facing planes through its position
+///
+/// The far side is open; the near plane passes through the camera, so anything
+/// behind it is outside.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    origin: Vec3,
+    normals: [Vec3; 5],
+}
+
+impl Frustum {
+    /// Frustum spanned by rays `forward + right * x * aspect * fov_scale + up * y * fov_scale`, x and y in [-1, 1]
+    pub fn new(origin: Vec3, forward: Vec3, right: Vec3, up: Vec3, fov_scale: f32, aspect: f32) -> Self {
+        let (h, v) = (right * aspect * fov_scale, up * fov_scale);
+        let corners = [forward - h - v, forward + h - v, forward + h + v, forward - h + v];
+        let side = |i: usize| {
+            let normal = corners[i].cross(corners[(i + 1) % 4]);
+            if normal.dot(forward) < 0.0 { -normal } else { normal }
+        };
+
+        Self { origin, normals: [side(0), side(1), side(2), side(3), forward] }
+    }
+
+    /// Conservative overlap test: false only when the box lies entirely outside one plane
+    pub fn intersects_aabb(&self, aabb: &AABB) -> bool {
+        self.normals.iter().all(|&n| {
+            // Corner furthest along the plane normal
+            let corner = Vec3::select(n.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            (corner - self.origin).dot(n) >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn looking_down_z() -> Frustum {
+        Frustum::new(Vec3::ZERO, Vec3::Z, Vec3::X, Vec3::Y, 0.5, 1.0)
+    }
+
+    fn unit_box_at(center: Vec3) -> AABB {
+        AABB::new(center - Vec3::splat(0.5), center + Vec3::splat(0.5))
+    }
+
+    #[test]
+    fn test_frustum_keeps_boxes_in_view() {
+        let frustum = looking_down_z();
+        assert!(frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, 0.0, 10.0))));
+        assert!(frustum.intersects_aabb(&unit_box_at(Vec3::new(4.5, 0.0, 10.0))));
+        assert!(frustum.intersects_aabb(&unit_box_at(Vec3::ZERO)));
+    }
+
+    #[test]
+    fn test_frustum_culls_boxes_outside() {
+        let frustum = looking_down_z();
+        assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, 0.0, -10.0))));
+        assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(8.0, 0.0, 10.0))));
+        assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, -8.0, 10.0))));
+    }
+}
@@ -14,6 +14,15 @@ pub fn world_to_cell(pos: Vec3, bounds_min: Vec3, cell_size: f32) -> (i32, i32,
     )
 }
 
+/// `world_to_cell` for grids whose cells have a different size along each axis
+pub fn world_to_cell_aniso(pos: Vec3, bounds_min: Vec3, cell_size: Vec3) -> (i32, i32, i32) {
+    debug_assert!(cell_size.cmpgt(Vec3::ZERO).all(), "cell_size must be positive on every axis");
+    debug_assert!(pos.is_finite() && bounds_min.is_finite(), "inputs must be finite");
+
+    let cell = ((pos - bounds_min) * cell_size.recip()).floor();
+    (cell.x as i32, cell.y as i32, cell.z as i32)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,4 +53,40 @@ mod tests {
         let cell = world_to_cell(pos, bounds_min, cell_size);
         assert_eq!(cell, (0, 0, 0));
     }
+
+    #[test]
+    fn test_world_to_cell_aniso_origin() {
+        let cell = world_to_cell_aniso(Vec3::ZERO, Vec3::ZERO, Vec3::new(16.0, 2.0, 8.0));
+        assert_eq!(cell, (0, 0, 0));
+    }
+
+    #[test]
+    fn test_world_to_cell_aniso_offset() {
+        let pos = Vec3::new(20.0, 30.0, 40.0);
+        let cell = world_to_cell_aniso(pos, Vec3::ZERO, Vec3::new(10.0, 5.0, 20.0));
+        assert_eq!(cell, (2, 6, 2));
+    }
+
+    #[test]
+    fn test_world_to_cell_aniso_negative() {
+        let pos = Vec3::new(-5.0, -5.0, -5.0);
+        let cell = world_to_cell_aniso(pos, Vec3::splat(-10.0), Vec3::new(10.0, 2.0, 4.0));
+        assert_eq!(cell, (0, 2, 1));
+    }
+
+    #[test]
+    fn test_world_to_cell_aniso_matches_scalar_for_cubes() {
+        let pos = Vec3::new(17.5, -3.0, 250.0);
+        let bounds_min = Vec3::new(-20.0, -40.0, 0.0);
+        assert_eq!(world_to_cell_aniso(pos, bounds_min, Vec3::splat(16.0)), world_to_cell(pos, bounds_min, 16.0));
+    }
+
+    #[test]
+    fn test_world_to_cell_aniso_thin_y() {
+        // Flat ground: 400 units wide, 2 units tall, split into 25 x 4 x 25 cells
+        let cell_size = Vec3::new(16.0, 0.5, 16.0);
+        let bounds_min = Vec3::new(-200.0, -1.0, -200.0);
+        assert_eq!(world_to_cell_aniso(Vec3::new(0.0, 0.0, 0.0), bounds_min, cell_size), (12, 2, 12));
+        assert_eq!(world_to_cell_aniso(Vec3::new(199.0, 0.99, -199.0), bounds_min, cell_size), (24, 3, 0));
+    }
 }
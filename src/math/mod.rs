@@ -1,9 +1,19 @@
 mod aabb;
 mod color;
+mod edge;
+mod frustum;
 mod grid;
+mod pattern;
 mod ray;
+mod rng;
+mod sampling;
 
 pub use aabb::AABB;
-pub use color::hsv_to_rgb;
-pub use grid::world_to_cell;
-pub use ray::intersect_aabb;
+pub use color::{aces, hsl_to_rgb, hsv_to_rgb, normal_to_color, reinhard, rgb_to_hsv, step_heatmap_color, HEATMAP_MAX_STEPS};
+pub use edge::{encode_depth, sobel_magnitude};
+pub use frustum::Frustum;
+pub use grid::{world_to_cell, world_to_cell_aniso};
+pub use pattern::checker_parity;
+pub use ray::{intersect_aabb, intersect_aabb_range, intersect_triangle, within_clip_range};
+pub use rng::Pcg32;
+pub use sampling::{concentric_disk_sample, dir_to_equirect_uv, glossy_reflect};
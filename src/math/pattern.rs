@@ -0,0 +1,30 @@
+/// Checkerboard tile parity at world (x, z): false for the base color, true for the alternate
+///
+/// Tiles are `tile` units square and anchored at the world origin, so the
+/// pattern is fixed in space. Mirrored by `checker_parity` in the shader.
+pub fn checker_parity(x: f32, z: f32, tile: f32) -> bool {
+    let (ix, iz) = ((x / tile).floor() as i64, (z / tile).floor() as i64);
+    (ix + iz).rem_euclid(2) == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checker_parity_alternates() {
+        assert!(!checker_parity(0.5, 0.5, 1.0));
+        assert!(checker_parity(1.5, 0.5, 1.0));
+        assert!(checker_parity(0.5, 1.5, 1.0));
+        assert!(!checker_parity(1.5, 1.5, 1.0));
+    }
+
+    #[test]
+    fn test_checker_parity_across_origin() {
+        // floor keeps tiles the same size on the negative side (no double-width tile at 0)
+        assert!(checker_parity(-0.5, 0.5, 1.0));
+        assert!(!checker_parity(-0.5, -0.5, 1.0));
+        assert!(checker_parity(-2.5, 2.5, 2.0));
+        assert_eq!(checker_parity(3.9, -7.2, 2.0), checker_parity(3.9 + 4.0, -7.2, 2.0));
+    }
+}
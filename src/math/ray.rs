@@ -1,6 +1,62 @@
 use glam::Vec3;
 
+/// Nearest positive hit distance (exit distance when starting inside), or -1.0 on miss
 pub fn intersect_aabb(ray_origin: Vec3, ray_dir: Vec3, box_min: Vec3, box_max: Vec3) -> f32 {
+    match intersect_aabb_range(ray_origin, ray_dir, box_min, box_max) {
+        Some((t_near, _)) if t_near >= 0.0 => t_near,
+        Some((_, t_far)) if t_far > 0.001 => t_far,
+        _ => -1.0,
+    }
+}
+
+/// Möller-Trumbore ray-triangle test: hit distance and barycentric weights of `(v0, v1, v2)`, or `None` on miss
+///
+/// Front faces wind counter-clockwise as seen by the ray; with `cull_back_faces` the other side misses.
+/// Mirrors the shader's `intersect_triangle`.
+pub fn intersect_triangle(
+    ray_origin: Vec3,
+    ray_dir: Vec3,
+    [v0, v1, v2]: [Vec3; 3],
+    cull_back_faces: bool,
+) -> Option<(f32, Vec3)> {
+    const EPSILON: f32 = 1e-6;
+
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let h = ray_dir.cross(edge2);
+    let det = edge1.dot(h);
+
+    // Parallel to the plane, or seen from behind when culling
+    if det.abs() < EPSILON || (cull_back_faces && det < 0.0) {
+        return None;
+    }
+
+    let f = 1.0 / det;
+    let s = ray_origin - v0;
+    let u = f * s.dot(h);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(edge1);
+    let v = f * ray_dir.dot(q);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = f * edge2.dot(q);
+    (t >= EPSILON).then(|| (t, Vec3::new(1.0 - u - v, u, v)))
+}
+
+/// True when a hit at `distance` lies inside the camera's `[near, far]` clip range
+pub fn within_clip_range(distance: f32, near: f32, far: f32) -> bool {
+    (near..=far).contains(&distance)
+}
+
+/// Entry and exit distances `(t_near, t_far)` along the ray, or `None` on miss
+///
+/// `t_near` is negative when the origin is inside the box.
+pub fn intersect_aabb_range(ray_origin: Vec3, ray_dir: Vec3, box_min: Vec3, box_max: Vec3) -> Option<(f32, f32)> {
     const EPSILON: f32 = 1e-8;
 
     // Precompute inverse direction with epsilon clamping to avoid division by zero
@@ -21,17 +77,9 @@ pub fn intersect_aabb(ray_origin: Vec3, ray_dir: Vec3, box_min: Vec3, box_max: V
     let t_far = t2.x.min(t2.y).min(t2.z);
 
     if t_near > t_far || t_far < 0.0 {
-        return -1.0;
-    }
-
-    if t_near < 0.0 {
-        if t_far > 0.001 {
-            t_far
-        } else {
-            -1.0
-        }
+        None
     } else {
-        t_near
+        Some((t_near, t_far))
     }
 }
 
@@ -69,4 +117,88 @@ mod tests {
         let t = intersect_aabb(ray_origin, ray_dir, box_min, box_max);
         assert!(t > 0.0);
     }
+
+    #[test]
+    fn test_intersect_aabb_range_through() {
+        let ray_origin = Vec3::new(0.0, 0.0, 0.0);
+        let ray_dir = Vec3::new(1.0, 0.0, 0.0);
+        let box_min = Vec3::new(5.0, -1.0, -1.0);
+        let box_max = Vec3::new(10.0, 1.0, 1.0);
+        let (t_near, t_far) = intersect_aabb_range(ray_origin, ray_dir, box_min, box_max).unwrap();
+        assert!((t_near - 5.0).abs() < 0.01);
+        assert!((t_far - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_intersect_aabb_range_inside() {
+        let ray_origin = Vec3::new(5.0, 0.0, 0.0);
+        let ray_dir = Vec3::new(1.0, 0.0, 0.0);
+        let box_min = Vec3::new(0.0, -1.0, -1.0);
+        let box_max = Vec3::new(10.0, 1.0, 1.0);
+        let (t_near, t_far) = intersect_aabb_range(ray_origin, ray_dir, box_min, box_max).unwrap();
+        assert!((t_near + 5.0).abs() < 0.01);
+        assert!((t_far - 5.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_intersect_aabb_range_miss() {
+        let ray_origin = Vec3::new(0.0, 0.0, 0.0);
+        let ray_dir = Vec3::new(1.0, 0.0, 0.0);
+        let box_min = Vec3::new(5.0, 2.0, 2.0);
+        let box_max = Vec3::new(10.0, 3.0, 3.0);
+        assert_eq!(intersect_aabb_range(ray_origin, ray_dir, box_min, box_max), None);
+    }
+
+    #[test]
+    fn test_intersect_aabb_range_behind() {
+        let ray_origin = Vec3::new(20.0, 0.0, 0.0);
+        let ray_dir = Vec3::new(1.0, 0.0, 0.0);
+        let box_min = Vec3::new(5.0, -1.0, -1.0);
+        let box_max = Vec3::new(10.0, 1.0, 1.0);
+        assert_eq!(intersect_aabb_range(ray_origin, ray_dir, box_min, box_max), None);
+    }
+
+    const TRIANGLE: [Vec3; 3] = [Vec3::new(-1.0, -1.0, 5.0), Vec3::new(1.0, -1.0, 5.0), Vec3::new(0.0, 2.0, 5.0)];
+
+    #[test]
+    fn test_intersect_triangle_hits_centroid() {
+        let centroid = (TRIANGLE[0] + TRIANGLE[1] + TRIANGLE[2]) / 3.0;
+        let ray_origin = Vec3::new(centroid.x, centroid.y, 0.0);
+        let ray_dir = Vec3::new(0.0, 0.0, 1.0);
+        let (t, barycentric) = intersect_triangle(ray_origin, ray_dir, TRIANGLE, false).unwrap();
+        assert!((t - 5.0).abs() < 0.01);
+        assert!((barycentric - Vec3::splat(1.0 / 3.0)).abs().max_element() < 1e-5);
+    }
+
+    #[test]
+    fn test_intersect_triangle_parallel_miss() {
+        let ray_origin = Vec3::new(-5.0, 0.0, 5.0);
+        let ray_dir = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(intersect_triangle(ray_origin, ray_dir, TRIANGLE, false), None);
+    }
+
+    #[test]
+    fn test_intersect_triangle_behind() {
+        let ray_origin = Vec3::new(0.0, 0.0, 10.0);
+        let ray_dir = Vec3::new(0.0, 0.0, 1.0);
+        assert_eq!(intersect_triangle(ray_origin, ray_dir, TRIANGLE, false), None);
+    }
+
+    #[test]
+    fn test_intersect_triangle_back_face_culling() {
+        // The triangle's normal faces +Z, so a ray along +Z sees its back
+        let ray_origin = Vec3::new(0.0, 0.0, 0.0);
+        let ray_dir = Vec3::new(0.0, 0.0, 1.0);
+        assert!(intersect_triangle(ray_origin, ray_dir, TRIANGLE, false).is_some());
+        assert_eq!(intersect_triangle(ray_origin, ray_dir, TRIANGLE, true), None);
+        assert!(intersect_triangle(Vec3::new(0.0, 0.0, 10.0), Vec3::NEG_Z, TRIANGLE, true).is_some());
+    }
+
+    #[test]
+    fn test_clip_range_rejects_hits_outside() {
+        assert!(within_clip_range(5.0, 1.0, 100.0));
+        assert!(within_clip_range(1.0, 1.0, 100.0));
+        assert!(!within_clip_range(0.5, 1.0, 100.0));
+        assert!(!within_clip_range(150.0, 1.0, 100.0));
+    }
 }
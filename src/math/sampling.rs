@@ -0,0 +1,117 @@
+use glam::Vec3;
+use std::f32::consts::{FRAC_PI_4, PI, TAU};
+
+/// Maps a point in the unit square to the unit disk (Shirley-Chiu concentric mapping)
+///
+/// Preserves relative area, so uniform `(u, v)` gives uniform lens samples.
+/// Mirrored by `concentric_disk_sample` in the shader.
+pub fn concentric_disk_sample(u: f32, v: f32) -> (f32, f32) {
+    let (ox, oy) = (u * 2.0 - 1.0, v * 2.0 - 1.0);
+
+    if ox == 0.0 && oy == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let (r, theta) = if ox.abs() > oy.abs() {
+        (ox, FRAC_PI_4 * (oy / ox))
+    } else {
+        (oy, 2.0 * FRAC_PI_4 - FRAC_PI_4 * (ox / oy))
+    };
+
+    (r * theta.cos(), r * theta.sin())
+}
+
+/// Maps a unit direction to equirectangular texture coordinates in [0, 1]
+///
+/// `u` wraps around the Y axis starting at -X; `v` is 0 straight up and 1
+/// straight down. Mirrored by `dir_to_equirect_uv` in the shader.
+pub fn dir_to_equirect_uv(dir: Vec3) -> (f32, f32) {
+    let u = 0.5 + dir.z.atan2(dir.x) / TAU;
+    let v = dir.y.clamp(-1.0, 1.0).acos() / PI;
+    (u, v)
+}
+
+/// Tilts a unit reflection direction by a disk offset of radius `roughness`
+///
+/// The result stays within `atan(roughness)` of `dir`; roughness 0 returns `dir`
+/// unchanged. Mirrored by `glossy_reflect` in the shader.
+pub fn glossy_reflect(dir: Vec3, roughness: f32, u: f32, v: f32) -> Vec3 {
+    if roughness <= 0.0 {
+        return dir;
+    }
+    let helper = if dir.x.abs() > 0.9 { Vec3::Y } else { Vec3::X };
+    let tangent = helper.cross(dir).normalize();
+    let bitangent = dir.cross(tangent);
+    let (x, y) = concentric_disk_sample(u, v);
+    (dir + (tangent * x + bitangent * y) * roughness).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concentric_disk_center() {
+        assert_eq!(concentric_disk_sample(0.5, 0.5), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_concentric_disk_samples_within_aperture() {
+        let aperture = 0.4;
+        let radius = aperture / 2.0;
+        let steps = 64;
+
+        for i in 0..=steps {
+            for j in 0..=steps {
+                let (x, y) = concentric_disk_sample(i as f32 / steps as f32, j as f32 / steps as f32);
+                let dist = (x * radius).hypot(y * radius);
+                assert!(dist <= radius + 1e-5, "sample ({}, {}) at {} exceeds radius {}", i, j, dist, radius);
+            }
+        }
+    }
+
+    #[test]
+    fn test_concentric_disk_corners_reach_edge() {
+        for (u, v) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.5)] {
+            let (x, y) = concentric_disk_sample(u, v);
+            assert!((x.hypot(y) - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_glossy_reflect_stays_within_cone() {
+        let dir = Vec3::new(0.3, 0.8, -0.5).normalize();
+        let steps = 32;
+
+        for roughness in [0.05f32, 0.2, 0.5] {
+            let max_angle = roughness.atan();
+            for i in 0..=steps {
+                for j in 0..=steps {
+                    let out = glossy_reflect(dir, roughness, i as f32 / steps as f32, j as f32 / steps as f32);
+                    assert!((out.length() - 1.0).abs() < 1e-5);
+                    assert!(out.angle_between(dir) <= max_angle + 1e-4, "roughness {}", roughness);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_roughness_is_mirror() {
+        let dir = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(glossy_reflect(dir, 0.0, 0.1, 0.9), dir);
+    }
+
+    #[test]
+    fn test_equirect_poles() {
+        assert_eq!(dir_to_equirect_uv(Vec3::Y).1, 0.0);
+        assert_eq!(dir_to_equirect_uv(Vec3::NEG_Y).1, 1.0);
+    }
+
+    #[test]
+    fn test_equirect_equator() {
+        let close = |(u, v): (f32, f32), expected: (f32, f32)| (u - expected.0).abs() < 1e-6 && (v - expected.1).abs() < 1e-6;
+        assert!(close(dir_to_equirect_uv(Vec3::X), (0.5, 0.5)));
+        assert!(close(dir_to_equirect_uv(Vec3::Z), (0.75, 0.5)));
+        assert!(close(dir_to_equirect_uv(Vec3::NEG_Z), (0.25, 0.5)));
+    }
+}
@@ -0,0 +1,128 @@
+// profile.rs - Headless per-scene frame time profiling (`ray-tracer profile`)
+use std::sync::Arc;
+
+use crate::core::{DisplayContext, GpuContext, PerfResult, PerfTest, RayTracingLayerBuilder};
+use crate::scenes;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const WARMUP_FRAMES: usize = 5;
+
+/// Frame time statistics for one scene
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileRow {
+    pub scene: String,
+    pub avg_ms: f64,
+    pub p95_ms: f64,
+}
+
+impl From<&PerfResult> for ProfileRow {
+    fn from(result: &PerfResult) -> Self {
+        Self {
+            scene: result.name.clone(),
+            avg_ms: result.avg_duration.as_secs_f64() * 1000.0,
+            p95_ms: result.p95_duration.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// Expand `all` into every generated scene
+pub fn resolve_scenes(scene: &str) -> Vec<String> {
+    match scene {
+        "all" => scenes::available().iter().map(|s| s.name.to_string()).collect(),
+        name => vec![name.to_string()],
+    }
+}
+
+/// Render the rows as a table with aligned columns, headed by the GPU name
+pub fn format_table(gpu_name: &str, rows: &[ProfileRow]) -> String {
+    let scene_width = rows
+        .iter()
+        .map(|r| r.scene.len())
+        .chain(std::iter::once("Scene".len()))
+        .max()
+        .unwrap_or(0);
+
+    let header = format!("{:<scene_width$}  {:>10}  {:>10}", "Scene", "Avg (ms)", "P95 (ms)");
+    let body = rows
+        .iter()
+        .map(|r| format!("{:<scene_width$}  {:>10.2}  {:>10.2}", r.scene, r.avg_ms, r.p95_ms));
+
+    std::iter::once(format!("GPU: {}", gpu_name))
+        .chain(std::iter::once(header.clone()))
+        .chain(std::iter::once("-".repeat(header.len())))
+        .chain(body)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Render each scene headless for `frames` frames and print a frame time table
+pub fn run_profile(scene: &str, frames: usize, width: u32, height: u32, stress_count: usize) -> Result<()> {
+    let gpu = Arc::new(pollster::block_on(GpuContext::new())?);
+    let context = DisplayContext::new(width, height);
+    let mask = vec![true; context.pixel_count()];
+
+    let rows = resolve_scenes(scene)
+        .iter()
+        .map(|name| {
+            println!("Profiling {} ({} frames)...", name, frames);
+            let layer = pollster::block_on(RayTracingLayerBuilder::new(gpu.clone(), name, width, height).stress_count(stress_count).build())?;
+            let result = PerfTest::new(name)
+                .with_warmup(WARMUP_FRAMES)
+                .with_iterations(frames)
+                .run(|| {
+                    std::hint::black_box(layer.render(&mask, &context));
+                });
+            Ok(ProfileRow::from(&result))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    println!("\n{}", format_table(&gpu.adapter_info().name, &rows));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_rows() -> Vec<ProfileRow> {
+        vec![
+            ProfileRow { scene: "fractal".into(), avg_ms: 4.5, p95_ms: 6.25 },
+            ProfileRow { scene: "reflected".into(), avg_ms: 12.125, p95_ms: 15.0 },
+            ProfileRow { scene: "gltf".into(), avg_ms: 103.0, p95_ms: 140.75 },
+        ]
+    }
+
+    #[test]
+    fn test_table_columns_are_aligned() {
+        let table = format_table("Test GPU", &sample_rows());
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines[0], "GPU: Test GPU");
+        assert_eq!(lines.len(), 6);
+
+        // Header, separator and rows all share one width
+        let width = lines[1].len();
+        assert!(lines[2..].iter().all(|l| l.len() == width));
+
+        // Numeric columns are right-aligned at the same offsets
+        let avg_end = lines[1].find("Avg (ms)").unwrap() + "Avg (ms)".len();
+        assert_eq!(&lines[3][avg_end - 4..avg_end], "4.50");
+        assert_eq!(&lines[4][avg_end - 5..avg_end], "12.12");
+        assert_eq!(&lines[5][avg_end - 6..avg_end], "103.00");
+        assert!(lines[5].ends_with("140.75"));
+    }
+
+    #[test]
+    fn test_scene_column_fits_longest_name() {
+        let table = format_table("GPU", &sample_rows());
+        let row = table.lines().find(|l| l.starts_with("reflected")).unwrap();
+        assert!(row.starts_with("reflected  "));
+    }
+
+    #[test]
+    fn test_resolve_all_scenes() {
+        assert_eq!(resolve_scenes("all").len(), scenes::available().len());
+        assert_eq!(resolve_scenes("walls"), vec!["walls".to_string()]);
+    }
+}
@@ -1,8 +1,15 @@
 const GRID_LEVELS: u32 = 4u;
 const MAX_OBJECTS_PER_CELL: u32 = 8192u;
 const EPSILON: f32 = 0.00001;
-const DEFAULT_FOV: f32 = 0.785398;  // π/4 = 45 degrees
-const LIGHT_DIRECTION: vec3<f32> = vec3<f32>(0.5, -1.0, 0.3);
+const HEATMAP_MAX_STEPS: f32 = 64.0;  // Mirrors math::HEATMAP_MAX_STEPS
+const PI: f32 = 3.14159265;
+const GLOSSY_SEED: u32 = 0x68bc21ebu;
+const SHADING_LIT: u32 = 0u;
+const SHADING_NORMALS: u32 = 1u;
+const SHADING_FLAT: u32 = 2u;
+const NO_FAR_CLIP: f32 = 1e10;
+const LIGHT_POINT: u32 = 1u;  // Mirrors types::LightType
+const SHADOW_BIAS: f32 = 0.001;
 
 struct Camera {
     position: vec3<f32>,
@@ -16,6 +23,14 @@ struct Camera {
     lod_factor: f32,
     min_pixel_size: f32,
     show_grid: f32,
+    aperture: f32,
+    focus_distance: f32,
+    samples_per_pixel: u32,
+    fov: f32,
+    ambient: f32,
+    near: f32,
+    far: f32,
+    sample_offset: u32,
     _pad4: f32,
 };
 
@@ -32,6 +47,11 @@ struct Box {
     _pad5: f32,
     half_size: vec3<f32>,
     _pad6: f32,
+    emissive: vec3<f32>,
+    material_index: i32,
+    rotation: vec4<f32>,
+    checker_color: vec3<f32>,
+    checker_tile: f32,
 };
 
 struct Triangle {
@@ -45,6 +65,12 @@ struct Triangle {
     uv1: vec2<f32>,
     uv2: vec2<f32>,
     _pad3: vec2<f32>,
+    n0: vec3<f32>,
+    has_normals: f32,
+    n1: vec3<f32>,
+    _pad4: f32,
+    n2: vec3<f32>,
+    _pad5: f32,
 };
 
 struct Material {
@@ -64,7 +90,9 @@ struct GridMetadata {
     bounds_min: vec3<f32>,
     num_levels: u32,
     bounds_max: vec3<f32>,
-    finest_cell_size: f32,
+    _pad: f32,
+    finest_cell_size: vec3<f32>,
+    _pad2: f32,
     grid_sizes: array<vec4<u32>, 4>,
 };
 
@@ -102,12 +130,17 @@ struct TraceResult {
     object_id: f32,
     num_steps: f32,
     reflectivity: f32,
+    roughness: f32,
 };
 
 struct DebugParams {
     debug_pixel: vec2<u32>,
     enabled: u32,
-    _pad: u32,
+    heatmap: u32,
+    shading_mode: u32,
+    reflections_enabled: u32,
+    wireframe: u32,
+    _pad1: u32,
 };
 
 struct RayDebugInfo {
@@ -126,7 +159,24 @@ struct RayDebugInfo {
 struct SceneConfig {
     num_boxes: u32,
     num_triangles: u32,
-    _pad: vec2<u32>,
+    skybox_enabled: u32,
+    sky_mode: u32,
+    sun_direction: vec3<f32>,
+    num_lights: u32,
+    fog_color: vec3<f32>,
+    fog_density: f32,
+    sky_top: vec3<f32>,
+    _pad: f32,
+    sky_bottom: vec3<f32>,
+    _pad2: f32,
+};
+
+// Directional lights store their travel direction in `position` (mirrors types::LightData)
+struct Light {
+    position: vec3<f32>,
+    light_type: u32,
+    color: vec3<f32>,
+    intensity: f32,
 };
 
 @group(0) @binding(0) var<uniform> camera: Camera;
@@ -137,11 +187,16 @@ struct SceneConfig {
 @group(0) @binding(5) var<storage, read> triangles: array<Triangle>;
 @group(0) @binding(6) var<storage, read> materials: array<Material>;
 @group(0) @binding(7) var<uniform> scene_config: SceneConfig;
-@group(0) @binding(8) var output_texture: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(8) var output_texture: texture_storage_2d<rgba16float, write>;
 @group(0) @binding(9) var<uniform> debug_params: DebugParams;
 @group(0) @binding(10) var<storage, read_write> debug_info: RayDebugInfo;
 @group(0) @binding(11) var texture_array: texture_2d_array<f32>;
 @group(0) @binding(12) var texture_sampler: sampler;
+@group(0) @binding(13) var skybox_texture: texture_2d<f32>;
+@group(0) @binding(14) var depth_texture: texture_storage_2d<r32float, write>;
+@group(0) @binding(15) var<storage, read> lights: array<Light>;
+// Running average of progressive frames, one RGBA texel per output pixel (mirrors RenderTargets::accumulation_buffer)
+@group(0) @binding(16) var<storage, read_write> accumulation: array<vec4<f32>>;
 
 // LOD culling
 fn should_cull_lod(object_center: vec3<f32>, object_size: vec3<f32>) -> bool {
@@ -189,6 +244,18 @@ fn intersect_aabb(ray: Ray, box_min: vec3<f32>, box_max: vec3<f32>) -> f32 {
     return t_near;
 }
 
+// Checkerboard tile parity at world X/Z (mirrors math::checker_parity)
+fn checker_parity(xz: vec2<f32>, tile: f32) -> bool {
+    let cell = vec2<i32>(floor(xz / tile));
+    return ((cell.x + cell.y) & 1) == 1;
+}
+
+// Rotate `v` by unit quaternion `q` (x, y, z, w)
+fn quat_rotate(q: vec4<f32>, v: vec3<f32>) -> vec3<f32> {
+    let t = 2.0 * cross(q.xyz, v);
+    return v + q.w * t + cross(q.xyz, t);
+}
+
 // Ray-box intersection (detailed hit info)
 fn intersect_box(ray: Ray, box: Box, time: f32, box_idx: u32) -> HitInfo {
     var hit: HitInfo;
@@ -202,10 +269,13 @@ fn intersect_box(ray: Ray, box: Box, time: f32, box_idx: u32) -> HitInfo {
     let interpolated_center = mix(box.center0, box.center1, t_lerp);
     let box_half_size = box.half_size;
 
-    let box_min = interpolated_center - box_half_size;
-    let box_max = interpolated_center + box_half_size;
+    // Slab test in the box's local frame (identity rotation for axis-aligned boxes)
+    let inverse_rotation = vec4<f32>(-box.rotation.xyz, box.rotation.w);
+    var local_ray: Ray;
+    local_ray.origin = quat_rotate(inverse_rotation, ray.origin - interpolated_center);
+    local_ray.direction = quat_rotate(inverse_rotation, ray.direction);
 
-    let t = intersect_aabb(ray, box_min, box_max);
+    let t = intersect_aabb(local_ray, -box_half_size, box_half_size);
     if t < 0.0 {
         return hit;
     }
@@ -214,22 +284,31 @@ fn intersect_box(ray: Ray, box: Box, time: f32, box_idx: u32) -> HitInfo {
     hit.distance = t;
     hit.position = ray.origin + ray.direction * t;
 
-    // Calculate normal using interpolated center
-    let p = hit.position - interpolated_center;
+    // Normal from the dominant local axis, rotated back to world space
+    let p = local_ray.origin + local_ray.direction * t;
     let d = abs(p) - box_half_size;
 
+    var local_normal: vec3<f32>;
     if d.x > d.y && d.x > d.z {
-        hit.normal = vec3<f32>(sign(p.x), 0.0, 0.0);
+        local_normal = vec3<f32>(sign(p.x), 0.0, 0.0);
     } else if d.y > d.z {
-        hit.normal = vec3<f32>(0.0, sign(p.y), 0.0);
+        local_normal = vec3<f32>(0.0, sign(p.y), 0.0);
     } else {
-        hit.normal = vec3<f32>(0.0, 0.0, sign(p.z));
+        local_normal = vec3<f32>(0.0, 0.0, sign(p.z));
     }
+    hit.normal = quat_rotate(box.rotation, local_normal);
 
     hit.color = box.color;
+    if box.checker_tile > 0.0 && checker_parity(hit.position.xz, box.checker_tile) {
+        hit.color = box.checker_color;
+    }
     hit.reflectivity = box.reflectivity;
-    hit.emissive = vec3<f32>(0.0);
-    hit.roughness = 1.0;
+    hit.emissive = box.emissive;
+    // Boxes without a material are mirror-smooth (mirrors BoxData::material_index)
+    hit.roughness = 0.0;
+    if box.material_index >= 0 && u32(box.material_index) < arrayLength(&materials) {
+        hit.roughness = materials[box.material_index].roughness;
+    }
 
     return hit;
 }
@@ -275,6 +354,10 @@ fn intersect_triangle(ray: Ray, tri: Triangle, tri_idx: u32) -> HitInfo {
         hit.distance = t;
         hit.position = ray.origin + ray.direction * t;
         hit.normal = normalize(cross(edge1, edge2));
+        // Smooth shading: interpolate vertex normals (weights match the UVs below)
+        if tri.has_normals > 0.5 {
+            hit.normal = normalize(tri.n0 * (1.0 - u - v) + tri.n1 * u + tri.n2 * v);
+        }
 
         // Get material color
         let mat_id = u32(tri.material_id);
@@ -330,9 +413,9 @@ fn intersect_triangle(ray: Ray, tri: Triangle, tri_idx: u32) -> HitInfo {
                 // Convert from [0,1] to [-1,1] and extract normal
                 let tangent_normal = normalize(normal_sample.rgb * 2.0 - 1.0);
 
-                // For simplicity, we'll perturb the geometric normal
+                // For simplicity, we'll perturb the (smooth or geometric) surface normal
                 // A full implementation would require tangent/bitangent vectors
-                let geometric_normal = normalize(cross(edge1, edge2));
+                let geometric_normal = hit.normal;
 
                 // Create a simple tangent space (not perfect but works for many cases)
                 let up = select(vec3<f32>(0.0, 1.0, 0.0), vec3<f32>(0.0, 0.0, 1.0),
@@ -378,15 +461,11 @@ fn intersect_triangle(ray: Ray, tri: Triangle, tri_idx: u32) -> HitInfo {
 }
 
 // Convert world position to grid cell coordinates
-fn world_to_cell(pos: vec3<f32>, cell_size: f32) -> vec3<u32> {
+fn world_to_cell(pos: vec3<f32>, cell_size: vec3<f32>) -> vec3<u32> {
     let rel_pos = pos - grid_meta.bounds_min;
     // Use multiplication by inverse to avoid division by zero
-    let inv_cell_size = 1.0 / max(cell_size, 1e-8);
-    return vec3<u32>(
-        u32(max(0.0, floor(rel_pos.x * inv_cell_size))),
-        u32(max(0.0, floor(rel_pos.y * inv_cell_size))),
-        u32(max(0.0, floor(rel_pos.z * inv_cell_size)))
-    );
+    let inv_cell_size = 1.0 / max(cell_size, vec3<f32>(1e-8));
+    return vec3<u32>(max(vec3<f32>(0.0), floor(rel_pos * inv_cell_size)));
 }
 
 // Get flattened index for coarse level
@@ -415,7 +494,7 @@ fn is_cell_valid(cell: vec3<u32>, level: u32) -> bool {
 }
 
 // Check if near grid boundary
-fn is_near_grid_boundary(pos: vec3<f32>, cell_size: f32, threshold: f32) -> bool {
+fn is_near_grid_boundary(pos: vec3<f32>, cell_size: vec3<f32>, threshold: f32) -> bool {
     let rel_pos = pos - grid_meta.bounds_min;
     let cell_local = (rel_pos % cell_size) / cell_size;
 
@@ -426,15 +505,30 @@ fn is_near_grid_boundary(pos: vec3<f32>, cell_size: f32, threshold: f32) -> bool
     return dist_x < threshold || dist_y < threshold || dist_z < threshold;
 }
 
+// Traversal cost heatmap: few steps blue, many steps red (mirrors math::step_heatmap_color)
+fn step_heatmap_color(num_steps: f32) -> vec3<f32> {
+    let t = clamp(num_steps / HEATMAP_MAX_STEPS, 0.0, 1.0);
+    let h = (1.0 - t) * 4.0;  // Hue in sextants: 4 = blue, 0 = red
+    let x = 1.0 - abs(h % 2.0 - 1.0);
+
+    if h < 1.0 {
+        return vec3<f32>(1.0, x, 0.0);
+    } else if h < 2.0 {
+        return vec3<f32>(x, 1.0, 0.0);
+    } else if h < 3.0 {
+        return vec3<f32>(0.0, 1.0, x);
+    }
+    return vec3<f32>(0.0, x, 1.0);
+}
+
 // DDA ray marching through grid
-fn trace_ray(ray: Ray) -> TraceResult {
-    var result: TraceResult;
-    result.num_steps = 0.0;
-    result.object_id = -1.0;
-    result.hit = false;
-    result.distance = 1e10;
-    result.reflectivity = 0.0;
+// Hits are only accepted within [near, far] (mirrors math::within_clip_range)
+fn within_clip_range(distance: f32, near: f32, far: f32) -> bool {
+    return distance >= near && distance <= far;
+}
 
+// Nearest box or triangle within [near, far]; counts steps and records the object id in `result`
+fn find_closest_hit(ray: Ray, near: f32, far: f32, result: ptr<function, TraceResult>) -> HitInfo {
     var closest_hit: HitInfo;
     closest_hit.hit = false;
     closest_hit.distance = 1e10;
@@ -445,16 +539,16 @@ fn trace_ray(ray: Ray) -> TraceResult {
         // Assume last 3 boxes are moving (hardcoded for performance)
         let moving_start = select(0u, num_boxes - 3u, num_boxes >= 3u);
         for (var i = moving_start; i < num_boxes; i++) {
-            result.num_steps += 1.0;
+            (*result).num_steps += 1.0;
             let t_lerp = (sin(camera.time * 2.0) + 1.0) * 0.5;
             let box_center = mix(boxes[i].center0, boxes[i].center1, t_lerp);
             let box_size = boxes[i].half_size * 2.0;
 
             if !should_cull_lod(box_center, box_size) {
                 let hit = intersect_box(ray, boxes[i], camera.time, i);
-                if hit.hit && hit.distance < closest_hit.distance {
+                if hit.hit && within_clip_range(hit.distance, near, far) && hit.distance < closest_hit.distance {
                     closest_hit = hit;
-                    result.object_id = f32(i);
+                    (*result).object_id = f32(i);
                 }
             }
         }
@@ -475,11 +569,9 @@ fn trace_ray(ray: Ray) -> TraceResult {
        ray_pos.z < bounds_min.z || ray_pos.z > bounds_max.z {
         let t_entry = intersect_aabb(ray, bounds_min, bounds_max);
         if t_entry < 0.0 {
-            // Ray misses grid - return sky
-            let t = (ray.direction.y + 1.0) * 0.5;
-            result.color = mix(vec3<f32>(0.3, 0.5, 0.7), vec3<f32>(0.5, 0.7, 1.0), t);
-            result.hit = false;
-            return result;
+            // Ray misses grid - no hit
+            closest_hit.hit = false;
+            return closest_hit;
         }
         t_offset = t_entry + 0.001;
         ray_pos = ray.origin + ray.direction * t_offset;
@@ -489,7 +581,8 @@ fn trace_ray(ray: Ray) -> TraceResult {
     }
 
     // DDA setup
-    var current_cell = world_to_cell(ray_pos, cell_size);
+    // Clamped: cells tile the bounds exactly, so rounding at bounds_max can land one past the end
+    var current_cell = min(world_to_cell(ray_pos, cell_size), grid_size - vec3<u32>(1u));
 
     let step = vec3<i32>(
         select(-1, 1, ray.direction.x >= 0.0),
@@ -499,9 +592,9 @@ fn trace_ray(ray: Ray) -> TraceResult {
 
     let cell_pos_world = bounds_min + vec3<f32>(current_cell) * cell_size;
     let next_boundary = cell_pos_world + vec3<f32>(
-        select(0.0, cell_size, step.x > 0),
-        select(0.0, cell_size, step.y > 0),
-        select(0.0, cell_size, step.z > 0)
+        select(0.0, cell_size.x, step.x > 0),
+        select(0.0, cell_size.y, step.y > 0),
+        select(0.0, cell_size.z, step.z > 0)
     );
 
     const EPSILON: f32 = 1e-8;
@@ -518,7 +611,7 @@ fn trace_ray(ray: Ray) -> TraceResult {
 
     // DDA traversal
     for (var i = 0; i < 200; i++) {
-        result.num_steps += 1.0;
+        (*result).num_steps += 1.0;
 
         // Test objects in current cell
         let fine_idx = get_fine_index(current_cell);
@@ -538,9 +631,9 @@ fn trace_ray(ray: Ray) -> TraceResult {
 
                     if !should_cull_lod(box_center, box_size) {
                         let hit = intersect_box(ray, box, camera.time, obj_idx);
-                        if hit.hit && hit.distance < closest_hit.distance {
+                        if hit.hit && within_clip_range(hit.distance, near, far) && hit.distance < closest_hit.distance {
                             closest_hit = hit;
-                            result.object_id = f32(obj_idx);
+                            (*result).object_id = f32(obj_idx);
                         }
                     }
                 } else {
@@ -548,9 +641,9 @@ fn trace_ray(ray: Ray) -> TraceResult {
                     let tri_idx = obj_idx - num_boxes;
                     if tri_idx < scene_config.num_triangles {
                         let hit = intersect_triangle(ray, triangles[tri_idx], tri_idx);
-                        if hit.hit && hit.distance < closest_hit.distance {
+                        if hit.hit && within_clip_range(hit.distance, near, far) && hit.distance < closest_hit.distance {
                             closest_hit = hit;
-                            result.object_id = f32(obj_idx);
+                            (*result).object_id = f32(obj_idx);
                         }
                     }
                 }
@@ -558,7 +651,12 @@ fn trace_ray(ray: Ray) -> TraceResult {
         }
 
         // If we found a hit closer than next cell, stop
-        if closest_hit.hit && closest_hit.distance < min(min(t_max.x, t_max.y), t_max.z) {
+        let next_cell_t = min(min(t_max.x, t_max.y), t_max.z);
+        if closest_hit.hit && closest_hit.distance < next_cell_t {
+            break;
+        }
+        // Nothing past the far clip can be accepted
+        if next_cell_t > far {
             break;
         }
 
@@ -587,84 +685,174 @@ fn trace_ray(ray: Ray) -> TraceResult {
         }
     }
 
-    // If no hit, return sky color
+    return closest_hit;
+}
+
+// Whether nothing blocks the segment from `position` towards a light `distance` away
+fn light_visible(position: vec3<f32>, normal: vec3<f32>, to_light: vec3<f32>, distance: f32) -> bool {
+    var scratch: TraceResult;
+    let shadow_ray = Ray(position + normal * SHADOW_BIAS, to_light);
+    return !find_closest_hit(shadow_ray, 0.0, distance - SHADOW_BIAS, &scratch).hit;
+}
+
+// Summed color of every unshadowed light reaching a surface
+fn direct_lighting(position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    var total = vec3<f32>(0.0);
+    let count = min(scene_config.num_lights, arrayLength(&lights));
+    for (var i = 0u; i < count; i++) {
+        let light = lights[i];
+        var to_light = -light.position;
+        var distance = NO_FAR_CLIP;
+        var falloff = 1.0;
+        if light.light_type == LIGHT_POINT {
+            let offset = light.position - position;
+            distance = length(offset);
+            to_light = offset / max(distance, EPSILON);
+            falloff = 1.0 / max(distance * distance, EPSILON);
+        }
+        let n_dot_l = dot(normal, to_light);
+        if n_dot_l > 0.0 && light_visible(position, normal, to_light, distance) {
+            total += light.color * (light.intensity * falloff * n_dot_l);
+        }
+    }
+    return total;
+}
+
+fn trace_ray(ray: Ray, near: f32, far: f32) -> TraceResult {
+    var result: TraceResult;
+    result.num_steps = 0.0;
+    result.object_id = -1.0;
+    result.hit = false;
+    result.distance = 1e10;
+    result.reflectivity = 0.0;
+
+    let closest_hit = find_closest_hit(ray, near, far, &result);
     if !closest_hit.hit {
-        let t = (ray.direction.y + 1.0) * 0.5;
-        result.color = mix(vec3<f32>(0.3, 0.5, 0.7), vec3<f32>(0.5, 0.7, 1.0), t);
-        result.hit = false;
+        result.color = sky_color(ray.direction);
         return result;
     }
 
-    // Lighting: ambient + directional
-    let light_dir = normalize(LIGHT_DIRECTION);
-    let diffuse = max(dot(closest_hit.normal, -light_dir), 0.0);
-    let ambient = 0.3;
-
-    var final_color = closest_hit.color * (ambient + diffuse * 0.7) + closest_hit.emissive;
+    // Lighting: ambient + every light in the scene, with shadows
+    let diffuse = direct_lighting(closest_hit.position, closest_hit.normal);
+    var final_color = closest_hit.color * (camera.ambient + diffuse) + closest_hit.emissive;
+    var reflectivity = closest_hit.reflectivity;
+
+    // Geometry debug views replace lighting and disable reflections
+    if debug_params.shading_mode == SHADING_NORMALS {
+        final_color = normal_to_color(closest_hit.normal);
+        reflectivity = 0.0;
+    } else if debug_params.shading_mode == SHADING_FLAT {
+        final_color = closest_hit.color * max(dot(closest_hit.normal, vec3<f32>(0.0, 1.0, 0.0)), 0.0);
+        reflectivity = 0.0;
+    }
+    if debug_params.reflections_enabled == 0u {
+        reflectivity = 0.0;
+    }
 
     // Grid visualization
     if camera.show_grid > 0.5 {
         let threshold = 0.02;
-        if is_near_grid_boundary(closest_hit.position, cell_size, threshold) {
+        if is_near_grid_boundary(closest_hit.position, grid_meta.finest_cell_size, threshold) {
             final_color = mix(final_color, vec3<f32>(0.0, 1.0, 0.0), 0.6);
         }
     }
 
+    if scene_config.fog_density > 0.0 {
+        final_color = mix(final_color, scene_config.fog_color, fog_factor(closest_hit.distance, scene_config.fog_density));
+    }
+
     result.color = final_color;
     result.hit = true;
     result.distance = closest_hit.distance;
     result.position = closest_hit.position;
     result.normal = closest_hit.normal;
     result.hit_color = closest_hit.color;
-    result.reflectivity = closest_hit.reflectivity;
+    result.reflectivity = reflectivity;
+    result.roughness = closest_hit.roughness;
 
     return result;
 }
 
-@compute @workgroup_size(8, 8, 1)
-fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
-    let screen_size = textureDimensions(output_texture);
+// Equirectangular texture coordinates of a unit direction (mirrors math::dir_to_equirect_uv)
+fn dir_to_equirect_uv(dir: vec3<f32>) -> vec2<f32> {
+    let u = 0.5 + atan2(dir.z, dir.x) / (2.0 * PI);
+    let v = acos(clamp(dir.y, -1.0, 1.0)) / PI;
+    return vec2<f32>(u, v);
+}
 
-    if global_id.x >= screen_size.x || global_id.y >= screen_size.y {
-        return;
-    }
+// Analytic sky lit by the key light (mirrors sky::sky_color)
+fn procedural_sky(dir: vec3<f32>, sun_dir: vec3<f32>) -> vec3<f32> {
+    let horizon_blend = pow(1.0 - max(dir.y, 0.0), 4.0);
+    let base = mix(vec3<f32>(0.25, 0.45, 0.85), vec3<f32>(0.75, 0.85, 0.95), horizon_blend);
 
-    let pixel_coords = vec2<i32>(global_id.xy);
+    let sun_cos = max(dot(dir, -sun_dir), 0.0);
+    let glow = pow(sun_cos, 64.0) * 0.5;
+    let disk = select(0.0, 2.0, sun_cos > 0.9995);
 
-    let uv = vec2<f32>(
-        (f32(pixel_coords.x) + 0.5) / f32(screen_size.x),
-        (f32(pixel_coords.y) + 0.5) / f32(screen_size.y)
-    );
+    return base + vec3<f32>(1.0, 0.95, 0.8) * (glow + disk);
+}
 
-    let ndc = uv * 2.0 - 1.0;
+// Background seen by rays that escape the scene: the skybox image, else the `sky_mode` background
+fn sky_color(dir: vec3<f32>) -> vec3<f32> {
+    if scene_config.skybox_enabled != 0u {
+        return textureSampleLevel(skybox_texture, texture_sampler, dir_to_equirect_uv(dir), 0.0).rgb;
+    }
+    if scene_config.sky_mode == 1u {
+        return procedural_sky(dir, scene_config.sun_direction);
+    }
+    if scene_config.sky_mode == 2u {
+        return scene_config.sky_top;
+    }
+    // Vertical gradient (mirrors sky::gradient_color)
+    let t = (dir.y + 1.0) * 0.5;
+    return mix(scene_config.sky_bottom, scene_config.sky_top, t);
+}
 
-    // Generate ray from camera
-    let aspect_ratio = f32(screen_size.x) / f32(screen_size.y);
-    let fov_scale = tan(DEFAULT_FOV);
+// Share of the fog color at a hit distance (mirrors fog::fog_factor)
+fn fog_factor(distance: f32, density: f32) -> f32 {
+    return 1.0 - exp(-density * distance);
+}
 
-    let ray_dir = normalize(
-        camera.forward +
-        camera.right * ndc.x * aspect_ratio * fov_scale +
-        camera.up * -ndc.y * fov_scale
-    );
+// Hit distance mapped to [0, 1) for the output alpha (mirrors math::encode_depth)
+fn encode_depth(distance: f32) -> f32 {
+    return distance / (1.0 + distance);
+}
 
-    var ray: Ray;
-    ray.origin = camera.position;
-    ray.direction = ray_dir;
+// Map a unit normal from [-1, 1] to an RGB color in [0, 1] (mirrors math::normal_to_color)
+fn normal_to_color(normal: vec3<f32>) -> vec3<f32> {
+    return normal * 0.5 + 0.5;
+}
 
-    // Trace with multiple reflection bounces
+// Tilt a reflection direction by a random disk offset scaled by roughness (mirrors math::glossy_reflect)
+fn glossy_reflect(dir: vec3<f32>, roughness: f32, u: vec2<f32>) -> vec3<f32> {
+    if roughness <= 0.0 {
+        return dir;
+    }
+    let helper = select(vec3<f32>(1.0, 0.0, 0.0), vec3<f32>(0.0, 1.0, 0.0), abs(dir.x) > 0.9);
+    let tangent = normalize(cross(helper, dir));
+    let bitangent = cross(dir, tangent);
+    let offset = concentric_disk_sample(u) * roughness;
+    return normalize(dir + tangent * offset.x + bitangent * offset.y);
+}
+
+// Trace a ray with reflection bounces, returning its color and the primary hit
+fn trace_path(ray: Ray, pixel: vec2<u32>, sample: u32, first_hit: ptr<function, TraceResult>) -> vec3<f32> {
     const MAX_BOUNCES: u32 = 8u;
     var accumulated_color = vec3<f32>(0.0);
     var current_ray = ray;
     var reflection_multiplier = 1.0;
 
-    var first_trace_result: TraceResult;
-
     for (var bounce = 0u; bounce < MAX_BOUNCES; bounce++) {
-        let trace_result = trace_ray(current_ray);
+        // Clipping applies to primary rays only; reflections see the whole scene
+        var trace_result: TraceResult;
+        if bounce == 0u {
+            trace_result = trace_ray(current_ray, camera.near, camera.far);
+        } else {
+            trace_result = trace_ray(current_ray, 0.0, NO_FAR_CLIP);
+        }
 
         if bounce == 0u {
-            first_trace_result = trace_result;
+            *first_hit = trace_result;
         }
 
         if !trace_result.hit {
@@ -682,16 +870,140 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
 
         reflection_multiplier *= trace_result.reflectivity;
 
-        // Calculate reflection ray
-        let reflect_dir = reflect(current_ray.direction, trace_result.normal);
+        // Calculate reflection ray, blurred by surface roughness
+        let mirror_dir = reflect(current_ray.direction, trace_result.normal);
+        var reflect_dir = mirror_dir;
+        if trace_result.roughness > 0.0 {
+            let u = random_unit_pair(pixel, pcg_hash(sample * MAX_BOUNCES + bounce) ^ GLOSSY_SEED);
+            reflect_dir = glossy_reflect(mirror_dir, trace_result.roughness, u);
+            // Keep rays from tunnelling below the surface
+            if dot(reflect_dir, trace_result.normal) <= 0.0 {
+                reflect_dir = mirror_dir;
+            }
+        }
         let reflect_origin = trace_result.position + trace_result.normal * 0.001;
 
         current_ray.origin = reflect_origin;
         current_ray.direction = reflect_dir;
     }
 
+    return accumulated_color;
+}
+
+// PCG hash for per-pixel, per-sample random numbers
+fn pcg_hash(input: u32) -> u32 {
+    let state = input * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn random_unit_pair(pixel: vec2<u32>, sample: u32) -> vec2<f32> {
+    let seed = pcg_hash(pixel.x ^ pcg_hash(pixel.y ^ pcg_hash(sample)));
+    let u = f32(pcg_hash(seed)) / 4294967295.0;
+    let v = f32(pcg_hash(seed + 1u)) / 4294967295.0;
+    return vec2<f32>(u, v);
+}
+
+// Shirley-Chiu concentric square-to-disk mapping (mirrors math::concentric_disk_sample)
+fn concentric_disk_sample(u: vec2<f32>) -> vec2<f32> {
+    let offset = u * 2.0 - 1.0;
+    if offset.x == 0.0 && offset.y == 0.0 {
+        return vec2<f32>(0.0);
+    }
+
+    var r: f32;
+    var theta: f32;
+    if abs(offset.x) > abs(offset.y) {
+        r = offset.x;
+        theta = (PI / 4.0) * (offset.y / offset.x);
+    } else {
+        r = offset.y;
+        theta = (PI / 2.0) - (PI / 4.0) * (offset.x / offset.y);
+    }
+    return r * vec2<f32>(cos(theta), sin(theta));
+}
+
+// Jitter the origin across the lens and aim through the focal point of the pinhole ray
+fn thin_lens_ray(pinhole: Ray, pixel: vec2<u32>, sample: u32) -> Ray {
+    let focal_point = pinhole.origin + pinhole.direction * camera.focus_distance;
+    let lens = concentric_disk_sample(random_unit_pair(pixel, sample)) * camera.aperture * 0.5;
+    let lens_up = cross(camera.right, camera.forward);
+
+    var ray: Ray;
+    ray.origin = pinhole.origin + camera.right * lens.x + lens_up * lens.y;
+    ray.direction = normalize(focal_point - ray.origin);
+    return ray;
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let screen_size = textureDimensions(output_texture);
+
+    if global_id.x >= screen_size.x || global_id.y >= screen_size.y {
+        return;
+    }
+
+    let pixel_coords = vec2<i32>(global_id.xy);
+
+    let uv = vec2<f32>(
+        (f32(pixel_coords.x) + 0.5) / f32(screen_size.x),
+        (f32(pixel_coords.y) + 0.5) / f32(screen_size.y)
+    );
+
+    let ndc = uv * 2.0 - 1.0;
+
+    // Generate ray from camera; horizontal extent scaled by width / height (mirrors Camera::primary_ray)
+    let aspect_ratio = f32(screen_size.x) / f32(screen_size.y);
+    let fov_scale = tan(camera.fov);
+
+    let ray_dir = normalize(
+        camera.forward +
+        camera.right * ndc.x * aspect_ratio * fov_scale +
+        camera.up * -ndc.y * fov_scale
+    );
+
+    var ray: Ray;
+    ray.origin = camera.position;
+    ray.direction = ray_dir;
+
+    // Center-of-lens sample doubles as the pinhole ray used for debug output; accumulated
+    // frames after the first jitter it too so depth of field converges with one sample per pixel
+    let sample_base = camera.sample_offset * camera.samples_per_pixel;
+    var first_ray = ray;
+    if camera.aperture > 0.0 && sample_base > 0u {
+        first_ray = thin_lens_ray(ray, global_id.xy, sample_base);
+    }
+    var first_trace_result: TraceResult;
+    var accumulated_color = trace_path(first_ray, global_id.xy, sample_base, &first_trace_result);
+
+    // Thin-lens depth of field and glossy reflections need multiple samples to converge
+    if camera.samples_per_pixel > 1u {
+        var lens_trace_result: TraceResult;
+        for (var s = 1u; s < camera.samples_per_pixel; s++) {
+            var sample_ray = ray;
+            if camera.aperture > 0.0 {
+                sample_ray = thin_lens_ray(ray, global_id.xy, sample_base + s);
+            }
+            accumulated_color += trace_path(sample_ray, global_id.xy, sample_base + s, &lens_trace_result);
+        }
+        accumulated_color /= f32(camera.samples_per_pixel);
+    }
+
+    // Progressive frames blend into the running average; frame 0 starts a fresh image
+    let accumulation_index = global_id.y * screen_size.x + global_id.x;
+    if camera.sample_offset > 0u {
+        let weight = 1.0 / f32(camera.sample_offset + 1u);
+        accumulated_color = mix(accumulation[accumulation_index].rgb, accumulated_color, weight);
+    }
+    accumulation[accumulation_index] = vec4<f32>(accumulated_color, 1.0);
+
     var final_color = accumulated_color;
 
+    // Grid occupancy heatmap (primary ray traversal cost)
+    if debug_params.heatmap > 0u {
+        final_color = mix(final_color, step_heatmap_color(first_trace_result.num_steps), 0.75);
+    }
+
     // Debug pixel highlighting
     let is_debug_pixel = debug_params.enabled > 0u &&
                          global_id.x == debug_params.debug_pixel.x &&
@@ -717,5 +1029,13 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         }
     }
 
-    textureStore(output_texture, pixel_coords, vec4<f32>(final_color, 1.0));
+    // Wireframe: the display pass finds edges in the encoded primary hit depth
+    var alpha = 1.0;
+    if debug_params.wireframe != 0u && first_trace_result.hit {
+        alpha = encode_depth(first_trace_result.distance);
+    }
+
+    textureStore(output_texture, pixel_coords, vec4<f32>(final_color, alpha));
+    // Primary hit distance for fog-aware compositing; misses read as NO_FAR_CLIP
+    textureStore(depth_texture, pixel_coords, vec4<f32>(select(NO_FAR_CLIP, first_trace_result.distance, first_trace_result.hit), 0.0, 0.0, 0.0));
 }
@@ -0,0 +1,189 @@
+use crate::camera::{DEFAULT_FAR_CLIP, DEFAULT_NEAR_CLIP};
+use crate::sky::{SkyMode, GRADIENT_BOTTOM, GRADIENT_TOP};
+use crate::types::DebugParams;
+
+/// How hit surfaces are shaded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShadingMode {
+    /// Full lighting with reflections
+    #[default]
+    Lit,
+    /// Surface normal mapped to RGB
+    Normals,
+    /// Single Lambert term against a fixed overhead light
+    Flat,
+}
+
+impl ShadingMode {
+    /// Value of `DebugParams::shading_mode` understood by the shader
+    pub const fn as_u32(self) -> u32 {
+        match self {
+            Self::Lit => 0,
+            Self::Normals => 1,
+            Self::Flat => 2,
+        }
+    }
+}
+
+/// Tone curve applied by the display pass
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ToneMap {
+    /// Clamp only
+    #[default]
+    None,
+    Reinhard,
+    Aces,
+}
+
+impl ToneMap {
+    pub const ALL: [ToneMap; 3] = [ToneMap::None, ToneMap::Reinhard, ToneMap::Aces];
+
+    /// Value of `DisplayParams::tone_map` understood by display.wgsl
+    pub const fn as_u32(self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::Reinhard => 1,
+            Self::Aces => 2,
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Reinhard => "Reinhard",
+            Self::Aces => "ACES",
+        }
+    }
+}
+
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+pub const MAX_RENDER_SCALE: f32 = 1.0;
+/// Upper end of the near clip slider
+pub const MAX_NEAR_CLIP: f32 = 100.0;
+
+/// Ray traced image size for a `width` x `height` window at `scale` (never below 1x1)
+pub fn scaled_size(width: u32, height: u32, scale: f32) -> (u32, u32) {
+    let scale = scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+    let scale_axis = |n: u32| ((n as f32 * scale).round() as u32).max(1);
+    (scale_axis(width), scale_axis(height))
+}
+
+/// User-tweakable render settings shown in the debug UI
+///
+/// Compared frame to frame: any difference invalidates accumulated frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderSettings {
+    pub show_grid: bool,
+    pub step_heatmap: bool,
+    pub shading: ShadingMode,
+    pub tone_map: ToneMap,
+    /// Fraction of the window resolution that is ray traced, then upsampled
+    pub render_scale: f32,
+    /// Trace reflection bounces; off shades every surface as diffuse
+    pub reflections: bool,
+    /// Darken depth and shading discontinuities in the display pass
+    pub wireframe: bool,
+    /// Primary-ray clip range; hits outside it show the background
+    pub near_clip: f32,
+    pub far_clip: f32,
+    /// Background for rays that miss; a `--skybox` image still takes precedence
+    pub sky: SkyMode,
+    pub sky_top: [f32; 3],
+    pub sky_bottom: [f32; 3],
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            show_grid: false,
+            step_heatmap: false,
+            shading: ShadingMode::default(),
+            tone_map: ToneMap::default(),
+            render_scale: MAX_RENDER_SCALE,
+            reflections: true,
+            wireframe: false,
+            near_clip: DEFAULT_NEAR_CLIP,
+            far_clip: DEFAULT_FAR_CLIP,
+            sky: SkyMode::default(),
+            sky_top: GRADIENT_TOP,
+            sky_bottom: GRADIENT_BOTTOM,
+        }
+    }
+}
+
+impl RenderSettings {
+    /// True if `self` differs from the settings used for the previous frame
+    pub fn changed_since(&self, previous: &Self) -> bool {
+        self != previous
+    }
+
+    /// Shader debug/shading uniform, inspecting `debug_pixel` (output texture coordinates) when set
+    pub fn debug_params(&self, debug_pixel: Option<[u32; 2]>) -> DebugParams {
+        DebugParams {
+            debug_pixel: debug_pixel.unwrap_or([0, 0]),
+            enabled: debug_pixel.is_some() as u32,
+            heatmap: self.step_heatmap as u32,
+            shading_mode: self.shading.as_u32(),
+            reflections_enabled: self.reflections as u32,
+            wireframe: self.wireframe as u32,
+            _pad: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_copy_is_not_a_change() {
+        let settings = RenderSettings { show_grid: true, ..Default::default() };
+        let copy = settings;
+        assert!(!copy.changed_since(&settings));
+    }
+
+    #[test]
+    fn test_every_field_is_detected() {
+        let base = RenderSettings::default();
+        let mutations: [fn(&mut RenderSettings); 12] = [
+            |s| s.show_grid = !s.show_grid,
+            |s| s.step_heatmap = !s.step_heatmap,
+            |s| s.shading = ShadingMode::Normals,
+            |s| s.tone_map = ToneMap::Aces,
+            |s| s.render_scale = 0.5,
+            |s| s.reflections = !s.reflections,
+            |s| s.wireframe = !s.wireframe,
+            |s| s.near_clip = 2.0,
+            |s| s.far_clip = 50.0,
+            |s| s.sky = SkyMode::Solid,
+            |s| s.sky_top = [0.0; 3],
+            |s| s.sky_bottom = [1.0; 3],
+        ];
+
+        for mutate in mutations {
+            let mut changed = base;
+            mutate(&mut changed);
+            assert!(changed.changed_since(&base), "{:?} not detected", changed);
+        }
+    }
+
+    #[test]
+    fn test_reflections_thread_into_uniform() {
+        let settings = RenderSettings::default();
+        assert!(settings.reflections);
+        assert_eq!(settings.debug_params(None).reflections_enabled, 1);
+
+        let diffuse = RenderSettings { reflections: false, ..settings };
+        assert_eq!(diffuse.debug_params(None).reflections_enabled, 0);
+        assert_eq!(diffuse.debug_params(Some([3, 4])).debug_pixel, [3, 4]);
+        assert_eq!(diffuse.debug_params(Some([3, 4])).enabled, 1);
+    }
+
+    #[test]
+    fn test_scaled_size() {
+        assert_eq!(scaled_size(1920, 1080, 1.0), (1920, 1080));
+        assert_eq!(scaled_size(1920, 1080, 0.5), (960, 540));
+        assert_eq!(scaled_size(1920, 1080, 0.01), (480, 270), "clamped to MIN_RENDER_SCALE");
+        assert_eq!(scaled_size(1, 1, MIN_RENDER_SCALE), (1, 1));
+    }
+}
@@ -1,43 +1,405 @@
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use anyhow::Context;
 use wgpu::util::DeviceExt;
 use winit::window::Window;
-use crate::camera::Camera;
-use crate::grid::HierarchicalGrid;
-use crate::scenes::{create_composed_scene, create_default_scene, create_fractal_scene, create_walls_scene, create_tunnel_scene, create_reflected_scene, create_gltf_triangles, create_pyramid_triangles};
-use crate::types::{RayDebugInfo, DebugParams, SceneConfig, MaterialData, TriangleData};
+use crate::accumulation::Accumulation;
+use crate::camera::{Camera, CameraBookmark};
+use crate::dirty::DirtyFlags;
+use crate::editor::{pick, BoxEdit, EditHistory};
+use crate::gpu_timing::GpuTimer;
+use crate::exposure::{exposure_step, LuminanceMeter, MAX_EXPOSURE, MIN_EXPOSURE};
+use crate::grid::{GridStats, HierarchicalGrid};
+use crate::lighting::LightingConfig;
+use crate::loaders::{load_skybox, TextureData};
+use crate::camera::DEFAULT_FAR_CLIP;
+use crate::resolution::{fit_rect, Rect, Resolution};
+use crate::render_settings::{scaled_size, RenderSettings, ShadingMode, ToneMap, MAX_NEAR_CLIP, MAX_RENDER_SCALE, MIN_RENDER_SCALE};
+use crate::scenes::{create_composed_scene, create_default_scene, create_fractal_scene, fractal_lod, create_walls_scene, create_tunnel_scene, create_reflected_scene, create_stress_scene, create_gltf_triangles, create_pyramid_triangles, load_scene, save_scene, scene_json, scene_backup_path, scene_save_path, watch, SceneFile, with_reflective_floor, default_ambient, SceneWatcher, scene_lights, SCENE_FILE_PREFIX};
+use crate::sky::SkyMode;
+use crate::types::{BoxData, CameraUniform, RayDebugInfo, DisplayParams, LightData, SceneConfig, MaterialData, TriangleData};
 
 pub const WORKGROUP_SIZE: u32 = 8;
-const DEFAULT_FOV: f32 = std::f32::consts::FRAC_PI_4;  // π/4 = 45 degrees = 0.785398
+
+/// Compute workgroups covering an image of `width` x `height` pixels
+pub fn workgroup_counts((width, height): (u32, u32)) -> (u32, u32) {
+    (width.div_ceil(WORKGROUP_SIZE), height.div_ceil(WORKGROUP_SIZE))
+}
+
+/// HDR ray traced image, tone mapped to the surface by the display pass
+pub const OUTPUT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// Bytes per `OUTPUT_FORMAT` texel
+pub const OUTPUT_TEXEL_BYTES: u32 = 8;
+/// Bytes per pixel of the progressive accumulation buffer (`vec4<f32>`)
+const ACCUMULATION_TEXEL_BYTES: u64 = 16;
+
+/// Bytes per row of a texture copy `width` texels of `texel_bytes` wide, padded to the copy alignment
+pub fn padded_row_bytes(width: u32, texel_bytes: u32) -> u32 {
+    (width * texel_bytes).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+}
+
+/// Part of the window showing the image: all of it, or the `aspect` rect with black bars
+fn viewport(size: winit::dpi::PhysicalSize<u32>, aspect: Option<f32>) -> Rect {
+    let window = Resolution::new(size.width, size.height);
+    fit_rect(aspect.unwrap_or(window.aspect_ratio()), window.aspect_ratio(), window)
+}
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+/// Per-scene settings from the command line, reapplied on every scene load
+#[derive(Debug, Clone, Default)]
+pub struct SceneOptions {
+    pub max_triangles: Option<usize>,
+    /// Box count for the `stress` scene
+    pub stress_count: usize,
+    pub reflective_floor: Option<f32>,
+    /// Ambient light override; `None` uses the scene's default
+    pub ambient: Option<f32>,
+    pub lighting: LightingConfig,
+    /// Lights replacing the scene's own; empty uses the directional lights of `lighting` plus the scene's
+    /// `DemoScene::lights` (see `resolve_lights`)
+    pub lights: Vec<LightData>,
+    pub sky: SkyMode,
+    /// Equirectangular background image; the gradient sky when `None`
+    pub skybox: Option<PathBuf>,
+    pub auto_exposure: bool,
+    /// Exponential fog density; 0 disables fog
+    pub fog_density: f32,
+    pub fog_color: [f32; 3],
+    /// Locked image aspect (width / height), letterboxed in other window shapes; `None` stretches to the window
+    pub aspect: Option<f32>,
+    /// Detail budget for a distance-based LOD fractal scene; `None` generates full detail
+    pub lod_budget: Option<f32>,
+    /// Camera position the LOD fractal is refined around
+    pub lod_origin: glam::Vec3,
+}
+
+/// Whether the egui windows are drawn: never with `--no-ui`, otherwise toggled at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UiVisibility {
+    disabled: bool,
+    visible: bool,
+}
+
+impl UiVisibility {
+    pub fn new(no_ui: bool) -> Self {
+        Self { disabled: no_ui, visible: true }
+    }
+
+    pub fn toggled(self) -> Self {
+        Self { visible: !self.visible, ..self }
+    }
+
+    /// Checked by the render path before building any egui window
+    pub fn shows_windows(self) -> bool {
+        self.visible && !self.disabled
+    }
+}
+
+/// Scene data for `ComputeResources::new`, shared by `RayTracer` and the ray tracing layer
+pub(crate) struct SceneUpload<'a> {
+    pub grid: &'a HierarchicalGrid,
+    pub boxes: &'a [BoxData],
+    pub triangles: &'a [TriangleData],
+    pub materials: &'a [MaterialData],
+    pub textures: &'a [TextureData],
+    pub lights: &'a [LightData],
+    pub scene_config: SceneConfig,
+    pub skybox: Option<&'a TextureData>,
+}
+
+/// Everything in the compute bind group except the output texture
+pub(crate) struct ComputeResources {
+    pub camera_buffer: wgpu::Buffer,
+    pub grid_meta_buffer: wgpu::Buffer,
+    pub coarse_buffer: wgpu::Buffer,
+    pub fine_buffer: wgpu::Buffer,
+    pub box_buffer: wgpu::Buffer,
+    pub triangle_buffer: wgpu::Buffer,
+    pub material_buffer: wgpu::Buffer,
+    pub scene_config_buffer: wgpu::Buffer,
+    pub light_buffer: wgpu::Buffer,
+    pub debug_params_buffer: wgpu::Buffer,
+    pub debug_info_buffer: wgpu::Buffer,
+    pub texture_array_view: wgpu::TextureView,
+    pub texture_sampler: wgpu::Sampler,
+    pub skybox_view: wgpu::TextureView,
+}
+
+impl ComputeResources {
+    /// Upload a scene; empty geometry lists get a placeholder element since bindings cannot be zero-sized
+    pub(crate) fn new(device: &wgpu::Device, queue: &wgpu::Queue, scene: &SceneUpload) -> Self {
+        let (grid_meta_buffer, coarse_buffer, fine_buffer, box_buffer) =
+            Self::create_geometry_buffers(device, scene.grid, scene.boxes);
+
+        let dummy_triangle = [TriangleData::new([0.0; 3], [0.0; 3], [0.0; 3], [0.0; 2], [0.0; 2], [0.0; 2], 0)];
+        let triangle_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Triangle Buffer"),
+            contents: if scene.triangles.is_empty() {
+                bytemuck::cast_slice(&dummy_triangle)
+            } else {
+                bytemuck::cast_slice(scene.triangles)
+            },
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+
+        let dummy_material = [MaterialData::new_color([1.0, 1.0, 1.0, 1.0])];
+        let material_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Material Buffer"),
+            contents: if scene.materials.is_empty() {
+                bytemuck::cast_slice(&dummy_material)
+            } else {
+                bytemuck::cast_slice(scene.materials)
+            },
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+
+        // Create texture sampler
+        let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            address_mode_w: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let light_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Light Buffer"),
+            contents: bytemuck::cast_slice(scene.lights),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let scene_config_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Scene Config Buffer"),
+            contents: bytemuck::cast_slice(&[scene.scene_config]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let debug_params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Debug Params Buffer"),
+            contents: bytemuck::cast_slice(&[RenderSettings::default().debug_params(None)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let debug_info_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Debug Info Buffer"),
+            contents: bytemuck::cast_slice(&[RayDebugInfo::default()]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+        });
+
+        Self {
+            camera_buffer: RayTracer::create_camera_buffer(device),
+            grid_meta_buffer,
+            coarse_buffer,
+            fine_buffer,
+            box_buffer,
+            triangle_buffer,
+            material_buffer,
+            scene_config_buffer,
+            light_buffer,
+            debug_params_buffer,
+            debug_info_buffer,
+            texture_array_view: RayTracer::create_texture_array_view(device, queue, scene.textures),
+            texture_sampler,
+            skybox_view: RayTracer::create_skybox_view(device, queue, scene.skybox),
+        }
+    }
+
+    /// Grid metadata, coarse counts, fine cells and box buffers for `boxes` and the grid built from them
+    fn create_geometry_buffers(
+        device: &wgpu::Device,
+        grid: &HierarchicalGrid,
+        boxes: &[BoxData],
+    ) -> (wgpu::Buffer, wgpu::Buffer, wgpu::Buffer, wgpu::Buffer) {
+        let (metadata, coarse_counts, fine_cells) = grid.to_gpu_buffers();
+
+        let grid_meta_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Grid Metadata"),
+            contents: bytemuck::cast_slice(&[metadata]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let coarse_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Coarse Counts"),
+            contents: &coarse_counts,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let fine_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Fine Cells"),
+            contents: bytemuck::cast_slice(&fine_cells),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // Use valid 1x1x1 box centered at origin to avoid degenerate AABB issues
+        let dummy_box = [BoxData::new(
+            [-0.5, -0.5, -0.5],  // min
+            [0.5, 0.5, 0.5],     // max
+            [0.5, 0.5, 0.5],     // color (gray)
+        )];
+        let box_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Box Buffer"),
+            contents: if boxes.is_empty() {
+                bytemuck::cast_slice(&dummy_box)
+            } else {
+                bytemuck::cast_slice(boxes)
+            },
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
+        (grid_meta_buffer, coarse_buffer, fine_buffer, box_buffer)
+    }
+
+    /// Swap in buffers sized for new geometry; the compute bind group must be rebuilt afterwards
+    pub(crate) fn replace_geometry(&mut self, device: &wgpu::Device, grid: &HierarchicalGrid, boxes: &[BoxData]) {
+        (self.grid_meta_buffer, self.coarse_buffer, self.fine_buffer, self.box_buffer) =
+            Self::create_geometry_buffers(device, grid, boxes);
+    }
+}
+
+/// Contents of the lights buffer: `overrides` when given, otherwise the `lighting` preset plus the lights the
+/// scene places itself
+pub(crate) fn resolve_lights(overrides: &[LightData], scene_name: &str, lighting: &LightingConfig) -> Vec<LightData> {
+    if !overrides.is_empty() {
+        return overrides.to_vec();
+    }
+    lighting.lights().into_iter().chain(scene_lights(scene_name)).collect()
+}
+
+/// Images the compute pass writes, reallocated together whenever the render size changes
+pub(crate) struct RenderTargets {
+    pub output_texture: wgpu::Texture,
+    pub output_view: wgpu::TextureView,
+    /// Per-pixel primary hit distance (binding 14), for fog-aware compositing; owned here so it lives as
+    /// long as the bind group using it
+    _depth_texture: wgpu::Texture,
+    pub depth_view: wgpu::TextureView,
+    /// Running average of progressive frames (binding 16), one `vec4<f32>` per pixel
+    pub accumulation_buffer: wgpu::Buffer,
+}
+
+impl RenderTargets {
+    pub(crate) fn new(device: &wgpu::Device, (width, height): (u32, u32)) -> Self {
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let output_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Output Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: OUTPUT_FORMAT,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Depth Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R32Float,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let accumulation_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Accumulation Buffer"),
+            size: (width as u64 * height as u64 * ACCUMULATION_TEXEL_BYTES).max(ACCUMULATION_TEXEL_BYTES),
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            output_view: output_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            output_texture,
+            depth_view: depth_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            _depth_texture: depth_texture,
+            accumulation_buffer,
+        }
+    }
+}
+
 pub struct RayTracer {
     device: wgpu::Device,
     queue: wgpu::Queue,
     surface: wgpu::Surface<'static>,
     size: winit::dpi::PhysicalSize<u32>,
+    surface_config: wgpu::SurfaceConfiguration,
+    /// See `SceneOptions::aspect`
+    aspect: Option<f32>,
+    /// Ray traced image size: the window size times `RenderSettings::render_scale`
+    render_size: (u32, u32),
     compute_pipeline: wgpu::ComputePipeline,
+    compute_bind_group_layout: wgpu::BindGroupLayout,
+    compute_resources: ComputeResources,
+    /// Re-uploaded when the background settings change
+    scene_config: SceneConfig,
     compute_bind_group: wgpu::BindGroup,
+    targets: RenderTargets,
+    /// Auto-exposure metering of the output texture
+    luminance_meter: LuminanceMeter,
     camera_buffer: wgpu::Buffer,
     render_pipeline: wgpu::RenderPipeline,
+    render_bind_group_layout: wgpu::BindGroupLayout,
+    display_sampler: wgpu::Sampler,
     render_bind_group: wgpu::BindGroup,
+    display_params_buffer: wgpu::Buffer,
     egui_renderer: egui_wgpu::Renderer,
     egui_state: egui_winit::State,
     egui_ctx: egui::Context,
-    num_boxes: usize,
+    boxes: Vec<BoxData>,
+    triangles: Vec<TriangleData>,
+    materials: Vec<MaterialData>,
+    /// Camera stored in a `file:` scene, to start from instead of the default viewpoint
+    scene_camera: Option<CameraBookmark>,
+    /// Grid over `boxes` and `triangles` as last uploaded
+    grid: HierarchicalGrid,
+    /// Boxes moved since the last upload, patched into `grid` in place; `None` rebuilds it from scratch
+    moved_boxes: Option<Vec<usize>>,
+    grid_stats: GridStats,
+    dirty: DirtyFlags,
+    last_camera_pose: Option<CameraBookmark>,
+    accumulation: Accumulation,
+    accumulate: Arc<Mutex<bool>>,
+    reset_requested: Arc<Mutex<bool>>,
+    /// Resolved ambient light: `--ambient` or the scene's default
+    ambient: f32,
+    /// Toggled in the UI; starts from `--auto-exposure`
+    auto_exposure: Arc<Mutex<bool>>,
+    /// Display exposure applied before tone mapping: set by the UI slider, or adapted each frame when
+    /// `auto_exposure` is on
+    exposure: Arc<Mutex<f32>>,
     current_scene: Arc<Mutex<String>>,
     needs_reload: Arc<Mutex<bool>>,
-    show_grid: Arc<Mutex<bool>>,
+    /// Set by the "Save Scene" button, handled like `needs_reload`
+    save_requested: Arc<Mutex<bool>>,
+    settings: Arc<Mutex<RenderSettings>>,
+    last_settings: RenderSettings,
     debug_params_buffer: wgpu::Buffer,
     debug_info_buffer: wgpu::Buffer,
     debug_info: RayDebugInfo,
     debug_pixel: Option<(u32, u32)>,
+    /// Box picked for editing with the nudge keys
+    selected: Option<usize>,
+    /// Undo/redo of box edits
+    history: EditHistory,
+    /// Boxes changed since the last auto-save backup
+    edited_since_backup: bool,
     clear_debug_requested: Arc<Mutex<bool>>,
     no_ui: bool,
+    ui: UiVisibility,
+    /// Compute/display pass timing; `None` without `TIMESTAMP_QUERY`
+    gpu_timer: Option<GpuTimer>,
+    /// Sets `needs_reload` when a `file:` scene changes on disk, except through our own saves
+    scene_watcher: Option<SceneWatcher>,
 }
 
 impl RayTracer {
-    pub async fn new(window: Arc<Window>, no_ui: bool) -> Result<Self> {
+    pub async fn new(window: Arc<Window>, scene_name: &str, no_ui: bool, options: &SceneOptions) -> Result<Self> {
         let size = window.inner_size();
 
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
@@ -51,21 +413,39 @@ impl RayTracer {
 
         let surface_config = Self::create_surface_config(&surface, &adapter, size);
         surface.configure(&device, &surface_config);
+        let gpu_timer = GpuTimer::new(&device, &queue);
+        if gpu_timer.is_none() && !no_ui {
+            println!("GPU timestamp queries unavailable; pass timings disabled");
+        }
 
-        let scene_name = std::env::var("SCENE").unwrap_or_else(|_| "fractal".to_string());
+        let scene_name = scene_name.to_string();
         if !no_ui {
             println!("Loading scene: {}", scene_name);
         }
 
+        let SceneFile { boxes: file_boxes, triangles: file_triangles, materials: file_materials, camera } =
+            match scene_name.strip_prefix(SCENE_FILE_PREFIX) {
+                Some(path) => load_scene(path)?,
+                None => SceneFile::default(),
+            };
         let boxes = match scene_name.as_str() {
             "composed" => create_composed_scene(),
             "walls" => create_walls_scene(),
             "tunnel" => create_tunnel_scene(),
             "default" => create_default_scene(),
             "reflected" => create_reflected_scene(),
+            "stress" => create_stress_scene(options.stress_count),
             "gltf" => vec![], // Use triangle-based rendering with textures
             "pyramid" => vec![], // Use triangle-based rendering
-            _ => create_fractal_scene(),
+            name if name.starts_with(SCENE_FILE_PREFIX) => file_boxes,
+            _ => match options.lod_budget {
+                Some(budget) => fractal_lod(options.lod_origin, budget),
+                None => create_fractal_scene(),
+            },
+        };
+        let boxes = match options.reflective_floor {
+            Some(reflectivity) => with_reflective_floor(boxes, reflectivity),
+            None => boxes,
         };
         let num_boxes = boxes.len();
 
@@ -88,223 +468,71 @@ impl RayTracer {
             }
             (tris, mats, vec![])
         } else if scene_name == "gltf" {
-            let (tris, mats, texs) = create_gltf_triangles();
-            (tris, mats, texs)
+            create_gltf_triangles(options.max_triangles)?
         } else {
-            (vec![], vec![], vec![])
+            (file_triangles, file_materials, vec![])
         };
 
         println!("Building Hierarchical Grid...");
         let grid = HierarchicalGrid::build(&boxes, &triangles);
-        let (metadata, coarse_counts, fine_cells) = grid.to_gpu_buffers();
-
-        let grid_meta_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Grid Metadata"),
-            contents: bytemuck::cast_slice(&[metadata]),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
-
-        let coarse_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Coarse Counts"),
-            contents: &coarse_counts,
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-
-        let fine_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Fine Cells"),
-            contents: bytemuck::cast_slice(&fine_cells),
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-
-        // Create box buffer with at least one dummy box to avoid zero-sized buffer
-        // Use valid 1x1x1 box centered at origin to avoid degenerate AABB issues
-        let dummy_box = [crate::types::BoxData::new(
-            [-0.5, -0.5, -0.5],  // min
-            [0.5, 0.5, 0.5],     // max
-            [0.5, 0.5, 0.5],     // color (gray)
-        )];
-        let box_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Box Buffer"),
-            contents: if boxes.is_empty() {
-                bytemuck::cast_slice(&dummy_box)
-            } else {
-                bytemuck::cast_slice(&boxes)
-            },
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-
-        let dummy_triangle = [TriangleData::new([0.0; 3], [0.0; 3], [0.0; 3], [0.0; 2], [0.0; 2], [0.0; 2], 0)];
-        let triangle_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Triangle Buffer"),
-            contents: if triangles.is_empty() {
-                bytemuck::cast_slice(&dummy_triangle)
-            } else {
-                bytemuck::cast_slice(&triangles)
-            },
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-
-        let dummy_material = [MaterialData::new_color([1.0, 1.0, 1.0, 1.0])];
-        let material_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Material Buffer"),
-            contents: if materials.is_empty() {
-                bytemuck::cast_slice(&dummy_material)
-            } else {
-                bytemuck::cast_slice(&materials)
-            },
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-
-        // Create GPU textures from TextureData
-        let texture_array_view = if textures.is_empty() {
-            // Create a 1x1 white dummy texture if no textures exist
-            let dummy_data = vec![255u8, 255u8, 255u8, 255u8];
-            let texture = device.create_texture(&wgpu::TextureDescriptor {
-                label: Some("Dummy Texture"),
-                size: wgpu::Extent3d {
-                    width: 1,
-                    height: 1,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8Unorm,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                view_formats: &[],
-            });
-            queue.write_texture(
-                texture.as_image_copy(),
-                &dummy_data,
-                wgpu::TexelCopyBufferLayout {
-                    offset: 0,
-                    bytes_per_row: Some(4),
-                    rows_per_image: Some(1),
-                },
-                wgpu::Extent3d {
-                    width: 1,
-                    height: 1,
-                    depth_or_array_layers: 1,
-                },
-            );
-            texture.create_view(&wgpu::TextureViewDescriptor {
-                dimension: Some(wgpu::TextureViewDimension::D2Array),
-                ..Default::default()
-            })
-        } else {
-            // Create texture array to hold all textures
-            let max_width = textures.iter().map(|t| t.width).max().unwrap_or(1);
-            let max_height = textures.iter().map(|t| t.height).max().unwrap_or(1);
-
-            let texture_array = device.create_texture(&wgpu::TextureDescriptor {
-                label: Some("Texture Array"),
-                size: wgpu::Extent3d {
-                    width: max_width,
-                    height: max_height,
-                    depth_or_array_layers: textures.len() as u32,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8Unorm,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                view_formats: &[],
-            });
+        let grid_stats = grid.stats();
 
-            // Upload each texture to its layer
-            for (i, tex_data) in textures.iter().enumerate() {
-                queue.write_texture(
-                    wgpu::TexelCopyTextureInfo {
-                        texture: &texture_array,
-                        mip_level: 0,
-                        origin: wgpu::Origin3d {
-                            x: 0,
-                            y: 0,
-                            z: i as u32,
-                        },
-                        aspect: wgpu::TextureAspect::All,
-                    },
-                    &tex_data.data,
-                    wgpu::TexelCopyBufferLayout {
-                        offset: 0,
-                        bytes_per_row: Some(4 * tex_data.width),
-                        rows_per_image: Some(tex_data.height),
-                    },
-                    wgpu::Extent3d {
-                        width: tex_data.width,
-                        height: tex_data.height,
-                        depth_or_array_layers: 1,
-                    },
-                );
-            }
-
-            if !no_ui {
-                println!("Created texture array: {}x{} with {} layers", max_width, max_height, textures.len());
-            }
-            texture_array.create_view(&wgpu::TextureViewDescriptor {
-                dimension: Some(wgpu::TextureViewDimension::D2Array),
-                ..Default::default()
-            })
-        };
-
-        // Create texture sampler
-        let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::Repeat,
-            address_mode_v: wgpu::AddressMode::Repeat,
-            address_mode_w: wgpu::AddressMode::Repeat,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+        let skybox = options.skybox.as_deref().map(load_skybox).transpose()?;
+        if let (Some(sky), false) = (&skybox, no_ui) {
+            println!("Loaded {}x{} skybox", sky.width, sky.height);
+        }
+        if !textures.is_empty() && !no_ui {
+            println!("Created texture array with {} layers", textures.len());
+        }
 
         // Create scene config buffer
-        let scene_config = SceneConfig::new(num_boxes, triangles.len());
-        let scene_config_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Scene Config Buffer"),
-            contents: bytemuck::cast_slice(&[scene_config]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        let settings = RenderSettings { sky: options.sky, ..RenderSettings::default() };
+        let lights = resolve_lights(&options.lights, &scene_name, &options.lighting);
+        let scene_config = SceneConfig::new(num_boxes, triangles.len())
+            .with_lights(&lights)
+            .with_sky(options.sky)
+            .with_skybox(skybox.is_some())
+            .with_fog(options.fog_color, options.fog_density);
+
+        let compute_resources = ComputeResources::new(&device, &queue, &SceneUpload {
+            grid: &grid,
+            boxes: &boxes,
+            triangles: &triangles,
+            materials: &materials,
+            textures: &textures,
+            lights: &lights,
+            scene_config,
+            skybox: skybox.as_ref(),
         });
-
-        let camera_buffer = Self::create_camera_buffer(&device);
-        let (_output_texture, output_texture_view) = Self::create_output_texture(&device, size);
-
-        let debug_params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Debug Params Buffer"),
-            contents: bytemuck::cast_slice(&[DebugParams {
-                debug_pixel: [0, 0],
-                enabled: 0,
-                _pad: 0,
-            }]),
+        let camera_buffer = compute_resources.camera_buffer.clone();
+        let debug_params_buffer = compute_resources.debug_params_buffer.clone();
+        let debug_info_buffer = compute_resources.debug_info_buffer.clone();
+
+        let (width, height) = viewport(size, options.aspect).size();
+        let render_size = scaled_size(width, height, settings.render_scale);
+        let targets = RenderTargets::new(&device, render_size);
+
+        let (compute_pipeline, compute_bind_group_layout) = Self::create_compute_pipeline(&device);
+        let compute_bind_group =
+            Self::create_compute_bind_group(&device, &compute_bind_group_layout, &compute_resources, &targets);
+        let luminance_meter = LuminanceMeter::new(&device, &targets.output_view);
+
+        let display_params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Display Params Buffer"),
+            contents: bytemuck::bytes_of(&DisplayParams::new(ToneMap::default().as_u32())),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        let debug_info_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Debug Info Buffer"),
-            contents: bytemuck::cast_slice(&[RayDebugInfo::default()]),
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
-        });
-
-        let (compute_pipeline, compute_bind_group) = Self::create_compute_pipeline(
+        let (render_pipeline, render_bind_group_layout) = Self::create_render_pipeline(&device, surface_config.format);
+        let display_sampler = Self::create_display_sampler(&device);
+        let render_bind_group = Self::create_render_bind_group(
             &device,
-            &camera_buffer,
-            &grid_meta_buffer,
-            &coarse_buffer,
-            &fine_buffer,
-            &box_buffer,
-            &triangle_buffer,
-            &material_buffer,
-            &scene_config_buffer,
-            &output_texture_view,
-            &debug_params_buffer,
-            &debug_info_buffer,
-            &texture_array_view,
-            &texture_sampler,
+            &render_bind_group_layout,
+            &display_sampler,
+            &targets.output_view,
+            &display_params_buffer,
         );
 
-        let (render_pipeline, render_bind_group) =
-            Self::create_render_pipeline(&device, &output_texture_view, surface_config.format);
-
         let egui_ctx = egui::Context::default();
         let egui_state = egui_winit::State::new(
             egui_ctx.clone(),
@@ -324,32 +552,89 @@ impl RayTracer {
             println!("Ray tracer initialized: {} boxes", num_boxes);
         }
 
+        let needs_reload = Arc::new(Mutex::new(false));
+        let scene_watcher = scene_name.strip_prefix(SCENE_FILE_PREFIX).and_then(|path| {
+            watch(path, needs_reload.clone())
+                .map_err(|e| eprintln!("Scene hot-reload disabled: {:#}", e))
+                .ok()
+        });
+
         Ok(Self {
             device,
             queue,
             surface,
             size,
+            surface_config,
+            aspect: options.aspect,
+            render_size,
             compute_pipeline,
+            compute_bind_group_layout,
+            compute_resources,
+            scene_config,
             compute_bind_group,
+            targets,
+            luminance_meter,
             camera_buffer,
             render_pipeline,
+            render_bind_group_layout,
+            display_sampler,
             render_bind_group,
+            display_params_buffer,
             egui_renderer,
             egui_state,
             egui_ctx,
-            num_boxes,
+            boxes,
+            triangles,
+            materials,
+            scene_camera: camera,
+            grid,
+            moved_boxes: Some(Vec::new()),
+            grid_stats,
+            dirty: DirtyFlags::default(),
+            last_camera_pose: None,
+            accumulation: Accumulation::default(),
+            accumulate: Arc::new(Mutex::new(true)),
+            reset_requested: Arc::new(Mutex::new(false)),
+            ambient: options.ambient.unwrap_or_else(|| default_ambient(&scene_name)),
+            auto_exposure: Arc::new(Mutex::new(options.auto_exposure)),
+            exposure: Arc::new(Mutex::new(1.0)),
             current_scene: Arc::new(Mutex::new(scene_name)),
-            needs_reload: Arc::new(Mutex::new(false)),
-            show_grid: Arc::new(Mutex::new(false)),
+            needs_reload,
+            save_requested: Arc::new(Mutex::new(false)),
+            edited_since_backup: false,
+            settings: Arc::new(Mutex::new(settings)),
+            last_settings: settings,
             debug_params_buffer,
             debug_info_buffer,
             debug_info: RayDebugInfo::default(),
             debug_pixel: None,
+            selected: None,
+            history: EditHistory::default(),
             clear_debug_requested: Arc::new(Mutex::new(false)),
             no_ui,
+            ui: UiVisibility::new(no_ui),
+            gpu_timer,
+            scene_watcher,
         })
     }
 
+    /// Probe for any usable GPU adapter without creating a device or pipelines
+    ///
+    /// Lets tests and CI skip GPU-dependent work on machines without a GPU.
+    pub fn is_gpu_available() -> bool {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::PRIMARY,
+            ..Default::default()
+        });
+
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }))
+        .is_ok()
+    }
+
     async fn request_adapter(
         instance: &wgpu::Instance,
         surface: &wgpu::Surface<'_>,
@@ -418,7 +703,7 @@ impl RayTracer {
 
     fn create_camera_buffer(device: &wgpu::Device) -> wgpu::Buffer {
         let camera = Camera::new();
-        let camera_uniform = camera.to_uniform(0.0, 800.0, DEFAULT_FOV, false);
+        let camera_uniform = camera.to_uniform(0.0, Resolution::new(800, 800), camera.fov, false);
 
         device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Camera Buffer"),
@@ -427,45 +712,135 @@ impl RayTracer {
         })
     }
 
-    fn create_output_texture(
-        device: &wgpu::Device,
-        size: winit::dpi::PhysicalSize<u32>,
-    ) -> (wgpu::Texture, wgpu::TextureView) {
+    /// Upload the skybox image, or a 1x1 placeholder so the binding is always valid
+    fn create_skybox_view(device: &wgpu::Device, queue: &wgpu::Queue, skybox: Option<&TextureData>) -> wgpu::TextureView {
+        let placeholder = TextureData { width: 1, height: 1, data: vec![0, 0, 0, 255] };
+        let image = skybox.unwrap_or(&placeholder);
+        let size = wgpu::Extent3d {
+            width: image.width,
+            height: image.height,
+            depth_or_array_layers: 1,
+        };
+
         let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("Output Texture"),
-            size: wgpu::Extent3d {
-                width: size.width,
-                height: size.height,
-                depth_or_array_layers: 1,
-            },
+            label: Some("Skybox Texture"),
+            size,
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
             view_formats: &[],
         });
-
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        (texture, view)
+        queue.write_texture(
+            texture.as_image_copy(),
+            &image.data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * image.width),
+                rows_per_image: Some(image.height),
+            },
+            size,
+        );
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
     }
 
-    fn create_compute_pipeline(
+    /// Upload textures into one array (sized to the largest), or a 1x1 white layer when there are none
+    fn create_texture_array_view(
         device: &wgpu::Device,
-        camera_buffer: &wgpu::Buffer,
-        grid_meta_buffer: &wgpu::Buffer,
-        coarse_buffer: &wgpu::Buffer,
-        fine_buffer: &wgpu::Buffer,
-        box_buffer: &wgpu::Buffer,
-        triangle_buffer: &wgpu::Buffer,
-        material_buffer: &wgpu::Buffer,
-        scene_config_buffer: &wgpu::Buffer,
-        output_texture_view: &wgpu::TextureView,
-        debug_params_buffer: &wgpu::Buffer,
-        debug_info_buffer: &wgpu::Buffer,
-        texture_array_view: &wgpu::TextureView,
-        texture_sampler: &wgpu::Sampler,
-    ) -> (wgpu::ComputePipeline, wgpu::BindGroup) {
+        queue: &wgpu::Queue,
+        textures: &[TextureData],
+    ) -> wgpu::TextureView {
+        if textures.is_empty() {
+            // Create a 1x1 white dummy texture if no textures exist
+            let dummy_data = vec![255u8, 255u8, 255u8, 255u8];
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Dummy Texture"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+            queue.write_texture(
+                texture.as_image_copy(),
+                &dummy_data,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4),
+                    rows_per_image: Some(1),
+                },
+                wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+            );
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2Array),
+                ..Default::default()
+            })
+        } else {
+            // Create texture array to hold all textures
+            let max_width = textures.iter().map(|t| t.width).max().unwrap_or(1);
+            let max_height = textures.iter().map(|t| t.height).max().unwrap_or(1);
+
+            let texture_array = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Texture Array"),
+                size: wgpu::Extent3d {
+                    width: max_width,
+                    height: max_height,
+                    depth_or_array_layers: textures.len() as u32,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+
+            // Upload each texture to its layer
+            for (i, tex_data) in textures.iter().enumerate() {
+                queue.write_texture(
+                    wgpu::TexelCopyTextureInfo {
+                        texture: &texture_array,
+                        mip_level: 0,
+                        origin: wgpu::Origin3d {
+                            x: 0,
+                            y: 0,
+                            z: i as u32,
+                        },
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    &tex_data.data,
+                    wgpu::TexelCopyBufferLayout {
+                        offset: 0,
+                        bytes_per_row: Some(4 * tex_data.width),
+                        rows_per_image: Some(tex_data.height),
+                    },
+                    wgpu::Extent3d {
+                        width: tex_data.width,
+                        height: tex_data.height,
+                        depth_or_array_layers: 1,
+                    },
+                );
+            }
+
+            texture_array.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2Array),
+                ..Default::default()
+            })
+        }
+    }
+
+    pub(crate) fn create_compute_pipeline(device: &wgpu::Device) -> (wgpu::ComputePipeline, wgpu::BindGroupLayout) {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Unified Compute Shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("raytracer_unified.wgsl").into()),
@@ -561,31 +936,93 @@ impl RayTracer {
                     },
                     count: None,
                 },
-                // Binding 8: Output Texture
+                // Binding 8: Output Texture
+                wgpu::BindGroupLayoutEntry {
+                    binding: 8,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: OUTPUT_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                // Binding 9: Debug Params
+                wgpu::BindGroupLayoutEntry {
+                    binding: 9,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // Binding 10: Debug Info
+                wgpu::BindGroupLayoutEntry {
+                    binding: 10,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // Binding 11: Texture Array
+                wgpu::BindGroupLayoutEntry {
+                    binding: 11,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                // Binding 12: Texture Sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 12,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                // Binding 13: Skybox
+                wgpu::BindGroupLayoutEntry {
+                    binding: 13,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                // Binding 14: Depth Texture
                 wgpu::BindGroupLayoutEntry {
-                    binding: 8,
+                    binding: 14,
                     visibility: wgpu::ShaderStages::COMPUTE,
                     ty: wgpu::BindingType::StorageTexture {
                         access: wgpu::StorageTextureAccess::WriteOnly,
-                        format: wgpu::TextureFormat::Rgba8Unorm,
+                        format: wgpu::TextureFormat::R32Float,
                         view_dimension: wgpu::TextureViewDimension::D2,
                     },
                     count: None,
                 },
-                // Binding 9: Debug Params
+                // Binding 15: Lights
                 wgpu::BindGroupLayoutEntry {
-                    binding: 9,
+                    binding: 15,
                     visibility: wgpu::ShaderStages::COMPUTE,
                     ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
                         has_dynamic_offset: false,
                         min_binding_size: None,
                     },
                     count: None,
                 },
-                // Binding 10: Debug Info
+                // Binding 16: Accumulation Buffer
                 wgpu::BindGroupLayoutEntry {
-                    binding: 10,
+                    binding: 16,
                     visibility: wgpu::ShaderStages::COMPUTE,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Storage { read_only: false },
@@ -594,30 +1031,54 @@ impl RayTracer {
                     },
                     count: None,
                 },
-                // Binding 11: Texture Array
-                wgpu::BindGroupLayoutEntry {
-                    binding: 11,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2Array,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
-                // Binding 12: Texture Sampler
-                wgpu::BindGroupLayoutEntry {
-                    binding: 12,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
             ],
             label: Some("unified_bind_group_layout"),
         });
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &bind_group_layout,
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Grid Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Grid Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        (pipeline, bind_group_layout)
+    }
+
+    /// Bind the scene resources and the render targets; rebuilt whenever the targets are
+    pub(crate) fn create_compute_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        resources: &ComputeResources,
+        targets: &RenderTargets,
+    ) -> wgpu::BindGroup {
+        let ComputeResources {
+            camera_buffer,
+            grid_meta_buffer,
+            coarse_buffer,
+            fine_buffer,
+            box_buffer,
+            triangle_buffer,
+            material_buffer,
+            scene_config_buffer,
+            light_buffer,
+            debug_params_buffer,
+            debug_info_buffer,
+            texture_array_view,
+            texture_sampler,
+            skybox_view,
+        } = resources;
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -653,7 +1114,7 @@ impl RayTracer {
                 },
                 wgpu::BindGroupEntry {
                     binding: 8,
-                    resource: wgpu::BindingResource::TextureView(output_texture_view),
+                    resource: wgpu::BindingResource::TextureView(&targets.output_view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 9,
@@ -671,33 +1132,31 @@ impl RayTracer {
                     binding: 12,
                     resource: wgpu::BindingResource::Sampler(texture_sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 13,
+                    resource: wgpu::BindingResource::TextureView(skybox_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 14,
+                    resource: wgpu::BindingResource::TextureView(&targets.depth_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 15,
+                    resource: light_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 16,
+                    resource: targets.accumulation_buffer.as_entire_binding(),
+                },
             ],
             label: Some("unified_bind_group"),
-        });
-
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Grid Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
-            push_constant_ranges: &[],
-        });
-
-        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            label: Some("Grid Pipeline"),
-            layout: Some(&pipeline_layout),
-            module: &shader,
-            entry_point: Some("main"),
-            compilation_options: Default::default(),
-            cache: None,
-        });
-
-        (pipeline, bind_group)
+        })
     }
 
     fn create_render_pipeline(
         device: &wgpu::Device,
-        output_texture_view: &wgpu::TextureView,
         surface_format: wgpu::TextureFormat,
-    ) -> (wgpu::RenderPipeline, wgpu::BindGroup) {
+    ) -> (wgpu::RenderPipeline, wgpu::BindGroupLayout) {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Display Shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("display.wgsl").into()),
@@ -721,33 +1180,18 @@ impl RayTracer {
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
-            ],
-            label: Some("render_bind_group_layout"),
-        });
-
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
-
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(output_texture_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
                 },
             ],
-            label: Some("render_bind_group"),
+            label: Some("render_bind_group_layout"),
         });
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -794,50 +1238,148 @@ impl RayTracer {
             cache: None,
         });
 
-        (pipeline, bind_group)
+        (pipeline, bind_group_layout)
+    }
+
+    /// Linear filtering upsamples the output texture when rendering below window resolution
+    fn create_display_sampler(device: &wgpu::Device) -> wgpu::Sampler {
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        })
+    }
+
+    fn create_render_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        output_texture_view: &wgpu::TextureView,
+        display_params_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(output_texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: display_params_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("render_bind_group"),
+        })
+    }
+
+    /// Reconfigure the surface for a new window size and reallocate the output to match
+    pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        if crate::window::is_minimized(size) {
+            return;
+        }
+        self.size = size;
+        self.surface_config.width = size.width;
+        self.surface_config.height = size.height;
+        self.surface.configure(&self.device, &self.surface_config);
+        self.resize_output(self.last_settings.render_scale);
+        self.reset_accumulation();
+    }
+
+    /// Reallocate the render targets at `scale` of the viewport and rebind them to both passes
+    fn resize_output(&mut self, scale: f32) {
+        let (width, height) = viewport(self.size, self.aspect).size();
+        self.render_size = scaled_size(width, height, scale);
+        self.targets = RenderTargets::new(&self.device, self.render_size);
+        self.luminance_meter.rebind(&self.device, &self.targets.output_view);
+        self.compute_bind_group = Self::create_compute_bind_group(
+            &self.device,
+            &self.compute_bind_group_layout,
+            &self.compute_resources,
+            &self.targets,
+        );
+        self.render_bind_group = Self::create_render_bind_group(
+            &self.device,
+            &self.render_bind_group_layout,
+            &self.display_sampler,
+            &self.targets.output_view,
+            &self.display_params_buffer,
+        );
     }
 
     pub fn render(
         &mut self,
         camera: &Camera,
         window: &Window,
-        _fps: f32,
+        fps: f32,
         time: f32,
-        _frame_number: u64,
+        frame_number: u64,
     ) -> std::result::Result<(), wgpu::SurfaceError> {
-        // Debug output every 60 frames to show rendering is active
-        use std::sync::atomic::{AtomicU32, Ordering};
-        static FRAME_COUNTER: AtomicU32 = AtomicU32::new(0);
+        let gpu_timings = self.gpu_timer.as_mut().map(|timer| timer.poll(&self.device));
 
-        let frame = FRAME_COUNTER.fetch_add(1, Ordering::Relaxed);
-        if frame % 60 == 0 && !self.no_ui {
+        // Debug output every 60 frames to show rendering is active
+        if frame_number % 60 == 0 && !self.no_ui {
             println!("🎨 Rendering active - Frame: {}, Camera: ({:.1}, {:.1}, {:.1})",
-                frame,
+                frame_number,
                 camera.position.x,
                 camera.position.y,
                 camera.position.z);
         }
 
-        let show_grid = *self.show_grid.lock().unwrap();
-        let camera_uniform = camera.to_uniform(time, self.size.height as f32, DEFAULT_FOV, show_grid);
+        // Camera-only frames never touch scene buffers
+        let pose = camera.snapshot();
+        if self.last_camera_pose != Some(pose) {
+            self.dirty = self.dirty.camera_moved();
+            self.last_camera_pose = Some(pose);
+            self.reset_accumulation();
+        }
+        if self.dirty.scene {
+            self.upload_scene_buffers();
+            self.reset_accumulation();
+        }
+
+        let settings = *self.settings.lock().unwrap();
+        if settings.changed_since(&self.last_settings) {
+            if settings.render_scale != self.last_settings.render_scale {
+                self.resize_output(settings.render_scale);
+            }
+            self.scene_config =
+                self.scene_config.with_sky(settings.sky).with_sky_colors(settings.sky_top, settings.sky_bottom);
+            let scene_config_buffer = &self.compute_resources.scene_config_buffer;
+            self.queue.write_buffer(scene_config_buffer, 0, bytemuck::bytes_of(&self.scene_config));
+            self.last_settings = settings;
+            self.reset_accumulation();
+        }
+
+        // Uploaded every frame regardless of camera movement: it also carries animation time
+        let camera_uniform = CameraUniform {
+            ambient: self.ambient,
+            near: settings.near_clip,
+            far: settings.far_clip,
+            ..camera.to_uniform(time, Resolution::from(self.render_size), camera.fov, settings.show_grid)
+        };
 
         let camera_array = [camera_uniform];
         let camera_data = bytemuck::cast_slice(&camera_array);
         self.queue.write_buffer(&self.camera_buffer, 0, camera_data);
+        self.dirty = self.dirty.camera_uploaded();
 
-        let debug_params = if let Some((x, y)) = self.debug_pixel {
-            DebugParams {
-                debug_pixel: [x, y],
-                enabled: 1,
-                _pad: 0,
-            }
-        } else {
-            DebugParams {
-                debug_pixel: [0, 0],
-                enabled: 0,
-                _pad: 0,
-            }
+        // Picked in window pixels; the shader indexes the scaled output texture inside the viewport
+        let rect = viewport(self.size, self.aspect);
+        let to_render = |p: u32, offset: f32, extent: f32, render: u32| {
+            (((p as f32 - offset) / extent.max(1.0)).clamp(0.0, 1.0) * (render - 1) as f32) as u32
         };
+        let debug_params = settings.debug_params(self.debug_pixel.map(|(x, y)| {
+            [to_render(x, rect.x, rect.width, self.render_size.0), to_render(y, rect.y, rect.height, self.render_size.1)]
+        }));
 
         let debug_array = [debug_params];
         let debug_data = bytemuck::cast_slice(&debug_array);
@@ -857,14 +1399,13 @@ impl RayTracer {
         {
             let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
                 label: Some("Compute Pass"),
-                timestamp_writes: None,
+                timestamp_writes: self.gpu_timer.as_ref().map(GpuTimer::compute_writes),
             });
             compute_pass.set_pipeline(&self.compute_pipeline);
             compute_pass.set_bind_group(0, &self.compute_bind_group, &[]);
 
-            let workgroup_size_x = self.size.width.div_ceil(WORKGROUP_SIZE);
-            let workgroup_size_y = self.size.height.div_ceil(WORKGROUP_SIZE);
-            compute_pass.dispatch_workgroups(workgroup_size_x, workgroup_size_y, 1);
+            let (workgroups_x, workgroups_y) = workgroup_counts(self.render_size);
+            compute_pass.dispatch_workgroups(workgroups_x, workgroups_y, 1);
         }
 
         if self.debug_pixel.is_some() {
@@ -919,6 +1460,22 @@ impl RayTracer {
             });
         }
 
+        // Metered a frame or two behind; the display pass applies exposure, so the reading does not depend on it
+        if *self.auto_exposure.lock().unwrap() {
+            if let Some(average) = self.luminance_meter.poll(&self.device) {
+                let delta = if fps > 0.0 { 1.0 / fps } else { 0.0 };
+                let mut exposure = self.exposure.lock().unwrap();
+                *exposure = exposure_step(*exposure, average, delta);
+            }
+            self.luminance_meter.measure(&mut encoder);
+        }
+
+        let display_params = DisplayParams::new(settings.tone_map.as_u32())
+            .with_wireframe(settings.wireframe)
+            .with_exposure(*self.exposure.lock().unwrap())
+            .with_viewport(rect.normalized(Resolution::new(self.size.width, self.size.height)));
+        self.queue.write_buffer(&self.display_params_buffer, 0, bytemuck::bytes_of(&display_params));
+
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Display Pass"),
@@ -933,7 +1490,7 @@ impl RayTracer {
                 })],
                 depth_stencil_attachment: None,
                 occlusion_query_set: None,
-                timestamp_writes: None,
+                timestamp_writes: self.gpu_timer.as_ref().map(GpuTimer::display_writes),
             });
             render_pass.set_pipeline(&self.render_pipeline);
             render_pass.set_bind_group(0, &self.render_bind_group, &[]);
@@ -941,8 +1498,116 @@ impl RayTracer {
         }
 
         let raw_input = self.egui_state.take_egui_input(window);
-        let full_output = self.egui_ctx.run(raw_input, |_ctx| {
-            // No UI windows - completely clean
+        let show_windows = self.ui.shows_windows();
+        let settings_state = self.settings.clone();
+        let accumulate_flag = self.accumulate.clone();
+        let auto_exposure_flag = self.auto_exposure.clone();
+        let exposure_state = self.exposure.clone();
+        let reset_flag = self.reset_requested.clone();
+        let save_flag = self.save_requested.clone();
+        let accumulated_frames = self.accumulation.frame;
+        let scene_name = self.current_scene.lock().unwrap().clone();
+        let (num_boxes, num_triangles) = (self.boxes.len(), self.triangles.len());
+        let grid_stats = self.grid_stats;
+        let full_output = self.egui_ctx.run(raw_input, |ctx| {
+            if !show_windows {
+                return;
+            }
+            egui::Window::new("Debug").show(ctx, |ui| {
+                ui.label(format!("FPS: {:.1}", fps));
+                ui.label(format!("Time: {:.2}s", time));
+                ui.label(format!("Frame: {}", frame_number));
+                match gpu_timings {
+                    Some(Some(timings)) => {
+                        ui.label(format!("GPU compute: {:.2} ms", timings.compute_ms));
+                        ui.label(format!("GPU display: {:.2} ms", timings.display_ms));
+                    }
+                    Some(None) => {
+                        ui.label("GPU timing: measuring...");
+                    }
+                    None => {
+                        ui.label("GPU timing: unavailable");
+                    }
+                }
+                ui.horizontal(|ui| {
+                    ui.label(format!("Camera: ({:.1}, {:.1}, {:.1})", pose.position.x, pose.position.y, pose.position.z));
+                    if ui.button("Copy").on_hover_text("Copy as a --camera value").clicked() {
+                        ui.ctx().copy_text(pose.to_line());
+                    }
+                });
+                ui.separator();
+                {
+                    let mut settings = settings_state.lock().unwrap();
+                    ui.checkbox(&mut settings.show_grid, "Show Grid Cells");
+                    ui.checkbox(&mut settings.step_heatmap, "Step Heatmap");
+                    ui.checkbox(&mut settings.reflections, "Reflections (R)");
+                    ui.checkbox(&mut settings.wireframe, "Wireframe");
+                }
+                ui.separator();
+                ui.checkbox(&mut accumulate_flag.lock().unwrap(), "Accumulate");
+                ui.horizontal(|ui| {
+                    ui.label(format!("Frames: {}", accumulated_frames));
+                    if ui.button("Reset").clicked() {
+                        *reset_flag.lock().unwrap() = true;
+                    }
+                });
+            });
+            egui::Window::new("Rendering").show(ctx, |ui| {
+                let mut settings = settings_state.lock().unwrap();
+                ui.label("Shading");
+                ui.radio_value(&mut settings.shading, ShadingMode::Lit, "Lit");
+                ui.radio_value(&mut settings.shading, ShadingMode::Normals, "Normals");
+                ui.radio_value(&mut settings.shading, ShadingMode::Flat, "Flat");
+                ui.separator();
+                egui::ComboBox::from_label("Tone Mapping")
+                    .selected_text(settings.tone_map.label())
+                    .show_ui(ui, |ui| {
+                        for tone_map in ToneMap::ALL {
+                            ui.selectable_value(&mut settings.tone_map, tone_map, tone_map.label());
+                        }
+                    });
+                ui.separator();
+                ui.add(egui::Slider::new(&mut settings.render_scale, MIN_RENDER_SCALE..=MAX_RENDER_SCALE).text("Render Scale"));
+                ui.separator();
+                ui.add(egui::Slider::new(&mut settings.near_clip, 0.0..=MAX_NEAR_CLIP).text("Near Clip"));
+                ui.add(egui::Slider::new(&mut settings.far_clip, 1.0..=DEFAULT_FAR_CLIP).logarithmic(true).text("Far Clip"));
+                settings.far_clip = settings.far_clip.max(settings.near_clip);
+                ui.separator();
+                egui::ComboBox::from_label("Background")
+                    .selected_text(settings.sky.label())
+                    .show_ui(ui, |ui| {
+                        for sky in SkyMode::ALL {
+                            ui.selectable_value(&mut settings.sky, sky, sky.label());
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    ui.color_edit_button_rgb(&mut settings.sky_top);
+                    ui.label("Top");
+                    ui.color_edit_button_rgb(&mut settings.sky_bottom);
+                    ui.label("Bottom");
+                });
+                ui.separator();
+                let mut auto_exposure = auto_exposure_flag.lock().unwrap();
+                ui.checkbox(&mut auto_exposure, "Auto Exposure");
+                ui.add_enabled(
+                    !*auto_exposure,
+                    egui::Slider::new(&mut *exposure_state.lock().unwrap(), MIN_EXPOSURE..=MAX_EXPOSURE)
+                        .logarithmic(true)
+                        .text("Exposure"),
+                );
+            });
+            egui::Window::new("Scene").show(ctx, |ui| {
+                ui.label(format!("Scene: {}", scene_name));
+                ui.label(format!("Objects: {} boxes, {} triangles", num_boxes, num_triangles));
+                if ui.button("Save Scene").on_hover_text("Write the boxes to JSON (Alt+S)").clicked() {
+                    *save_flag.lock().unwrap() = true;
+                }
+                ui.separator();
+                ui.label(format!("Coarse cells: {}", grid_stats.coarse_cells));
+                ui.label(format!("Fine cells: {}/{} occupied", grid_stats.occupied_cells, grid_stats.fine_cells));
+                ui.label(format!("Max per cell: {}", grid_stats.max_primitives_per_cell));
+                ui.label(format!("Empty: {:.1}%", grid_stats.empty_ratio * 100.0));
+            });
         });
 
         self.egui_state
@@ -1005,8 +1670,24 @@ impl RayTracer {
             self.egui_renderer.free_texture(id);
         }
 
+        if let Some(timer) = self.gpu_timer.as_mut() {
+            timer.resolve(&mut encoder);
+        }
         self.queue.submit(std::iter::once(encoder.finish()));
         output.present();
+        if let Some(timer) = self.gpu_timer.as_mut() {
+            timer.request_readback();
+        }
+        self.luminance_meter.request_readback();
+
+        let accumulate = *self.accumulate.lock().unwrap();
+        if accumulate != self.accumulation.enabled {
+            self.accumulation = self.accumulation.with_enabled(accumulate);
+        }
+        if std::mem::take(&mut *self.reset_requested.lock().unwrap()) {
+            self.reset_accumulation();
+        }
+        self.accumulation = self.accumulation.advance();
 
         if *self.clear_debug_requested.lock().unwrap() {
             self.debug_pixel = None;
@@ -1023,14 +1704,237 @@ impl RayTracer {
         self.egui_state.on_window_event(window, event).consumed
     }
 
-    pub fn needs_reload(&self) -> bool {
-        *self.needs_reload.lock().unwrap()
+    /// True once per reload request (scene button or watched file change), clearing it
+    ///
+    /// A failed reload is not retried until the next request, e.g. the next save.
+    pub fn take_reload_request(&self) -> bool {
+        std::mem::take(&mut *self.needs_reload.lock().unwrap())
+    }
+
+    /// True once per "Save Scene" click, clearing it
+    pub fn take_save_request(&self) -> bool {
+        std::mem::take(&mut *self.save_requested.lock().unwrap())
+    }
+
+    /// Write the current (possibly edited) scene to the scene's save path, returning that path
+    ///
+    /// Saving over a watched `file:` scene does not trigger a hot reload, so edits and undo history survive.
+    pub fn save_scene(&self) -> anyhow::Result<PathBuf> {
+        let path = scene_save_path(&self.get_current_scene());
+        let json = scene_json(&self.scene_file())?;
+        if let Some(watcher) = &self.scene_watcher {
+            watcher.expect_write(json.as_bytes());
+        }
+        std::fs::write(&path, json).with_context(|| format!("Failed to write scene file: {:?}", path))?;
+        Ok(path)
+    }
+
+    /// Boxes, triangles and materials as rendered, with the last drawn camera pose
+    pub fn scene_file(&self) -> SceneFile {
+        SceneFile {
+            boxes: self.boxes.clone(),
+            triangles: self.triangles.clone(),
+            materials: self.materials.clone(),
+            camera: self.last_camera_pose,
+        }
+    }
+
+    /// Camera saved in the loaded scene file, if any
+    pub fn scene_camera(&self) -> Option<CameraBookmark> {
+        self.scene_camera
     }
 
     pub fn get_current_scene(&self) -> String {
         self.current_scene.lock().unwrap().clone()
     }
 
+    /// Discard accumulated frames so the next frame starts a fresh image
+    pub fn reset_accumulation(&mut self) {
+        self.accumulation = self.accumulation.reset();
+    }
+
+    /// Frames blended into the current image (always 0 in single-shot mode)
+    pub fn accumulated_frames(&self) -> u32 {
+        self.accumulation.frame
+    }
+
+    /// Replace a box as an undoable edit; its buffers are re-uploaded on the next frame
+    pub fn set_box(&mut self, index: usize, data: BoxData) -> bool {
+        let Some(slot) = self.boxes.get_mut(index) else {
+            return false;
+        };
+        let before = std::mem::replace(slot, data);
+        self.history.push(BoxEdit { index, before, after: data });
+        self.mark_edited(index);
+        true
+    }
+
+    /// Select the box under window pixel `(x, y)` as seen from `camera`; clicking empty space clears the selection
+    pub fn select_at(&mut self, camera: &Camera, x: u32, y: u32) -> Option<usize> {
+        let rect = viewport(self.size, self.aspect);
+        let pixel = ((x as f32 - rect.x).max(0.0) as u32, (y as f32 - rect.y).max(0.0) as u32);
+        let dir = camera.primary_ray(pixel, Resolution::new(rect.width as u32, rect.height as u32));
+        self.selected = pick(camera.position, dir, &self.boxes);
+        self.selected
+    }
+
+    pub fn selected_box(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Drop the editor selection; false when nothing was selected
+    pub fn clear_selection(&mut self) -> bool {
+        self.selected.take().is_some()
+    }
+
+    /// Move the selected box by `delta` as an undoable edit; false when nothing is selected
+    pub fn nudge_selected(&mut self, delta: glam::Vec3) -> bool {
+        let moved = self.selected.and_then(|index| Some((index, self.boxes.get(index)?.translated(delta))));
+        match moved {
+            Some((index, after)) => self.set_box(index, after),
+            None => false,
+        }
+    }
+
+    /// Record that box `index` moved, for the next upload to patch into the grid
+    fn mark_edited(&mut self, index: usize) {
+        if let Some(moved) = &mut self.moved_boxes {
+            moved.push(index);
+        }
+        self.dirty = self.dirty.scene_edited();
+        self.edited_since_backup = true;
+    }
+
+    /// Back up the scene to its auto-save file if the boxes changed since the last backup
+    pub fn save_backup(&mut self) -> anyhow::Result<Option<PathBuf>> {
+        if !self.edited_since_backup {
+            return Ok(None);
+        }
+        let path = scene_backup_path(&self.get_current_scene());
+        save_scene(&self.scene_file(), &path)?;
+        self.edited_since_backup = false;
+        Ok(Some(path))
+    }
+
+    /// Revert the latest box edit (Ctrl+Z)
+    pub fn undo_edit(&mut self) -> bool {
+        match self.history.undo(&mut self.boxes) {
+            Some(index) => {
+                self.mark_edited(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Re-apply the latest undone box edit (Ctrl+Y)
+    pub fn redo_edit(&mut self) -> bool {
+        match self.history.redo(&mut self.boxes) {
+            Some(index) => {
+                self.mark_edited(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Re-upload box and grid buffers after an edit: moved boxes are patched into the grid and only their
+    /// cells written, anything else rebuilds the grid and rewrites the buffers (in place when the sizes match)
+    fn upload_scene_buffers(&mut self) {
+        let moved = self.moved_boxes.replace(Vec::new());
+        if !moved.is_some_and(|moved| self.patch_moved_boxes(&moved)) {
+            self.grid = HierarchicalGrid::build(&self.boxes, &self.triangles);
+            self.upload_grid();
+        }
+
+        self.scene_config.num_boxes = self.boxes.len() as u32;
+        self.queue.write_buffer(&self.compute_resources.scene_config_buffer, 0, bytemuck::bytes_of(&self.scene_config));
+        self.grid_stats = self.grid.stats();
+        self.dirty = self.dirty.scene_uploaded();
+    }
+
+    /// Move boxes in `grid` and write just their buffer entries; false when a box left the grid bounds and
+    /// the grid must be rebuilt
+    fn patch_moved_boxes(&mut self, moved: &[usize]) -> bool {
+        let mut updates = Vec::with_capacity(moved.len());
+        for &index in moved {
+            match self.boxes.get(index).and_then(|b| self.grid.update_primitive(index as u32, b)) {
+                Some(update) => updates.push(update),
+                None => return false,
+            }
+        }
+
+        let resources = &self.compute_resources;
+        let box_size = std::mem::size_of::<BoxData>() as u64;
+        for &index in moved {
+            let bytes = bytemuck::bytes_of(&self.boxes[index]);
+            self.queue.write_buffer(&resources.box_buffer, index as u64 * box_size, bytes);
+        }
+        for update in updates {
+            for range in update.coarse {
+                self.queue.write_buffer(&resources.coarse_buffer, range.start, &self.grid.coarse_bytes(range));
+            }
+            for range in update.fine {
+                self.queue.write_buffer(&resources.fine_buffer, range.start, &self.grid.fine_bytes(range));
+            }
+        }
+        true
+    }
+
+    /// Write the whole grid and box buffers, reallocating and rebinding them when the sizes changed
+    fn upload_grid(&mut self) {
+        let (metadata, coarse_counts, fine_cells) = self.grid.to_gpu_buffers();
+        let fine_bytes: &[u8] = bytemuck::cast_slice(&fine_cells);
+        let box_bytes: &[u8] = bytemuck::cast_slice(&self.boxes);
+
+        let resources = &self.compute_resources;
+        let same_size = coarse_counts.len() as u64 == resources.coarse_buffer.size()
+            && fine_bytes.len() as u64 == resources.fine_buffer.size()
+            && box_bytes.len() as u64 == resources.box_buffer.size();
+        if same_size {
+            self.queue.write_buffer(&resources.box_buffer, 0, box_bytes);
+            self.queue.write_buffer(&resources.grid_meta_buffer, 0, bytemuck::cast_slice(&[metadata]));
+            self.queue.write_buffer(&resources.coarse_buffer, 0, &coarse_counts);
+            self.queue.write_buffer(&resources.fine_buffer, 0, fine_bytes);
+        } else {
+            self.compute_resources.replace_geometry(&self.device, &self.grid, &self.boxes);
+            self.compute_bind_group = Self::create_compute_bind_group(
+                &self.device,
+                &self.compute_bind_group_layout,
+                &self.compute_resources,
+                &self.targets,
+            );
+        }
+    }
+
+    /// Regenerate the LOD fractal around `origin` and upload it to this renderer, keeping the device,
+    /// pipelines, window state and settings; the edit history and selection refer to the old boxes and are cleared
+    pub fn refine_lod(&mut self, origin: glam::Vec3, options: &SceneOptions) {
+        let Some(budget) = options.lod_budget else {
+            return;
+        };
+        let boxes = fractal_lod(origin, budget);
+        self.boxes = match options.reflective_floor {
+            Some(reflectivity) => with_reflective_floor(boxes, reflectivity),
+            None => boxes,
+        };
+        self.selected = None;
+        self.history = EditHistory::default();
+        self.moved_boxes = None;
+        self.dirty = self.dirty.scene_edited();
+    }
+
+    /// Flip reflection bounces on/off; accumulation restarts on the next frame
+    pub fn toggle_reflections(&self) {
+        let mut settings = self.settings.lock().unwrap();
+        settings.reflections = !settings.reflections;
+    }
+
+    /// Hide or show the egui windows (H); they stay constructed while hidden
+    pub fn toggle_ui(&mut self) {
+        self.ui = self.ui.toggled();
+    }
+
     pub fn set_debug_pixel(&mut self, x: u32, y: u32) {
         self.debug_pixel = Some((x, y));
         if !self.no_ui {
@@ -1038,3 +1942,50 @@ impl RayTracer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gpu_probe_does_not_panic() {
+        // Either answer is fine; the probe must just complete on any machine
+        let available = RayTracer::is_gpu_available();
+        assert_eq!(available, RayTracer::is_gpu_available());
+    }
+
+    #[test]
+    fn test_light_buffer_contains_demo_scene_lights() {
+        let lighting = LightingConfig::default();
+        let demo_lights = crate::scenes::composed_lights();
+        assert!(!demo_lights.is_empty());
+
+        let lights = resolve_lights(&[], "composed", &lighting);
+        assert_eq!(lights.len(), lighting.lights().len() + demo_lights.len());
+        assert!(demo_lights.iter().all(|light| lights.contains(light)));
+        assert_eq!(resolve_lights(&[], "walls", &lighting), lighting.lights());
+
+        let custom = [LightData::point(glam::Vec3::Y, [1.0; 3], 5.0)];
+        assert_eq!(resolve_lights(&custom, "composed", &lighting), custom);
+    }
+
+    #[test]
+    fn test_ui_toggle_hides_windows() {
+        let ui = UiVisibility::new(false);
+        assert!(ui.shows_windows());
+        assert!(!ui.toggled().shows_windows());
+        assert_eq!(ui.toggled().toggled(), ui);
+
+        // --no-ui wins over the hotkey
+        assert!(!UiVisibility::new(true).shows_windows());
+        assert!(!UiVisibility::new(true).toggled().shows_windows());
+    }
+
+    #[test]
+    fn test_workgroups_follow_render_scale() {
+        let window = (1920, 1080);
+        assert_eq!(workgroup_counts(window), (240, 135));
+        assert_eq!(workgroup_counts(scaled_size(window.0, window.1, 0.5)), (120, 68));
+        assert_eq!(workgroup_counts(scaled_size(window.0, window.1, MIN_RENDER_SCALE)), (60, 34));
+    }
+}
@@ -0,0 +1,145 @@
+// resolution.rs - Render target size and the field-of-view helpers that depend on it
+
+/// Size in pixels of a render target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Resolution {
+    pub const fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    /// Width over height (1.0 for an empty target)
+    pub fn aspect_ratio(self) -> f32 {
+        if self.height == 0 {
+            1.0
+        } else {
+            self.width as f32 / self.height as f32
+        }
+    }
+
+    /// Horizontal half-angle for a vertical half-angle `fov` (radians, like `Camera::fov`)
+    ///
+    /// Primary rays keep the vertical FOV and spread horizontally by the aspect ratio,
+    /// so wide targets see more to the sides rather than less above and below.
+    pub fn horizontal_fov(self, fov: f32) -> f32 {
+        (self.aspect_ratio() * fov.tan()).atan()
+    }
+
+    /// Pixels covered by a unit-sized object at unit distance, used for LOD culling
+    ///
+    /// `fov` is the vertical half-angle in radians, like `Camera::fov`: primary rays span
+    /// `tan(fov)` above and below the view axis and that times the aspect ratio to the sides,
+    /// so the factor is equal along both axes.
+    pub fn lod_factor(self, fov: f32) -> f32 {
+        debug_assert!(fov > 0.0 && fov < std::f32::consts::FRAC_PI_2, "FOV half-angle must be in range (0, π/2)");
+        self.height as f32 / (2.0 * fov.tan())
+    }
+}
+
+impl From<(u32, u32)> for Resolution {
+    fn from((width, height): (u32, u32)) -> Self {
+        Self::new(width, height)
+    }
+}
+
+/// Axis-aligned rectangle in window pixels, origin at the top left
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    /// Pixel size, rounded and at least 1x1
+    pub fn size(self) -> (u32, u32) {
+        ((self.width.round() as u32).max(1), (self.height.round() as u32).max(1))
+    }
+
+    /// `[x, y, width, height]` as fractions of `window_size`, for the display shader
+    pub fn normalized(self, window_size: Resolution) -> [f32; 4] {
+        let (w, h) = (window_size.width.max(1) as f32, window_size.height.max(1) as f32);
+        [self.x / w, self.y / h, self.width / w, self.height / h]
+    }
+}
+
+/// Largest centered rect of `content_aspect` inside the window: bars top and bottom (letterbox)
+/// for wider content, left and right (pillarbox) for taller content
+pub fn fit_rect(content_aspect: f32, window_aspect: f32, window_size: Resolution) -> Rect {
+    let (w, h) = (window_size.width as f32, window_size.height as f32);
+    if content_aspect > window_aspect {
+        let height = w / content_aspect;
+        Rect { x: 0.0, y: (h - height) * 0.5, width: w, height }
+    } else {
+        let width = h * content_aspect;
+        Rect { x: (w - width) * 0.5, y: 0.0, width, height: h }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_4;
+
+    #[test]
+    fn test_aspect_ratio() {
+        assert_eq!(Resolution::new(1920, 1080).aspect_ratio(), 16.0 / 9.0);
+        assert_eq!(Resolution::new(600, 600).aspect_ratio(), 1.0);
+        assert_eq!(Resolution::new(300, 600).aspect_ratio(), 0.5);
+        assert_eq!(Resolution::new(10, 0).aspect_ratio(), 1.0);
+    }
+
+    #[test]
+    fn test_wider_content_is_letterboxed() {
+        let window = Resolution::new(800, 800);
+        let rect = fit_rect(2.0, window.aspect_ratio(), window);
+        assert_eq!(rect, Rect { x: 0.0, y: 200.0, width: 800.0, height: 400.0 });
+        assert_eq!(rect.normalized(window), [0.0, 0.25, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn test_taller_content_is_pillarboxed() {
+        let window = Resolution::new(1600, 900);
+        let rect = fit_rect(4.0 / 3.0, window.aspect_ratio(), window);
+        assert_eq!(rect, Rect { x: 200.0, y: 0.0, width: 1200.0, height: 900.0 });
+        assert_eq!(rect.size(), (1200, 900));
+    }
+
+    #[test]
+    fn test_matching_aspect_fills_window() {
+        let window = Resolution::new(1280, 720);
+        let rect = fit_rect(window.aspect_ratio(), window.aspect_ratio(), window);
+        assert_eq!(rect, Rect { x: 0.0, y: 0.0, width: 1280.0, height: 720.0 });
+    }
+
+    #[test]
+    fn test_wide_aspect_widens_horizontal_fov() {
+        let fov = FRAC_PI_4;
+        assert!((Resolution::new(600, 600).horizontal_fov(fov) - fov).abs() < 1e-6);
+
+        // tan(h) = 2 * tan(45°) for a 2:1 target
+        let wide = Resolution::new(1200, 600).horizontal_fov(fov);
+        assert!((wide - 2.0f32.atan()).abs() < 1e-6);
+        assert!(wide > fov);
+        assert!(Resolution::new(300, 600).horizontal_fov(fov) < fov);
+    }
+
+    #[test]
+    fn test_lod_factor_matches_along_both_axes() {
+        let resolution = Resolution::new(1200, 600);
+        let fov = 0.4f32;
+        let horizontal_density = resolution.width as f32 / (2.0 * resolution.horizontal_fov(fov).tan());
+        assert!((resolution.lod_factor(fov) - horizontal_density).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_lod_factor_uses_half_angle() {
+        // A 45° half-angle spans one unit above and below the axis at unit distance: 300 pixels per unit
+        assert!((Resolution::new(600, 600).lod_factor(FRAC_PI_4) - 300.0).abs() < 1e-3);
+    }
+}
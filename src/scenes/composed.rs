@@ -1,11 +1,20 @@
 use glam::Vec3;
-use crate::types::BoxData;
+use crate::types::{BoxData, LightData};
 use crate::demo::*;
 use crate::math::hsv_to_rgb;
 
 /// Example demo scene built using the demo module
 /// Showcases the composability and builder pattern
 pub fn create_composed_scene() -> Vec<BoxData> {
+    create_composed_demo().boxes
+}
+
+/// Point lights of the composed scene
+pub fn composed_lights() -> Vec<LightData> {
+    create_composed_demo().lights
+}
+
+fn create_composed_demo() -> DemoScene {
     DemoBuilder::new()
         // Add a reflective ground
         .add_reflective_ground([0.15, 0.15, 0.2], 0.5)
@@ -65,5 +74,9 @@ pub fn create_composed_scene() -> Vec<BoxData> {
             [0.3, 0.3, 1.0],
         )
 
+        // Warm light just above the central box, cool fill over the rings
+        .add_point_light(Vec3::new(0.0, 22.0, 0.0), [1.0, 0.9, 0.7], 400.0)
+        .add_point_light(Vec3::new(30.0, 30.0, 30.0), [0.6, 0.7, 1.0], 900.0)
+
         .build()
 }
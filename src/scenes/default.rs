@@ -1,10 +1,11 @@
 use glam::Vec3;
+use crate::math::Pcg32;
 use crate::types::BoxData;
 
-pub fn create_default_scene() -> Vec<BoxData> {
-    use std::collections::hash_map::RandomState;
-    use std::hash::{BuildHasher, Hash, Hasher};
+/// Seed for the scattered boxes, so the scene is identical on every run
+const SCATTER_SEED: u64 = 0x5ca7_7e4d;
 
+pub fn create_default_scene() -> Vec<BoxData> {
     let ground = BoxData::new([-50.0, -1.0, -50.0], [50.0, -0.99, 50.0], [0.3, 0.3, 0.3]);
 
     let dense_grid = (-10..10).flat_map(|x| {
@@ -46,27 +47,19 @@ pub fn create_default_scene() -> Vec<BoxData> {
         })
     });
 
-    let hasher_builder = RandomState::new();
-    let scattered_boxes = (0..200).map(|i| {
-        let mut hasher = hasher_builder.build_hasher();
-        i.hash(&mut hasher);
-        let hash = hasher.finish();
-
-        let x = ((hash % 100) as f32 / 100.0) * 40.0 - 20.0;
-        let y = (((hash >> 8) % 100) as f32 / 100.0) * 8.0 - 2.0;
-        let z = (((hash >> 16) % 100) as f32 / 100.0) * 40.0 - 30.0;
-        let size = (((hash >> 24) % 50) as f32 / 100.0) * 0.4 + 0.2;
-        let color = [
-            ((hash % 100) as f32 / 100.0) * 0.8 + 0.2,
-            (((hash >> 8) % 100) as f32 / 100.0) * 0.8 + 0.2,
-            (((hash >> 16) % 100) as f32 / 100.0) * 0.8 + 0.2,
-        ];
+    let mut rng = Pcg32::new(SCATTER_SEED);
+    let scattered_boxes: Vec<BoxData> = (0..200).map(|_| {
+        let x = rng.range(-20.0, 20.0);
+        let y = rng.range(-2.0, 6.0);
+        let z = rng.range(-30.0, 10.0);
+        let size = rng.range(0.2, 0.4);
+        let color = [0.0; 3].map(|_| rng.range(0.2, 1.0));
         BoxData::new(
             [x - size, y - size, z - size],
             [x + size, y + size, z + size],
             color,
         )
-    });
+    }).collect();
 
     let pillars = [-15.0, 15.0].iter().flat_map(|&side| {
         (-5..5).flat_map(move |z| {
@@ -125,3 +118,14 @@ pub fn create_default_scene() -> Vec<BoxData> {
 
     boxes
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_scene_is_deterministic() {
+        let (a, b) = (create_default_scene(), create_default_scene());
+        assert_eq!(bytemuck::cast_slice::<BoxData, u8>(&a), bytemuck::cast_slice::<BoxData, u8>(&b));
+    }
+}
@@ -0,0 +1,446 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::camera::CameraBookmark;
+use crate::types::{BoxData, MaterialData, TriangleData, IDENTITY_ROTATION};
+
+/// Prefix for `SCENE=file:<path>` scenes loaded from JSON on disk
+pub const SCENE_FILE_PREFIX: &str = "file:";
+
+/// Format version written by `save_scene`; version 1 files are a bare array of boxes
+pub const SCENE_FILE_VERSION: u32 = 2;
+
+/// Everything a scene file stores: geometry, materials, and an optional camera to start from
+#[derive(Debug, Clone, Default)]
+pub struct SceneFile {
+    pub boxes: Vec<BoxData>,
+    pub triangles: Vec<TriangleData>,
+    pub materials: Vec<MaterialData>,
+    pub camera: Option<CameraBookmark>,
+}
+
+impl SceneFile {
+    pub fn from_boxes(boxes: Vec<BoxData>) -> Self {
+        Self { boxes, ..Self::default() }
+    }
+}
+
+/// On-disk representation of a single box (GPU padding omitted)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BoxRecord {
+    min: [f32; 3],
+    max: [f32; 3],
+    color: [f32; 3],
+    reflectivity: f32,
+    center0: [f32; 3],
+    center1: [f32; 3],
+    half_size: [f32; 3],
+    #[serde(default)]
+    is_moving: bool,
+    #[serde(default)]
+    emissive: [f32; 3],
+    /// Index into `materials`, -1 for none
+    #[serde(default = "no_material")]
+    material_index: i32,
+    #[serde(default = "identity_rotation")]
+    rotation: [f32; 4],
+    #[serde(default)]
+    checker_color: [f32; 3],
+    #[serde(default)]
+    checker_tile: f32,
+}
+
+fn identity_rotation() -> [f32; 4] {
+    IDENTITY_ROTATION
+}
+
+/// Unit-length `rotation`, or identity when it cannot be normalized (e.g. all zeros in a hand-edited file)
+fn normalized_rotation(rotation: [f32; 4]) -> [f32; 4] {
+    glam::Vec4::from_array(rotation).try_normalize().map_or(IDENTITY_ROTATION, |q| q.to_array())
+}
+
+fn no_material() -> i32 {
+    -1
+}
+
+impl From<&BoxData> for BoxRecord {
+    fn from(b: &BoxData) -> Self {
+        Self {
+            min: b.min,
+            max: b.max,
+            color: b.color,
+            reflectivity: b.reflectivity,
+            center0: b.center0,
+            center1: b.center1,
+            half_size: b.half_size,
+            is_moving: b.is_moving != 0.0,
+            emissive: b.emissive,
+            material_index: b.material_index,
+            rotation: b.rotation,
+            checker_color: b.checker_color,
+            checker_tile: b.checker_tile,
+        }
+    }
+}
+
+impl From<BoxRecord> for BoxData {
+    /// Rotated boxes get their world AABB recomputed, so a stale or hand-edited `min`/`max` cannot clip them
+    fn from(r: BoxRecord) -> Self {
+        let data = Self {
+            min: r.min,
+            is_moving: if r.is_moving { 1.0 } else { 0.0 },
+            max: r.max,
+            _pad2: 0.0,
+            color: r.color,
+            reflectivity: BoxData::clamp_reflectivity(r.reflectivity),
+            center0: r.center0,
+            _pad4: 0.0,
+            center1: r.center1,
+            _pad5: 0.0,
+            half_size: r.half_size,
+            _pad6: 0.0,
+            emissive: r.emissive,
+            material_index: r.material_index,
+            rotation: normalized_rotation(r.rotation),
+            checker_color: r.checker_color,
+            checker_tile: r.checker_tile,
+        };
+        if data.is_rotated() {
+            data.with_rotated_bounds()
+        } else {
+            data
+        }
+    }
+}
+
+/// On-disk representation of a triangle; normals are omitted for flat-shaded triangles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TriangleRecord {
+    vertices: [[f32; 3]; 3],
+    #[serde(default)]
+    uvs: [[f32; 2]; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    normals: Option<[[f32; 3]; 3]>,
+    #[serde(default)]
+    material_id: u32,
+}
+
+impl From<&TriangleData> for TriangleRecord {
+    fn from(t: &TriangleData) -> Self {
+        Self {
+            vertices: [t.v0, t.v1, t.v2],
+            uvs: [t.uv0, t.uv1, t.uv2],
+            normals: (t.has_normals != 0.0).then_some([t.n0, t.n1, t.n2]),
+            material_id: t.material_id as u32,
+        }
+    }
+}
+
+impl From<TriangleRecord> for TriangleData {
+    fn from(r: TriangleRecord) -> Self {
+        let [v0, v1, v2] = r.vertices;
+        let [uv0, uv1, uv2] = r.uvs;
+        let triangle = TriangleData::new(v0, v1, v2, uv0, uv1, uv2, r.material_id);
+        match r.normals {
+            Some([n0, n1, n2]) => triangle.with_normals(n0, n1, n2),
+            None => triangle,
+        }
+    }
+}
+
+/// On-disk representation of a material; textures are not saved, so `scene_json` refuses textured materials
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MaterialRecord {
+    base_color: [f32; 4],
+    #[serde(default)]
+    emissive: [f32; 3],
+    #[serde(default)]
+    metallic: f32,
+    #[serde(default = "default_roughness")]
+    roughness: f32,
+    #[serde(default)]
+    alpha_mode: u32,
+    #[serde(default = "default_alpha_cutoff")]
+    alpha_cutoff: f32,
+}
+
+fn default_roughness() -> f32 {
+    1.0
+}
+
+fn default_alpha_cutoff() -> f32 {
+    0.5
+}
+
+impl From<&MaterialData> for MaterialRecord {
+    fn from(m: &MaterialData) -> Self {
+        Self {
+            base_color: m.base_color,
+            emissive: m.emissive,
+            metallic: m.metallic,
+            roughness: m.roughness,
+            alpha_mode: m.alpha_mode,
+            alpha_cutoff: m.alpha_cutoff,
+        }
+    }
+}
+
+impl From<MaterialRecord> for MaterialData {
+    fn from(r: MaterialRecord) -> Self {
+        Self {
+            emissive: r.emissive,
+            metallic: r.metallic,
+            roughness: r.roughness,
+            alpha_mode: r.alpha_mode,
+            alpha_cutoff: r.alpha_cutoff,
+            ..MaterialData::new_color(r.base_color)
+        }
+    }
+}
+
+fn has_texture(m: &MaterialData) -> bool {
+    m.texture_index >= 0 || m.normal_texture_index >= 0 || m.emissive_texture_index >= 0
+}
+
+/// Versioned on-disk scene, written as a JSON object
+#[derive(Debug, Serialize, Deserialize)]
+struct SceneRecord {
+    version: u32,
+    #[serde(default)]
+    boxes: Vec<BoxRecord>,
+    #[serde(default)]
+    triangles: Vec<TriangleRecord>,
+    #[serde(default)]
+    materials: Vec<MaterialRecord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    camera: Option<CameraBookmark>,
+}
+
+/// Any scene file `load_scene` accepts
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SceneJson {
+    Versioned(SceneRecord),
+    Legacy(Vec<BoxRecord>),
+}
+
+/// Where "Save Scene" writes `scene`: a `file:` scene saves over its own file, others to `<scene>.json`
+pub fn scene_save_path(scene: &str) -> PathBuf {
+    match scene.strip_prefix(SCENE_FILE_PREFIX) {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(format!("{}.json", scene)),
+    }
+}
+
+/// Where auto-save backs up `scene`, next to its save path so the scene file itself is never overwritten
+pub fn scene_backup_path(scene: &str) -> PathBuf {
+    scene_save_path(scene).with_extension("autosave.json")
+}
+
+/// Saves a scene to a JSON scene file at the current `SCENE_FILE_VERSION`
+pub fn save_scene(scene: &SceneFile, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    std::fs::write(path, scene_json(scene)?).context(format!("Failed to write scene file: {:?}", path))
+}
+
+/// The JSON `save_scene` writes for `scene`; fails if a material uses a texture, since textures are not saved
+pub fn scene_json(scene: &SceneFile) -> Result<String> {
+    if let Some(index) = scene.materials.iter().position(has_texture) {
+        anyhow::bail!("Material {} uses a texture, which scene files cannot store", index);
+    }
+    let record = SceneRecord {
+        version: SCENE_FILE_VERSION,
+        boxes: scene.boxes.iter().map(BoxRecord::from).collect(),
+        triangles: scene.triangles.iter().map(TriangleRecord::from).collect(),
+        materials: scene.materials.iter().map(MaterialRecord::from).collect(),
+        camera: scene.camera,
+    };
+    serde_json::to_string_pretty(&record).context("Failed to serialize scene")
+}
+
+/// Loads a JSON scene file written by `save_scene`, including version 1 box-only files
+pub fn load_scene(path: impl AsRef<Path>) -> Result<SceneFile> {
+    let path = path.as_ref();
+    let json = std::fs::read_to_string(path)
+        .context(format!("Failed to read scene file: {:?}", path))?;
+    let record = match serde_json::from_str(&json).context(format!("Failed to parse scene file: {:?}", path))? {
+        SceneJson::Versioned(record) => record,
+        SceneJson::Legacy(boxes) => {
+            SceneRecord { version: 1, boxes, triangles: vec![], materials: vec![], camera: None }
+        }
+    };
+    if record.version > SCENE_FILE_VERSION {
+        anyhow::bail!(
+            "Scene file {:?} is version {}, newer than supported version {}",
+            path,
+            record.version,
+            SCENE_FILE_VERSION
+        );
+    }
+
+    println!(
+        "Loaded {} boxes, {} triangles and {} materials from {:?}",
+        record.boxes.len(),
+        record.triangles.len(),
+        record.materials.len(),
+        path
+    );
+
+    Ok(SceneFile {
+        boxes: record.boxes.into_iter().map(BoxData::from).collect(),
+        triangles: record.triangles.into_iter().map(TriangleData::from).collect(),
+        materials: record.materials.into_iter().map(MaterialData::from).collect(),
+        camera: record.camera,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenes::create_default_scene;
+
+    #[test]
+    fn test_default_scene_round_trip() {
+        let boxes = create_default_scene();
+        let path = std::env::temp_dir().join(format!("ray_tracer_scene_{}.json", std::process::id()));
+
+        save_scene(&SceneFile::from_boxes(boxes.clone()), &path).unwrap();
+        let loaded = load_scene(&path).unwrap().boxes;
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.len(), boxes.len());
+
+        // Last box is a moving box, so it exercises center0/center1 as well
+        for idx in [0, boxes.len() / 2, boxes.len() - 1] {
+            let (original, restored) = (&boxes[idx], &loaded[idx]);
+            assert_eq!(restored.min, original.min);
+            assert_eq!(restored.max, original.max);
+            assert_eq!(restored.color, original.color);
+            assert_eq!(restored.reflectivity, original.reflectivity);
+            assert_eq!(restored.center0, original.center0);
+            assert_eq!(restored.center1, original.center1);
+            assert_eq!(restored.half_size, original.half_size);
+            assert_eq!(restored.is_moving, original.is_moving);
+        }
+    }
+
+    #[test]
+    fn test_edited_scene_round_trip() {
+        let mut boxes = create_default_scene();
+        boxes[0] = boxes[0].translated(glam::Vec3::new(0.25, 0.0, -0.5));
+        boxes[0].reflectivity = 0.75;
+        boxes.push(BoxData::new_moving(
+            [0.0, 0.0, 0.0],
+            [1.0, 1.0, 1.0],
+            [0.2, 0.4, 0.6],
+            [0.5, 0.5, 0.5],
+            [3.5, 0.5, 0.5],
+            [0.5, 0.5, 0.5],
+        ));
+        let path = std::env::temp_dir().join(format!("ray_tracer_edited_{}.json", std::process::id()));
+
+        save_scene(&SceneFile::from_boxes(boxes.clone()), &path).unwrap();
+        let loaded = load_scene(&path).unwrap().boxes;
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(bytemuck::cast_slice::<_, u8>(&loaded), bytemuck::cast_slice::<_, u8>(&boxes));
+        assert_eq!(loaded[0].reflectivity, 0.75);
+        assert_eq!(loaded.last().unwrap().is_moving, 1.0);
+    }
+
+    #[test]
+    fn test_triangle_scene_round_trip() {
+        let glowing = MaterialData { emissive: [0.5, 0.0, 0.0], metallic: 0.25, ..MaterialData::new_color([1.0; 4]) };
+        let scene = SceneFile {
+            boxes: vec![BoxData::new([0.0, -1.0, 0.0], [4.0, 0.0, 4.0], [0.8, 0.8, 0.8]).with_material(1)],
+            triangles: vec![
+                TriangleData::new([0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0; 2], [1.0, 0.0], [0.0, 1.0], 0),
+                TriangleData::new([0.0; 3], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [0.0; 2], [0.0; 2], [0.0; 2], 1)
+                    .with_normals([1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+            ],
+            materials: vec![glowing, MaterialData::new_color([0.2, 0.4, 0.6, 0.5])],
+            camera: Some(CameraBookmark { position: glam::Vec3::new(1.0, 2.0, 3.0), yaw: 0.5, pitch: -0.1, fov: 0.8 }),
+        };
+        let path = std::env::temp_dir().join(format!("ray_tracer_triangles_{}.json", std::process::id()));
+
+        save_scene(&scene, &path).unwrap();
+        let loaded = load_scene(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(bytemuck::cast_slice::<_, u8>(&loaded.boxes), bytemuck::cast_slice::<_, u8>(&scene.boxes));
+        assert_eq!(bytemuck::cast_slice::<_, u8>(&loaded.triangles), bytemuck::cast_slice::<_, u8>(&scene.triangles));
+        assert_eq!(bytemuck::cast_slice::<_, u8>(&loaded.materials), bytemuck::cast_slice::<_, u8>(&scene.materials));
+        assert_eq!(loaded.camera, scene.camera);
+    }
+
+    #[test]
+    fn test_loaded_rotations_are_normalized() {
+        let record = |rotation| BoxRecord { rotation, ..BoxRecord::from(&BoxData::new([0.0; 3], [1.0; 3], [0.5; 3])) };
+
+        assert_eq!(BoxData::from(record([0.0, 0.0, 2.0, 0.0])).rotation, [0.0, 0.0, 1.0, 0.0]);
+        assert_eq!(BoxData::from(record([0.0; 4])).rotation, IDENTITY_ROTATION);
+        assert_eq!(BoxData::from(record(IDENTITY_ROTATION)).rotation, IDENTITY_ROTATION);
+    }
+
+    #[test]
+    fn test_rotated_box_bounds_recomputed_on_load() {
+        let rotation = glam::Quat::from_rotation_y(std::f32::consts::FRAC_PI_4);
+        let rotated = BoxData::new_rotated(glam::Vec3::new(1.0, 2.0, 3.0), glam::Vec3::splat(2.0), rotation, [0.5; 3]);
+        let stale = BoxRecord { min: [0.5, 1.5, 2.5], max: [1.5, 2.5, 3.5], ..BoxRecord::from(&rotated) };
+
+        let loaded = BoxData::from(stale);
+        for axis in 0..3 {
+            assert!((loaded.min[axis] - rotated.min[axis]).abs() < 1e-5, "{:?} vs {:?}", loaded.min, rotated.min);
+            assert!((loaded.max[axis] - rotated.max[axis]).abs() < 1e-5, "{:?} vs {:?}", loaded.max, rotated.max);
+        }
+        // The 45-degree turn widens X and Z to the corner distance, Y keeps the half size
+        assert!((loaded.max[0] - 1.0 - 2.0f32.sqrt()).abs() < 1e-5);
+        assert!((loaded.max[1] - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_textured_material_is_not_saved() {
+        let scene = SceneFile { materials: vec![MaterialData::new_textured([1.0; 4], 0)], ..SceneFile::default() };
+        assert!(scene_json(&scene).is_err());
+
+        let normal_mapped = MaterialData { normal_texture_index: 2, ..MaterialData::new_color([1.0; 4]) };
+        assert!(scene_json(&SceneFile { materials: vec![normal_mapped], ..SceneFile::default() }).is_err());
+    }
+
+    #[test]
+    fn test_load_version_1_box_array() {
+        let path = std::env::temp_dir().join(format!("ray_tracer_v1_{}.json", std::process::id()));
+        let records = vec![BoxRecord::from(&BoxData::new([0.0; 3], [1.0; 3], [0.5; 3]))];
+        std::fs::write(&path, serde_json::to_string(&records).unwrap()).unwrap();
+
+        let loaded = load_scene(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.boxes.len(), 1);
+        assert!(loaded.triangles.is_empty() && loaded.camera.is_none());
+    }
+
+    #[test]
+    fn test_load_newer_version_fails() {
+        let path = std::env::temp_dir().join(format!("ray_tracer_v99_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"version": 99}"#).unwrap();
+
+        let result = load_scene(&path);
+        std::fs::remove_file(&path).ok();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_scene_save_path() {
+        assert_eq!(scene_save_path("file:scenes/room.json"), PathBuf::from("scenes/room.json"));
+        assert_eq!(scene_save_path("walls"), PathBuf::from("walls.json"));
+        assert_eq!(scene_backup_path("file:scenes/room.json"), PathBuf::from("scenes/room.autosave.json"));
+    }
+
+    #[test]
+    fn test_load_missing_file_fails() {
+        assert!(load_scene("does/not/exist.json").is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use crate::types::BoxData;
+
+/// Index of the ground box: the lowest box, ties broken by largest XZ footprint
+pub fn find_ground_box(boxes: &[BoxData]) -> Option<usize> {
+    let footprint = |b: &BoxData| (b.max[0] - b.min[0]) * (b.max[2] - b.min[2]);
+
+    boxes
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            a.min[1]
+                .total_cmp(&b.min[1])
+                .then_with(|| footprint(b).total_cmp(&footprint(a)))
+        })
+        .map(|(i, _)| i)
+}
+
+/// Make only the ground box reflective, leaving the rest of the scene untouched
+pub fn with_reflective_floor(mut boxes: Vec<BoxData>, reflectivity: f32) -> Vec<BoxData> {
+    if let Some(i) = find_ground_box(&boxes) {
+        boxes[i] = boxes[i].with_reflectivity(reflectivity);
+    }
+    boxes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_lowest_largest_box() {
+        let boxes = vec![
+            BoxData::new([-1.0, 0.0, -1.0], [1.0, 2.0, 1.0], [1.0, 0.0, 0.0]),
+            BoxData::new([-2.0, -1.0, -2.0], [2.0, -0.5, 2.0], [0.0, 1.0, 0.0]),
+            BoxData::new([-20.0, -1.0, -20.0], [20.0, -0.5, 20.0], [0.5, 0.5, 0.5]),
+            BoxData::new([5.0, 3.0, 5.0], [6.0, 4.0, 6.0], [0.0, 0.0, 1.0]),
+        ];
+
+        assert_eq!(find_ground_box(&boxes), Some(2));
+    }
+
+    #[test]
+    fn test_sets_reflectivity_on_ground_only() {
+        let boxes = vec![
+            BoxData::new([-1.0, 0.0, -1.0], [1.0, 2.0, 1.0], [1.0, 0.0, 0.0]),
+            BoxData::new([-20.0, -1.0, -20.0], [20.0, -0.5, 20.0], [0.5, 0.5, 0.5]),
+        ];
+
+        let boxes = with_reflective_floor(boxes, 0.6);
+        assert_eq!(boxes[0].reflectivity, 0.0);
+        assert_eq!(boxes[1].reflectivity, 0.6);
+    }
+
+    #[test]
+    fn test_empty_scene_has_no_ground() {
+        assert_eq!(find_ground_box(&[]), None);
+        assert!(with_reflective_floor(vec![], 0.5).is_empty());
+    }
+}
@@ -2,8 +2,20 @@ use glam::Vec3;
 use crate::types::BoxData;
 use super::common::{should_terminate_fractal, generate_fractal_color};
 
-fn create_menger_sponge(center: Vec3, size: f32, depth: u32, color_seed: u32) -> Vec<BoxData> {
-    if should_terminate_fractal(depth, size, 0.3) {
+/// Detail budget for `fractal_lod`: a cube is subdivided while it is closer than `size * budget`
+pub const DEFAULT_LOD_BUDGET: f32 = 8.0;
+
+/// Camera travel after which an LOD fractal scene is regenerated around the new position
+pub const LOD_REGENERATE_DISTANCE: f32 = 15.0;
+
+/// Refine cubes that are large relative to their distance from `camera_pos`
+fn lod_refine(camera_pos: Vec3, detail_budget: f32) -> impl Fn(Vec3, f32) -> bool {
+    move |center, size| size * detail_budget > camera_pos.distance(center)
+}
+
+/// Menger sponge that only subdivides cubes accepted by `refine`; rejected subtrees become one solid box
+fn menger_sponge(center: Vec3, size: f32, depth: u32, color_seed: u32, refine: &dyn Fn(Vec3, f32) -> bool) -> Vec<BoxData> {
+    if should_terminate_fractal(depth, size, 0.3) || !refine(center, size) {
         let half = size * 0.5;
         let color = generate_fractal_color(color_seed, 0.7, 0.8);
         return vec![BoxData::new(
@@ -31,11 +43,12 @@ fn create_menger_sponge(center: Vec3, size: f32, depth: u32, color_seed: u32) ->
                     z as f32 * offset,
                 );
 
-                boxes.extend(create_menger_sponge(
+                boxes.extend(menger_sponge(
                     new_center,
                     new_size,
                     depth - 1,
                     color_seed.wrapping_add((x + y * 3 + z * 9) as u32),
+                    refine,
                 ));
             }
         }
@@ -124,6 +137,16 @@ fn create_fractal_tree(center: Vec3, size: f32, depth: u32, direction: Vec3, ang
 }
 
 pub fn create_fractal_scene() -> Vec<BoxData> {
+    build_fractal_scene(&|_, _| true)
+}
+
+/// Fractal scene with Menger sponges refined near `camera_pos` and merged into coarse boxes far away
+pub fn fractal_lod(camera_pos: Vec3, detail_budget: f32) -> Vec<BoxData> {
+    build_fractal_scene(&lod_refine(camera_pos, detail_budget))
+}
+
+/// Fractal scene whose sponges subdivide only where `refine` allows
+fn build_fractal_scene(refine: &dyn Fn(Vec3, f32) -> bool) -> Vec<BoxData> {
     let mut boxes = Vec::new();
 
     let ground = BoxData::new([-100.0, -1.0, -100.0], [100.0, -0.99, 100.0], [0.2, 0.2, 0.2]);
@@ -131,7 +154,7 @@ pub fn create_fractal_scene() -> Vec<BoxData> {
 
     println!("Generating fractal scene...");
 
-    boxes.extend(create_menger_sponge(Vec3::new(0.0, 5.0, -20.0), 12.0, 3, 0));
+    boxes.extend(menger_sponge(Vec3::new(0.0, 5.0, -20.0), 12.0, 3, 0, refine));
     println!("  Menger sponge generated: {} boxes", boxes.len());
 
     let sierpinski_boxes = create_sierpinski_pyramid(Vec3::new(-25.0, 8.0, -30.0), 16.0, 4, 100);
@@ -164,11 +187,12 @@ pub fn create_fractal_scene() -> Vec<BoxData> {
             let angle = (i as f32 / count as f32) * std::f32::consts::TAU;
             let x = angle.cos() * radius;
             let z = angle.sin() * radius;
-            boxes.extend(create_menger_sponge(
+            boxes.extend(menger_sponge(
                 Vec3::new(x, 5.0, z - 20.0),
                 size,
                 depth,
                 300u32.wrapping_add(i * 17).wrapping_add(ring * 100),
+                refine,
             ));
         }
     }
@@ -199,3 +223,26 @@ pub fn create_fractal_scene() -> Vec<BoxData> {
     println!("Fractal scene created: {} total boxes", boxes.len());
     boxes
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOT: Vec3 = Vec3::new(0.0, 5.0, -20.0);
+
+    fn sponge_seen_from(camera_pos: Vec3) -> Vec<BoxData> {
+        menger_sponge(ROOT, 12.0, 3, 0, &lod_refine(camera_pos, DEFAULT_LOD_BUDGET))
+    }
+
+    #[test]
+    fn test_far_sponge_has_fewer_boxes_than_near() {
+        let near = sponge_seen_from(ROOT + Vec3::new(0.0, 0.0, 10.0));
+        let far = sponge_seen_from(ROOT + Vec3::new(0.0, 0.0, 500.0));
+        let full = menger_sponge(ROOT, 12.0, 3, 0, &|_, _| true);
+
+        assert!(far.len() < near.len(), "far {} vs near {}", far.len(), near.len());
+        assert_eq!(far.len(), 1);
+        assert!(near.len() <= full.len());
+        assert_eq!(full.len(), 20usize.pow(3));
+    }
+}
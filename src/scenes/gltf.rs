@@ -1,13 +1,20 @@
 use crate::loaders::gltf::load_gltf_with_animation;
-use crate::loaders::gltf_triangles::{load_gltf_triangles, TextureData};
+use crate::loaders::gltf_triangles::{load_gltf_triangles_with_budget, TextureData};
 use crate::types::{BoxData, TriangleData, MaterialData};
 
+/// Model loaded by the `gltf` scene when GLTF_FILE is unset
+pub const DEFAULT_GLTF_FILE: &str = "models/no_animation/scene.gltf";
+
+/// Path of the `gltf` scene's model: GLTF_FILE, else `DEFAULT_GLTF_FILE`
+pub fn gltf_file() -> String {
+    std::env::var("GLTF_FILE").unwrap_or_else(|_| DEFAULT_GLTF_FILE.to_string())
+}
+
 /// Creates a scene by loading a glTF file
 /// The file path can be specified via the GLTF_FILE environment variable,
 /// or defaults to "models/no_animation/scene.gltf"
 pub fn create_gltf_scene() -> Vec<BoxData> {
-    let file_path =
-        std::env::var("GLTF_FILE").unwrap_or_else(|_| "models/no_animation/scene.gltf".to_string());
+    let file_path = gltf_file();
 
     println!("Loading glTF file: {}", file_path);
 
@@ -46,21 +53,14 @@ pub fn create_gltf_scene() -> Vec<BoxData> {
 
 /// Loads triangles, materials, and textures from a glTF file
 /// Returns a tuple of (triangles, materials, textures)
-pub fn create_gltf_triangles() -> (Vec<TriangleData>, Vec<MaterialData>, Vec<TextureData>) {
-    let file_path =
-        std::env::var("GLTF_FILE").unwrap_or_else(|_| "models/no_animation/scene.gltf".to_string());
+/// Models over `max_triangles` are rejected with a descriptive error before any triangles are extracted
+pub fn create_gltf_triangles(
+    max_triangles: Option<usize>,
+) -> anyhow::Result<(Vec<TriangleData>, Vec<MaterialData>, Vec<TextureData>)> {
+    let file_path = gltf_file();
 
-    match load_gltf_triangles(&file_path) {
-        Ok(scene) => {
-            println!("Successfully loaded {} triangles, {} materials, and {} textures from glTF file",
-                scene.triangles.len(), scene.materials.len(), scene.textures.len());
-            (scene.triangles, scene.materials, scene.textures)
-        }
-        Err(e) => {
-            eprintln!("Failed to load glTF triangles: {}", e);
-            eprintln!("Error details: {:?}", e);
-            // Return empty vecs on error
-            (vec![], vec![], vec![])
-        }
-    }
+    let scene = load_gltf_triangles_with_budget(&file_path, max_triangles)?;
+    println!("Successfully loaded {} triangles, {} materials, and {} textures from glTF file",
+        scene.triangles.len(), scene.materials.len(), scene.textures.len());
+    Ok((scene.triangles, scene.materials, scene.textures))
 }
@@ -1,3 +1,5 @@
+use crate::types::{BoxData, LightData, TriangleData};
+
 mod common;
 mod composed;
 mod fractal;
@@ -7,13 +9,64 @@ mod default;
 mod reflected;
 mod gltf;
 mod pyramid;
+mod stress;
+mod file;
+mod floor;
+mod watch;
 
-pub use composed::create_composed_scene;
-pub use fractal::create_fractal_scene;
+pub use composed::{composed_lights, create_composed_scene};
+pub use fractal::{create_fractal_scene, fractal_lod, DEFAULT_LOD_BUDGET, LOD_REGENERATE_DISTANCE};
 pub use walls::create_walls_scene;
 pub use tunnel::create_tunnel_scene;
 pub use default::create_default_scene;
 pub use reflected::create_reflected_scene;
-pub use gltf::{create_gltf_scene, create_gltf_triangles};
+pub use gltf::{create_gltf_scene, create_gltf_triangles, gltf_file, DEFAULT_GLTF_FILE};
 pub use crate::loaders::gltf_triangles::TextureData;
 pub use pyramid::{create_pyramid_scene, create_pyramid_triangles};
+pub use stress::{create_stress_scene, DEFAULT_STRESS_COUNT};
+pub use file::{save_scene, scene_json, load_scene, scene_save_path, scene_backup_path, SceneFile, SCENE_FILE_PREFIX, SCENE_FILE_VERSION};
+pub use floor::{find_ground_box, with_reflective_floor};
+pub use watch::{watch, SceneWatcher, RELOAD_DEBOUNCE};
+
+/// A procedurally generated scene that can be built without a GPU or assets on disk
+pub struct SceneEntry {
+    pub name: &'static str,
+    pub boxes: fn() -> Vec<BoxData>,
+    pub triangles: fn() -> Vec<TriangleData>,
+}
+
+const SCENES: &[SceneEntry] = &[
+    SceneEntry { name: "fractal", boxes: create_fractal_scene, triangles: Vec::new },
+    SceneEntry { name: "composed", boxes: create_composed_scene, triangles: Vec::new },
+    SceneEntry { name: "walls", boxes: create_walls_scene, triangles: Vec::new },
+    SceneEntry { name: "tunnel", boxes: create_tunnel_scene, triangles: Vec::new },
+    SceneEntry { name: "default", boxes: create_default_scene, triangles: Vec::new },
+    SceneEntry { name: "reflected", boxes: create_reflected_scene, triangles: Vec::new },
+    SceneEntry { name: "pyramid", boxes: Vec::new, triangles: create_pyramid_triangles },
+    SceneEntry { name: "stress", boxes: || create_stress_scene(DEFAULT_STRESS_COUNT), triangles: Vec::new },
+];
+
+/// Ambient light used when a scene has no override
+pub const DEFAULT_AMBIENT: f32 = 0.3;
+
+/// Per-scene ambient light; enclosed scenes like the tunnel are darker
+pub fn default_ambient(scene_name: &str) -> f32 {
+    match scene_name {
+        "tunnel" => 0.1,
+        "walls" => 0.2,
+        _ => DEFAULT_AMBIENT,
+    }
+}
+
+/// Lights a generated scene places itself (`DemoScene::lights`); empty for scenes lit by the lighting preset
+pub fn scene_lights(scene_name: &str) -> Vec<LightData> {
+    match scene_name {
+        "composed" => composed_lights(),
+        _ => Vec::new(),
+    }
+}
+
+/// All registered generated scenes (`gltf` and `file:` scenes load from disk and are not listed)
+pub fn available() -> &'static [SceneEntry] {
+    SCENES
+}
@@ -59,13 +59,13 @@ pub fn create_reflected_scene() -> Vec<BoxData> {
         reflectivity,
     ));
 
-    // Central light source - bright and emissive-looking
+    // Central light source
     let light_size = 4.0;
-    boxes.push(BoxData::new_reflective(
+    boxes.push(BoxData::new_emissive(
         [-light_size, -light_size, -light_size],
         [light_size, light_size, light_size],
         [1.0, 0.95, 0.8],
-        0.1,
+        [1.0, 0.95, 0.8],
     ));
 
     // Add some colorful objects around the room to see reflections
@@ -107,16 +107,13 @@ pub fn create_reflected_scene() -> Vec<BoxData> {
     }
 
     // Add a moving reflective object
-    let moving_box = BoxData::create_moving_box(
+    boxes.push(BoxData::create_moving_box_reflective(
         Vec3::splat(4.0),
         Vec3::new(0.0, 10.0, 0.0),
         Vec3::new(0.0, -10.0, 0.0),
         [0.9, 0.9, 0.95],
-    );
-    // Manually set reflectivity for the moving box
-    let mut moving_reflective = moving_box;
-    moving_reflective.reflectivity = 0.9;
-    boxes.push(moving_reflective);
+        0.9,
+    ));
 
     println!("Reflected scene created: {} total boxes", boxes.len());
     boxes
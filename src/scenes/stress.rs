@@ -0,0 +1,44 @@
+use crate::math::hsv_to_rgb;
+use crate::types::BoxData;
+
+/// Box count for `SCENE=stress` when `--stress-count` is not given
+pub const DEFAULT_STRESS_COUNT: usize = 10_000;
+
+const STRESS_BOX_SIZE: f32 = 0.5;
+const STRESS_SPACING: f32 = 1.0;
+
+/// `count` small boxes on a cubic lattice centered on the origin, hue cycling along the fill order
+pub fn create_stress_scene(count: usize) -> Vec<BoxData> {
+    let side = (1..).find(|n: &usize| n.pow(3) >= count).unwrap_or(1);
+    let offset = (side as f32 - 1.0) * STRESS_SPACING * 0.5;
+
+    (0..count)
+        .map(|i| {
+            let (x, y, z) = (i % side, (i / side) % side, i / (side * side));
+            let min = [x, y, z].map(|c| c as f32 * STRESS_SPACING - offset);
+            let max = min.map(|c| c + STRESS_BOX_SIZE);
+            BoxData::new(min, max, hsv_to_rgb(i as f32 / count as f32, 0.8, 0.9))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stress_scene_has_requested_count_and_valid_bounds() {
+        let boxes = create_stress_scene(1000);
+        assert_eq!(boxes.len(), 1000);
+        assert!(boxes.iter().all(|b| (0..3).all(|axis| b.min[axis] < b.max[axis])));
+        assert!(create_stress_scene(0).is_empty());
+        assert_eq!(create_stress_scene(9).len(), 9);
+    }
+
+    #[test]
+    fn test_stress_boxes_do_not_overlap() {
+        let boxes = create_stress_scene(27);
+        let distinct: std::collections::HashSet<_> = boxes.iter().map(|b| b.min.map(f32::to_bits)).collect();
+        assert_eq!(distinct.len(), 27);
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Quiet period after the last write before a reload; editors often write a file several times per save
+pub const RELOAD_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Keeps a scene file under watch; dropping it stops watching
+pub struct SceneWatcher {
+    _watcher: RecommendedWatcher,
+    /// Hash of the contents this process last wrote to the file, see `expect_write`
+    own_write: Arc<Mutex<Option<u64>>>,
+}
+
+impl SceneWatcher {
+    /// Note that we are about to write `contents` to the watched file, so the change it causes is not
+    /// reported as a reload (which would discard the editor state that was just saved)
+    pub fn expect_write(&self, contents: &[u8]) {
+        *self.own_write.lock().unwrap() = Some(content_hash(contents));
+    }
+}
+
+fn content_hash(contents: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// True when the file now holds exactly what this process last wrote, i.e. nobody else changed it
+pub fn is_own_write(contents: &[u8], own_write: Option<u64>) -> bool {
+    own_write == Some(content_hash(contents))
+}
+
+/// Watch `path` and set `needs_reload` once its contents settle after a change
+///
+/// The parent directory is watched so editors that save by replacing the file
+/// are still picked up. Writes announced through `SceneWatcher::expect_write` are ignored.
+pub fn watch(path: impl AsRef<Path>, needs_reload: Arc<Mutex<bool>>) -> Result<SceneWatcher> {
+    let path = path.as_ref().to_path_buf();
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => ".".into(),
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let watched = path.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        if event.is_ok_and(|e| is_scene_change(&e, &watched)) {
+            tx.send(()).ok();
+        }
+    })
+    .context(format!("Failed to create watcher for {:?}", path))?;
+    watcher
+        .watch(&directory, RecursiveMode::NonRecursive)
+        .context(format!("Failed to watch {:?}", directory))?;
+
+    let own_write = Arc::new(Mutex::new(None));
+    let expected = own_write.clone();
+    std::thread::spawn(move || {
+        forward_debounced(&rx, RELOAD_DEBOUNCE, || {
+            let own_write = *expected.lock().unwrap();
+            let written_by_us = std::fs::read(&path).is_ok_and(|contents| is_own_write(&contents, own_write));
+            if !written_by_us {
+                *needs_reload.lock().unwrap() = true;
+            }
+        });
+    });
+
+    Ok(SceneWatcher { _watcher: watcher, own_write })
+}
+
+/// True for events that create or modify the file at `path`
+pub fn is_scene_change(event: &Event, path: &Path) -> bool {
+    let touches_path = event.paths.iter().any(|p| p.file_name() == path.file_name());
+    touches_path && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+}
+
+/// Call `on_change` once per burst of events spaced less than `quiet` apart; returns when the sender hangs up
+pub fn forward_debounced(events: &Receiver<()>, quiet: Duration, mut on_change: impl FnMut()) {
+    while events.recv().is_ok() {
+        while events.recv_timeout(quiet).is_ok() {}
+        on_change();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, ModifyKind};
+
+    #[test]
+    fn test_file_change_sets_reload_flag() {
+        let path = Path::new("/tmp/scenes/room.json");
+        let write = Event::new(EventKind::Modify(ModifyKind::Any)).add_path(path.to_path_buf());
+        let other = Event::new(EventKind::Create(CreateKind::File)).add_path("/tmp/scenes/notes.txt".into());
+        assert!(is_scene_change(&write, path));
+        assert!(!is_scene_change(&other, path));
+
+        let (tx, rx) = mpsc::channel();
+        [&write, &other].into_iter().filter(|e| is_scene_change(e, path)).for_each(|_| tx.send(()).unwrap());
+        drop(tx);
+
+        let needs_reload = Arc::new(Mutex::new(false));
+        forward_debounced(&rx, Duration::from_millis(10), || *needs_reload.lock().unwrap() = true);
+        assert!(*needs_reload.lock().unwrap());
+    }
+
+    #[test]
+    fn test_own_write_is_not_a_change() {
+        let saved = br#"{"version": 3, "boxes": []}"#;
+        assert!(is_own_write(saved, Some(content_hash(saved))));
+        assert!(!is_own_write(br#"{"version": 3, "boxes": [1]}"#, Some(content_hash(saved))));
+        assert!(!is_own_write(saved, None));
+    }
+
+    #[test]
+    fn test_rapid_events_collapse_into_one_reload() {
+        let (tx, rx) = mpsc::channel();
+        tx.send(()).unwrap();
+        tx.send(()).unwrap();
+        drop(tx);
+
+        let mut reloads = 0;
+        forward_debounced(&rx, Duration::from_millis(10), || reloads += 1);
+        assert_eq!(reloads, 1);
+    }
+
+    #[test]
+    fn test_spaced_events_reload_separately() {
+        let (tx, rx) = mpsc::channel();
+        let sender = std::thread::spawn(move || {
+            tx.send(()).unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+            tx.send(()).unwrap();
+        });
+
+        let mut reloads = 0;
+        forward_debounced(&rx, Duration::from_millis(10), || reloads += 1);
+        sender.join().unwrap();
+        assert_eq!(reloads, 2);
+    }
+}
@@ -0,0 +1,134 @@
+// sky.rs - Background for rays that escape the scene (mirrored by `sky_color` in the shader)
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+/// Color straight up in the procedural sky
+pub const ZENITH_COLOR: [f32; 3] = [0.25, 0.45, 0.85];
+/// Color at (and below) the horizon in the procedural sky
+pub const HORIZON_COLOR: [f32; 3] = [0.75, 0.85, 0.95];
+const SUN_COLOR: [f32; 3] = [1.0, 0.95, 0.8];
+/// Default top (straight up) color of the vertical gradient background
+pub const GRADIENT_TOP: [f32; 3] = [0.5, 0.7, 1.0];
+/// Default bottom (straight down) color of the vertical gradient background
+pub const GRADIENT_BOTTOM: [f32; 3] = [0.3, 0.5, 0.7];
+/// Higher values squeeze the horizon brightening towards the horizon
+const HORIZON_FALLOFF: i32 = 4;
+const SUN_GLOW_EXPONENT: f32 = 64.0;
+const SUN_GLOW_INTENSITY: f32 = 0.5;
+/// Cosine of the sun disk's angular radius (about 1.8 degrees)
+const SUN_DISK_COS: f32 = 0.9995;
+const SUN_DISK_INTENSITY: f32 = 2.0;
+
+/// Background selectable with `--sky`; `--skybox` takes precedence over both
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkyMode {
+    /// Fixed blue vertical gradient, the renderer's original sky
+    #[default]
+    Gradient,
+    /// Analytic sky lit by the key light
+    Procedural,
+    /// Flat gradient top color in every direction
+    Solid,
+}
+
+impl SkyMode {
+    pub const ALL: [SkyMode; 3] = [SkyMode::Gradient, SkyMode::Procedural, SkyMode::Solid];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "gradient" => Some(Self::Gradient),
+            "procedural" => Some(Self::Procedural),
+            "solid" => Some(Self::Solid),
+            _ => None,
+        }
+    }
+
+    /// Value of `SceneConfig::sky_mode` understood by the shader
+    pub const fn as_u32(self) -> u32 {
+        match self {
+            Self::Gradient => 0,
+            Self::Procedural => 1,
+            Self::Solid => 2,
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Gradient => "Gradient",
+            Self::Procedural => "Procedural",
+            Self::Solid => "Solid",
+        }
+    }
+}
+
+/// Vertical gradient background: `bottom` straight down, `top` straight up, their midpoint at the horizon
+pub fn gradient_color(dir: Vec3, top: [f32; 3], bottom: [f32; 3]) -> [f32; 3] {
+    let t = (dir.y + 1.0) * 0.5;
+    Vec3::from_array(bottom).lerp(Vec3::from_array(top), t).to_array()
+}
+
+/// Cheap analytic sky: zenith-to-horizon blend plus a glow and disk around the sun
+///
+/// `dir` is the view direction and `sun_dir` the direction sunlight travels
+/// (the key light's direction); both unit length.
+pub fn sky_color(dir: Vec3, sun_dir: Vec3) -> [f32; 3] {
+    let horizon_blend = (1.0 - dir.y.max(0.0)).powi(HORIZON_FALLOFF);
+    let base = Vec3::from_array(ZENITH_COLOR).lerp(Vec3::from_array(HORIZON_COLOR), horizon_blend);
+
+    let sun_cos = dir.dot(-sun_dir).max(0.0);
+    let glow = sun_cos.powf(SUN_GLOW_EXPONENT) * SUN_GLOW_INTENSITY;
+    let disk = if sun_cos > SUN_DISK_COS { SUN_DISK_INTENSITY } else { 0.0 };
+
+    (base + Vec3::from_array(SUN_COLOR) * (glow + disk)).to_array()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brightness(rgb: [f32; 3]) -> f32 {
+        rgb.iter().sum()
+    }
+
+    #[test]
+    fn test_looking_at_sun_is_brightest() {
+        let sun_dir = Vec3::new(0.5, -1.0, 0.3).normalize();
+        let at_sun = brightness(sky_color(-sun_dir, sun_dir));
+
+        let others = [Vec3::Y, Vec3::X, Vec3::NEG_X, Vec3::Z, Vec3::NEG_Z, Vec3::new(-0.4, 0.9, 0.2).normalize()];
+        for dir in others {
+            assert!(at_sun > brightness(sky_color(dir, sun_dir)), "{:?}", dir);
+        }
+    }
+
+    #[test]
+    fn test_straight_up_is_zenith() {
+        // Sun low on the horizon so its glow doesn't reach the zenith
+        let sun_dir = Vec3::new(-1.0, -0.1, 0.0).normalize();
+        let up = sky_color(Vec3::Y, sun_dir);
+        assert!(up.iter().zip(ZENITH_COLOR).all(|(a, b)| (a - b).abs() < 1e-6), "{:?}", up);
+    }
+
+    #[test]
+    fn test_horizon_is_brighter_than_zenith() {
+        let sun_dir = Vec3::new(0.0, -0.1, 1.0).normalize();
+        assert!(brightness(sky_color(Vec3::X, sun_dir)) > brightness(sky_color(Vec3::Y, sun_dir)));
+    }
+
+    #[test]
+    fn test_vertical_gradient_up_horizon_down() {
+        let (top, bottom) = ([1.0, 0.5, 0.0], [0.0, 0.5, 1.0]);
+        assert_eq!(gradient_color(Vec3::Y, top, bottom), top);
+        assert_eq!(gradient_color(Vec3::X, top, bottom), [0.5, 0.5, 0.5]);
+        assert_eq!(gradient_color(Vec3::NEG_Y, top, bottom), bottom);
+    }
+
+    #[test]
+    fn test_sky_mode_names() {
+        assert_eq!(SkyMode::from_name("procedural"), Some(SkyMode::Procedural));
+        assert_eq!(SkyMode::from_name("gradient"), Some(SkyMode::Gradient));
+        assert_eq!(SkyMode::from_name("solid"), Some(SkyMode::Solid));
+        assert_eq!(SkyMode::from_name("starry"), None);
+    }
+}
@@ -1,5 +1,8 @@
-use glam::Vec3;
-use crate::math::AABB;
+use glam::{Quat, Vec3};
+use crate::fog::DEFAULT_FOG_COLOR;
+use crate::lighting::LightingConfig;
+use crate::math::{checker_parity, AABB};
+use crate::sky::{SkyMode, GRADIENT_BOTTOM, GRADIENT_TOP};
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -15,6 +18,21 @@ pub struct CameraUniform {
     pub lod_factor: f32,
     pub min_pixel_size: f32,
     pub show_grid: f32,
+    /// Thin-lens diameter; 0 = pinhole
+    pub aperture: f32,
+    pub focus_distance: f32,
+    /// Depth of field is only sampled when this is > 1
+    pub samples_per_pixel: u32,
+    /// Field of view (radians) used to scale primary rays
+    pub fov: f32,
+    /// Ambient light term added to every lit surface
+    pub ambient: f32,
+    /// Primary-ray hits closer than this are ignored
+    pub near: f32,
+    /// Primary-ray hits beyond this show the background
+    pub far: f32,
+    /// Frames already accumulated; offsets the lens and glossy sample sequence so each frame adds new samples
+    pub sample_offset: u32,
     pub _pad4: f32,
 }
 
@@ -33,8 +51,21 @@ pub struct BoxData {
     pub _pad5: f32,
     pub half_size: [f32; 3],
     pub _pad6: f32,
+    /// Light emitted regardless of scene lighting
+    pub emissive: [f32; 3],
+    /// Index into the materials buffer whose `roughness` blurs this box's reflections; -1 = mirror
+    pub material_index: i32,
+    /// Orientation quaternion (x, y, z, w) about the center; `min`/`max` hold the rotated box's world AABB
+    pub rotation: [f32; 4],
+    /// Alternate color of the X/Z checkerboard
+    pub checker_color: [f32; 3],
+    /// Checkerboard tile size in world units; 0 = solid `color`
+    pub checker_tile: f32,
 }
 
+/// `BoxData::rotation` of an axis-aligned box
+pub const IDENTITY_ROTATION: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
 impl BoxData {
     const fn calculate_center(min: [f32; 3], max: [f32; 3]) -> [f32; 3] {
         [
@@ -68,9 +99,32 @@ impl BoxData {
             _pad5: 0.0,
             half_size,
             _pad6: 0.0,
+            emissive: [0.0; 3],
+            material_index: -1,
+            rotation: IDENTITY_ROTATION,
+            checker_color: [0.0; 3],
+            checker_tile: 0.0,
         }
     }
 
+    /// The box moved by `delta`: bounds and both motion centers shift, size and rotation are kept
+    pub fn translated(self, delta: Vec3) -> Self {
+        let shift = |p: [f32; 3]| (Vec3::from_array(p) + delta).to_array();
+        Self {
+            min: shift(self.min),
+            max: shift(self.max),
+            center0: shift(self.center0),
+            center1: shift(self.center1),
+            ..self
+        }
+    }
+
+    /// Reflectivity limited to [0, 1]; the shader's reflection weighting is undefined outside it
+    pub const fn clamp_reflectivity(reflectivity: f32) -> f32 {
+        debug_assert!(!reflectivity.is_nan(), "reflectivity must be a number");
+        reflectivity.clamp(0.0, 1.0)
+    }
+
     pub const fn new_reflective(min: [f32; 3], max: [f32; 3], color: [f32; 3], reflectivity: f32) -> Self {
         let center = Self::calculate_center(min, max);
         let half_size = Self::calculate_half_size(min, max);
@@ -80,16 +134,88 @@ impl BoxData {
             max,
             _pad2: 0.0,
             color,
-            reflectivity,
+            reflectivity: Self::clamp_reflectivity(reflectivity),
             center0: center,
             _pad4: 0.0,
             center1: center,
             _pad5: 0.0,
             half_size,
             _pad6: 0.0,
+            emissive: [0.0; 3],
+            material_index: -1,
+            rotation: IDENTITY_ROTATION,
+            checker_color: [0.0; 3],
+            checker_tile: 0.0,
         }
     }
 
+    pub const fn new_emissive(min: [f32; 3], max: [f32; 3], color: [f32; 3], emissive: [f32; 3]) -> Self {
+        Self {
+            emissive,
+            ..Self::new(min, max, color)
+        }
+    }
+
+
+    /// Box of `size` centered at `center`, turned by `rotation`; bounds are the conservative world AABB
+    pub fn new_rotated(center: Vec3, size: Vec3, rotation: Quat, color: [f32; 3]) -> Self {
+        let half_size = size * 0.5;
+        let extent = Self::rotated_extent(rotation, half_size);
+        Self {
+            rotation: rotation.to_array(),
+            half_size: half_size.to_array(),
+            ..Self::new((center - extent).to_array(), (center + extent).to_array(), color)
+        }
+    }
+
+    /// Half-extent of the world AABB of a box with `half_size` turned by `rotation`
+    fn rotated_extent(rotation: Quat, half_size: Vec3) -> Vec3 {
+        glam::Mat3::from_quat(rotation).abs() * half_size
+    }
+
+    /// `min`/`max` recomputed from `rotation` and `half_size` as the conservative world AABB,
+    /// covering the whole motion from `center0` to `center1`
+    pub fn with_rotated_bounds(self) -> Self {
+        let extent = Self::rotated_extent(self.rotation(), Vec3::from_array(self.half_size));
+        let (center0, center1) = (Vec3::from_array(self.center0), Vec3::from_array(self.center1));
+        Self {
+            min: (center0.min(center1) - extent).to_array(),
+            max: (center0.max(center1) + extent).to_array(),
+            ..self
+        }
+    }
+
+    pub const fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self { reflectivity: Self::clamp_reflectivity(reflectivity), ..self }
+    }
+
+    /// Take surface properties (roughness) from material `index` of the scene's materials
+    pub const fn with_material(self, index: u32) -> Self {
+        Self { material_index: index as i32, ..self }
+    }
+
+    /// Checkerboard of `color` and `checker_color` in `tile`-sized squares over world X/Z
+    pub fn with_checker(self, checker_color: [f32; 3], tile: f32) -> Self {
+        Self { checker_color, checker_tile: tile, ..self }
+    }
+
+    /// Surface color at world `point`, resolving the checkerboard
+    pub fn color_at(&self, point: Vec3) -> [f32; 3] {
+        if self.checker_tile > 0.0 && checker_parity(point.x, point.z, self.checker_tile) {
+            self.checker_color
+        } else {
+            self.color
+        }
+    }
+
+    pub fn rotation(&self) -> Quat {
+        Quat::from_array(self.rotation)
+    }
+
+    pub fn is_rotated(&self) -> bool {
+        self.rotation != IDENTITY_ROTATION
+    }
+
     pub fn new_moving(min: [f32; 3], max: [f32; 3], color: [f32; 3], center0: [f32; 3], center1: [f32; 3], half_size: [f32; 3]) -> Self {
         Self {
             min,
@@ -104,6 +230,11 @@ impl BoxData {
             _pad5: 0.0,
             half_size,
             _pad6: 0.0,
+            emissive: [0.0; 3],
+            material_index: -1,
+            rotation: IDENTITY_ROTATION,
+            checker_color: [0.0; 3],
+            checker_tile: 0.0,
         }
     }
 
@@ -149,6 +280,17 @@ impl BoxData {
             half_size.to_array(),
         )
     }
+
+    /// `create_moving_box` with a mirror-like surface
+    pub fn create_moving_box_reflective(
+        size: Vec3,
+        center0: Vec3,
+        center1: Vec3,
+        color: [f32; 3],
+        reflectivity: f32,
+    ) -> Self {
+        Self::create_moving_box(size, center0, center1, color).with_reflectivity(reflectivity)
+    }
 }
 
 
@@ -189,9 +331,50 @@ impl Default for RayDebugInfo {
 pub struct DebugParams {
     pub debug_pixel: [u32; 2],
     pub enabled: u32,
+    /// Non-zero tints output by traversal step count (see `step_heatmap_color`)
+    pub heatmap: u32,
+    /// 0 = lit, 1 = surface normals, 2 = flat Lambert (see `ShadingMode`)
+    pub shading_mode: u32,
+    /// Zero skips the reflection bounce, shading reflective surfaces as diffuse
+    pub reflections_enabled: u32,
+    /// Non-zero writes the encoded primary hit depth to the output alpha for edge detection
+    pub wireframe: u32,
     pub _pad: u32,
 }
 
+/// Display pass parameters, shared by `RayTracer` and `SurfaceRenderer` (display.wgsl)
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct DisplayParams {
+    /// See `ToneMap::as_u32`
+    pub tone_map: u32,
+    /// Non-zero darkens depth and luminance edges (needs `DebugParams::wireframe`)
+    pub wireframe: u32,
+    /// Linear scale applied before tone mapping
+    pub exposure: f32,
+    pub _pad: u32,
+    /// Image rect `[x, y, width, height]` as fractions of the window; black bars outside
+    pub viewport: [f32; 4],
+}
+
+impl DisplayParams {
+    pub const fn new(tone_map: u32) -> Self {
+        Self { tone_map, wireframe: 0, exposure: 1.0, _pad: 0, viewport: [0.0, 0.0, 1.0, 1.0] }
+    }
+
+    pub const fn with_viewport(self, viewport: [f32; 4]) -> Self {
+        Self { viewport, ..self }
+    }
+
+    pub const fn with_exposure(self, exposure: f32) -> Self {
+        Self { exposure, ..self }
+    }
+
+    pub const fn with_wireframe(self, wireframe: bool) -> Self {
+        Self { wireframe: wireframe as u32, ..self }
+    }
+}
+
 /// Triangle data for ray tracing with UV coordinates
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -206,6 +389,13 @@ pub struct TriangleData {
     pub uv1: [f32; 2],
     pub uv2: [f32; 2],
     pub _pad3: [f32; 2],
+    /// Per-vertex normals, interpolated for smooth shading when `has_normals` is 1
+    pub n0: [f32; 3],
+    pub has_normals: f32,
+    pub n1: [f32; 3],
+    pub _pad4: f32,
+    pub n2: [f32; 3],
+    pub _pad5: f32,
 }
 
 impl TriangleData {
@@ -229,9 +419,20 @@ impl TriangleData {
             uv1,
             uv2,
             _pad3: [0.0, 0.0],
+            n0: [0.0; 3],
+            has_normals: 0.0,
+            n1: [0.0; 3],
+            _pad4: 0.0,
+            n2: [0.0; 3],
+            _pad5: 0.0,
         }
     }
 
+    /// Shade with normals interpolated from these vertex normals instead of the flat face normal
+    pub fn with_normals(self, n0: [f32; 3], n1: [f32; 3], n2: [f32; 3]) -> Self {
+        Self { n0, has_normals: 1.0, n1, n2, ..self }
+    }
+
     pub fn bounds(&self) -> AABB {
         let v0 = Vec3::from_array(self.v0);
         let v1 = Vec3::from_array(self.v1);
@@ -292,13 +493,59 @@ impl MaterialData {
     }
 }
 
+/// Kind of light stored in `LightData::light_type`
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightType {
+    Directional = 0,
+    Point = 1,
+}
+
+/// One entry of the shader's light list (matches the WGSL `Light` struct)
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightData {
+    /// World position of a point light, or the normalized travel direction of a directional light
+    pub position: [f32; 3],
+    /// `LightType` as u32
+    pub light_type: u32,
+    pub color: [f32; 3],
+    /// Point lights fall off with the inverse square of distance
+    pub intensity: f32,
+}
+
+impl LightData {
+    pub fn directional(direction: Vec3, color: [f32; 3], intensity: f32) -> Self {
+        Self { position: direction.normalize().to_array(), light_type: LightType::Directional as u32, color, intensity }
+    }
+
+    pub fn point(position: Vec3, color: [f32; 3], intensity: f32) -> Self {
+        Self { position: position.to_array(), light_type: LightType::Point as u32, color, intensity }
+    }
+}
+
 /// Scene configuration for unified shader
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct SceneConfig {
     pub num_boxes: u32,
     pub num_triangles: u32,
-    pub _pad: [u32; 2],
+    /// 1 when the skybox texture replaces the gradient sky
+    pub skybox_enabled: u32,
+    /// `SkyMode::as_u32` of the background used when there is no skybox
+    pub sky_mode: u32,
+    /// Sun the procedural sky is lit by, normalized
+    pub sun_direction: [f32; 3],
+    /// Entries of the lights buffer summed per hit
+    pub num_lights: u32,
+    pub fog_color: [f32; 3],
+    /// Exponential fog density; 0 disables fog
+    pub fog_density: f32,
+    /// Gradient colors straight up and down; `SkyMode::Solid` fills with `sky_top`
+    pub sky_top: [f32; 3],
+    pub _pad: f32,
+    pub sky_bottom: [f32; 3],
+    pub _pad2: f32,
 }
 
 impl SceneConfig {
@@ -306,7 +553,100 @@ impl SceneConfig {
         Self {
             num_boxes: num_boxes as u32,
             num_triangles: num_triangles as u32,
-            _pad: [0, 0],
+            skybox_enabled: 0,
+            sky_mode: SkyMode::Gradient.as_u32(),
+            sun_direction: [0.0; 3],
+            num_lights: 0,
+            fog_color: DEFAULT_FOG_COLOR,
+            fog_density: 0.0,
+            sky_top: GRADIENT_TOP,
+            _pad: 0.0,
+            sky_bottom: GRADIENT_BOTTOM,
+            _pad2: 0.0,
         }
+        .with_lights(&LightingConfig::default().lights())
+    }
+
+    pub fn with_skybox(self, enabled: bool) -> Self {
+        Self { skybox_enabled: enabled as u32, ..self }
+    }
+
+    pub fn with_sky(self, sky: SkyMode) -> Self {
+        Self { sky_mode: sky.as_u32(), ..self }
+    }
+
+    pub fn with_sky_colors(self, sky_top: [f32; 3], sky_bottom: [f32; 3]) -> Self {
+        Self { sky_top, sky_bottom, ..self }
+    }
+
+    /// Light count for `lights`; the first directional light becomes the procedural sky's sun
+    pub fn with_lights(self, lights: &[LightData]) -> Self {
+        let sun = lights.iter().find(|light| light.light_type == LightType::Directional as u32);
+        Self {
+            sun_direction: sun.map_or(LightingConfig::default().key.direction.to_array(), |light| light.position),
+            num_lights: lights.len() as u32,
+            ..self
+        }
+    }
+
+    pub fn with_fog(self, fog_color: [f32; 3], fog_density: f32) -> Self {
+        Self { fog_color, fog_density: fog_density.max(0.0), ..self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::{align_of, offset_of, size_of};
+
+    #[test]
+    fn test_light_data_matches_wgsl_layout() {
+        assert_eq!(size_of::<LightData>(), 32);
+        assert_eq!(align_of::<LightData>(), 4);
+        assert_eq!(offset_of!(LightData, light_type), 12);
+        assert_eq!(offset_of!(LightData, color), 16);
+        assert_eq!(offset_of!(LightData, intensity), 28);
+
+        let light = LightData::point(Vec3::new(1.0, 2.0, 3.0), [0.5; 3], 4.0);
+        let words: &[u32] = bytemuck::cast_slice(bytemuck::bytes_of(&light));
+        assert_eq!(words[3], LightType::Point as u32);
+        assert_eq!(f32::from_bits(words[7]), 4.0);
+        assert_eq!(bytemuck::cast_slice::<LightData, u8>(&[light, light]).len(), 64);
+    }
+
+    #[test]
+    fn test_reflectivity_is_clamped() {
+        let (min, max, color) = ([0.0; 3], [1.0; 3], [1.0; 3]);
+        assert_eq!(BoxData::new_reflective(min, max, color, 1.5).reflectivity, 1.0);
+        assert_eq!(BoxData::new_reflective(min, max, color, -0.2).reflectivity, 0.0);
+        assert_eq!(BoxData::new(min, max, color).with_reflectivity(1.5).reflectivity, 1.0);
+        assert_eq!(BoxData::new(min, max, color).with_reflectivity(-0.2).reflectivity, 0.0);
+        assert_eq!(BoxData::new(min, max, color).with_reflectivity(0.4).reflectivity, 0.4);
+    }
+
+    #[test]
+    fn test_translated_shifts_bounds_and_centers() {
+        let delta = Vec3::new(0.25, -1.0, 2.0);
+        let original = BoxData::create_moving_box(Vec3::splat(2.0), Vec3::ZERO, Vec3::new(0.0, 4.0, 0.0), [1.0; 3]);
+        let moved = original.translated(delta);
+        let shifted = |p: [f32; 3]| (Vec3::from_array(p) + delta).to_array();
+
+        assert_eq!(moved.min, shifted(original.min));
+        assert_eq!(moved.max, shifted(original.max));
+        assert_eq!(moved.center0, delta.to_array());
+        assert_eq!(moved.center1, [0.25, 3.0, 2.0]);
+        assert_eq!(moved.half_size, original.half_size);
+    }
+
+    #[test]
+    fn test_moving_box_reflective_sets_centers_and_reflectivity() {
+        let (center0, center1) = (Vec3::new(0.0, 10.0, 0.0), Vec3::new(0.0, -10.0, 0.0));
+        let b = BoxData::create_moving_box_reflective(Vec3::splat(4.0), center0, center1, [0.9, 0.9, 0.95], 0.9);
+
+        assert_eq!(b.center0, center0.to_array());
+        assert_eq!(b.center1, center1.to_array());
+        assert_eq!(b.half_size, [2.0; 3]);
+        assert_eq!(b.reflectivity, 0.9);
+        assert!(b.is_moving());
     }
 }
@@ -0,0 +1,146 @@
+// views.rs - Per-window state for comparing scenes in separate OS windows
+use crate::camera::Camera;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use winit::window::WindowId;
+
+/// Camera handle held by each window; with `--sync-camera` every window holds the same one
+pub type SharedCamera = Arc<Mutex<Camera>>;
+
+/// Wrap each window's starting camera; when `sync`, every window gets the first camera instead
+pub fn window_cameras(cameras: Vec<Camera>, sync: bool) -> Vec<SharedCamera> {
+    let count = cameras.len();
+    let handles = cameras.into_iter().map(|camera| Arc::new(Mutex::new(camera)));
+    if sync {
+        handles.take(1).flat_map(|shared| std::iter::repeat_n(shared, count)).collect()
+    } else {
+        handles.collect()
+    }
+}
+
+/// State for each open window; events are routed only to the window they were sent to
+#[derive(Debug)]
+pub struct Views<S> {
+    by_id: HashMap<WindowId, S>,
+    /// Window ids in opening order
+    order: Vec<WindowId>,
+}
+
+impl<S> Views<S> {
+    pub fn new() -> Self {
+        Self { by_id: HashMap::new(), order: Vec::new() }
+    }
+
+    pub fn insert(&mut self, id: WindowId, state: S) {
+        if self.by_id.insert(id, state).is_none() {
+            self.order.push(id);
+        }
+    }
+
+    pub fn remove(&mut self, id: WindowId) -> Option<S> {
+        self.order.retain(|&open| open != id);
+        self.by_id.remove(&id)
+    }
+
+    pub fn get_mut(&mut self, id: WindowId) -> Option<&mut S> {
+        self.by_id.get_mut(&id)
+    }
+
+    /// Apply `f` to the state of window `id`; `None` for unknown (e.g. already closed) windows
+    pub fn route<R>(&mut self, id: WindowId, f: impl FnOnce(&mut S) -> R) -> Option<R> {
+        self.by_id.get_mut(&id).map(f)
+    }
+
+    /// First window still open
+    pub fn first_id(&self) -> Option<WindowId> {
+        self.order.first().copied()
+    }
+
+    /// All states, in opening order
+    pub fn iter(&self) -> impl Iterator<Item = &S> {
+        self.order.iter().filter_map(|id| self.by_id.get(id))
+    }
+
+    /// All states, in no particular order
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut S> {
+        self.by_id.values_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+impl<S> Default for Views<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_windows() -> (Views<Camera>, WindowId, WindowId) {
+        let (a, b) = (WindowId::from(1), WindowId::from(2));
+        let mut views = Views::new();
+        views.insert(a, Camera::for_scene("walls"));
+        views.insert(b, Camera::for_scene("tunnel"));
+        (views, a, b)
+    }
+
+    #[test]
+    fn test_event_for_one_window_leaves_the_other_alone() {
+        let (mut views, a, b) = two_windows();
+        let before = views.get_mut(b).unwrap().snapshot();
+
+        views.route(a, |camera| {
+            camera.movement.forward = true;
+            camera.update();
+        });
+
+        assert_eq!(views.get_mut(b).unwrap().snapshot(), before);
+        assert_ne!(views.get_mut(a).unwrap().snapshot(), Camera::for_scene("walls").snapshot());
+    }
+
+    #[test]
+    fn test_closed_window_receives_nothing() {
+        let (mut views, a, b) = two_windows();
+        assert!(views.remove(a).is_some());
+
+        assert_eq!(views.route(a, |_| ()), None);
+        assert_eq!(views.first_id(), Some(b));
+        assert_eq!(views.len(), 1);
+    }
+
+    #[test]
+    fn test_synced_camera_update_reaches_every_window() {
+        let ids = [WindowId::from(1), WindowId::from(2), WindowId::from(3)];
+        let mut views = Views::new();
+        ids.into_iter()
+            .zip(window_cameras(vec![Camera::for_scene("walls"), Camera::for_scene("tunnel"), Camera::new()], true))
+            .for_each(|(id, camera)| views.insert(id, camera));
+
+        views.route(ids[0], |camera| {
+            let mut camera = camera.lock().unwrap();
+            camera.movement.forward = true;
+            camera.update();
+        });
+
+        let moved = views.get_mut(ids[0]).unwrap().lock().unwrap().snapshot();
+        assert_ne!(moved, Camera::for_scene("walls").snapshot());
+        assert!(views.iter().all(|camera| camera.lock().unwrap().snapshot() == moved));
+    }
+
+    #[test]
+    fn test_unsynced_cameras_are_independent() {
+        let cameras = window_cameras(vec![Camera::for_scene("walls"), Camera::for_scene("walls")], false);
+        cameras[0].lock().unwrap().movement.forward = true;
+        cameras[0].lock().unwrap().update();
+        assert_eq!(cameras[1].lock().unwrap().snapshot(), Camera::for_scene("walls").snapshot());
+    }
+}
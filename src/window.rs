@@ -1,9 +1,15 @@
 use std::sync::Arc;
+use winit::dpi::PhysicalSize;
 use winit::window::Window as WinitWindow;
 use crate::camera::Camera;
 use crate::renderer::RayTracer;
 use crate::frame::FrameInfo;
 
+/// A zero-extent window (minimized) has no surface to render into
+pub fn is_minimized(size: PhysicalSize<u32>) -> bool {
+    size.width == 0 || size.height == 0
+}
+
 /// Wrapper around winit Window with imperative draw API
 pub struct Window {
     inner: Arc<WinitWindow>,
@@ -18,15 +24,14 @@ impl Window {
         &self.inner
     }
 
-    /// Draw a frame using the provided renderer, camera, and frame info
+    /// Draw a frame using the provided renderer, camera, and frame info (including its smoothed FPS)
     pub fn draw(
         &self,
         raytracer: &mut RayTracer,
         camera: &Camera,
-        fps: f32,
         frame: &FrameInfo,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        raytracer.render(camera, &self.inner, fps, frame.time, frame.number)?;
+        raytracer.render(camera, &self.inner, frame.fps, frame.time, frame.frame_number)?;
         Ok(())
     }
 
@@ -34,7 +39,21 @@ impl Window {
         self.inner.request_redraw();
     }
 
-    pub fn inner_size(&self) -> winit::dpi::PhysicalSize<u32> {
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
         self.inner.inner_size()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_sizes_pause_rendering() {
+        assert!(is_minimized(PhysicalSize::new(0, 0)));
+        assert!(is_minimized(PhysicalSize::new(800, 0)));
+        assert!(is_minimized(PhysicalSize::new(0, 600)));
+        assert!(!is_minimized(PhysicalSize::new(800, 600)));
+        assert!(!is_minimized(PhysicalSize::new(1, 1)));
+    }
+}
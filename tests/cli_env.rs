@@ -0,0 +1,76 @@
+//! Env-var precedence tests live in their own binary: they mutate the process
+//! environment, which would race with the parsing tests in `cli.rs`
+
+use clap::Parser;
+use ray_tracer::cli::Cli;
+use std::sync::Mutex;
+
+/// Serializes tests that read or write process environment variables
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Runs `f` with `vars` set, then restores each variable's previous value (or absence)
+fn with_env<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let previous: Vec<_> = vars.iter().map(|(k, _)| (*k, std::env::var_os(k))).collect();
+    vars.iter().for_each(|(k, v)| std::env::set_var(k, v));
+    let result = f();
+    previous.into_iter().for_each(|(k, value)| match value {
+        Some(value) => std::env::set_var(k, value),
+        None => std::env::remove_var(k),
+    });
+    result
+}
+
+#[test]
+fn env_var_used_when_flag_absent() {
+    let cli = with_env(
+        &[
+            ("SCENE", "tunnel"),
+            ("RAY_TRACER_WIDTH", "320"),
+            ("RAY_TRACER_FPS", "24"),
+            ("RAY_TRACER_NO_UI", "true"),
+            ("RAY_TRACER_REFLECTIVE_FLOOR", "0.5"),
+        ],
+        || Cli::try_parse_from(["ray-tracer"]).unwrap(),
+    );
+
+    assert_eq!(cli.scene_name(), "tunnel");
+    assert_eq!(cli.width, Some(320));
+    assert_eq!(cli.fps, Some(24.0));
+    assert_eq!(cli.no_ui, Some(true));
+    assert_eq!(cli.reflective_floor, Some(0.5));
+}
+
+#[test]
+fn flag_beats_env_var() {
+    let cli = with_env(&[("SCENE", "tunnel"), ("RAY_TRACER_WIDTH", "320")], || {
+        Cli::try_parse_from(["ray-tracer", "--scene", "walls", "--width", "1024"]).unwrap()
+    });
+
+    assert_eq!(cli.scene_name(), "walls");
+    assert_eq!(cli.width, Some(1024));
+}
+
+#[test]
+fn invalid_env_var_rejected() {
+    let result = with_env(&[("RAY_TRACER_HEIGHT", "0")], || Cli::try_parse_from(["ray-tracer"]));
+    assert!(result.is_err());
+}
+
+#[test]
+fn ambient_from_env() {
+    let cli = with_env(&[("RAY_TRACER_AMBIENT", "0.5")], || Cli::try_parse_from(["ray-tracer"])).unwrap();
+    assert_eq!(cli.ambient, Some(0.5));
+}
+
+#[test]
+fn fractal_lod_from_env() {
+    let cli = with_env(&[("RAY_TRACER_FRACTAL_LOD", "true")], || Cli::try_parse_from(["ray-tracer"])).unwrap();
+    assert!(cli.config().unwrap().fractal_lod);
+}
+
+#[test]
+fn stress_count_from_env() {
+    let cli = with_env(&[("RAY_TRACER_STRESS_COUNT", "50000")], || Cli::try_parse_from(["ray-tracer"])).unwrap();
+    assert_eq!(cli.config().unwrap().stress_count, 50_000);
+}
@@ -0,0 +1,188 @@
+//! In-memory glTF fixtures for loader tests
+//!
+//! Builds minimal, spec-valid binary glTF (GLB) files programmatically so
+//! tests don't depend on model files checked into `models/`.
+
+#![allow(dead_code)]
+
+use serde_json::json;
+use std::path::PathBuf;
+
+const GLB_MAGIC: u32 = 0x4654_6C67; // "glTF"
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4E4F_534A; // "JSON"
+const CHUNK_BIN: u32 = 0x004E_4942; // "BIN\0"
+
+const COMPONENT_FLOAT: u32 = 5126;
+const COMPONENT_UNSIGNED_INT: u32 = 5125;
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Builder for a single-mesh, single-material glTF document
+#[derive(Debug, Clone)]
+pub struct GltfFixture {
+    positions: Vec<[f32; 3]>,
+    indices: Option<Vec<u32>>,
+    base_color: [f32; 4],
+    extensions_used: Vec<String>,
+}
+
+impl GltfFixture {
+    /// One counter-clockwise triangle in the XY plane with a single grey material
+    pub fn triangle() -> Self {
+        Self {
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            indices: None,
+            base_color: [0.5, 0.5, 0.5, 1.0],
+            extensions_used: vec![],
+        }
+    }
+
+    pub fn positions(mut self, positions: Vec<[f32; 3]>) -> Self {
+        self.positions = positions;
+        self
+    }
+
+    pub fn indices(mut self, indices: Vec<u32>) -> Self {
+        self.indices = Some(indices);
+        self
+    }
+
+    pub fn base_color(mut self, base_color: [f32; 4]) -> Self {
+        self.base_color = base_color;
+        self
+    }
+
+    /// Declare an extension in `extensionsUsed` (not `extensionsRequired`)
+    pub fn extension_used(mut self, name: &str) -> Self {
+        self.extensions_used.push(name.to_string());
+        self
+    }
+
+    /// Serialize to GLB bytes (JSON chunk + BIN chunk)
+    pub fn to_glb(&self) -> Vec<u8> {
+        let position_bytes: Vec<u8> = self
+            .positions
+            .iter()
+            .flatten()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+        let index_bytes: Vec<u8> = self
+            .indices
+            .iter()
+            .flatten()
+            .flat_map(|i| i.to_le_bytes())
+            .collect();
+
+        let bin = [position_bytes.as_slice(), index_bytes.as_slice()].concat();
+        let json = self.document(position_bytes.len(), index_bytes.len(), bin.len());
+
+        let json_chunk = pad_to_four(serde_json::to_vec(&json).unwrap(), b' ');
+        let bin_chunk = pad_to_four(bin, 0);
+        let total_len = 12 + 8 + json_chunk.len() + 8 + bin_chunk.len();
+
+        [
+            GLB_MAGIC.to_le_bytes().as_slice(),
+            &GLB_VERSION.to_le_bytes(),
+            &(total_len as u32).to_le_bytes(),
+            &(json_chunk.len() as u32).to_le_bytes(),
+            &CHUNK_JSON.to_le_bytes(),
+            &json_chunk,
+            &(bin_chunk.len() as u32).to_le_bytes(),
+            &CHUNK_BIN.to_le_bytes(),
+            &bin_chunk,
+        ]
+        .concat()
+    }
+
+    /// Write GLB bytes to a unique file in the system temp dir
+    pub fn write_glb(&self, name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "ray_tracer_fixture_{}_{}.glb",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, self.to_glb()).expect("Failed to write glTF fixture");
+        path
+    }
+
+    fn document(&self, position_len: usize, index_len: usize, buffer_len: usize) -> serde_json::Value {
+        let (min, max) = self.position_bounds();
+
+        let mut primitive = json!({
+            "attributes": { "POSITION": 0 },
+            "material": 0,
+        });
+        let mut buffer_views = vec![json!({
+            "buffer": 0,
+            "byteOffset": 0,
+            "byteLength": position_len,
+            "target": TARGET_ARRAY_BUFFER,
+        })];
+        let mut accessors = vec![json!({
+            "bufferView": 0,
+            "componentType": COMPONENT_FLOAT,
+            "count": self.positions.len(),
+            "type": "VEC3",
+            "min": min,
+            "max": max,
+        })];
+
+        if let Some(indices) = &self.indices {
+            primitive["indices"] = json!(1);
+            buffer_views.push(json!({
+                "buffer": 0,
+                "byteOffset": position_len,
+                "byteLength": index_len,
+                "target": TARGET_ELEMENT_ARRAY_BUFFER,
+            }));
+            accessors.push(json!({
+                "bufferView": 1,
+                "componentType": COMPONENT_UNSIGNED_INT,
+                "count": indices.len(),
+                "type": "SCALAR",
+            }));
+        }
+
+        let mut document = json!({
+            "asset": { "version": "2.0" },
+            "scene": 0,
+            "scenes": [{ "nodes": [0] }],
+            "nodes": [{ "mesh": 0 }],
+            "meshes": [{ "primitives": [primitive] }],
+            "materials": [{
+                "pbrMetallicRoughness": {
+                    "baseColorFactor": self.base_color,
+                    "metallicFactor": 0.0,
+                    "roughnessFactor": 1.0,
+                }
+            }],
+            "buffers": [{ "byteLength": buffer_len }],
+            "bufferViews": buffer_views,
+            "accessors": accessors,
+        });
+
+        if !self.extensions_used.is_empty() {
+            document["extensionsUsed"] = json!(self.extensions_used);
+        }
+
+        document
+    }
+
+    fn position_bounds(&self) -> ([f32; 3], [f32; 3]) {
+        self.positions.iter().fold(
+            ([f32::MAX; 3], [f32::MIN; 3]),
+            |(min, max), p| {
+                (
+                    [min[0].min(p[0]), min[1].min(p[1]), min[2].min(p[2])],
+                    [max[0].max(p[0]), max[1].max(p[1]), max[2].max(p[2])],
+                )
+            },
+        )
+    }
+}
+
+fn pad_to_four(mut bytes: Vec<u8>, fill: u8) -> Vec<u8> {
+    bytes.resize(bytes.len().div_ceil(4) * 4, fill);
+    bytes
+}
@@ -0,0 +1,122 @@
+mod fixtures;
+
+use fixtures::GltfFixture;
+use ray_tracer::loaders::{load_gltf_triangles, load_gltf_triangles_with_budget};
+
+#[test]
+fn test_load_single_triangle_fixture() {
+    let path = GltfFixture::triangle()
+        .base_color([1.0, 0.25, 0.0, 1.0])
+        .write_glb("single_triangle");
+
+    let scene = load_gltf_triangles(&path).expect("fixture should load");
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(scene.triangles.len(), 1);
+    assert_eq!(scene.materials.len(), 1);
+    assert!(scene.textures.is_empty());
+    assert!(scene.warnings.is_empty());
+
+    let tri = &scene.triangles[0];
+    assert_eq!(tri.v0, [0.0, 0.0, 0.0]);
+    assert_eq!(tri.v1, [1.0, 0.0, 0.0]);
+    assert_eq!(tri.v2, [0.0, 1.0, 0.0]);
+    assert_eq!(tri.material_id, 0.0);
+    assert_eq!(scene.materials[0].base_color, [1.0, 0.25, 0.0, 1.0]);
+}
+
+#[test]
+fn test_fixture_is_valid_glb() {
+    let bytes = GltfFixture::triangle().indices(vec![0, 1, 2]).to_glb();
+
+    assert_eq!(&bytes[0..4], b"glTF");
+    assert_eq!(bytes.len() % 4, 0);
+    assert_eq!(u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize, bytes.len());
+    assert!(gltf::Gltf::from_slice(&bytes).is_ok());
+}
+
+#[test]
+fn test_unsupported_extension_is_reported() {
+    let path = GltfFixture::triangle()
+        .extension_used("KHR_materials_clearcoat")
+        .write_glb("unsupported_extension");
+
+    let scene = load_gltf_triangles(&path).expect("fixture should load");
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(scene.triangles.len(), 1);
+    assert_eq!(scene.warnings.len(), 1);
+    assert!(scene.warnings[0].contains("KHR_materials_clearcoat"));
+}
+
+fn triangle_vertices(path: &std::path::Path) -> Vec<[[f32; 3]; 3]> {
+    let scene = load_gltf_triangles(path).expect("fixture should load");
+    std::fs::remove_file(path).ok();
+    scene.triangles.iter().map(|t| [t.v0, t.v1, t.v2]).collect()
+}
+
+#[test]
+fn test_indexed_and_non_indexed_quads_match() {
+    let (a, b, c, d) = ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]);
+
+    let indexed = GltfFixture::triangle()
+        .positions(vec![a, b, c, d])
+        .indices(vec![0, 1, 2, 2, 1, 3])
+        .write_glb("quad_indexed");
+    let non_indexed = GltfFixture::triangle()
+        .positions(vec![a, b, c, c, b, d])
+        .write_glb("quad_non_indexed");
+
+    let indexed = triangle_vertices(&indexed);
+    let non_indexed = triangle_vertices(&non_indexed);
+
+    assert_eq!(indexed.len(), 2);
+    assert_eq!(indexed, non_indexed);
+    assert_eq!(indexed[1], [c, b, d]);
+}
+
+#[test]
+fn test_out_of_range_index_is_rejected() {
+    let path = GltfFixture::triangle()
+        .indices(vec![0, 1, 7])
+        .write_glb("out_of_range_index");
+
+    let result = load_gltf_triangles(&path);
+    std::fs::remove_file(&path).ok();
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_triangle_budget_exceeded_is_descriptive_error() {
+    let quad = GltfFixture::triangle()
+        .positions(vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]])
+        .indices(vec![0, 1, 2, 2, 1, 3]);
+    let path = quad.write_glb("over_budget");
+
+    let over = load_gltf_triangles_with_budget(&path, Some(1));
+    let within = load_gltf_triangles_with_budget(&path, Some(2));
+    std::fs::remove_file(&path).ok();
+
+    let message = over.err().expect("budget of 1 should be exceeded").to_string();
+    assert!(message.contains("2 triangles"), "{}", message);
+    assert!(message.contains("budget of 1"), "{}", message);
+    assert_eq!(within.unwrap().triangles.len(), 2);
+}
+
+#[test]
+fn test_gltf_scene_over_budget_fails_instead_of_rendering_empty() {
+    let quad = GltfFixture::triangle()
+        .positions(vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]])
+        .indices(vec![0, 1, 2, 2, 1, 3]);
+    let path = quad.write_glb("scene_over_budget");
+
+    // Only test in this binary that reads GLTF_FILE
+    std::env::set_var("GLTF_FILE", &path);
+    let result = ray_tracer::scenes::create_gltf_triangles(Some(1));
+    std::env::remove_var("GLTF_FILE");
+    std::fs::remove_file(&path).ok();
+
+    let message = result.err().expect("budget of 1 should be exceeded").to_string();
+    assert!(message.contains("budget of 1"), "{}", message);
+}
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use ray_tracer::core::GpuContext;
+use ray_tracer::headless::{render_frames, write_png};
+use ray_tracer::renderer::RayTracer;
+use ray_tracer::scenes::DEFAULT_STRESS_COUNT;
+
+#[test]
+fn headless_render_writes_decodable_png() {
+    // CI machines without a GPU adapter cannot run the renderer
+    if !RayTracer::is_gpu_available() {
+        eprintln!("Skipping headless render test: no GPU adapter");
+        return;
+    }
+    let gpu = Arc::new(pollster::block_on(GpuContext::new()).unwrap());
+
+    // 30 px rows (120 bytes) are padded to the 256-byte copy pitch and must come back tightly packed
+    let (width, height) = (30, 16);
+    let pixels = render_frames(gpu, "pyramid", width, height, 2, DEFAULT_STRESS_COUNT).unwrap();
+    assert_eq!(pixels.len(), (width * height * 4) as usize);
+
+    let out = std::env::temp_dir().join("ray_tracer_headless_render.png");
+    write_png(&out, &pixels, width, height).unwrap();
+
+    let decoded = image::open(&out).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (width, height));
+    std::fs::remove_file(&out).ok();
+}
+
+#[test]
+fn png_writer_round_trips() {
+    let (width, height) = (4, 2);
+    let pixels: Vec<u8> = (0..width * height * 4).map(|i| i as u8).collect();
+
+    let out = std::env::temp_dir().join("ray_tracer_png_round_trip.png");
+    write_png(&out, &pixels, width, height).unwrap();
+
+    let decoded = image::open(&out).unwrap().to_rgba8();
+    assert_eq!(decoded.into_raw(), pixels);
+    std::fs::remove_file(&out).ok();
+}
@@ -0,0 +1,48 @@
+use ray_tracer::grid::HierarchicalGrid;
+use ray_tracer::scenes;
+
+#[test]
+fn every_scene_builds_with_a_grid() {
+    assert!(!scenes::available().is_empty());
+
+    for scene in scenes::available() {
+        let boxes = (scene.boxes)();
+        let triangles = (scene.triangles)();
+        assert!(
+            !boxes.is_empty() || !triangles.is_empty(),
+            "scene '{}' produced no geometry",
+            scene.name
+        );
+
+        let grid = HierarchicalGrid::build(&boxes, &triangles);
+        assert!(
+            grid.bounds.min.is_finite() && grid.bounds.max.is_finite(),
+            "scene '{}' has non-finite grid bounds",
+            scene.name
+        );
+        assert!(
+            grid.bounds.min.cmplt(grid.bounds.max).all(),
+            "scene '{}' has empty grid bounds",
+            scene.name
+        );
+
+        let (_, coarse_counts, fine_cells) = grid.to_gpu_buffers();
+        assert!(!coarse_counts.is_empty(), "scene '{}' has no coarse cells", scene.name);
+        assert!(!fine_cells.is_empty(), "scene '{}' has no fine cells", scene.name);
+    }
+}
+
+#[test]
+fn scene_names_are_unique() {
+    let mut names: Vec<_> = scenes::available().iter().map(|s| s.name).collect();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), scenes::available().len());
+}
+
+#[test]
+fn enclosed_scenes_default_to_darker_ambient() {
+    assert_eq!(scenes::default_ambient("default"), scenes::DEFAULT_AMBIENT);
+    assert!(scenes::default_ambient("tunnel") < scenes::DEFAULT_AMBIENT);
+    assert!(scenes::available().iter().all(|s| (0.0..=1.0).contains(&scenes::default_ambient(s.name))));
+}